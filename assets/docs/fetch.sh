@@ -0,0 +1,43 @@
+#!/bin/sh
+# Downloads the Swagger UI and ReDoc files served from the binary by the `bundled-docs` feature.
+# Keep the versions in sync with the CDN links in src/handlers/docs.rs
+#
+# The files are checked against SHA256SUMS before they are written here, a mismatch or a
+# missing sum fails the script. After bumping a version run `fetch.sh --pin` once to record
+# the new sums, review the downloaded files and commit SHA256SUMS.
+set -eu
+cd "$(dirname "$0")"
+
+SWAGGER_UI=5.17.14
+REDOC=2.1.5
+
+FILES="swagger-ui.css swagger-ui-bundle.js redoc.standalone.js"
+
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+curl -fsSL -o "$tmp/swagger-ui.css" "https://unpkg.com/swagger-ui-dist@$SWAGGER_UI/swagger-ui.css"
+curl -fsSL -o "$tmp/swagger-ui-bundle.js" "https://unpkg.com/swagger-ui-dist@$SWAGGER_UI/swagger-ui-bundle.js"
+curl -fsSL -o "$tmp/redoc.standalone.js" "https://cdn.redoc.ly/redoc/v$REDOC/bundles/redoc.standalone.js"
+
+if [ "${1:-}" = "--pin" ]; then
+    (cd "$tmp" && sha256sum $FILES) > SHA256SUMS
+    echo "recorded sums for swagger-ui $SWAGGER_UI and redoc $REDOC in SHA256SUMS" >&2
+else
+    if [ ! -f SHA256SUMS ]; then
+        echo "SHA256SUMS is missing, run \`$0 --pin\` and commit it" >&2
+        exit 1
+    fi
+    for file in $FILES; do
+        if ! grep -q " $file\$" SHA256SUMS; then
+            echo "no pinned sum for $file in SHA256SUMS" >&2
+            exit 1
+        fi
+    done
+    cp SHA256SUMS "$tmp/SHA256SUMS"
+    (cd "$tmp" && sha256sum --check --strict --quiet SHA256SUMS)
+fi
+
+for file in $FILES; do
+    mv "$tmp/$file" "$file"
+done
@@ -0,0 +1,504 @@
+use crate::query::ToQuery;
+use crate::{Error, Result};
+use api_types::*;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Header carrying the partner api key, same as the server default
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Client of the `/v2` routes, cheap to clone
+#[derive(Clone, Debug)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    api_key: Option<String>,
+}
+
+impl Client {
+    /// `base_url` is the root of the api without the version prefix, e.g. `https://api.example.com`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self {
+            http,
+            base_url,
+            token: None,
+            api_key: None,
+        }
+    }
+
+    /// Jwt returned by [`Client::sign_in`], sent as a bearer token
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Partner api key lifting the anonymous rate limits
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let mut request = self
+            .http
+            .request(method, format!("{}/v2{path}", self.base_url));
+        if let Some(token) = &self.token {
+            request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+        }
+        if let Some(api_key) = &self.api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        request
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Vec<u8>> {
+        let response = request.send().await?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs);
+        let body = response.bytes().await?;
+        if !status.is_success() {
+            return Err(Error::from_response(status, retry_after, &body));
+        }
+        Ok(body.to_vec())
+    }
+
+    async fn json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let body = self.send(request).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &impl Serialize) -> Result<T> {
+        self.json(self.request(Method::GET, path).query(query))
+            .await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &str, body: &impl Serialize) -> Result<T> {
+        self.json(self.request(Method::POST, path).json(body)).await
+    }
+
+    pub async fn nfts(&self, query: &NFTListQuery) -> Result<VecWith<NFT>> {
+        self.get("/nfts", &query.to_query()).await
+    }
+
+    pub async fn nfts_random_buy(&self, query: &NFTListRandomBuyQuery) -> Result<VecWith<NFT>> {
+        self.post("/nfts/random-buy", query).await
+    }
+
+    pub async fn nfts_sell_count(&self, query: &NFTSellCountQuery) -> Result<NFTSellCountResponse> {
+        self.get("/nfts/sell-count", query).await
+    }
+
+    pub async fn nfts_top(&self, query: &NFTTopListQuery) -> Result<VecWith<NFT>> {
+        self.post("/nfts/top", query).await
+    }
+
+    pub async fn nft(&self, query: &NFTParam) -> Result<GetNFTResult> {
+        self.get("/nft/details", &query.to_query()).await
+    }
+
+    pub async fn nft_direct_buys(&self, query: &NFTParam) -> Result<VecWith<DirectBuy>> {
+        self.post("/nft/direct/buy", query).await
+    }
+
+    pub async fn nft_price_history(&self, query: &NftPriceHistoryQuery) -> Result<Vec<NFTPrice>> {
+        self.post("/nft/price-history", query).await
+    }
+
+    pub async fn collections(
+        &self,
+        query: &ListCollectionsParams,
+    ) -> Result<VecWithTotal<CollectionDetails>> {
+        self.get("/collections", &query.to_query()).await
+    }
+
+    pub async fn collections_simple(
+        &self,
+        query: &ListCollectionsSimpleParams,
+    ) -> Result<VecWithTotal<CollectionSimple>> {
+        self.post("/collections/simple", query).await
+    }
+
+    pub async fn collection(&self, collection: &str) -> Result<CollectionDetails> {
+        let param = CollectionParam {
+            collection: collection.to_string(),
+        };
+        self.post("/collection/details", &param).await
+    }
+
+    pub async fn collections_by_owner(
+        &self,
+        query: &OwnerParam,
+    ) -> Result<VecWithTotal<Collection>> {
+        self.post("/collections/by-owner", query).await
+    }
+
+    /// Requires a token of the collection owner
+    pub async fn upsert_collection_custom(
+        &self,
+        payload: &UpsertCollectionCustomPayload,
+    ) -> Result<()> {
+        let request = self
+            .request(Method::POST, "/collections-custom")
+            .json(payload);
+        self.send(request).await.map(drop)
+    }
+
+    /// Requires a token of the collection owner or an admin
+    pub async fn collection_custom_history(
+        &self,
+        collection: &str,
+        query: &CustomHistoryQuery,
+    ) -> Result<VecWithTotal<WriteAuditEntry>> {
+        self.get(&format!("/collection/{collection}/custom/history"), query)
+            .await
+    }
+
+    /// Requires a token of the collection owner or an admin
+    pub async fn revert_collection_custom(
+        &self,
+        collection: &str,
+        payload: &RevertCollectionCustomPayload,
+    ) -> Result<()> {
+        let request = self
+            .request(
+                Method::POST,
+                &format!("/collection/{collection}/custom/revert"),
+            )
+            .json(payload);
+        self.send(request).await.map(drop)
+    }
+
+    /// Requires a token, answers `RateLimited` while the nft is in its cooldown
+    pub async fn refresh_metadata(&self, nft: &str) -> Result<MetadataRefresh> {
+        let request = self.request(Method::POST, &format!("/nft/{nft}/refresh-metadata"));
+        self.json(request).await
+    }
+
+    /// Most recent refresh job of the nft
+    pub async fn metadata_refresh(&self, nft: &str) -> Result<MetadataRefresh> {
+        let request = self.request(Method::GET, &format!("/nft/{nft}/refresh-metadata"));
+        self.json(request).await
+    }
+
+    /// Requires a token of the collection owner or an admin
+    pub async fn refresh_collection_metadata(
+        &self,
+        collection: &str,
+    ) -> Result<MetadataRefreshBatch> {
+        let request = self.request(
+            Method::POST,
+            &format!("/collection/{collection}/refresh-metadata"),
+        );
+        self.json(request).await
+    }
+
+    pub async fn quote(&self, query: &QuoteQuery) -> Result<SaleQuote> {
+        self.get("/quote", query).await
+    }
+
+    /// The nft lists are filled when the client's token is the collection owner's
+    pub async fn metadata_report(
+        &self,
+        collection: &str,
+        query: &MetadataReportQuery,
+    ) -> Result<CollectionMetadataReport> {
+        self.get(&format!("/collection/{collection}/metadata-report"), query)
+            .await
+    }
+
+    pub async fn orderbook(
+        &self,
+        collection: &str,
+        query: &OrderBookQuery,
+    ) -> Result<CollectionOrderBook> {
+        self.get(&format!("/collection/{collection}/orderbook"), query)
+            .await
+    }
+
+    pub async fn metadata_overrides(
+        &self,
+        collection: &str,
+        query: &CustomHistoryQuery,
+    ) -> Result<VecWithTotal<NftMetadataOverride>> {
+        self.get(
+            &format!("/collection/{collection}/metadata-overrides"),
+            query,
+        )
+        .await
+    }
+
+    /// Requires a token of the collection owner
+    pub async fn write_metadata_overrides(
+        &self,
+        collection: &str,
+        items: &[NftMetadataOverrideItem],
+    ) -> Result<NftMetadataOverrideBatch> {
+        self.post(
+            &format!("/collection/{collection}/metadata-overrides"),
+            &items,
+        )
+        .await
+    }
+
+    /// Same as `write_metadata_overrides` with a csv file, the first line names the columns
+    pub async fn write_metadata_overrides_csv(
+        &self,
+        collection: &str,
+        csv: impl Into<String>,
+    ) -> Result<NftMetadataOverrideBatch> {
+        let request = self
+            .request(
+                Method::POST,
+                &format!("/collection/{collection}/metadata-overrides"),
+            )
+            .header(CONTENT_TYPE, "text/csv")
+            .body(csv.into());
+        self.json(request).await
+    }
+
+    /// Requires a token of the collection owner
+    pub async fn set_metadata_override(
+        &self,
+        nft: &str,
+        payload: &NftMetadataOverridePayload,
+    ) -> Result<()> {
+        let request = self
+            .request(Method::POST, &format!("/nft/{nft}/metadata-override"))
+            .json(payload);
+        self.send(request).await.map(drop)
+    }
+
+    /// Requires a token of the collection owner
+    pub async fn remove_metadata_override(&self, nft: &str) -> Result<()> {
+        let request = self.request(
+            Method::POST,
+            &format!("/nft/{nft}/metadata-override/remove"),
+        );
+        self.send(request).await.map(drop)
+    }
+
+    pub async fn auctions(&self, query: &AuctionsQuery) -> Result<VecWith<Auction>> {
+        self.post("/auctions", query).await
+    }
+
+    pub async fn auction(&self, auction: &str) -> Result<GetAuctionResult> {
+        self.get("/auction", &[("auction", auction)]).await
+    }
+
+    pub async fn auction_bids(&self, query: &AuctionBidsQuery) -> Result<VecWith<AuctionBid>> {
+        self.post("/auction/bids", query).await
+    }
+
+    pub async fn events(&self, query: &EventsQuery) -> Result<NftEvents> {
+        self.get("/events", &query.to_query()).await
+    }
+
+    pub async fn search(&self, text: &str) -> Result<SearchRes> {
+        let request = self.request(Method::POST, "/search").body(text.to_string());
+        self.json(request).await
+    }
+
+    /// Typed search, pass `next_cursor` back as `cursor` for the next page
+    pub async fn search_query(&self, query: &SearchQuery) -> Result<SearchRes> {
+        self.post("/search", query).await
+    }
+
+    /// Names completing `query.q`, served from the in-memory index of the server
+    pub async fn search_suggest(&self, query: &SuggestQuery) -> Result<SuggestRes> {
+        self.get("/search/suggest", query).await
+    }
+
+    pub async fn owner_bids_out(&self, query: &OwnerBidsOutQuery) -> Result<VecWith<AuctionBid>> {
+        self.post("/owner/bids-out", query).await
+    }
+
+    pub async fn owner_bids_in(&self, query: &OwnerBidsInQuery) -> Result<VecWith<AuctionBid>> {
+        self.post("/owner/bids-in", query).await
+    }
+
+    pub async fn owner_direct_buys(
+        &self,
+        query: &OwnerDirectBuyQuery,
+    ) -> Result<VecWith<DirectBuy>> {
+        self.post("/owner/direct/buy", query).await
+    }
+
+    pub async fn owner_direct_buys_in(
+        &self,
+        query: &OwnerDirectBuyQuery,
+    ) -> Result<VecWith<DirectBuy>> {
+        self.post("/owner/direct/buy-in", query).await
+    }
+
+    pub async fn owner_direct_sells(
+        &self,
+        query: &OwnerDirectSellQuery,
+    ) -> Result<VecWith<DirectSell>> {
+        self.post("/owner/direct/sell", query).await
+    }
+
+    pub async fn owner_fee(&self, query: &OwnerFeeQuery) -> Result<OwnerFee> {
+        self.get("/owner/fee", query).await
+    }
+
+    pub async fn metrics_summary(&self, query: &MetricsSummaryQuery) -> Result<MetricsSummaryBase> {
+        self.get("/metrics/summary", query).await
+    }
+
+    pub async fn token_price_history(
+        &self,
+        token: &str,
+        query: &TokenPriceHistoryQuery,
+    ) -> Result<Vec<TokenPrice>> {
+        self.get(&format!("/tokens/{token}/price-history"), query)
+            .await
+    }
+
+    /// Users without a profile come back with the address only
+    pub async fn user(&self, address: &str) -> Result<UserDto> {
+        let request = self.request(Method::GET, &format!("/user/{address}"));
+        self.json(request).await
+    }
+
+    /// Requires a token of `payload.address`
+    pub async fn upsert_user(&self, payload: &UpsertUserPayload) -> Result<()> {
+        let request = self.request(Method::POST, "/user").json(payload);
+        self.send(request).await.map(drop)
+    }
+
+    /// Jwt to pass to [`Client::with_token`]
+    pub async fn sign_in(&self, payload: &SignInPayload) -> Result<String> {
+        self.post("/user/sign_in", payload).await
+    }
+
+    pub async fn roots(&self) -> Result<Roots> {
+        self.json(self.request(Method::GET, "/roots")).await
+    }
+
+    pub async fn graphql(&self, request: &GraphQLRequest) -> Result<GraphQLResponse> {
+        self.post("/graphql", request).await
+    }
+
+    /// Requires a token of the collection owner
+    pub async fn create_verification_request(
+        &self,
+        payload: &CreateVerificationRequestPayload,
+    ) -> Result<VerificationRequest> {
+        let request = self
+            .request(Method::POST, "/collections/verification-requests")
+            .json(payload);
+        self.json(request).await
+    }
+
+    /// Requests sent by the signed in address
+    pub async fn verification_requests(
+        &self,
+        query: &VerificationRequestsQuery,
+    ) -> Result<VecWithTotal<VerificationRequest>> {
+        self.get("/collections/verification-requests", query).await
+    }
+
+    /// Requires a signed in address, one open report per target and reporter
+    pub async fn create_report(&self, payload: &CreateReportPayload) -> Result<Report> {
+        self.post("/reports", payload).await
+    }
+
+    /// Admin routes below require a token of an address listed in `admin_addresses`
+    pub async fn set_collection_verified(
+        &self,
+        payload: &SetCollectionVerifiedPayload,
+    ) -> Result<()> {
+        let request = self
+            .request(Method::POST, "/admin/collections/verified")
+            .json(payload);
+        self.send(request).await.map(drop)
+    }
+
+    pub async fn upsert_root(&self, payload: &UpsertRootPayload) -> Result<()> {
+        let request = self.request(Method::POST, "/admin/roots").json(payload);
+        self.send(request).await.map(drop)
+    }
+
+    pub async fn remove_root(&self, payload: &RemoveRootPayload) -> Result<()> {
+        let request = self
+            .request(Method::POST, "/admin/roots/remove")
+            .json(payload);
+        self.send(request).await.map(drop)
+    }
+
+    pub async fn refresh_nft_verified(&self) -> Result<()> {
+        let request = self.request(Method::POST, "/admin/nft-verified/refresh");
+        self.send(request).await.map(drop)
+    }
+
+    pub async fn admin_verification_requests(
+        &self,
+        query: &VerificationRequestsQuery,
+    ) -> Result<VecWithTotal<VerificationRequest>> {
+        self.get("/admin/verification-requests", query).await
+    }
+
+    pub async fn approve_verification_request(
+        &self,
+        payload: &ReviewVerificationRequestPayload,
+    ) -> Result<VerificationRequest> {
+        self.post("/admin/verification-requests/approve", payload)
+            .await
+    }
+
+    pub async fn reject_verification_request(
+        &self,
+        payload: &ReviewVerificationRequestPayload,
+    ) -> Result<VerificationRequest> {
+        self.post("/admin/verification-requests/reject", payload)
+            .await
+    }
+
+    pub async fn admin_audit(
+        &self,
+        query: &AdminAuditQuery,
+    ) -> Result<VecWithTotal<AdminAuditEntry>> {
+        self.get("/admin/audit", query).await
+    }
+
+    pub async fn write_audit(
+        &self,
+        query: &WriteAuditQuery,
+    ) -> Result<VecWithTotal<WriteAuditEntry>> {
+        self.get("/admin/audit/writes", query).await
+    }
+
+    pub async fn reports(&self, query: &ReportsQuery) -> Result<VecWithTotal<Report>> {
+        self.get("/admin/moderation/reports", query).await
+    }
+
+    pub async fn set_moderation(&self, payload: &SetModerationPayload) -> Result<()> {
+        let request = self
+            .request(Method::POST, "/admin/moderation/status")
+            .json(payload);
+        self.send(request).await.map(drop)
+    }
+
+    pub async fn dismiss_report(&self, payload: &DismissReportPayload) -> Result<Report> {
+        self.post("/admin/moderation/reports/dismiss", payload)
+            .await
+    }
+
+    pub async fn moderation(&self, query: &ModerationQuery) -> Result<VecWithTotal<Moderation>> {
+        self.get("/admin/moderation", query).await
+    }
+}
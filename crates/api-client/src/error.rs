@@ -0,0 +1,61 @@
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::time::Duration;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("bad request: {0}")]
+    BadRequest(String),
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+    #[error("not found")]
+    NotFound,
+    #[error("rate limited: {message}")]
+    RateLimited {
+        /// Value of the `retry-after` header
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    #[error("server responded with {status}: {message}")]
+    Status { status: StatusCode, message: String },
+    #[error("unexpected response body: {0}")]
+    Decode(#[from] serde_json::Error),
+}
+
+/// Json body of the errors produced by the rejection handlers
+#[derive(Deserialize)]
+struct ErrorResponse {
+    message: String,
+}
+
+impl Error {
+    /// Error of a non-successful response, the handlers reply with plain text
+    /// while rejections carry a `{"message", "status"}` json body
+    pub(crate) fn from_response(
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        body: &[u8],
+    ) -> Self {
+        let message = match serde_json::from_slice::<ErrorResponse>(body) {
+            Ok(e) => e.message,
+            Err(_) => String::from_utf8_lossy(body).into_owned(),
+        };
+        match status {
+            StatusCode::BAD_REQUEST => Error::BadRequest(message),
+            StatusCode::UNAUTHORIZED => Error::Unauthorized(message),
+            StatusCode::FORBIDDEN => Error::Forbidden(message),
+            StatusCode::NOT_FOUND => Error::NotFound,
+            StatusCode::TOO_MANY_REQUESTS => Error::RateLimited {
+                retry_after,
+                message,
+            },
+            status => Error::Status { status, message },
+        }
+    }
+}
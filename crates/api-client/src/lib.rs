@@ -0,0 +1,13 @@
+//! Async client of the marketplace API.
+//!
+//! Requests and responses are the `api-types` models shared with the server, re-exported
+//! as [`types`]. Lists can be walked page by page with [`fetch_all`].
+mod client;
+mod error;
+mod pagination;
+mod query;
+
+pub use self::client::{Client, API_KEY_HEADER};
+pub use self::error::{Error, Result};
+pub use self::pagination::{fetch_all, Page, Paginated};
+pub use api_types as types;
@@ -0,0 +1,129 @@
+use crate::Result;
+use api_types::{
+    AuctionBidsQuery, AuctionsQuery, EventsQuery, ListCollectionsParams,
+    ListCollectionsSimpleParams, NFTListQuery, NFTParam, NftEvents, OwnerBidsInQuery,
+    OwnerBidsOutQuery, OwnerDirectBuyQuery, OwnerDirectSellQuery, OwnerParam, VecWith,
+    VecWithTotal,
+};
+use std::future::Future;
+
+/// Response holding one page of a list along with the total count
+pub trait Page {
+    type Item;
+
+    fn total_count(&self) -> i64;
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+impl<T> Page for VecWith<T> {
+    type Item = T;
+
+    fn total_count(&self) -> i64 {
+        self.count
+    }
+
+    fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> Page for VecWithTotal<T> {
+    type Item = T;
+
+    fn total_count(&self) -> i64 {
+        self.count
+    }
+
+    fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl Page for NftEvents {
+    type Item = api_types::NftEvent;
+
+    fn total_count(&self) -> i64 {
+        self.total_rows
+    }
+
+    fn into_items(self) -> Vec<Self::Item> {
+        self.data
+    }
+}
+
+/// Request of a list taking `limit` and `offset`
+pub trait Paginated: Clone {
+    fn set_page(&mut self, limit: usize, offset: usize);
+}
+
+macro_rules! paginated {
+    ($($query:ty),*) => {
+        $(
+            impl Paginated for $query {
+                fn set_page(&mut self, limit: usize, offset: usize) {
+                    self.limit = Some(limit);
+                    self.offset = Some(offset);
+                }
+            }
+        )*
+    };
+}
+
+paginated!(
+    AuctionBidsQuery,
+    AuctionsQuery,
+    EventsQuery,
+    ListCollectionsParams,
+    ListCollectionsSimpleParams,
+    NFTListQuery,
+    NFTParam,
+    OwnerBidsInQuery,
+    OwnerBidsOutQuery,
+    OwnerDirectBuyQuery,
+    OwnerDirectSellQuery,
+    OwnerParam
+);
+
+/// Requests the pages of `query` one by one from the first until a short
+/// page comes back or the total count is reached.
+///
+/// ```no_run
+/// # async fn run(client: api_client::Client) -> api_client::Result<()> {
+/// use api_client::types::EventsQuery;
+///
+/// let events = api_client::fetch_all(EventsQuery::default(), 100, |query| {
+///     let client = client.clone();
+///     async move { client.events(&query).await }
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn fetch_all<Q, P, F, Fut>(
+    query: Q,
+    page_size: usize,
+    mut fetch: F,
+) -> Result<Vec<P::Item>>
+where
+    Q: Paginated,
+    P: Page,
+    F: FnMut(Q) -> Fut,
+    Fut: Future<Output = Result<P>>,
+{
+    let page_size = page_size.max(1);
+    let mut query = query;
+    let mut offset = 0;
+    let mut items = Vec::new();
+    loop {
+        query.set_page(page_size, offset);
+        let page = fetch(query.clone()).await?;
+        let total_count = usize::try_from(page.total_count()).unwrap_or_default();
+        let page = page.into_items();
+        let received = page.len();
+        items.extend(page);
+        offset += received;
+        if received < page_size || offset >= total_count {
+            return Ok(items);
+        }
+    }
+}
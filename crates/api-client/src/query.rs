@@ -0,0 +1,117 @@
+use api_types::{AuctionBidsQuery, EventsQuery, ListCollectionsParams, NFTListQuery, NFTParam};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Requests of the GET endpoints encoded the way the server reads them back:
+/// lists as repeated keys, nested objects with dots and attribute filters as
+/// `attributes[Trait]=Value`. Empty lists are left out and read back as absent.
+pub(crate) trait ToQuery {
+    fn to_query(&self) -> Vec<(String, String)>;
+}
+
+#[derive(Default)]
+struct Pairs(Vec<(String, String)>);
+
+impl Pairs {
+    fn add<T: Serialize>(&mut self, key: &str, value: &T) {
+        match serde_json::to_value(value) {
+            Ok(Value::Null) | Err(_) => {}
+            Ok(Value::String(value)) => self.0.push((key.to_string(), value)),
+            Ok(value) => self.0.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    fn list<T: Serialize>(&mut self, key: &str, values: &Option<Vec<T>>) {
+        for value in values.iter().flatten() {
+            self.add(key, value);
+        }
+    }
+}
+
+impl ToQuery for NFTListQuery {
+    fn to_query(&self) -> Vec<(String, String)> {
+        let mut q = Pairs::default();
+        q.list("owners", &self.owners);
+        q.list("collections", &self.collections);
+        q.add("priceFrom", &self.price_from);
+        q.add("priceTo", &self.price_to);
+        q.add("priceToken", &self.price_token);
+        q.add("forsale", &self.forsale);
+        q.add("auction", &self.auction);
+        q.add("verified", &self.verified);
+        q.add("limit", &self.limit);
+        q.add("offset", &self.offset);
+        for attribute in self.attributes.iter().flatten() {
+            let key = format!("attributes[{}]", attribute.trait_type);
+            for value in &attribute.trait_values {
+                q.add(&key, value);
+            }
+        }
+        if let Some(order) = &self.order {
+            q.add("order.field", &order.field);
+            q.add("order.direction", &order.direction);
+        }
+        q.add("withCount", &self.with_count);
+        q.list("media", &self.media);
+        q.add("text", &self.text);
+        q.add("mintedFrom", &self.minted_from);
+        q.add("mintedTo", &self.minted_to);
+        q.add("nftIdFrom", &self.nft_id_from);
+        q.add("nftIdTo", &self.nft_id_to);
+        q.add("manager", &self.manager);
+        q.0
+    }
+}
+
+impl ToQuery for ListCollectionsParams {
+    fn to_query(&self) -> Vec<(String, String)> {
+        let mut q = Pairs::default();
+        q.add("name", &self.name);
+        q.list("owners", &self.owners);
+        q.add("verified", &self.verified);
+        q.list("collections", &self.collections);
+        q.add("limit", &self.limit);
+        q.add("offset", &self.offset);
+        if let Some(order) = &self.order {
+            q.add("order.field", &order.field);
+            q.add("order.direction", &order.direction);
+        }
+        q.0
+    }
+}
+
+impl ToQuery for EventsQuery {
+    fn to_query(&self) -> Vec<(String, String)> {
+        let mut q = Pairs::default();
+        q.add("owner", &self.owner);
+        q.list("collections", &self.collections);
+        q.add("nft", &self.nft);
+        q.list("types", &self.event_type);
+        q.add("limit", &self.limit);
+        q.add("offset", &self.offset);
+        q.add("withCount", &self.with_count);
+        q.add("verified", &self.verified);
+        q.0
+    }
+}
+
+impl ToQuery for NFTParam {
+    fn to_query(&self) -> Vec<(String, String)> {
+        let mut q = Pairs::default();
+        q.add("nft", &self.nft);
+        q.list("status", &self.status);
+        q.add("limit", &self.limit);
+        q.add("offset", &self.offset);
+        q.0
+    }
+}
+
+impl ToQuery for AuctionBidsQuery {
+    fn to_query(&self) -> Vec<(String, String)> {
+        let mut q = Pairs::default();
+        q.add("auction", &self.auction);
+        q.add("limit", &self.limit);
+        q.add("offset", &self.offset);
+        q.0
+    }
+}
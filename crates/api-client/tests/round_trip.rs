@@ -0,0 +1,678 @@
+//! The client against the api's own router served over `InMemoryRepo`: requests are parsed
+//! by the real handlers, responses are decoded from what they serialize.
+use api::cfg::{BucketConfig, RateLimitConfig, VersioningConfig};
+use api::db::memory::{InMemoryRepo, MemoryState};
+use api::db::queries::Queries;
+use api::db::*;
+use api::graphql::build_schema;
+use api::handlers::register_routes;
+use api::handlers::router::{RouteContext, RouterRegistry};
+use api::model::NftEvent;
+use api::services::auth::AuthService;
+use api::services::error::handle_limit_rejection;
+use api::services::rate_limit::{ClientIpSource, RateLimiter};
+use api::services::suggest::SuggestIndex;
+use api::token::TokenDict;
+use api_client::types::{
+    AttributeFilter, CollectionListOrder, CollectionListOrderField, DirectBuyState, EventsQuery,
+    ListCollectionsParams, ListCollectionsSimpleParams, MetricsSummaryQuery, NFTListOrder,
+    NFTListOrderField, NFTListQuery, NFTParam, NFTSellCountQuery, NFTTopListQuery, NftEventType,
+    NftMediaKind, OrderDirection, OwnerBidsOutQuery, OwnerFeeQuery, PriceHistoryScale, RootType,
+    SearchEntity, SearchQuery, SignInPayload, Social, SuggestQuery, TokenPriceHistoryQuery,
+    UpsertCollectionCustomPayload,
+};
+use api_client::{fetch_all, Client, Error};
+use chrono::{DateTime, NaiveDateTime};
+use moka::future::Cache;
+use serde_json::{json, Value};
+use sqlx::types::BigDecimal;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::Filter;
+
+const VERIFIED: &str = "0:c1";
+const OTHER: &str = "0:c2";
+const UNVERIFIED: &str = "0:c3";
+const ALICE: &str = "0:a1";
+const BOB: &str = "0:b0";
+const MANAGER: &str = "0:m0";
+const TOKEN: &str = "0:70";
+
+struct Server {
+    client: Client,
+    repo: InMemoryRepo,
+    auth: Arc<AuthService>,
+}
+
+impl Server {
+    fn bearer(&self, address: &str) -> Client {
+        self.client
+            .clone()
+            .with_token(self.auth.create_jwt(address))
+    }
+}
+
+/// Serves the registered routes the way `main` does: behind the rate limiter, under the
+/// `/v1`, `/v2` and legacy prefixes. Queries backing graphql and api keys never connect
+async fn start(state: MemoryState, rate_limit: RateLimitConfig) -> Server {
+    let repo = InMemoryRepo::new(TokenDict::new(vec![]), state);
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(200))
+        .connect_lazy("postgres://localhost/test")
+        .expect("lazy pool");
+    let queries = Queries::new(Arc::new(pool), TokenDict::new(vec![]));
+    let rate_limiter = RateLimiter::new(queries.clone(), rate_limit);
+    let auth = Arc::new(AuthService::new(60, "secret".into(), String::new()));
+    let suggest = SuggestIndex::new(100);
+    suggest.refresh(&repo).await.expect("suggestions load");
+    let ctx = RouteContext {
+        db: repo.clone(),
+        auth: auth.clone(),
+        graphql: build_schema(queries),
+        rate_limiter: rate_limiter.clone(),
+        cache_5_minutes: Cache::new(0),
+        cache_minute: Cache::new(0),
+        cache_10_sec: Cache::new(0),
+        cache_1_sec: Cache::new(0),
+        collections_cache: Cache::new(0),
+        realtime_ttl: Duration::from_secs(1),
+        client_ip: ClientIpSource::Peer,
+        suggest,
+        metadata_refresh_cooldown: Duration::from_secs(1),
+    };
+    let mut registry = RouterRegistry::default();
+    register_routes(&ctx, &mut registry);
+    let api = rate_limiter
+        .filter()
+        .and(registry.build(&VersioningConfig::default()))
+        .recover(handle_limit_rejection);
+
+    let (addr, server) = warp::serve(api).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    Server {
+        client: Client::new(format!("http://{addr}")),
+        repo,
+        auth,
+    }
+}
+
+fn no_limits() -> RateLimitConfig {
+    RateLimitConfig {
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+fn ts(secs: i64) -> NaiveDateTime {
+    DateTime::from_timestamp(secs, 0)
+        .expect("valid timestamp")
+        .naive_utc()
+}
+
+fn nft(address: &str, collection: &str, owner: &str, name: &str) -> NftDetails {
+    NftDetails {
+        address: Some(address.to_string()),
+        collection: Some(collection.to_string()),
+        meta: Some(json!({
+            "files": [{ "source": format!("https://img/{address}.mp4"), "mimetype": "video/mp4" }],
+            "attributes": [{ "trait_type": "Background", "value": "Blue sky" }],
+        })),
+        owner: Some(owner.to_string()),
+        manager: Some(MANAGER.to_string()),
+        name: Some(name.to_string()),
+        burned: Some(false),
+        description: None,
+        updated: Some(ts(1_700_000_000)),
+        tx_lt: Some(1),
+        auction: None,
+        forsale: Some(format!("{address}:sell")),
+        auction_status: None,
+        forsale_status: Some(DirectSellState::Active),
+        best_offer: None,
+        floor_price_usd: None,
+        deal_price_usd: None,
+        total_count: 0,
+        floor_price: None,
+        floor_price_token: None,
+        nft_id: Some("42".to_string()),
+        meta_override: None,
+    }
+}
+
+fn collection(address: &str, owner: &str, verified: bool) -> NftCollectionDetails {
+    NftCollectionDetails {
+        address: Some(address.to_string()),
+        owner: Some(owner.to_string()),
+        name: Some(format!("Collection {address}")),
+        description: None,
+        created: Some(ts(1_690_000_000)),
+        updated: Some(ts(1_690_000_000)),
+        verified: Some(verified),
+        wallpaper: None,
+        logo: None,
+        owners_count: Some(2),
+        nft_count: Some(5),
+        max_price: None,
+        total_price: None,
+        floor_price_usd: None,
+        total_volume_usd: None,
+        attributes: None,
+        cnt: 0,
+        previews: json!([]),
+        first_mint: Some(ts(1_690_000_000)),
+        social: None,
+    }
+}
+
+fn mint(id: i64, nft: &str, secs: i64) -> NftEvent {
+    serde_json::from_value(json!({
+        "id": id,
+        "eventType": "mint",
+        "name": null,
+        "description": null,
+        "datetime": secs,
+        "address": nft,
+        "previewUrl": null,
+        "directSell": null,
+        "directBuy": null,
+        "auction": null,
+        "mint": { "owner": ALICE, "creator": ALICE },
+        "transfer": null,
+    }))
+    .expect("valid event")
+}
+
+fn direct_sell(nft: &NftDetails, price: i64) -> NftDirectSell {
+    NftDirectSell {
+        address: nft.forsale.clone().expect("for sale"),
+        nft: nft.address.clone().expect("address"),
+        collection: nft.collection.clone(),
+        seller: nft.owner.clone(),
+        price_token: TOKEN.to_string(),
+        price: BigDecimal::from(price),
+        usd_price: None,
+        state: DirectSellState::Active,
+        valid: true,
+        created: ts(1_700_000_000),
+        updated: ts(1_700_000_000),
+        finished_at: None,
+        expired_at: None,
+        tx_lt: 1,
+        cnt: 0,
+        fee_numerator: None,
+        fee_denominator: None,
+    }
+}
+
+fn direct_buy(address: &str, nft: &str, state: DirectBuyState) -> NftDirectBuy {
+    NftDirectBuy {
+        address: address.to_string(),
+        nft: nft.to_string(),
+        collection: Some(VERIFIED.to_string()),
+        buyer: Some(BOB.to_string()),
+        price_token: TOKEN.to_string(),
+        price: BigDecimal::from(40),
+        usd_price: None,
+        state,
+        valid: true,
+        created: ts(1_700_000_000),
+        updated: ts(1_700_000_000),
+        finished_at: None,
+        expired_at: None,
+        tx_lt: 1,
+        cnt: 0,
+        fee_numerator: None,
+        fee_denominator: None,
+    }
+}
+
+fn auction(address: &str, nft: &str) -> NftAuction {
+    NftAuction {
+        address: Some(address.to_string()),
+        nft: Some(nft.to_string()),
+        wallet_for_bids: None,
+        price_token: Some(TOKEN.to_string()),
+        start_price: Some(BigDecimal::from(100)),
+        max_bid: Some(BigDecimal::from(150)),
+        min_bid: Some(BigDecimal::from(100)),
+        start_usd_price: None,
+        max_usd_bid: None,
+        min_usd_bid: None,
+        status: Some(AuctionStatus::Active),
+        created_at: Some(ts(1_700_000_000)),
+        finished_at: Some(ts(1_800_000_000)),
+        tx_lt: Some(1),
+        bids_count: Some(2),
+        last_bid_from: Some(BOB.to_string()),
+        last_bid_ts: Some(ts(1_700_000_100)),
+        last_bid_value: Some(BigDecimal::from(150)),
+        last_bid_usd_value: None,
+        cnt: 0,
+        fee_numerator: None,
+        fee_denominator: None,
+    }
+}
+
+fn bid(price: i64, active: bool) -> NftAuctionBid {
+    NftAuctionBid {
+        auction: "0:au1".to_string(),
+        buyer: BOB.to_string(),
+        price: BigDecimal::from(price),
+        usd_price: None,
+        next_bid_value: BigDecimal::from(price + 10),
+        next_bid_usd_value: None,
+        created_at: ts(1_700_000_000 + price),
+        tx_lt: price,
+        active,
+        cnt: 0,
+    }
+}
+
+/// `0:n1` passes every filter of `NFTListQuery`, each of the other nfts fails one of them
+fn state() -> MemoryState {
+    let target = nft("0:n1", VERIFIED, ALICE, "Space Cat 7");
+    let decoy = |address: &str, change: &dyn Fn(&mut NftDetails)| {
+        let mut nft = nft(address, VERIFIED, ALICE, "Space Cat 8");
+        change(&mut nft);
+        nft
+    };
+    let nfts = vec![
+        target.clone(),
+        decoy("0:d_owner", &|n| n.owner = Some(BOB.to_string())),
+        decoy("0:d_collection", &|n| {
+            n.collection = Some(OTHER.to_string())
+        }),
+        decoy("0:d_verified", &|n| {
+            n.collection = Some(UNVERIFIED.to_string())
+        }),
+        decoy("0:d_forsale", &|n| {
+            n.forsale = None;
+            n.forsale_status = None;
+        }),
+        decoy("0:d_attribute", &|n| {
+            n.meta.as_mut().expect("meta")["attributes"][0]["value"] = json!("Red");
+        }),
+        decoy("0:d_media", &|n| {
+            n.meta.as_mut().expect("meta")["files"][0]["mimetype"] = json!("image/png");
+        }),
+        decoy("0:d_text", &|n| n.name = Some("Space Dog".to_string())),
+        decoy("0:d_id", &|n| n.nft_id = Some("500".to_string())),
+        decoy("0:d_manager", &|n| n.manager = Some(BOB.to_string())),
+        decoy("0:d_minted", &|_| {}),
+    ];
+    let mut events: Vec<NftEvent> = nfts
+        .iter()
+        .enumerate()
+        .map(|(i, n)| {
+            mint(
+                i as i64 + 1,
+                n.address.as_deref().expect("address"),
+                1_700_100_000,
+            )
+        })
+        .collect();
+    events.last_mut().expect("minted decoy").datetime = 1_600_000_000;
+
+    let mut top = target.clone();
+    top.address = Some("0:top".to_string());
+    top.forsale = None;
+    top.deal_price_usd = Some(BigDecimal::from(12));
+
+    MemoryState {
+        direct_sells: vec![direct_sell(&target, 50), direct_sell(&nfts[1], 500)],
+        nfts: nfts.into_iter().chain([top]).collect(),
+        collections: vec![
+            collection(VERIFIED, ALICE, true),
+            collection(OTHER, ALICE, true),
+            collection(UNVERIFIED, BOB, false),
+        ],
+        events,
+        direct_buys: vec![
+            direct_buy("0:db1", "0:n1", DirectBuyState::Active),
+            direct_buy("0:db2", "0:n1", DirectBuyState::Cancelled),
+            direct_buy("0:db3", "0:n1", DirectBuyState::Expired),
+        ],
+        auctions: vec![auction("0:au1", "0:d_owner")],
+        bids: vec![bid(100, false), bid(150, true)],
+        token_prices: vec![
+            TokenUsdPrice {
+                token: TOKEN.to_string(),
+                usd_price: BigDecimal::from(2),
+                ts: ts(1_700_000_000),
+            },
+            TokenUsdPrice {
+                token: TOKEN.to_string(),
+                usd_price: BigDecimal::from(3),
+                ts: ts(1_800_000_000),
+            },
+        ],
+        default_fee: Some((25, 1000)),
+        ..Default::default()
+    }
+}
+
+fn addresses<T>(items: &[T], address: impl Fn(&T) -> &str) -> Vec<String> {
+    items.iter().map(|i| address(i).to_string()).collect()
+}
+
+#[tokio::test]
+async fn nft_list_query_round_trips() {
+    let server = start(state(), no_limits()).await;
+
+    let query = NFTListQuery {
+        owners: Some(vec![ALICE.to_string()]),
+        collections: Some(vec![VERIFIED.to_string(), UNVERIFIED.to_string()]),
+        price_from: None,
+        price_to: None,
+        price_token: None,
+        forsale: Some(true),
+        auction: Some(false),
+        verified: Some(true),
+        limit: Some(20),
+        offset: Some(0),
+        attributes: Some(vec![AttributeFilter {
+            trait_type: "Background".to_string(),
+            trait_values: vec!["Blue sky".to_string(), "007".to_string()],
+        }]),
+        order: Some(NFTListOrder {
+            field: NFTListOrderField::Name,
+            direction: OrderDirection::Desc,
+        }),
+        with_count: Some(true),
+        media: Some(vec![NftMediaKind::Video, NftMediaKind::Model]),
+        text: Some("space cat".to_string()),
+        minted_from: Some(1_700_000_000),
+        minted_to: Some(1_700_600_000),
+        nft_id_from: Some(10),
+        nft_id_to: Some(99),
+        manager: Some(MANAGER.to_string()),
+    };
+    let nfts = server.client.nfts(&query).await.expect("nfts");
+    assert_eq!(addresses(&nfts.items, |n| &n.contract.address), ["0:n1"]);
+    assert_eq!(nfts.count, 1);
+    assert_eq!(nfts.items[0].forsale.as_deref(), Some("0:n1:sell"));
+
+    let page = NFTListQuery {
+        owners: None,
+        collections: Some(vec![VERIFIED.to_string()]),
+        limit: Some(2),
+        offset: Some(1),
+        order: Some(NFTListOrder {
+            field: NFTListOrderField::Name,
+            direction: OrderDirection::Asc,
+        }),
+        text: Some("dog".to_string()),
+        ..query
+    };
+    let nfts = server.client.nfts(&page).await.expect("nfts");
+    assert!(nfts.items.is_empty(), "only one space dog to skip");
+}
+
+#[tokio::test]
+async fn get_queries_round_trip() {
+    let server = start(state(), no_limits()).await;
+
+    let collections = ListCollectionsParams {
+        name: Some("c1".to_string()),
+        owners: Some(vec![ALICE.to_string()]),
+        verified: Some(true),
+        collections: Some(vec![VERIFIED.to_string(), UNVERIFIED.to_string()]),
+        limit: Some(5),
+        offset: Some(0),
+        order: Some(CollectionListOrder {
+            field: CollectionListOrderField::FirstMint,
+            direction: OrderDirection::Asc,
+        }),
+    };
+    let page = server
+        .client
+        .collections(&collections)
+        .await
+        .expect("collections");
+    assert_eq!(
+        addresses(&page.items, |c| &c.collection.contract.address),
+        [VERIFIED]
+    );
+
+    let events = EventsQuery {
+        owner: Some(ALICE.to_string()),
+        collections: Some(vec![VERIFIED.to_string()]),
+        nft: None,
+        event_type: Some(vec![NftEventType::Mint, NftEventType::AuctionBidPlaced]),
+        limit: Some(3),
+        offset: Some(1),
+        with_count: Some(true),
+        verified: Some(true),
+    };
+    let page = server.client.events(&events).await.expect("events");
+    // the owner's mints in the verified collection, minus the burned and the foreign ones
+    assert_eq!(page.total_rows, 8);
+    assert_eq!(page.data.len(), 3);
+
+    let param = NFTParam {
+        nft: "0:n1".to_string(),
+        status: Some(vec![DirectBuyState::Active, DirectBuyState::Cancelled]),
+        limit: None,
+        offset: None,
+    };
+    let details = server.client.nft(&param).await.expect("nft");
+    assert_eq!(details.nft.contract.address, "0:n1");
+    assert_eq!(details.collection.len(), 1);
+    let buys = server
+        .client
+        .nft_direct_buys(&param)
+        .await
+        .expect("direct buys");
+    let mut found = addresses(&buys.items, |b| &b.address);
+    found.sort();
+    assert_eq!(found, ["0:db1", "0:db2"]);
+}
+
+#[tokio::test]
+async fn flat_queries_round_trip() {
+    let server = start(state(), no_limits()).await;
+
+    let count = server
+        .client
+        .nfts_sell_count(&NFTSellCountQuery { max_price: 100 })
+        .await
+        .expect("sell count");
+    assert_eq!(count.count, 1);
+
+    let fee = OwnerFeeQuery {
+        owner: ALICE.to_string(),
+        root_code: RootType::Auction,
+    };
+    let owner_fee = server.client.owner_fee(&fee).await.expect("owner fee");
+    assert_eq!(
+        (owner_fee.fee.numerator, owner_fee.fee.denominator),
+        (25, 1000)
+    );
+
+    let metrics = MetricsSummaryQuery {
+        from: 1,
+        to: 2,
+        limit: 2,
+        offset: 1,
+    };
+    let summary = server
+        .client
+        .metrics_summary(&metrics)
+        .await
+        .expect("metrics");
+    assert_eq!(summary.total_rows_count, 3);
+    assert_eq!(summary.data.len(), 2);
+
+    let history = TokenPriceHistoryQuery {
+        from: 1_600_000_000,
+        to: 1_750_000_000,
+        scale: Some(PriceHistoryScale::Days),
+    };
+    let prices = server
+        .client
+        .token_price_history(TOKEN, &history)
+        .await
+        .expect("price history");
+    assert_eq!(prices.len(), 1);
+    assert_eq!(prices[0].ts, 1_700_000_000);
+
+    let suggest = SuggestQuery {
+        q: "space c".to_string(),
+        limit: Some(3),
+    };
+    let names = server
+        .client
+        .search_suggest(&suggest)
+        .await
+        .expect("suggest");
+    assert_eq!(names.nfts.len(), 3);
+    assert!(names.nfts.iter().all(|n| n.name.starts_with("Space Cat")));
+}
+
+#[tokio::test]
+async fn post_bodies_round_trip() {
+    let server = start(state(), no_limits()).await;
+
+    let top = NFTTopListQuery {
+        from: 1,
+        limit: 10,
+        offset: 0,
+    };
+    let top = server.client.nfts_top(&top).await.expect("top");
+    assert_eq!(addresses(&top.items, |n| &n.contract.address), ["0:top"]);
+
+    let bids = OwnerBidsOutQuery {
+        owner: BOB.to_string(),
+        collections: None,
+        lastbid: Some(true),
+        limit: Some(5),
+        offset: None,
+    };
+    let bids = server.client.owner_bids_out(&bids).await.expect("bids");
+    assert_eq!(bids.items.len(), 1);
+    assert_eq!(bids.items[0].price, "150");
+
+    let found = server.client.search(VERIFIED).await.expect("search");
+    assert_eq!(found.items[0].address, VERIFIED);
+    assert_eq!(found.items[0].object_type, "collection");
+
+    let search = SearchQuery {
+        query: "space cat".to_string(),
+        types: vec![SearchEntity::Nft],
+        limit: Some(2),
+        ..Default::default()
+    };
+    let first = server.client.search_query(&search).await.expect("search");
+    assert_eq!(first.items.len(), 2);
+    let cursor = first.next_cursor.clone().expect("more results");
+    let next = SearchQuery {
+        cursor: Some(cursor),
+        ..search
+    };
+    let second = server.client.search_query(&next).await.expect("search");
+    let first: Vec<Value> = first.items.iter().map(|i| json!(i)).collect();
+    assert!(second.items.iter().all(|i| !first.contains(&json!(i))));
+}
+
+#[tokio::test]
+async fn credentials_are_sent() {
+    let server = start(state(), no_limits()).await;
+
+    let payload = UpsertCollectionCustomPayload {
+        address: VERIFIED.to_string(),
+        name: Some("Renamed".to_string()),
+        description: None,
+        wallpaper: None,
+        logo: None,
+        social: Some(Social {
+            twitter: Some("@name".to_string()),
+            ..Default::default()
+        }),
+    };
+    assert!(matches!(
+        server.client.upsert_collection_custom(&payload).await,
+        Err(Error::Unauthorized(_))
+    ));
+    assert!(server
+        .bearer(BOB)
+        .upsert_collection_custom(&payload)
+        .await
+        .is_err());
+
+    // the api key can't be looked up without a database, it's served as anonymous
+    server
+        .bearer(ALICE)
+        .with_api_key("partner")
+        .upsert_collection_custom(&payload)
+        .await
+        .expect("upsert");
+    let renamed = server
+        .client
+        .collection(VERIFIED)
+        .await
+        .expect("collection");
+    assert_eq!(renamed.collection.contract.name.as_deref(), Some("Renamed"));
+    assert_eq!(server.repo.state().write_audit.len(), 1);
+}
+
+#[tokio::test]
+async fn errors_are_typed() {
+    let server = start(
+        state(),
+        RateLimitConfig {
+            ip: BucketConfig {
+                per_sec: 0.001,
+                burst: 2,
+            },
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let sign_in = SignInPayload {
+        public_key: "not a key".to_string(),
+        address: ALICE.to_string(),
+        wallet_type: "EverWallet".to_string(),
+        timestamp: 1_700_000_000,
+        signature: "not a signature".to_string(),
+        with_signature_id: None,
+    };
+    assert!(matches!(
+        server.client.sign_in(&sign_in).await,
+        Err(Error::BadRequest(_))
+    ));
+
+    // the sign in took the first of the two tokens
+    server.client.roots().await.expect("roots");
+    match server.client.roots().await {
+        Err(Error::RateLimited { retry_after, .. }) => {
+            assert!(retry_after.is_some_and(|r| r > Duration::ZERO));
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn fetch_all_walks_the_pages() {
+    let mut state = state();
+    state.collections = (0..5)
+        .map(|i| collection(&format!("0:{i}"), ALICE, true))
+        .collect();
+    let server = start(state, no_limits()).await;
+
+    let client = server.client.clone();
+    let query = ListCollectionsSimpleParams {
+        name: Some("Collection".to_string()),
+        ..Default::default()
+    };
+    let collections = fetch_all(query, 2, |query| {
+        let client = client.clone();
+        async move { client.collections_simple(&query).await }
+    })
+    .await
+    .expect("all pages");
+
+    let mut found = addresses(&collections, |c| &c.address);
+    found.sort();
+    assert_eq!(found, ["0:0", "0:1", "0:2", "0:3", "0:4"]);
+}
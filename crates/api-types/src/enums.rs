@@ -0,0 +1,453 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "auction_status", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "snake_case")]
+pub enum AuctionStatus {
+    Active = 0,
+    Cancelled = 1,
+    Completed = 2,
+    Expired = 3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "direct_sell_state", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "snake_case")]
+pub enum DirectSellState {
+    Create = 0,
+    AwaitNft = 1,
+    Active = 2,
+    Filled = 3,
+    Cancelled = 4,
+    Expired = 5,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "direct_buy_state", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "snake_case")]
+pub enum DirectBuyState {
+    Create = 0,
+    AwaitTokens = 1,
+    Active = 2,
+    Filled = 3,
+    Cancelled = 4,
+    Expired = 5,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "t_root_types"))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RootType {
+    #[cfg_attr(feature = "sqlx", sqlx(rename = "auction"))]
+    Auction,
+    #[cfg_attr(feature = "sqlx", sqlx(rename = "buy"))]
+    Buy,
+    #[cfg_attr(feature = "sqlx", sqlx(rename = "sell"))]
+    Sell,
+}
+
+/// How an nft is sold, each is served by the market roots of one `RootType`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SaleType {
+    DirectSell,
+    Auction,
+    AcceptOffer,
+}
+
+impl SaleType {
+    pub fn root_type(self) -> RootType {
+        match self {
+            SaleType::DirectSell => RootType::Sell,
+            SaleType::Auction => RootType::Auction,
+            SaleType::AcceptOffer => RootType::Buy,
+        }
+    }
+}
+
+/// Changes recorded in `admin_audit`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "t_admin_action", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    CollectionVerified,
+    CollectionUnverified,
+    RootUpserted,
+    RootRemoved,
+    NftVerifiedRefreshed,
+    VerificationRejected,
+    ModerationChanged,
+    ReportDismissed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "t_verification_status", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// Set by the indexer, which picks the pending jobs up
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "t_metadata_refresh_status", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataRefreshStatus {
+    Pending,
+    Processing,
+    Done,
+    Failed,
+}
+
+impl MetadataRefreshStatus {
+    /// Not picked up or not finished yet
+    pub fn is_open(&self) -> bool {
+        matches!(self, Self::Pending | Self::Processing)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "t_moderation_target", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationTarget {
+    Nft,
+    Collection,
+    User,
+}
+
+/// Flagged items stay listed, hidden ones are left out of the public lists
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "t_moderation_status", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationStatus {
+    Visible,
+    Flagged,
+    Hidden,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "t_report_reason", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Stolen,
+    Nsfw,
+    Infringement,
+    Scam,
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "t_report_status", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    Open,
+    Resolved,
+    Dismissed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SearchEntity {
+    Nft,
+    Collection,
+    User,
+}
+
+impl SearchEntity {
+    pub const ALL: [SearchEntity; 3] = [Self::Nft, Self::Collection, Self::User];
+
+    /// Value of `object_type` in the search results
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Nft => "nft",
+            Self::Collection => "collection",
+            Self::User => "user",
+        }
+    }
+}
+
+/// What a file of the nft metadata is used for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum NftMediaRole {
+    Preview,
+    Image,
+    /// `animation_url`, usually a video or an html page
+    Animation,
+    Video,
+    Audio,
+    /// 3D model, gltf or glb
+    Model,
+    Other,
+}
+
+/// Media filter of `/nfts`, matched against the mimetypes of the metadata files
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum NftMediaKind {
+    Image,
+    Video,
+    Audio,
+    #[serde(rename = "3d")]
+    Model,
+}
+
+impl NftMediaKind {
+    /// Value returned by the `nft_media_kinds` sql function
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Video => "video",
+            Self::Audio => "audio",
+            Self::Model => "3d",
+        }
+    }
+}
+
+/// Field of an nft replaced by the collection owner instead of read from the metadata
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "snake_case")]
+pub enum NftOverrideField {
+    Name,
+    Preview,
+    Attributes,
+}
+
+/// Problem of an nft found by the metadata report of its collection
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataIssue {
+    /// Not indexed yet or the metadata couldn't be fetched
+    MissingMetadata,
+    /// Not a json object
+    UnparsableMetadata,
+    MissingName,
+    /// No file at all, neither `preview`, `files` nor `image`
+    MissingFiles,
+    /// Nothing to show in the lists, no `preview` and no image file
+    MissingPreview,
+    /// A file source that isn't an `https`, `http`, `ipfs`, `ar` or `data` url
+    InvalidFileUrl,
+    DuplicateName,
+    /// `attributes` is a map or a scalar instead of a list
+    AttributesNotArray,
+    /// Entries of `attributes` without a trait type or a value
+    MalformedAttributes,
+    /// A trait spelled otherwise or holding another kind of value than in the rest of the collection
+    InconsistentTraitType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum NftAttributeKind {
+    Text,
+    Number,
+    Boolean,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "t_write_action", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum WriteAction {
+    CollectionCustomUpdated,
+    CollectionCustomReverted,
+    UserUpdated,
+    NftMetadataOverrideUpdated,
+    NftMetadataOverrideRemoved,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "event_kind"))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "snake_case")]
+pub enum NftEventType {
+    SellActive,
+    SellPurchased,
+    SellCanceled,
+
+    OfferActive,
+    OfferFilled,
+    OfferCanceled,
+
+    AuctionActive,
+    AuctionBidPlaced,
+    AuctionCanceled,
+    AuctionComplete,
+
+    Mint,
+
+    Transfer,
+}
+
+impl ToString for NftEventType {
+    fn to_string(&self) -> String {
+        match self {
+            NftEventType::SellActive => "sell_active".to_string(),
+            NftEventType::SellPurchased => "sell_purchased".to_string(),
+            NftEventType::SellCanceled => "sell_canceled".to_string(),
+            NftEventType::OfferActive => "offer_active".to_string(),
+            NftEventType::OfferFilled => "offer_filled".to_string(),
+            NftEventType::OfferCanceled => "offer_canceled".to_string(),
+            NftEventType::AuctionActive => "auction_active".to_string(),
+            NftEventType::AuctionBidPlaced => "auction_bid_placed".to_string(),
+            NftEventType::AuctionCanceled => "auction_canceled".to_string(),
+            NftEventType::AuctionComplete => "auction_complete".to_string(),
+            NftEventType::Mint => "mint".to_string(),
+            NftEventType::Transfer => "transfer".to_string(),
+        }
+    }
+}
+
+impl Default for AuctionStatus {
+    fn default() -> Self {
+        Self::Active
+    }
+}
+
+impl Default for DirectBuyState {
+    fn default() -> Self {
+        Self::Create
+    }
+}
+
+impl Default for DirectSellState {
+    fn default() -> Self {
+        Self::Create
+    }
+}
+
+impl From<i16> for AuctionStatus {
+    fn from(state: i16) -> Self {
+        match state {
+            0 => Self::Active,
+            1 => Self::Cancelled,
+            2 => Self::Completed,
+            _ => panic!("Unknown state of DirectSell"),
+        }
+    }
+}
+
+impl From<i16> for DirectSellState {
+    fn from(state: i16) -> Self {
+        match state {
+            0 => Self::Create,
+            1 => Self::AwaitNft,
+            2 => Self::Active,
+            3 => Self::Filled,
+            4 => Self::Cancelled,
+            5 => Self::Expired,
+            _ => panic!("Unknown state of DirectSell"),
+        }
+    }
+}
+
+impl From<i16> for DirectBuyState {
+    fn from(state: i16) -> Self {
+        match state {
+            0 => Self::Create,
+            1 => Self::AwaitTokens,
+            2 => Self::Active,
+            3 => Self::Filled,
+            4 => Self::Cancelled,
+            5 => Self::Expired,
+            _ => panic!("Unknown state of DirectBuy"),
+        }
+    }
+}
+
+impl Display for AuctionStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = serde_json::to_value(self).expect("error serialize AuctionStatus");
+        let str = val.as_str().expect("not a string");
+        f.write_str(str)
+    }
+}
+
+impl Display for DirectBuyState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = serde_json::to_value(self).expect("error serialize DirectBuyState");
+        let str = val.as_str().expect("not a string");
+        f.write_str(str)
+    }
+}
+
+impl Display for DirectSellState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = serde_json::to_value(self).expect("error serialize DirectBuyState");
+        let str = val.as_str().expect("not a string");
+        f.write_str(str)
+    }
+}
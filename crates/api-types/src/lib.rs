@@ -0,0 +1,15 @@
+//! Request and response models shared by the API server and its clients.
+//!
+//! The `openapi`, `graphql` and `sqlx` features add the derives the server needs,
+//! clients only depend on serde.
+pub mod enums;
+pub mod model;
+pub mod params;
+#[cfg(feature = "openapi")]
+pub mod schema;
+
+pub use self::enums::*;
+pub use self::model::*;
+pub use self::params::*;
+
+pub type Address = String;
@@ -0,0 +1,939 @@
+use crate::enums::{
+    AdminAction, AuctionStatus, DirectBuyState, DirectSellState, MetadataIssue,
+    MetadataRefreshStatus, ModerationStatus, ModerationTarget, NftAttributeKind, NftEventType,
+    NftMediaRole, NftOverrideField, ReportReason, ReportStatus, SaleType, VerificationStatus,
+    WriteAction,
+};
+use crate::Address;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VecWithTotal<T> {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<T>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VecWith<T> {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<T>,
+    pub nft: Option<HashMap<Address, NFT>>,
+    pub collection: Option<HashMap<Address, Collection>>,
+    pub auction: Option<HashMap<Address, Auction>>,
+    pub direct_buy: Option<HashMap<Address, DirectBuy>>,
+    pub direct_sell: Option<HashMap<Address, DirectSell>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct Contract {
+    pub address: Address,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub owner: Option<Address>,
+    pub verified: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct Price {
+    #[serde(rename = "priceToken")]
+    #[cfg_attr(feature = "graphql", graphql(name = "priceToken"))]
+    pub token: Address,
+    pub price: String,
+    #[serde(rename = "usdPrice")]
+    pub usd_price: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct Fee {
+    pub numerator: i32,
+    pub denominator: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NFTPrice {
+    #[serde(rename = "usdPrice")]
+    pub usd_price: String,
+    pub ts: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TokenPrice {
+    pub usd_price: String,
+    pub ts: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct NftTrait {
+    pub trait_type: Option<String>,
+    pub trait_value: Option<String>,
+    pub cnt: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct MetaRoyalty {
+    pub description: Option<String>,
+    pub royalty_type: Option<String>,
+    /// Share of the sale price paid to the creator, as declared by the metadata
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee: Option<Fee>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<Address>,
+}
+
+/// The GraphQL objects named `*Fields` are flattened into the server's GraphQL nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "graphql", graphql(name = "NFTFields"))]
+#[serde(rename_all = "camelCase")]
+pub struct NFT {
+    #[serde(flatten)]
+    #[cfg_attr(feature = "graphql", graphql(flatten))]
+    pub contract: Contract,
+    #[cfg_attr(feature = "graphql", graphql(name = "collectionAddress"))]
+    pub collection: Address,
+    pub image: Option<String>,
+    pub mimetype: Option<String>,
+    pub full_image: Option<String>,
+    pub full_image_mimetype: Option<String>,
+    #[serde(rename = "type")]
+    #[cfg_attr(feature = "graphql", graphql(name = "type"))]
+    pub typ: Option<String>,
+    #[cfg_attr(feature = "graphql", graphql(name = "auctionAddress"))]
+    pub auction: Option<Address>,
+    #[cfg_attr(feature = "graphql", graphql(name = "forsaleAddress"))]
+    pub forsale: Option<Address>,
+    #[cfg_attr(feature = "graphql", graphql(name = "bestOfferAddress"))]
+    pub best_offer: Option<Address>,
+    pub manager: Option<Address>,
+    pub deal_price_usd: Option<String>,
+    pub floor_price: Option<Price>,
+    pub nft_id: Option<Address>,
+    pub royalty: Option<MetaRoyalty>,
+    /// Fields set by the collection owner rather than read from the metadata
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overridden: Vec<NftOverrideField>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct Social {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub twitter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telegram: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discord: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub youtube: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facebook: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub medium: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "graphql", graphql(name = "CollectionFields"))]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    #[serde(flatten)]
+    #[cfg_attr(feature = "graphql", graphql(flatten))]
+    pub contract: Contract,
+    pub created_at: usize,
+    pub wallpaper: Option<String>,
+    pub logo: Option<String>,
+    pub owners_count: usize,
+    pub nft_count: usize,
+    pub lowest_price: Option<String>,
+    pub total_price: Option<String>,
+    pub first_mint: i64,
+    pub social: Option<Social>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct CollectionDetailsPreviewMeta {
+    pub source: Option<String>,
+    pub mimetype: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "graphql", graphql(name = "CollectionDetailsFields"))]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionDetails {
+    #[serde(flatten)]
+    #[cfg_attr(feature = "graphql", graphql(flatten))]
+    pub collection: Collection,
+    pub floor_price_usd: Option<String>,
+    pub total_volume_usd: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub previews: Vec<CollectionDetailsPreviewMeta>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct Attribute {
+    pub trait_type: String,
+    pub trait_values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionSimple {
+    pub address: Address,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub verified: bool,
+    pub logo: Option<String>,
+    pub nft_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Root {
+    pub address: Address,
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Roots {
+    pub roots: Vec<Root>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "graphql", graphql(name = "AuctionFields"))]
+#[serde(rename_all = "camelCase")]
+pub struct Auction {
+    pub address: Address,
+    #[cfg_attr(feature = "graphql", graphql(name = "nftAddress"))]
+    pub nft: Address,
+    pub status: AuctionStatus,
+    pub bid_token: Address,
+    pub wallet_for_bids: Option<Address>,
+    pub start_bid: Option<String>,
+    pub start_usd_bid: Option<String>,
+    pub min_bid: Option<String>,
+    pub min_usd_bid: Option<String>,
+    pub max_bid: Option<String>,
+    pub max_usd_bid: Option<String>,
+    pub start_time: Option<i64>,
+    pub finish_time: Option<i64>,
+    pub last_bid_from: Option<Address>,
+    #[serde(rename = "lastBidTime")]
+    #[cfg_attr(feature = "graphql", graphql(name = "lastBidTime"))]
+    pub last_bid_ts: Option<i64>,
+    pub last_bid_value: Option<String>,
+    pub last_bid_usd_value: Option<String>,
+    pub fee: Fee,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionBid {
+    pub from: Address,
+    pub auction: Address,
+    pub nft: Address,
+    pub price: String,
+    pub usd_price: Option<String>,
+    pub active: bool,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "graphql", graphql(name = "DirectSellFields"))]
+pub struct DirectSell {
+    pub address: Address,
+    #[cfg_attr(feature = "graphql", graphql(name = "nftAddress"))]
+    pub nft: Address,
+    pub seller: Option<Address>,
+    pub price: Price,
+    pub status: DirectSellState,
+    /// Active, not expired, and the nft isn't burned and still belongs to the seller
+    pub valid: bool,
+    #[serde(rename = "createdAt")]
+    #[cfg_attr(feature = "graphql", graphql(name = "createdAt"))]
+    pub created: i64,
+    #[serde(rename = "finishedAt")]
+    #[cfg_attr(feature = "graphql", graphql(name = "finishedAt"))]
+    pub finished: Option<i64>,
+    #[serde(rename = "expiredAt")]
+    #[cfg_attr(feature = "graphql", graphql(name = "expiredAt"))]
+    pub expired: Option<i64>,
+    pub fee: Fee,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "graphql", graphql(name = "DirectBuyFields"))]
+pub struct DirectBuy {
+    pub address: Address,
+    #[cfg_attr(feature = "graphql", graphql(name = "nftAddress"))]
+    pub nft: Address,
+    pub buyer: Option<Address>,
+    pub price: Price,
+    pub status: DirectBuyState,
+    /// Active, not expired, and the nft isn't burned nor already owned by the buyer.
+    /// Wallet balances aren't indexed, an offer the buyer can no longer pay for still reads as valid
+    pub valid: bool,
+    #[serde(rename = "createdAt")]
+    #[cfg_attr(feature = "graphql", graphql(name = "createdAt"))]
+    pub created: i64,
+    #[serde(rename = "finishedAt")]
+    #[cfg_attr(feature = "graphql", graphql(name = "finishedAt"))]
+    pub finished: Option<i64>,
+    #[serde(rename = "expiredAt")]
+    #[cfg_attr(feature = "graphql", graphql(name = "expiredAt"))]
+    pub expired: Option<i64>,
+    pub fee: Fee,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub address: Address,
+    /// `nft`, `collection` or `user`
+    pub object_type: String,
+    pub nft_name: Option<String>,
+    pub collection_name: Option<String>,
+    pub image: Option<String>,
+    #[serde(default)]
+    pub collection: Option<Address>,
+    #[serde(default)]
+    pub nft_id: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Relevance, higher first
+    #[serde(default)]
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SearchRes {
+    pub items: Vec<SearchResult>,
+    pub count: usize,
+    /// Set when there are more results
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub address: Address,
+    pub name: String,
+    pub image: Option<String>,
+    /// Collection of an nft, the address itself for a collection
+    pub collection: Option<Address>,
+}
+
+/// Completions of `/search/suggest`, best first
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestRes {
+    pub collections: Vec<Suggestion>,
+    pub nfts: Vec<Suggestion>,
+    pub users: Vec<Suggestion>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NftEvents {
+    pub data: Vec<NftEvent>,
+    pub total_rows: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "graphql", graphql(name = "NftEventFields"))]
+#[serde(rename_all = "camelCase")]
+pub struct NftEvent {
+    pub id: i64,
+    pub event_type: NftEventType,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub datetime: i64,
+    /// NFT address
+    #[cfg_attr(feature = "graphql", graphql(name = "nftAddress"))]
+    pub address: String,
+    pub preview_url: Option<String>,
+    pub direct_sell: Option<NftEventDirectSell>,
+    pub direct_buy: Option<NftEventDirectBuy>,
+    pub auction: Option<NftEventAuction>,
+    pub mint: Option<NftEventMint>,
+    pub transfer: Option<NftEventTransfer>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct NftEventDirectSell {
+    pub creator: String,
+    pub start_time: u64,
+    pub end_time: Option<u64>,
+    pub duration_time: Option<i64>,
+    pub price: String,
+    pub usd_price: Option<String>,
+    pub status: i64,
+    pub payment_token: String,
+    pub new_owner: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct NftEventDirectBuy {
+    pub creator: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub duration_time: Option<i64>,
+    pub price: String,
+    pub usd_price: Option<String>,
+    pub status: i64,
+    pub spent_token: String,
+    pub old_owner: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct NftEventAuction {
+    pub auction_active: Option<AuctionActive>,
+    pub auction_complete: Option<AuctionComplete>,
+    pub auction_canceled: Option<AuctionCanceled>,
+    pub auction_bid_placed: Option<AuctionBidPlaced>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionActive {
+    pub nft_owner: String,
+    pub auction_start_time: i64,
+    pub auction_end_time: i64,
+    pub auction_duration: i64,
+    pub state: i64,
+    pub payment_token: String,
+    pub price: String,
+    pub usd_price: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionComplete {
+    pub nft_owner: String,
+    pub auction_start_time: i64,
+    pub auction_end_time: i64,
+    pub auction_duration: i64,
+    pub state: i64,
+    pub payment_token: String,
+    pub price: String,
+    pub usd_price: Option<String>,
+    pub max_bid_value: String,
+    pub max_bid_address: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionCanceled {
+    pub nft_owner: String,
+    pub auction_start_time: i64,
+    pub auction_end_time: i64,
+    pub auction_duration: i64,
+    pub state: i64,
+    pub payment_token: String,
+    pub price: String,
+    pub usd_price: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionBidPlaced {
+    pub bid_sender: String,
+    pub payment_token: String,
+    pub bid_value: String,
+    pub usd_price: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct NftEventMint {
+    pub owner: String,
+    pub creator: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[serde(rename_all = "camelCase")]
+pub struct NftEventTransfer {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSummaryBase {
+    pub total_rows_count: i32,
+    pub data: Vec<MetricsSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSummary {
+    pub collection: String,
+    pub name: Option<String>,
+    pub logo: Option<String>,
+    pub floor_price: Option<String>,
+    pub total_volume_usd_now: String,
+    pub total_volume_usd_previous: String,
+    pub owners_count: i32,
+    pub nfts_count: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct OwnerFee {
+    pub fee: Fee,
+    pub nft_id: Option<String>,
+    pub collection: Option<String>,
+}
+
+/// What the seller gets out of a sale, amounts in the smallest units of the token.
+/// The fee and the royalty are rounded down to whole units, as the market contracts do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SaleQuote {
+    pub nft: Address,
+    pub collection: Address,
+    pub sale_type: SaleType,
+    pub token: Address,
+    pub price: String,
+    /// `None` without a usd price of the token
+    pub price_usd: Option<String>,
+    pub royalty: Option<QuoteRoyalty>,
+    /// One per market root of the sale type
+    pub roots: Vec<RootQuote>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteRoyalty {
+    pub fee: Fee,
+    pub recipient: Option<Address>,
+    pub amount: String,
+    pub amount_usd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RootQuote {
+    pub root: Address,
+    /// `None` when the root never announced a fee
+    pub fee: Option<Fee>,
+    /// The fee is set for the collection rather than the root default
+    pub collection_fee: bool,
+    /// No fee is known for the root, `proceeds` only takes off the royalty
+    pub fee_unknown: bool,
+    pub fee_amount: Option<String>,
+    pub fee_amount_usd: Option<String>,
+    /// Price less the fee and the royalty
+    pub proceeds: String,
+    pub proceeds_usd: Option<String>,
+}
+
+/// Valid orders of a collection grouped into price levels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionOrderBook {
+    pub collection: Address,
+    pub direct_sells: usize,
+    pub auctions: usize,
+    pub direct_buys: usize,
+    /// Orders in tokens with a usd price
+    pub usd: OrderBook,
+    /// One per token, in the smallest units of the token
+    pub tokens: Vec<OrderBook>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBook {
+    /// `None` for the usd book
+    pub token: Option<Address>,
+    /// `None` when there are no orders
+    pub step: Option<String>,
+    pub best_ask: Option<String>,
+    pub best_bid: Option<String>,
+    /// Best ask less best bid, negative when the book is crossed
+    pub spread: Option<String>,
+    /// Cheapest first, listings and auctions
+    pub asks: Vec<PriceLevel>,
+    /// Highest first, offers
+    pub bids: Vec<PriceLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PriceLevel {
+    /// Lower bound of the level, it spans one step
+    pub price: String,
+    pub count: usize,
+    /// Orders at this level and the better ones
+    pub cumulative: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "graphql", graphql(name = "UserFields"))]
+#[serde(rename_all = "camelCase")]
+pub struct UserDto {
+    pub address: String,
+    pub logo_nft: Option<String>,
+    pub username: Option<String>,
+    pub bio: Option<String>,
+    pub twitter: Option<String>,
+    pub instagram: Option<String>,
+    pub facebook: Option<String>,
+    pub link: Option<String>,
+    pub email: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GetNFTResult {
+    pub nft: NFT,
+    pub collection: HashMap<Address, Collection>,
+    pub auction: HashMap<Address, Auction>,
+    #[serde(rename = "directSell")]
+    pub direct_sell: HashMap<Address, DirectSell>,
+    #[serde(rename = "directBuy")]
+    pub direct_buy: HashMap<Address, DirectBuy>,
+    pub traits: Vec<NftTrait>,
+    #[serde(default)]
+    pub metadata: NftMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NftMediaFile {
+    pub source: String,
+    /// Guessed from the file extension when the metadata has none
+    pub mimetype: Option<String>,
+    pub role: NftMediaRole,
+}
+
+/// Trait of the metadata, values are kept as text with the kind they were given in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NftAttribute {
+    pub trait_type: String,
+    pub value: String,
+    pub kind: NftAttributeKind,
+    pub display_type: Option<String>,
+    pub max_value: Option<String>,
+}
+
+/// Everything read from the metadata json, whatever schema variant it follows
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NftMetadata {
+    pub files: Vec<NftMediaFile>,
+    pub external_url: Option<String>,
+    pub attributes: Vec<NftAttribute>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GetAuctionResult {
+    pub auction: Auction,
+    pub bid: Option<AuctionBid>,
+    pub nft: HashMap<Address, NFT>,
+    pub collection: HashMap<Address, Collection>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NFTSellCountResponse {
+    pub count: i64,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GraphQLResponse {
+    pub data: Option<Value>,
+    pub errors: Option<Vec<Value>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct AdminAuditEntry {
+    pub id: i64,
+    pub admin: Address,
+    pub action: AdminAction,
+    pub target: Option<Address>,
+    /// New and previous values of the change
+    pub details: Value,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationRequest {
+    pub id: i64,
+    pub collection: Address,
+    pub applicant: Address,
+    pub description: String,
+    pub links: Vec<String>,
+    pub status: VerificationStatus,
+    /// Set by the admin on review, always present on rejection
+    pub reason: Option<String>,
+    pub reviewer: Option<Address>,
+    pub created_at: i64,
+    pub reviewed_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub id: i64,
+    pub target_type: ModerationTarget,
+    pub target: Address,
+    pub reason: ReportReason,
+    pub comment: Option<String>,
+    pub reporter: Address,
+    pub status: ReportStatus,
+    pub resolution: Option<String>,
+    pub resolver: Option<Address>,
+    pub created_at: i64,
+    pub resolved_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Moderation {
+    pub target_type: ModerationTarget,
+    pub target: Address,
+    pub status: ModerationStatus,
+    pub reason: Option<String>,
+    pub moderator: Address,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataRefresh {
+    pub id: i64,
+    pub nft: Address,
+    pub collection: Option<Address>,
+    pub requested_by: Address,
+    pub status: MetadataRefreshStatus,
+    /// Why the indexer failed to read the metadata
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub finished_at: Option<i64>,
+    /// Last write of the stored metadata
+    pub metadata_updated: Option<i64>,
+    /// A new refresh of the nft is accepted from then on
+    pub next_request_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataRefreshBatch {
+    pub collection: Address,
+    pub queued: i64,
+    /// Nfts with an open job or still in their cooldown
+    pub skipped: i64,
+}
+
+/// Values replacing the metadata of an nft, `null` fields keep the indexed value
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NftMetadataOverride {
+    pub nft: Address,
+    pub collection: Address,
+    pub name: Option<String>,
+    pub preview: Option<String>,
+    pub preview_mimetype: Option<String>,
+    pub attributes: Option<Vec<NftAttribute>>,
+    pub updated_by: Address,
+    pub updated: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NftMetadataOverrideBatch {
+    pub collection: Address,
+    pub written: usize,
+    /// Nfts missing from the collection
+    pub skipped: Vec<Address>,
+}
+
+/// Metadata problems of a page of the nfts of a collection, burned nfts are left out.
+/// The nft lists are only filled for the collection owner, everyone else gets the counts
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionMetadataReport {
+    pub collection: Address,
+    /// Nfts of this page
+    pub checked: usize,
+    /// Nfts without any issue
+    pub healthy: usize,
+    /// Issues found at least once
+    pub issues: Vec<MetadataIssueNfts>,
+    /// Names and trait types are compared within the page
+    pub duplicate_names: Vec<MetadataDuplicateName>,
+    pub inconsistent_traits: Vec<MetadataTraitInconsistency>,
+    /// `after` of the next page, `null` on the last one
+    pub next: Option<Address>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataIssueNfts {
+    pub issue: MetadataIssue,
+    pub count: usize,
+    /// The first nfts by address when there are more
+    pub nfts: Vec<Address>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataDuplicateName {
+    pub name: String,
+    pub nfts: Vec<Address>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataTraitInconsistency {
+    /// The most used spelling
+    pub trait_type: String,
+    pub spellings: Vec<String>,
+    pub kinds: Vec<NftAttributeKind>,
+    /// Nfts using another spelling or kind than the most used one
+    pub nfts: Vec<Address>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct WriteAuditEntry {
+    pub id: i64,
+    pub actor: Address,
+    pub action: WriteAction,
+    pub target: Address,
+    /// Values before the write, `null` when there were none
+    pub before: Option<Value>,
+    pub after: Value,
+    /// `{"field": {"from": .., "to": ..}}` for every changed field
+    pub changes: Value,
+    /// `revertedTo` for reverts
+    pub details: Value,
+    /// Only shown to admins
+    pub ip: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum OrderDirection {
+    #[serde(rename = "asc")]
+    Asc,
+    #[serde(rename = "desc")]
+    Desc,
+}
+
+impl Display for OrderDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderDirection::Asc => write!(f, "asc"),
+            OrderDirection::Desc => write!(f, "desc"),
+        }
+    }
+}
@@ -0,0 +1,599 @@
+use crate::enums::{
+    DirectBuyState, DirectSellState, ModerationStatus, ModerationTarget, NftEventType,
+    NftMediaKind, ReportReason, ReportStatus, RootType, SaleType, SearchEntity, VerificationStatus,
+    WriteAction,
+};
+use crate::model::{OrderDirection, Social};
+use crate::Address;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NFTParam {
+    pub nft: Address,
+    pub status: Option<Vec<DirectBuyState>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NFTTopListQuery {
+    pub from: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NFTListRandomBuyQuery {
+    pub max_price: i64,
+    pub limit: i32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NFTSellCountQuery {
+    pub max_price: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AttributeFilter {
+    #[serde(rename = "traitType")]
+    pub trait_type: String,
+    #[serde(rename = "traitValues")]
+    pub trait_values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NFTListQuery {
+    pub owners: Option<Vec<String>>,
+    pub collections: Option<Vec<String>>,
+    #[serde(rename = "priceFrom")]
+    pub price_from: Option<u64>,
+    #[serde(rename = "priceTo")]
+    pub price_to: Option<u64>,
+    #[serde(rename = "priceToken")]
+    pub price_token: Option<String>,
+    pub forsale: Option<bool>,
+    pub auction: Option<bool>,
+    pub verified: Option<bool>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    pub attributes: Option<Vec<AttributeFilter>>,
+    pub order: Option<NFTListOrder>,
+    #[serde(rename = "withCount")]
+    pub with_count: Option<bool>,
+    /// Nfts with a file of any of these kinds
+    pub media: Option<Vec<NftMediaKind>>,
+    /// Words of the name or the description, matched as prefixes
+    pub text: Option<String>,
+    /// Unix time of the mint, inclusive
+    #[serde(rename = "mintedFrom")]
+    pub minted_from: Option<i64>,
+    #[serde(rename = "mintedTo")]
+    pub minted_to: Option<i64>,
+    #[serde(rename = "nftIdFrom")]
+    pub nft_id_from: Option<u64>,
+    #[serde(rename = "nftIdTo")]
+    pub nft_id_to: Option<u64>,
+    pub manager: Option<Address>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum NFTListOrderField {
+    #[serde(rename = "floorPriceUsd")]
+    FloorPriceUsd,
+    #[serde(rename = "dealPriceUsd")]
+    DealPriceUsd,
+    #[serde(rename = "name")]
+    Name,
+}
+
+impl Display for NFTListOrderField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NFTListOrderField::FloorPriceUsd => write!(f, "floor_price_usd"),
+            NFTListOrderField::DealPriceUsd => write!(f, "deal_price_usd"),
+            NFTListOrderField::Name => write!(f, "name"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NFTListOrder {
+    pub field: NFTListOrderField,
+    pub direction: OrderDirection,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum PriceHistoryScale {
+    #[serde(rename = "h")]
+    Hours,
+    #[serde(rename = "d")]
+    Days,
+}
+
+impl Display for PriceHistoryScale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PriceHistoryScale::Days => write!(f, "day"),
+            PriceHistoryScale::Hours => write!(f, "hour"),
+        }
+    }
+}
+
+impl Default for PriceHistoryScale {
+    fn default() -> Self {
+        Self::Days
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NftPriceHistoryQuery {
+    pub nft: Address,
+    pub scale: Option<PriceHistoryScale>,
+    pub from: i64,
+    pub to: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum CollectionListOrderField {
+    #[serde(rename = "firstMint")]
+    FirstMint,
+}
+
+impl Display for CollectionListOrderField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CollectionListOrderField::FirstMint => write!(f, "first_mint"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CollectionListOrder {
+    pub field: CollectionListOrderField,
+    pub direction: OrderDirection,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ListCollectionsParams {
+    pub name: Option<String>,
+    pub owners: Option<Vec<String>>,
+    pub verified: Option<bool>,
+    pub collections: Option<Vec<String>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    pub order: Option<CollectionListOrder>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CollectionParam {
+    pub collection: Address,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ListCollectionsSimpleParams {
+    pub name: Option<String>,
+    pub verified: Option<bool>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OwnerParam {
+    pub owner: Address,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpsertCollectionCustomPayload {
+    pub address: Address,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub wallpaper: Option<String>,
+    pub logo: Option<String>,
+    pub social: Option<Social>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuctionsQuery {
+    pub owners: Option<Vec<Address>>,
+    pub collections: Option<Vec<Address>>,
+    pub tokens: Option<Vec<Address>>,
+    pub sort: Option<AuctionsSortOrder>,
+    pub limit: Option<usize>,
+    #[cfg_attr(feature = "openapi", schema(example = 1001))]
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuctionBidsQuery {
+    pub auction: Address,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum AuctionsSortOrder {
+    #[serde(rename = "start-date")]
+    StartDate,
+    #[serde(rename = "bids-count")]
+    BidsCount,
+    #[serde(rename = "average")]
+    Average,
+    #[serde(rename = "average-in-hour")]
+    AverageInHour,
+    #[serde(rename = "average-in-day")]
+    AverageInDay,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EventsQuery {
+    pub owner: Option<String>,
+    pub collections: Option<Vec<String>>,
+    pub nft: Option<String>,
+    #[serde(rename = "types")]
+    pub event_type: Option<Vec<NftEventType>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    #[serde(rename = "withCount")]
+    pub with_count: Option<bool>,
+    pub verified: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct MetricsSummaryQuery {
+    pub from: i64,
+    pub to: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OwnerBidsOutQuery {
+    pub owner: Address,
+    pub collections: Option<Vec<Address>>,
+    pub lastbid: Option<bool>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OwnerBidsInQuery {
+    pub owner: Address,
+    pub collections: Option<Vec<Address>>,
+    pub active: Option<bool>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OwnerDirectSellQuery {
+    pub owner: Address,
+    pub collections: Option<Vec<Address>>,
+    pub status: Option<Vec<DirectSellState>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OwnerDirectBuyQuery {
+    pub owner: Address,
+    pub collections: Option<Vec<Address>>,
+    pub status: Option<Vec<DirectBuyState>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct OwnerFeeQuery {
+    pub owner: Address,
+    #[serde(rename = "rootCode")]
+    pub root_code: RootType,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteQuery {
+    pub nft: Address,
+    pub sale_type: SaleType,
+    /// In the smallest units of the token
+    pub price: String,
+    pub token: Address,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataReportQuery {
+    /// Checks the nfts after this address, `next` of the previous page
+    pub after: Option<Address>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBookQuery {
+    /// Width of the usd levels, two significant digits of the best price by default
+    pub usd_step: Option<String>,
+    /// Width of the levels of `token` in its smallest units, chosen like `usdStep` by default
+    pub token_step: Option<String>,
+    /// Token `tokenStep` applies to, required with it, the other tokens keep the default step
+    pub token: Option<Address>,
+    /// Levels per side, 50 by default and 200 at most
+    pub levels: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct TokenPriceHistoryQuery {
+    pub from: i64,
+    pub to: i64,
+    pub scale: Option<PriceHistoryScale>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpsertUserPayload {
+    pub address: Address,
+    pub username: Option<String>,
+    pub bio: Option<String>,
+    pub logo_nft: Option<String>,
+    pub twitter: Option<String>,
+    pub instagram: Option<String>,
+    pub facebook: Option<String>,
+    pub link: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SignInPayload {
+    pub public_key: String,
+    pub address: String,
+    pub wallet_type: String,
+    pub timestamp: u64,
+    pub signature: String,
+    pub with_signature_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SetCollectionVerifiedPayload {
+    pub collection: Address,
+    pub verified: bool,
+    /// Kept in the audit log
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertRootPayload {
+    pub address: Address,
+    pub code: RootType,
+    /// Unix seconds, offers of the root leave the whitelist afterwards
+    pub expiry_date: Option<i64>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RemoveRootPayload {
+    pub address: Address,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct AdminAuditQuery {
+    pub admin: Option<Address>,
+    pub target: Option<Address>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateVerificationRequestPayload {
+    pub collection: Address,
+    pub description: String,
+    /// Website, socials, announcements proving the collection is genuine
+    #[serde(default)]
+    pub links: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct VerificationRequestsQuery {
+    pub collection: Option<Address>,
+    pub status: Option<VerificationStatus>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReviewVerificationRequestPayload {
+    pub id: i64,
+    /// Shown to the applicant, required to reject
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReportPayload {
+    pub target_type: ModerationTarget,
+    pub target: Address,
+    pub reason: ReportReason,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+#[serde(rename_all = "camelCase")]
+pub struct ReportsQuery {
+    pub status: Option<ReportStatus>,
+    pub target_type: Option<ModerationTarget>,
+    pub target: Option<Address>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SetModerationPayload {
+    /// `nft` or `collection`, users have no status of their own
+    pub target_type: ModerationTarget,
+    pub target: Address,
+    pub status: ModerationStatus,
+    /// Closes the open reports on the target and is kept in the audit log
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct ModerationQuery {
+    pub status: Option<ModerationStatus>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DismissReportPayload {
+    pub id: i64,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct WriteAuditQuery {
+    pub actor: Option<Address>,
+    pub target: Option<Address>,
+    pub action: Option<WriteAction>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema, utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct CustomHistoryQuery {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RevertCollectionCustomPayload {
+    /// Id of the history entry whose values are restored
+    pub id: i64,
+}
+
+/// Replaces the whole override of the nft, `null` fields keep the indexed value
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NftMetadataOverridePayload {
+    pub name: Option<String>,
+    pub preview: Option<String>,
+    /// Guessed from the extension of `preview` when missing
+    pub preview_mimetype: Option<String>,
+    /// `[{"trait_type": .., "value": ..}]` or a `{trait: value}` map
+    pub attributes: Option<Value>,
+}
+
+/// Row of a bulk override, as a json item or a csv line
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NftMetadataOverrideItem {
+    pub nft: Address,
+    pub name: Option<String>,
+    pub preview: Option<String>,
+    pub preview_mimetype: Option<String>,
+    pub attributes: Option<Value>,
+}
+
+/// Body of `/search` sent as `application/json`, a plain text body is the `query` alone
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SearchQuery {
+    /// Words of names and descriptions, an address, a username or an nft id
+    pub query: String,
+    /// Every type when empty
+    #[serde(default)]
+    pub types: Vec<SearchEntity>,
+    /// Nfts and collections of these collections only
+    pub collections: Option<Vec<Address>>,
+    /// 20 by default, at most 100
+    pub limit: Option<usize>,
+    /// `nextCursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+/// Query string of `/search/suggest`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SuggestQuery {
+    /// Start of a name or of any of its words
+    pub q: String,
+    /// Per type, 5 by default, at most 20
+    pub limit: Option<usize>,
+}
+
+/// Shape of the body accepted by `/graphql`, parsed by async-graphql itself
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequest {
+    pub query: String,
+    pub operation_name: Option<String>,
+    pub variables: Option<Value>,
+}
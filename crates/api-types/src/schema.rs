@@ -0,0 +1,132 @@
+//! Concrete shapes of the generic responses, for the OpenAPI spec only
+use crate::model::{
+    AdminAuditEntry, Auction, AuctionBid, Collection, CollectionDetails, CollectionSimple,
+    DirectBuy, DirectSell, Moderation, NftMetadataOverride, Report, VerificationRequest,
+    WriteAuditEntry, NFT,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+#[derive(ToSchema, Serialize)]
+pub struct Address(String);
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VecWithAuction {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<Auction>,
+    pub nft: Option<HashMap<Address, NFT>>,
+    pub collection: Option<HashMap<Address, Collection>>,
+    pub auction: Option<HashMap<Address, Auction>>,
+    pub direct_buy: Option<HashMap<Address, DirectBuy>>,
+    pub direct_sell: Option<HashMap<Address, DirectSell>>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VecWithAuctionBids {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<AuctionBid>,
+    pub nft: Option<HashMap<Address, NFT>>,
+    pub collection: Option<HashMap<Address, Collection>>,
+    pub auction: Option<HashMap<Address, Auction>>,
+    pub direct_buy: Option<HashMap<Address, DirectBuy>>,
+    pub direct_sell: Option<HashMap<Address, DirectSell>>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VecWithDirectBuy {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<DirectBuy>,
+    pub nft: Option<HashMap<String, NFT>>,
+    pub collection: Option<HashMap<String, Collection>>,
+    pub auction: Option<HashMap<String, Auction>>,
+    pub direct_buy: Option<HashMap<String, DirectBuy>>,
+    pub direct_sell: Option<HashMap<String, DirectSell>>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VecWithDirectSell {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<DirectSell>,
+    pub nft: Option<HashMap<String, NFT>>,
+    pub collection: Option<HashMap<String, Collection>>,
+    pub auction: Option<HashMap<String, Auction>>,
+    pub direct_buy: Option<HashMap<String, DirectBuy>>,
+    pub direct_sell: Option<HashMap<String, DirectSell>>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VecWithNFT {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<NFT>,
+    pub nft: Option<HashMap<String, NFT>>,
+    pub collection: Option<HashMap<String, Collection>>,
+    pub auction: Option<HashMap<String, Auction>>,
+    pub direct_buy: Option<HashMap<String, DirectBuy>>,
+    pub direct_sell: Option<HashMap<String, DirectSell>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VecCollectionsWithTotal {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<CollectionDetails>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VecCollectionSimpleWithTotal {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<CollectionSimple>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VecAdminAuditWithTotal {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<AdminAuditEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VecVerificationRequestsWithTotal {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<VerificationRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VecReportsWithTotal {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<Report>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VecModerationWithTotal {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<Moderation>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VecWriteAuditWithTotal {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<WriteAuditEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VecNftMetadataOverrideWithTotal {
+    #[serde(rename = "totalCount")]
+    pub count: i64,
+    pub items: Vec<NftMetadataOverride>,
+}
@@ -1,5 +1,8 @@
 #!/bin/bash
 
-# sqlx migrate run &&
+# RUN_MIGRATIONS=true applies migrations/ before serving
+if [ "$RUN_MIGRATIONS" = "true" ]; then
+    set -- --migrate "$@"
+fi
 
-/app/application $1
+/app/application "$@"
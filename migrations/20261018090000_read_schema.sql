@@ -0,0 +1,687 @@
+-- Read schema of the indexer database queried by the api.
+-- Every object is only created when missing, on a database filled by the indexer this is a no-op.
+
+do
+$$
+    begin
+        create domain t_address as varchar(67);
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create domain t_uri as varchar(4096);
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create domain t_email as varchar(320);
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type t_root_types as enum ('auction', 'sell', 'buy');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type auction_status as enum ('created', 'active', 'cancelled', 'completed', 'expired');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type direct_sell_state as enum ('create', 'await_nft', 'active', 'filled', 'cancelled', 'expired');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type direct_buy_state as enum ('create', 'await_tokens', 'active', 'filled', 'cancelled', 'expired');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type event_category as enum ('auction', 'direct_buy', 'direct_sell', 'nft', 'collection');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type event_type as enum (
+            'auction_deployed',
+            'auction_created',
+            'auction_root_ownership_transferred',
+            'auction_active',
+            'auction_declined',
+            'auction_bid_placed',
+            'auction_bid_declined',
+            'auction_cancelled',
+            'auction_complete',
+            'direct_buy_deployed',
+            'direct_buy_declined',
+            'factory_direct_buy_ownership_transferred',
+            'direct_buy_state_changed',
+            'direct_sell_deployed',
+            'direct_sell_declined',
+            'factory_direct_sell_ownership_transferred',
+            'direct_sell_state_changed',
+            'nft_owner_changed',
+            'nft_manager_changed',
+            'collection_ownership_transferred',
+            'nft_created',
+            'nft_burned',
+            'market_fee_default_changed',
+            'market_fee_changed'
+            );
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+-- kinds of the activity feed, computed from the raw events
+do
+$$
+    begin
+        create type event_kind as enum (
+            'mint',
+            'transfer',
+            'auction_active',
+            'auction_bid_placed',
+            'auction_canceled',
+            'auction_complete',
+            'offer_active',
+            'sell_active',
+            'offer_filled',
+            'sell_purchased',
+            'sell_canceled',
+            'offer_canceled'
+            );
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type nft_price_source as enum ('auctionBid', 'directBuy', 'directSell');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+-- market contracts
+
+create table if not exists roots
+(
+    address     t_address    not null primary key,
+    code        t_root_types not null,
+    expiry_date timestamp
+);
+
+create table if not exists deployed_offers
+(
+    address t_address not null primary key,
+    root    t_address not null,
+    created timestamp not null default now()
+);
+
+create index if not exists deployed_offers_root_idx on deployed_offers (root);
+
+create table if not exists token_usd_prices
+(
+    token     t_address not null primary key,
+    usd_price numeric   not null,
+    ts        timestamp not null
+);
+
+-- collections and nfts
+
+create table if not exists nft_collection
+(
+    address         t_address not null primary key,
+    owner           t_address,
+    name            text,
+    description     text,
+    created         timestamp not null,
+    updated         timestamp not null,
+    wallpaper       t_uri,
+    logo            t_uri,
+    verified        boolean   not null default false,
+    first_mint      timestamp,
+    fee_numerator   integer,
+    fee_denominator integer
+);
+
+create index if not exists nft_collection_owner_idx on nft_collection (owner);
+
+create table if not exists nft_collection_custom
+(
+    address     t_address not null primary key references nft_collection (address),
+    updated     timestamp not null,
+    name        text,
+    description text,
+    wallpaper   t_uri,
+    logo        t_uri,
+    social      jsonb
+);
+
+create table if not exists nft
+(
+    address           t_address not null primary key,
+    collection        t_address,
+    owner             t_address,
+    manager           t_address,
+    name              varchar,
+    description       text,
+    burned            boolean   not null default false,
+    updated           timestamp not null,
+    owner_update_lt   bigint    not null default 0,
+    manager_update_lt bigint    not null default 0,
+    id                numeric
+);
+
+create index if not exists nft_collection_idx on nft (collection);
+create index if not exists nft_owner_idx on nft (owner);
+
+create table if not exists nft_metadata
+(
+    nft     t_address not null primary key,
+    meta    jsonb     not null,
+    updated timestamp not null
+);
+
+-- offers
+
+create table if not exists nft_auction
+(
+    address         t_address      not null primary key,
+    nft             t_address      not null,
+    collection      t_address,
+    nft_owner       t_address,
+    wallet_for_bids t_address,
+    price_token     t_address,
+    start_price     numeric,
+    max_bid         numeric,
+    min_bid         numeric,
+    status          auction_status not null,
+    created_at      timestamp,
+    finished_at     timestamp,
+    tx_lt           bigint         not null
+);
+
+create index if not exists nft_auction_nft_idx on nft_auction (nft);
+
+create table if not exists nft_auction_bid
+(
+    auction        t_address not null,
+    buyer          t_address not null,
+    price          numeric   not null,
+    price_token    t_address,
+    next_bid_value numeric,
+    declined       boolean   not null default false,
+    created_at     timestamp not null,
+    tx_lt          bigint    not null,
+    nft            t_address,
+    collection     t_address,
+    nft_owner      t_address,
+    primary key (auction, buyer, price)
+);
+
+create index if not exists nft_auction_bid_buyer_idx on nft_auction_bid (buyer);
+
+create table if not exists nft_direct_sell
+(
+    address     t_address         not null primary key,
+    nft         t_address         not null,
+    collection  t_address,
+    price_token t_address         not null,
+    price       numeric           not null,
+    seller      t_address,
+    finished_at timestamp,
+    expired_at  timestamp,
+    state       direct_sell_state not null,
+    created     timestamp         not null,
+    updated     timestamp         not null,
+    tx_lt       bigint            not null
+);
+
+create index if not exists nft_direct_sell_nft_idx on nft_direct_sell (nft);
+create index if not exists nft_direct_sell_seller_idx on nft_direct_sell (seller);
+
+create table if not exists nft_direct_buy
+(
+    address     t_address        not null primary key,
+    nft         t_address        not null,
+    collection  t_address,
+    price_token t_address        not null,
+    price       numeric          not null,
+    buyer       t_address,
+    finished_at timestamp,
+    expired_at  timestamp,
+    state       direct_buy_state not null,
+    created     timestamp        not null,
+    updated     timestamp        not null,
+    tx_lt       bigint           not null
+);
+
+create index if not exists nft_direct_buy_nft_idx on nft_direct_buy (nft);
+create index if not exists nft_direct_buy_buyer_idx on nft_direct_buy (buyer);
+
+create table if not exists nft_price_history
+(
+    source      t_address        not null,
+    source_type nft_price_source not null,
+    ts          timestamp        not null,
+    price       numeric          not null,
+    price_token t_address,
+    usd_price   numeric,
+    nft         t_address,
+    collection  t_address,
+    is_deal     boolean          not null default false
+);
+
+create index if not exists nft_price_history_nft_ts_idx on nft_price_history (nft, ts);
+
+-- raw contract events, `created_at` is a unix timestamp in seconds
+create table if not exists nft_events
+(
+    id         bigserial      not null primary key,
+    event_cat  event_category not null,
+    event_type event_type     not null,
+    address    t_address      not null,
+    created_lt bigint         not null,
+    created_at bigint         not null,
+    args       jsonb,
+    nft        t_address,
+    collection t_address
+);
+
+create index if not exists nft_events_nft_idx on nft_events (nft);
+create index if not exists nft_events_type_idx on nft_events (event_type);
+
+create table if not exists users
+(
+    address   t_address not null primary key,
+    logo_nft  t_address,
+    username  varchar,
+    bio       text,
+    twitter   varchar,
+    instagram varchar,
+    facebook  varchar,
+    link      varchar,
+    email     t_email,
+    created   timestamp not null default now()
+);
+
+-- derived relations
+
+do
+$$
+    begin
+        if to_regclass('offers_whitelist') is null then
+            -- offers deployed by one of the known market roots
+            create view offers_whitelist as
+            select o.address
+            from deployed_offers o
+                     join roots r on r.address = o.root;
+        end if;
+    end
+$$;
+
+do
+$$
+    begin
+        if to_regclass('nft_attributes') is null then
+            create view nft_attributes as
+            select n.collection::varchar     as collection,
+                   (a ->> 'trait_type')::varchar as trait_type,
+                   a -> 'value'              as value,
+                   n.address::varchar        as nft
+            from nft n
+                     join nft_metadata m on m.nft = n.address
+                     cross join lateral jsonb_array_elements(
+                    case when jsonb_typeof(m.meta -> 'attributes') = 'array' then m.meta -> 'attributes'
+                         else '[]'::jsonb end) a
+            where a ->> 'trait_type' is not null;
+        end if;
+    end
+$$;
+
+create materialized view if not exists nft_verified_mv as
+select n.*
+from nft n
+         join nft_collection c on c.address = n.collection
+where c.verified;
+
+create unique index if not exists nft_verified_mv_address_idx on nft_verified_mv (address);
+create index if not exists nft_verified_mv_owner_idx on nft_verified_mv (owner);
+create index if not exists nft_verified_mv_collection_idx on nft_verified_mv (collection);
+
+do
+$$
+    begin
+        if to_regclass('nft_details') is null then
+            -- the status columns keep the names the query macros expect
+            create view nft_details as
+            select n.address,
+                   n.collection,
+                   n.owner,
+                   n.manager,
+                   n.name::text                          as name,
+                   n.description,
+                   n.burned,
+                   n.updated,
+                   n.owner_update_lt                     as tx_lt,
+                   m.meta,
+                   auc.auction,
+                   auc."auction_status: _",
+                   sale.forsale,
+                   sale."forsale_status: _",
+                   ( select distinct on (s.address) first_value(s.address) over w
+                     from nft_direct_buy s
+                              left join token_usd_prices tup on tup.token = s.price_token
+                     where state = 'active'
+                       and nft = n.address
+                     window w as (partition by nft order by s.price * tup.usd_price desc)
+                     limit 1 )                           as best_offer,
+                   least(auc.price_usd, sale.price_usd)  as floor_price_usd,
+                   last_deal.last_price                  as deal_price_usd,
+                   case when least(auc.price_usd, sale.price_usd) = auc.price_usd then auc.min_bid
+                        when least(auc.price_usd, sale.price_usd) = sale.price_usd then sale.price
+                        else null::numeric end           as floor_price,
+                   case when least(auc.price_usd, sale.price_usd) = auc.price_usd
+                            then auc.token::character varying
+                        when least(auc.price_usd, sale.price_usd) = sale.price_usd
+                            then sale.token::character varying
+                        else null::character varying end as floor_price_token,
+                   n.id::text                            as nft_id
+            from nft n
+                     left join lateral ( select nph.price * tup.usd_price as last_price
+                                         from nft_price_history nph
+                                                  join offers_whitelist ow on ow.address = nph.source
+                                                  left join token_usd_prices tup on tup.token = nph.price_token
+                                         where nph.nft = n.address
+                                         order by nph.ts desc
+                                         limit 1 ) last_deal on true
+                     left join lateral ( select a.address                 as auction,
+                                                case when a.status = 'active' and
+                                                          to_timestamp(0) < a.finished_at and
+                                                          a.finished_at < now() then 'expired'
+                                                     else a.status end    as "auction_status: _",
+                                                a.min_bid * tup.usd_price as price_usd,
+                                                tup.token,
+                                                a.min_bid
+                                         from nft_auction a
+                                                  join offers_whitelist ow on ow.address = a.address
+                                                  left join token_usd_prices tup on tup.token = a.price_token
+                                         where a.nft = n.address
+                                           and a.status in ('active', 'expired')
+                                         limit 1 ) auc on true
+                     left join nft_metadata m on m.nft = n.address
+                     left join lateral ( select s.address                                as forsale,
+                                                case when s.state = 'active' and
+                                                          to_timestamp(0) < s.expired_at and s.expired_at < now()
+                                                         then 'expired' else s.state end as "forsale_status: _",
+                                                s.price * tup.usd_price                  as price_usd,
+                                                s.price,
+                                                tup.token
+                                         from nft_direct_sell s
+                                                  join offers_whitelist ow on ow.address = s.address
+                                                  left join token_usd_prices tup on tup.token = s.price_token
+                                         where s.nft = n.address
+                                           and s.state in ('active', 'expired')
+                                         limit 1 ) sale on true;
+        end if;
+    end
+$$;
+
+do
+$$
+    begin
+        if to_regclass('nft_collection_details') is null then
+            create view nft_collection_details as
+            select c.address,
+                   c.owner,
+                   coalesce(cc.name, c.name)                                 as name,
+                   coalesce(cc.description, c.description)                   as description,
+                   c.created,
+                   greatest(c.updated, cc.updated)                           as updated,
+                   c.verified,
+                   coalesce(cc.wallpaper, c.wallpaper)::varchar              as wallpaper,
+                   coalesce(cc.logo, c.logo)::varchar                        as logo,
+                   stats.owners_count,
+                   stats.nft_count,
+                   floor.floor_price_usd,
+                   volume.total_volume_usd,
+                   attributes.attributes,
+                   coalesce(c.first_mint, stats.first_mint, c.created)       as first_mint,
+                   cc.social,
+                   count(1) over ()                                          as total_count,
+                   count(1) filter (where c.verified) over ()                as verified_count
+            from nft_collection c
+                     left join nft_collection_custom cc on cc.address = c.address
+                     left join lateral ( select count(distinct n.owner) as owners_count,
+                                                count(1)                as nft_count,
+                                                min(n.updated)          as first_mint
+                                         from nft n
+                                         where n.collection = c.address
+                                           and not n.burned ) stats on true
+                     left join lateral ( select min(o.price_usd) as floor_price_usd
+                                         from ( select s.price * tup.usd_price as price_usd
+                                                from nft_direct_sell s
+                                                         join offers_whitelist ow on ow.address = s.address
+                                                         join token_usd_prices tup on tup.token = s.price_token
+                                                where s.collection = c.address
+                                                  and s.state = 'active'
+                                                  and (s.expired_at = to_timestamp(0) or s.expired_at > now())
+                                                union all
+                                                select a.min_bid * tup.usd_price
+                                                from nft_auction a
+                                                         join offers_whitelist ow on ow.address = a.address
+                                                         join token_usd_prices tup on tup.token = a.price_token
+                                                where a.collection = c.address
+                                                  and a.status = 'active'
+                                                  and (a.finished_at = to_timestamp(0) or a.finished_at > now()) ) o ) floor
+                               on true
+                     left join lateral ( select sum(nph.price * tup.usd_price) as total_volume_usd
+                                         from nft_price_history nph
+                                                  join offers_whitelist ow on ow.address = nph.source
+                                                  join token_usd_prices tup on tup.token = nph.price_token
+                                         where nph.collection = c.address
+                                           and nph.is_deal ) volume on true
+                     left join lateral ( select json_agg(json_build_object('traitType', t.trait_type,
+                                                                           'traitValues', t.trait_values)) as attributes
+                                         from ( select a.trait_type,
+                                                       json_agg(distinct a.value #>> '{}') as trait_values
+                                                from nft_attributes a
+                                                where a.collection = c.address
+                                                group by a.trait_type ) t ) attributes on true;
+        end if;
+    end
+$$;
+
+do
+$$
+    begin
+        if to_regclass('nft_auction_search') is null then
+            -- auctions with the status, bids and usd prices resolved
+            create view nft_auction_search as
+            select distinct on (a.address) a.address,
+                                           a.nft,
+                                           a.collection,
+                                           a.nft_owner,
+                                           a.wallet_for_bids,
+                                           a.price_token,
+                                           a.start_price,
+                                           a.max_bid,
+                                           a.min_bid,
+                                           case when a.status = 'active'::auction_status and
+                                                     to_timestamp(0) < a.finished_at and a.finished_at < now()::timestamp
+                                                    then 'expired'::auction_status
+                                                else a.status end                         as "status: _",
+                                           a.created_at,
+                                           a.finished_at,
+                                           a.tx_lt,
+                                           sum(case when b.auction is null then 0 else 1 end)
+                                           over (partition by a.address)                  as bids_count,
+                                           first_value(b.buyer) over bids_w               as last_bid_from,
+                                           first_value(b.price) over bids_w               as last_bid_value,
+                                           first_value(b.price * p.usd_price) over bids_w as last_bid_usd_value,
+                                           first_value(b.created_at) over bids_w          as last_bid_ts,
+                                           a.start_price * p.usd_price                    as start_usd_price,
+                                           a.max_bid * p.usd_price                        as max_usd_bid,
+                                           a.min_bid * p.usd_price                        as min_usd_bid,
+                                           ev.fee_numerator,
+                                           ev.fee_denominator
+            from nft_auction a
+                     join offers_whitelist ow on ow.address = a.address
+                     left join nft_auction_bid b on b.auction = a.address and b.declined is false
+                     left join token_usd_prices p on p.token = a.price_token
+                     left join lateral ( select (ne.args -> 'fee' -> 'numerator')::int   as fee_numerator,
+                                                (ne.args -> 'fee' -> 'denominator')::int as fee_denominator
+                                         from nft_events ne
+                                         where ne.event_type = 'market_fee_changed'
+                                           and ne.args ->> 'auction' = a.address
+                                         order by ne.created_lt desc
+                                         limit 1 ) as ev on true
+            window bids_w as (partition by b.auction order by b.created_at desc)
+            order by a.address;
+        end if;
+    end
+$$;
+
+-- activity feed, events of the whitelisted offers and roots plus mints and transfers
+do
+$do$
+    begin
+        if to_regprocedure('get_events(t_address, event_kind[], t_address, t_address[], integer, integer, boolean, boolean)') is null then
+            create function get_events(p_owner t_address,
+                                       p_event_kind event_kind[],
+                                       p_nft t_address,
+                                       p_collections t_address[],
+                                       p_limit integer,
+                                       p_offset integer,
+                                       p_with_count boolean,
+                                       p_verified boolean)
+                returns table
+                        (
+                            id                  bigint,
+                            event_type          event_type,
+                            computed_event_kind event_kind,
+                            created_at          bigint,
+                            created_lt          bigint,
+                            nft                 t_address,
+                            collection          t_address,
+                            args                jsonb,
+                            new_owner           t_address,
+                            old_owner           t_address,
+                            total_rows          bigint
+                        )
+                language sql
+                stable
+            as
+            $$
+            with events_whitelist as ( select o.address
+                                       from deployed_offers o
+                                                join roots r on r.address = o.root
+                                       union
+                                       select address
+                                       from roots ),
+                 events as ( select e.*,
+                                    case e.event_type
+                                        when 'nft_created' then 'mint'::event_kind
+                                        when 'nft_owner_changed' then 'transfer'
+                                        when 'auction_active' then 'auction_active'
+                                        when 'auction_bid_placed' then 'auction_bid_placed'
+                                        when 'auction_cancelled' then 'auction_canceled'
+                                        when 'auction_complete' then 'auction_complete'
+                                        when 'direct_buy_state_changed' then
+                                            case (e.args -> 'value2' ->> 'status')::int
+                                                when 2 then 'offer_active'::event_kind
+                                                when 3 then 'offer_filled'
+                                                when 4 then 'offer_canceled' end
+                                        when 'direct_sell_state_changed' then
+                                            case (e.args -> 'value2' ->> 'status')::int
+                                                when 2 then 'sell_active'::event_kind
+                                                when 3 then 'sell_purchased'
+                                                when 4 then 'sell_canceled' end
+                                        end as computed_event_kind
+                             from nft_events e
+                             where e.nft is not null
+                               and (e.event_cat = 'nft' or e.address in ( select address from events_whitelist )) ),
+                 filtered as ( select e.*,
+                                      (t.args ->> 'new_owner')::t_address as new_owner,
+                                      (t.args ->> 'old_owner')::t_address as old_owner
+                               from events e
+                                        left join lateral ( select o.args
+                                                            from nft_events o
+                                                            where e.computed_event_kind in ('sell_purchased', 'offer_filled')
+                                                              and o.nft = e.nft
+                                                              and o.event_type = 'nft_owner_changed'
+                                                              and o.created_lt >= e.created_lt
+                                                            order by o.created_lt
+                                                            limit 1 ) t on true
+                               where e.computed_event_kind is not null
+                                 and (p_event_kind = '{}' or e.computed_event_kind = any (p_event_kind))
+                                 and (p_nft is null or e.nft = p_nft)
+                                 and (p_collections = '{}' or e.collection = any (p_collections))
+                                 and (p_owner is null or p_owner in (e.args ->> 'owner',
+                                                                     e.args ->> 'creator',
+                                                                     e.args ->> 'old_owner',
+                                                                     e.args ->> 'new_owner',
+                                                                     e.args ->> 'buyer',
+                                                                     e.args -> 'value0' ->> 'subject_owner',
+                                                                     e.args -> 'value2' ->> 'subject_owner',
+                                                                     e.args -> 'value2' ->> 'creator'))
+                                 and (p_verified is not true or exists( select 1
+                                                                        from nft_collection c
+                                                                        where c.address = e.collection
+                                                                          and c.verified )) )
+            select f.id,
+                   f.event_type,
+                   f.computed_event_kind,
+                   f.created_at,
+                   f.created_lt,
+                   f.nft,
+                   f.collection,
+                   f.args,
+                   f.new_owner,
+                   f.old_owner,
+                   case when p_with_count then count(1) over () else 0 end
+            from filtered f
+            order by f.created_at desc, f.created_lt desc, f.id desc
+            limit p_limit offset p_offset
+            $$;
+        end if;
+    end
+$do$;
@@ -0,0 +1,49 @@
+create table if not exists token_usd_prices_history
+(
+    token     varchar   not null,
+    usd_price numeric   not null,
+    ts        timestamp not null,
+    primary key (token, ts)
+);
+
+insert into token_usd_prices_history (token, usd_price, ts)
+select token, usd_price, ts
+from token_usd_prices
+on conflict do nothing;
+
+-- usd rate of a token at `p_ts`: the last one recorded up to it, for moments older than the history
+-- the first one recorded after
+create or replace function token_usd_price_at(p_token varchar, p_ts timestamp)
+    returns numeric
+    language sql
+    stable
+as
+$$
+select coalesce(( select ph.usd_price
+                  from token_usd_prices_history ph
+                  where ph.token = p_token
+                    and ph.ts <= p_ts
+                  order by ph.ts desc
+                  limit 1 ),
+                ( select ph.usd_price
+                  from token_usd_prices_history ph
+                  where ph.token = p_token
+                    and ph.ts > p_ts
+                  order by ph.ts
+                  limit 1 ))
+$$;
+
+-- trades of whitelisted offers valued at the usd rate of their token when they happened
+create or replace view nft_price_history_usd as
+select nph.source,
+       nph.source_type,
+       nph.ts,
+       nph.price,
+       nph.price_token,
+       nph.usd_price,
+       nph.nft,
+       nph.collection,
+       nph.is_deal,
+       nph.price * token_usd_price_at(nph.price_token, nph.ts) as price_usd
+from nft_price_history nph
+         join offers_whitelist ow on ow.address = nph.source;
@@ -0,0 +1,19 @@
+-- partner api keys, only the sha256 hex digest of the key is stored
+create table if not exists api_keys
+(
+    key_hash     varchar   not null primary key,
+    name         varchar   not null,
+    rate_per_sec float8    not null default 20 check (rate_per_sec > 0),
+    burst        integer   not null default 40 check (burst > 0),
+    daily_quota  bigint,
+    active       boolean   not null default true,
+    created_at   timestamp not null default now()
+);
+
+create table if not exists api_key_usage
+(
+    key_hash varchar not null references api_keys (key_hash) on delete cascade,
+    day      date    not null,
+    requests bigint  not null default 0,
+    primary key (key_hash, day)
+);
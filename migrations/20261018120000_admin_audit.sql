@@ -0,0 +1,28 @@
+-- changes made through the /admin routes
+do
+$$
+    begin
+        create type t_admin_action as enum (
+            'collection_verified',
+            'collection_unverified',
+            'root_upserted',
+            'root_removed',
+            'nft_verified_refreshed'
+            );
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+create table if not exists admin_audit
+(
+    id         bigserial      not null primary key,
+    admin      t_address      not null,
+    action     t_admin_action not null,
+    target     t_address,
+    details    jsonb          not null default '{}'::jsonb,
+    created_at timestamp      not null default now()
+);
+
+create index if not exists admin_audit_admin_idx on admin_audit (admin, id desc);
+create index if not exists admin_audit_target_idx on admin_audit (target, id desc);
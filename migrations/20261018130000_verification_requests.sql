@@ -0,0 +1,31 @@
+-- verification applications sent by collection owners
+do
+$$
+    begin
+        create type t_verification_status as enum ('pending', 'approved', 'rejected');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+alter type t_admin_action add value if not exists 'verification_rejected';
+
+create table if not exists verification_requests
+(
+    id          bigserial             not null primary key,
+    collection  t_address             not null,
+    applicant   t_address             not null,
+    description text                  not null,
+    links       jsonb                 not null default '[]'::jsonb,
+    status      t_verification_status not null default 'pending',
+    reason      text,
+    reviewer    t_address,
+    created_at  timestamp             not null default now(),
+    reviewed_at timestamp
+);
+
+-- a single open application per collection
+create unique index if not exists verification_requests_pending_idx
+    on verification_requests (collection) where status = 'pending';
+create index if not exists verification_requests_applicant_idx on verification_requests (applicant, id desc);
+create index if not exists verification_requests_status_idx on verification_requests (status, id);
@@ -0,0 +1,85 @@
+-- user reports and the moderation status of nfts and collections
+do
+$$
+    begin
+        create type t_moderation_target as enum ('nft', 'collection', 'user');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type t_moderation_status as enum ('visible', 'flagged', 'hidden');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type t_report_reason as enum ('stolen', 'nsfw', 'infringement', 'scam', 'other');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+do
+$$
+    begin
+        create type t_report_status as enum ('open', 'resolved', 'dismissed');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+alter type t_admin_action add value if not exists 'moderation_changed';
+alter type t_admin_action add value if not exists 'report_dismissed';
+
+create table if not exists moderation_reports
+(
+    id          bigserial           not null primary key,
+    target_type t_moderation_target not null,
+    target      t_address           not null,
+    reason      t_report_reason     not null,
+    comment     text,
+    reporter    t_address           not null,
+    status      t_report_status     not null default 'open',
+    resolution  text,
+    resolver    t_address,
+    created_at  timestamp           not null default now(),
+    resolved_at timestamp
+);
+
+-- one open report per reporter and target
+create unique index if not exists moderation_reports_open_idx
+    on moderation_reports (target, reporter) where status = 'open';
+create index if not exists moderation_reports_status_idx on moderation_reports (status, id);
+create index if not exists moderation_reports_target_idx on moderation_reports (target, id desc);
+
+-- rows are kept when an item is made visible again, only `hidden` ones are filtered out
+create table if not exists moderation
+(
+    address     t_address           not null primary key,
+    target_type t_moderation_target not null,
+    status      t_moderation_status not null,
+    reason      text,
+    moderator   t_address           not null,
+    updated_at  timestamp           not null default now()
+);
+
+create index if not exists moderation_hidden_idx on moderation (address) where status = 'hidden';
+
+create or replace function moderation_hidden(p_nft t_address, p_collection t_address)
+    returns boolean
+    language sql
+    stable
+as
+$$
+select exists( select 1
+               from moderation m
+               where m.address in (p_nft, p_collection)
+                 and m.status = 'hidden' )
+$$;
@@ -0,0 +1,29 @@
+-- writes made with a user token, snapshots of the values before and after
+do
+$$
+    begin
+        create type t_write_action as enum (
+            'collection_custom_updated',
+            'collection_custom_reverted',
+            'user_updated'
+            );
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+create table if not exists write_audit
+(
+    id         bigserial      not null primary key,
+    actor      t_address      not null,
+    action     t_write_action not null,
+    target     t_address      not null,
+    before     jsonb,
+    after      jsonb          not null,
+    details    jsonb          not null default '{}'::jsonb,
+    ip         varchar,
+    created_at timestamp      not null default now()
+);
+
+create index if not exists write_audit_target_idx on write_audit (target, id desc);
+create index if not exists write_audit_actor_idx on write_audit (actor, id desc);
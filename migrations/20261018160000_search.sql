@@ -0,0 +1,13 @@
+-- full text and trigram indexes of /search, on expressions so the indexer tables keep their shape
+create extension if not exists pg_trgm;
+
+create index if not exists nft_verified_mv_search_idx on nft_verified_mv
+    using gin (to_tsvector('simple', coalesce(name, '') || ' ' || coalesce(description, '')));
+create index if not exists nft_verified_mv_name_trgm_idx on nft_verified_mv using gin (lower(name) gin_trgm_ops);
+create index if not exists nft_verified_mv_collection_id_idx on nft_verified_mv (collection, id);
+
+create index if not exists nft_collection_search_idx on nft_collection
+    using gin (to_tsvector('simple', coalesce(name, '') || ' ' || coalesce(description, '')));
+create index if not exists nft_collection_name_trgm_idx on nft_collection using gin (lower(name) gin_trgm_ops);
+
+create index if not exists users_username_trgm_idx on users using gin (lower(username) gin_trgm_ops);
@@ -0,0 +1,29 @@
+-- media kinds of the /nfts `media` filter: mimetypes of `files`, or the file extension when there is none,
+-- and the extensions of `image` and `animation_url`
+create or replace function nft_media_kinds(p_meta jsonb)
+    returns text[]
+    language sql
+    immutable
+as
+$$
+select coalesce(array_agg(distinct kind), '{}')
+from ( select case
+                  when t like 'image/%' or (t is null and u ~* '\.(png|jpe?g|gif|webp|svg)([?#]|$)') then 'image'
+                  when t like 'video/%' or (t is null and u ~* '\.(mp4|webm|mov)([?#]|$)') then 'video'
+                  when t like 'audio/%' or (t is null and u ~* '\.(mp3|wav|ogg)([?#]|$)') then 'audio'
+                  when t like 'model/%' or (t is null and u ~* '\.(glb|gltf)([?#]|$)') then '3d'
+                  end as kind
+       from ( select nullif(lower(coalesce(f ->> 'mimetype', f ->> 'mimeType')), '') as t,
+                     coalesce(f ->> 'source', f ->> 'uri', f ->> 'url', f #>> '{}')  as u
+              from jsonb_array_elements(case jsonb_typeof(p_meta -> 'files')
+                                            when 'array' then p_meta -> 'files'
+                                            else '[]'::jsonb end) f
+              union all
+              select null, p_meta ->> key
+              from unnest(array ['image', 'animation_url', 'animationUrl']) key ) files ) kinds
+where kind is not null
+$$;
+
+create index if not exists nft_metadata_media_kinds_idx on nft_metadata using gin (nft_media_kinds(meta));
+create index if not exists nft_events_minted_idx on nft_events (nft, created_at) where event_type = 'nft_created';
+create index if not exists nft_verified_mv_manager_idx on nft_verified_mv (manager);
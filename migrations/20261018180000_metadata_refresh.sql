@@ -0,0 +1,32 @@
+-- metadata refresh requests, picked up by the indexer:
+--   select .. where status = 'pending' order by id for update skip locked, then 'processing',
+--   then 'done' or 'failed' (with `error`) and `finished_at`
+do
+$$
+    begin
+        create type t_metadata_refresh_status as enum ('pending', 'processing', 'done', 'failed');
+    exception
+        when duplicate_object then null;
+    end
+$$;
+
+create table if not exists metadata_refresh_jobs
+(
+    id           bigserial                 not null primary key,
+    nft          t_address                 not null,
+    collection   t_address,
+    requested_by t_address                 not null,
+    status       t_metadata_refresh_status not null default 'pending',
+    error        text,
+    -- `nft_metadata.updated` when the refresh was requested, the api drops its caches once it changes
+    meta_updated timestamp,
+    created_at   timestamp                 not null default now(),
+    finished_at  timestamp
+);
+
+-- a single open job per nft
+create unique index if not exists metadata_refresh_jobs_open_idx
+    on metadata_refresh_jobs (nft) where status in ('pending', 'processing');
+create index if not exists metadata_refresh_jobs_nft_idx on metadata_refresh_jobs (nft, id desc);
+create index if not exists metadata_refresh_jobs_status_idx on metadata_refresh_jobs (status, id);
+create index if not exists metadata_refresh_jobs_created_idx on metadata_refresh_jobs (created_at);
@@ -0,0 +1,34 @@
+-- per nft overrides of the indexed metadata, written by the collection owner
+alter type t_write_action add value if not exists 'nft_metadata_override_updated';
+alter type t_write_action add value if not exists 'nft_metadata_override_removed';
+
+create table if not exists nft_metadata_override
+(
+    nft              t_address not null primary key,
+    collection       t_address not null,
+    -- `null` keeps the indexed value
+    name             text,
+    preview          text,
+    preview_mimetype text,
+    attributes       jsonb,
+    updated_by       t_address not null,
+    updated          timestamp not null default now()
+);
+
+create index if not exists nft_metadata_override_collection_idx on nft_metadata_override (collection, nft);
+
+-- the overridden fields in the metadata layout, `null` without override
+create or replace function nft_meta_override(p_nft t_address) returns jsonb
+    language sql
+    stable
+as
+$$
+select jsonb_strip_nulls(jsonb_build_object(
+        'name', o.name,
+        'preview', case
+                       when o.preview is not null
+                           then jsonb_build_object('source', o.preview, 'mimetype', o.preview_mimetype) end,
+        'attributes', o.attributes))
+from nft_metadata_override o
+where o.nft = p_nft
+$$;
@@ -0,0 +1,109 @@
+-- active orders that can't be filled anymore: expired, on a burned nft, a sell of an nft the seller
+-- no longer owns or an offer for an nft the buyer already owns. Such orders are kept `active` by the
+-- indexer until the offer contract reports otherwise, these checks keep them out of floors and offers
+-- Not covered: an offer whose buyer wallet no longer holds the offered amount. The indexer doesn't
+-- record token wallet balances, so such offers still count as valid and can set the best offer
+create or replace function direct_sell_valid(s nft_direct_sell) returns boolean
+    language sql
+    stable
+as
+$$
+select s.state = 'active'
+   and not coalesce(to_timestamp(0) < s.expired_at and s.expired_at < now(), false)
+   and exists(select 1
+              from nft n
+              where n.address = s.nft
+                and not n.burned
+                and n.owner = s.seller)
+$$;
+
+create or replace function direct_buy_valid(b nft_direct_buy) returns boolean
+    language sql
+    stable
+as
+$$
+select b.state = 'active'
+   and not coalesce(to_timestamp(0) < b.expired_at and b.expired_at < now(), false)
+   and exists(select 1
+              from nft n
+              where n.address = b.nft
+                and not n.burned
+                and n.owner is distinct from b.buyer)
+$$;
+
+-- the read views `nft_details` and `nft_collection_details` belong to the indexer, the api reads
+-- floors and best offers through these functions instead
+
+-- the valid offer with the highest usd value
+create or replace function nft_best_offer(p_nft t_address) returns varchar
+    language sql
+    stable
+as
+$$
+select b.address::varchar
+from nft_direct_buy b
+         join offers_whitelist ow on ow.address = b.address
+         join token_usd_prices tup on tup.token = b.price_token
+where b.nft = p_nft
+  and direct_buy_valid(b)
+order by b.price * tup.usd_price desc
+limit 1
+$$;
+
+-- the cheaper in usd of a running auction and a valid direct sell of the nft
+create or replace function nft_floor(p_nft t_address)
+    returns table
+            (
+                price_usd numeric,
+                price     numeric,
+                token     varchar
+            )
+    language sql
+    stable
+as
+$$
+select o.price_usd, o.price, o.token
+from ( select a.min_bid * tup.usd_price as price_usd,
+              a.min_bid                 as price,
+              tup.token::varchar        as token
+       from nft_auction a
+                join offers_whitelist ow on ow.address = a.address
+                join token_usd_prices tup on tup.token = a.price_token
+       where a.nft = p_nft
+         and a.status = 'active'
+         and (a.finished_at = to_timestamp(0) or a.finished_at > now())
+       union all
+       select s.price * tup.usd_price,
+              s.price,
+              tup.token::varchar
+       from nft_direct_sell s
+                join offers_whitelist ow on ow.address = s.address
+                join token_usd_prices tup on tup.token = s.price_token
+       where s.nft = p_nft
+         and direct_sell_valid(s) ) o
+order by o.price_usd
+limit 1
+$$;
+
+-- the lowest usd price of the running auctions and valid direct sells of the collection
+create or replace function collection_floor_usd(p_collection t_address) returns numeric
+    language sql
+    stable
+as
+$$
+select min(o.price_usd)
+from ( select s.price * tup.usd_price as price_usd
+       from nft_direct_sell s
+                join offers_whitelist ow on ow.address = s.address
+                join token_usd_prices tup on tup.token = s.price_token
+       where s.collection = p_collection
+         and direct_sell_valid(s)
+       union all
+       select a.min_bid * tup.usd_price
+       from nft_auction a
+                join offers_whitelist ow on ow.address = a.address
+                join token_usd_prices tup on tup.token = a.price_token
+       where a.collection = p_collection
+         and a.status = 'active'
+         and (a.finished_at = to_timestamp(0) or a.finished_at > now()) ) o
+$$;
@@ -0,0 +1,461 @@
+-- Fixtures for local development, loaded with `application --migrate --seed seed/dev.sql`.
+-- Three owners, two verified collections and an unverified one, with an auction, sells, buys
+-- and the events and price history they produce. Timestamps are relative to the load time
+-- and every insert is skipped when the row exists, so the file can be loaded again.
+--
+-- alice 0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe
+-- bob   0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f
+-- carol 0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e
+--
+-- Amounts are in the smallest units, WEVER has 9 decimals and USDT 6, the usd prices are per unit.
+
+insert into roots (address, code, expiry_date)
+values ('0:a4a49d818b3b003fb0af107a25a52fb29524efacc18e13af32cc6ec087750233', 'auction', null),
+       ('0:504f0e59ec1c85af9f3b2bec0ee37b5e37937d1dbe053fa37e746c687ce64e3e', 'sell', null),
+       ('0:60bd876bb151ea932cf11cd0b8044ef39aec3024b916aebcd213b216d72c92ba', 'buy', null)
+on conflict do nothing;
+
+insert into token_usd_prices (token, usd_price, ts)
+values ('0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14', 0.000000000045, now()),
+       ('0:a519f99bb5d6d51ef958ed24d337ad75a1c770885dcd42d51d6663f9fcdacfb2', 0.000001, now())
+on conflict do nothing;
+
+insert into token_usd_prices_history (token, usd_price, ts)
+select '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+       0.000000000045 + 0.000000000001 * (d % 5),
+       date_trunc('day', now()::timestamp) - make_interval(days => d)
+from generate_series(0, 29) d
+on conflict do nothing;
+
+insert into token_usd_prices_history (token, usd_price, ts)
+select '0:a519f99bb5d6d51ef958ed24d337ad75a1c770885dcd42d51d6663f9fcdacfb2',
+       0.000001,
+       date_trunc('day', now()::timestamp) - make_interval(days => d)
+from generate_series(0, 29) d
+on conflict do nothing;
+
+-- collections
+
+insert into nft_collection (address, owner, name, description, created, updated, wallpaper, logo, verified,
+                            first_mint, fee_numerator, fee_denominator)
+values ('0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+        'Venom Punks', 'Ten thousand pixel punks living on chain.',
+        now() - interval '60 days', now() - interval '2 days',
+        'https://assets.example.com/punks/wallpaper.png', 'https://assets.example.com/punks/logo.png',
+        true, now() - interval '59 days', 2, 100),
+       ('0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45',
+        '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f',
+        'Forest Spirits', 'Hand drawn spirits of the old forest.',
+        now() - interval '40 days', now() - interval '5 days',
+        null, 'https://assets.example.com/spirits/logo.png',
+        true, now() - interval '39 days', null, null),
+       ('0:243f2e4cabfb65f1335f6605afb70a27210e96f1dde0e980e286c9b83c50ff5f',
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+        'Unverified Drops', null,
+        now() - interval '20 days', now() - interval '20 days',
+        null, null,
+        false, now() - interval '20 days', null, null)
+on conflict do nothing;
+
+insert into nft_collection_custom (address, updated, name, description, wallpaper, logo, social)
+values ('0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45', now() - interval '4 days',
+        'Forest Spirits', 'Hand drawn spirits of the old forest, one per tree.',
+        'https://assets.example.com/spirits/wallpaper.png', null,
+        '{"twitter": "https://twitter.com/forestspirits", "discord": "https://discord.gg/forestspirits"}')
+on conflict do nothing;
+
+-- nfts
+
+insert into nft (address, collection, owner, manager, name, description, burned, updated, owner_update_lt,
+                 manager_update_lt, id)
+values ('0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+        '0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768',
+        'Punk #1', 'The first punk.', false, now() - interval '1 day', 1001, 1002, 1),
+       ('0:676d3cc00846d00090b5ea5e6d15f640402b34f8fcdf14e85c3a95625f758e32',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+        '0:c26728be5ca4f779cc91bb778b84fceb8d1e2d5a92a2d7646f7e1127f01daafb',
+        'Punk #2', 'A punk with a hat.', false, now() - interval '3 days', 1011, 1012, 2),
+       ('0:3c7e02f42822cb4e58defe4a28f85bbfb2c94662b52014f592f6a9670617373e',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f',
+        '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f',
+        'Punk #3', 'A punk with sunglasses.', false, now() - interval '6 days', 1021, 1021, 3),
+       ('0:365b2307219a3a081b4adaf2ee01a392cbacb85149481fbdd053282b6e88d7f3',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+        'Punk #4', 'Burned by its owner.', true, now() - interval '10 days', 1031, 1031, 4),
+       ('0:bda22d45dab103e6bcd526e91d214f73cbe6d3bf3a22720e08ce418501dde1ad',
+        '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45',
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+        'Oak Spirit', 'Guardian of the oldest oak.', false, now() - interval '3 days', 2001, 2001, 1),
+       ('0:ce7dd71b290ad778b8855c31cf0b52643b9c25dfadaa5b7d5458c1cf297ee103',
+        '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45',
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+        'Birch Spirit', 'Whispers through the birch leaves.', false, now() - interval '5 days', 2011, 2011, 2),
+       ('0:168a8b63300796da63f24f91ed0cf664781e9a8dd17334fd72096c2fb9b76f34',
+        '0:243f2e4cabfb65f1335f6605afb70a27210e96f1dde0e980e286c9b83c50ff5f',
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+        'Drop #1', 'Not verified yet.', false, now() - interval '10 days', 3001, 3001, 1)
+on conflict do nothing;
+
+insert into nft_metadata (nft, meta, updated)
+select m.nft,
+       jsonb_build_object(
+               'type', 'Basic NFT',
+               'name', n.name,
+               'description', n.description,
+               'preview', jsonb_build_object('source', m.image, 'mimetype', 'image/png'),
+               'files', jsonb_build_array(jsonb_build_object('source', m.image, 'mimetype', 'image/png')),
+               'attributes', m.attributes,
+               'external_url', 'https://example.com/nft/' || m.nft),
+       n.updated
+from ( values ('0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+               'https://assets.example.com/punks/1.png',
+               '[{"trait_type": "Background", "value": "Blue"}, {"trait_type": "Hat", "value": "None"}]'::jsonb),
+              ('0:676d3cc00846d00090b5ea5e6d15f640402b34f8fcdf14e85c3a95625f758e32',
+               'https://assets.example.com/punks/2.png',
+               '[{"trait_type": "Background", "value": "Red"}, {"trait_type": "Hat", "value": "Cap"}]'),
+              ('0:3c7e02f42822cb4e58defe4a28f85bbfb2c94662b52014f592f6a9670617373e',
+               'https://assets.example.com/punks/3.png',
+               '[{"trait_type": "Background", "value": "Blue"}, {"trait_type": "Hat", "value": "Cap"}]'),
+              ('0:365b2307219a3a081b4adaf2ee01a392cbacb85149481fbdd053282b6e88d7f3',
+               'https://assets.example.com/punks/4.png',
+               '[{"trait_type": "Background", "value": "Green"}]'),
+              ('0:bda22d45dab103e6bcd526e91d214f73cbe6d3bf3a22720e08ce418501dde1ad',
+               'https://assets.example.com/spirits/oak.png',
+               '[{"trait_type": "Tree", "value": "Oak"}, {"trait_type": "Age", "value": 300}]'),
+              ('0:ce7dd71b290ad778b8855c31cf0b52643b9c25dfadaa5b7d5458c1cf297ee103',
+               'https://assets.example.com/spirits/birch.png',
+               '[{"trait_type": "Tree", "value": "Birch"}, {"trait_type": "Age", "value": 80}]'),
+              ('0:168a8b63300796da63f24f91ed0cf664781e9a8dd17334fd72096c2fb9b76f34',
+               'https://assets.example.com/drops/1.png',
+               null) ) m(nft, image, attributes)
+         join nft n on n.address = m.nft
+on conflict do nothing;
+
+-- offers, every one is deployed by a root so it passes the whitelist
+
+insert into deployed_offers (address, root, created)
+values ('0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768',
+        '0:a4a49d818b3b003fb0af107a25a52fb29524efacc18e13af32cc6ec087750233', now() - interval '1 day'),
+       ('0:f6f677fabfa22fd09810e3a566d642d94a9941c3a5cf3ad8dffcf5e05080f973',
+        '0:a4a49d818b3b003fb0af107a25a52fb29524efacc18e13af32cc6ec087750233', now() - interval '8 days'),
+       ('0:c26728be5ca4f779cc91bb778b84fceb8d1e2d5a92a2d7646f7e1127f01daafb',
+        '0:504f0e59ec1c85af9f3b2bec0ee37b5e37937d1dbe053fa37e746c687ce64e3e', now() - interval '3 days'),
+       ('0:57ea541902650ae21dcf63411a0843462bf4c7a46753089742ec15711c5da5a7',
+        '0:504f0e59ec1c85af9f3b2bec0ee37b5e37937d1dbe053fa37e746c687ce64e3e', now() - interval '7 days'),
+       ('0:3d4254526b0204cee4aebff14be4aa447b36b55c75f0b30bdadc302293dd1372',
+        '0:504f0e59ec1c85af9f3b2bec0ee37b5e37937d1dbe053fa37e746c687ce64e3e', now() - interval '12 days'),
+       ('0:c9f273544893e6f7a8be3fcbf9601140ad6c0885a2c82b84233a77a7a1ae1c90',
+        '0:60bd876bb151ea932cf11cd0b8044ef39aec3024b916aebcd213b216d72c92ba', now() - interval '2 days'),
+       ('0:2518786ce3949c569e56edd595ac86f7032e2a500fba1a87341f88c740316715',
+        '0:60bd876bb151ea932cf11cd0b8044ef39aec3024b916aebcd213b216d72c92ba', now() - interval '11 days')
+on conflict do nothing;
+
+-- alice auctions Punk #1, bob and carol bid; carol won Oak Spirit from bob
+insert into nft_auction (address, nft, collection, nft_owner, wallet_for_bids, price_token, start_price, max_bid,
+                         min_bid, status, created_at, finished_at, tx_lt)
+values ('0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768',
+        '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+        '0:d75760363899ba3c628b7e3a7ebb3be153642699218459e5958fd4c7e031e748',
+        '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+        100000000000, 125000000000, 130000000000, 'active',
+        now() - interval '1 day', now() + interval '6 days', 1003),
+       ('0:f6f677fabfa22fd09810e3a566d642d94a9941c3a5cf3ad8dffcf5e05080f973',
+        '0:bda22d45dab103e6bcd526e91d214f73cbe6d3bf3a22720e08ce418501dde1ad',
+        '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45',
+        '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f',
+        '0:d75760363899ba3c628b7e3a7ebb3be153642699218459e5958fd4c7e031e748',
+        '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+        200000000000, 240000000000, 250000000000, 'completed',
+        now() - interval '8 days', now() - interval '3 days', 1995)
+on conflict do nothing;
+
+insert into nft_auction_bid (auction, buyer, price, price_token, next_bid_value, declined, created_at, tx_lt, nft,
+                             collection, nft_owner)
+values ('0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768',
+        '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f', 110000000000,
+        '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14', 120000000000, false,
+        now() - interval '20 hours', 1004,
+        '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe'),
+       ('0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768',
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e', 125000000000,
+        '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14', 130000000000, false,
+        now() - interval '6 hours', 1005,
+        '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe'),
+       ('0:f6f677fabfa22fd09810e3a566d642d94a9941c3a5cf3ad8dffcf5e05080f973',
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e', 240000000000,
+        '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14', 250000000000, false,
+        now() - interval '4 days', 1990,
+        '0:bda22d45dab103e6bcd526e91d214f73cbe6d3bf3a22720e08ce418501dde1ad',
+        '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45',
+        '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f')
+on conflict do nothing;
+
+-- Punk #2 is on sale, Birch Spirit was sold by bob to carol, Punk #3 sale was cancelled
+insert into nft_direct_sell (address, nft, collection, price_token, price, seller, finished_at, expired_at, state,
+                             created, updated, tx_lt)
+values ('0:c26728be5ca4f779cc91bb778b84fceb8d1e2d5a92a2d7646f7e1127f01daafb',
+        '0:676d3cc00846d00090b5ea5e6d15f640402b34f8fcdf14e85c3a95625f758e32',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14', 150000000000,
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+        null, to_timestamp(0), 'active', now() - interval '3 days', now() - interval '3 days', 1013),
+       ('0:57ea541902650ae21dcf63411a0843462bf4c7a46753089742ec15711c5da5a7',
+        '0:ce7dd71b290ad778b8855c31cf0b52643b9c25dfadaa5b7d5458c1cf297ee103',
+        '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45',
+        '0:a519f99bb5d6d51ef958ed24d337ad75a1c770885dcd42d51d6663f9fcdacfb2', 8000000,
+        '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f',
+        now() - interval '5 days', to_timestamp(0), 'filled', now() - interval '7 days', now() - interval '5 days',
+        2009),
+       ('0:3d4254526b0204cee4aebff14be4aa447b36b55c75f0b30bdadc302293dd1372',
+        '0:3c7e02f42822cb4e58defe4a28f85bbfb2c94662b52014f592f6a9670617373e',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14', 500000000000,
+        '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f',
+        now() - interval '9 days', to_timestamp(0), 'cancelled', now() - interval '12 days',
+        now() - interval '9 days', 1019)
+on conflict do nothing;
+
+-- carol offers for Punk #3, alice bought Drop #1 from carol
+insert into nft_direct_buy (address, nft, collection, price_token, price, buyer, finished_at, expired_at, state,
+                            created, updated, tx_lt)
+values ('0:c9f273544893e6f7a8be3fcbf9601140ad6c0885a2c82b84233a77a7a1ae1c90',
+        '0:3c7e02f42822cb4e58defe4a28f85bbfb2c94662b52014f592f6a9670617373e',
+        '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453',
+        '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14', 90000000000,
+        '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+        null, now() + interval '28 days', 'active', now() - interval '2 days', now() - interval '2 days', 1022),
+       ('0:2518786ce3949c569e56edd595ac86f7032e2a500fba1a87341f88c740316715',
+        '0:168a8b63300796da63f24f91ed0cf664781e9a8dd17334fd72096c2fb9b76f34',
+        '0:243f2e4cabfb65f1335f6605afb70a27210e96f1dde0e980e286c9b83c50ff5f',
+        '0:a519f99bb5d6d51ef958ed24d337ad75a1c770885dcd42d51d6663f9fcdacfb2', 4000000,
+        '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+        now() - interval '10 days', to_timestamp(0), 'filled', now() - interval '11 days',
+        now() - interval '10 days', 3000)
+on conflict do nothing;
+
+insert into nft_price_history (source, source_type, ts, price, price_token, usd_price, nft, collection, is_deal)
+select v.source, v.source_type::nft_price_source, v.ts, v.price, v.price_token, v.price * p.usd_price, v.nft,
+       v.collection, v.is_deal
+from ( values ('0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768', 'auctionBid',
+               now()::timestamp - interval '20 hours', 110000000000,
+               '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+               '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+               '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453', false),
+              ('0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768', 'auctionBid',
+               now()::timestamp - interval '6 hours', 125000000000,
+               '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+               '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+               '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453', false),
+              ('0:f6f677fabfa22fd09810e3a566d642d94a9941c3a5cf3ad8dffcf5e05080f973', 'auctionBid',
+               now()::timestamp - interval '3 days', 240000000000,
+               '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+               '0:bda22d45dab103e6bcd526e91d214f73cbe6d3bf3a22720e08ce418501dde1ad',
+               '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45', true),
+              ('0:57ea541902650ae21dcf63411a0843462bf4c7a46753089742ec15711c5da5a7', 'directSell',
+               now()::timestamp - interval '5 days', 8000000,
+               '0:a519f99bb5d6d51ef958ed24d337ad75a1c770885dcd42d51d6663f9fcdacfb2',
+               '0:ce7dd71b290ad778b8855c31cf0b52643b9c25dfadaa5b7d5458c1cf297ee103',
+               '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45', true),
+              ('0:2518786ce3949c569e56edd595ac86f7032e2a500fba1a87341f88c740316715', 'directBuy',
+               now()::timestamp - interval '10 days', 4000000,
+               '0:a519f99bb5d6d51ef958ed24d337ad75a1c770885dcd42d51d6663f9fcdacfb2',
+               '0:168a8b63300796da63f24f91ed0cf664781e9a8dd17334fd72096c2fb9b76f34',
+               '0:243f2e4cabfb65f1335f6605afb70a27210e96f1dde0e980e286c9b83c50ff5f', true) )
+         v(source, source_type, ts, price, price_token, nft, collection, is_deal)
+         join token_usd_prices p on p.token = v.price_token
+where not exists( select 1 from nft_price_history h where h.source = v.source and h.price = v.price );
+
+-- raw events, ids are fixed so loading the file twice does not duplicate them
+
+insert into nft_events (id, event_cat, event_type, address, created_lt, created_at, args, nft, collection)
+select v.id, v.event_cat::event_category, v.event_type::event_type, v.address, v.created_lt,
+       extract(epoch from now() - v.age)::bigint, v.args::jsonb, v.nft, v.collection
+from ( values
+           -- market fees
+           (1, 'auction', 'market_fee_default_changed',
+            '0:a4a49d818b3b003fb0af107a25a52fb29524efacc18e13af32cc6ec087750233', 10, interval '60 days',
+            '{"fee": {"numerator": 25, "denominator": 1000}}', null, null),
+           (2, 'direct_sell', 'market_fee_default_changed',
+            '0:504f0e59ec1c85af9f3b2bec0ee37b5e37937d1dbe053fa37e746c687ce64e3e', 11, interval '60 days',
+            '{"fee": {"numerator": 25, "denominator": 1000}}', null, null),
+           (3, 'direct_buy', 'market_fee_default_changed',
+            '0:60bd876bb151ea932cf11cd0b8044ef39aec3024b916aebcd213b216d72c92ba', 12, interval '60 days',
+            '{"fee": {"numerator": 25, "denominator": 1000}}', null, null),
+           (4, 'auction', 'market_fee_changed',
+            '0:a4a49d818b3b003fb0af107a25a52fb29524efacc18e13af32cc6ec087750233', 1002, interval '1 day',
+            '{"auction": "0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768", "fee": {"numerator": 2, "denominator": 100}}',
+            null, null),
+           -- mints
+           (5, 'nft', 'nft_created', '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453', 100,
+            interval '59 days',
+            '{"owner": "0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe", "creator": "0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe"}',
+            '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+            '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453'),
+           (6, 'nft', 'nft_created', '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453', 101,
+            interval '58 days',
+            '{"owner": "0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe", "creator": "0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe"}',
+            '0:676d3cc00846d00090b5ea5e6d15f640402b34f8fcdf14e85c3a95625f758e32',
+            '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453'),
+           (7, 'nft', 'nft_created', '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453', 102,
+            interval '57 days',
+            '{"owner": "0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f", "creator": "0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe"}',
+            '0:3c7e02f42822cb4e58defe4a28f85bbfb2c94662b52014f592f6a9670617373e',
+            '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453'),
+           (8, 'nft', 'nft_created', '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45', 200,
+            interval '39 days',
+            '{"owner": "0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f", "creator": "0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f"}',
+            '0:bda22d45dab103e6bcd526e91d214f73cbe6d3bf3a22720e08ce418501dde1ad',
+            '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45'),
+           (9, 'nft', 'nft_created', '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45', 201,
+            interval '38 days',
+            '{"owner": "0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f", "creator": "0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f"}',
+            '0:ce7dd71b290ad778b8855c31cf0b52643b9c25dfadaa5b7d5458c1cf297ee103',
+            '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45'),
+           -- Punk #1 auction
+           (10, 'auction', 'auction_active', '0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768',
+            1003, interval '1 day',
+            json_build_object('value0', json_build_object(
+                    'subject_owner', '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+                    'payment_token', '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+                    'price', '100000000000',
+                    'start_time', extract(epoch from now() - interval '1 day')::bigint,
+                    'end_time', extract(epoch from now() + interval '6 days')::bigint,
+                    'duration', 604800))::text,
+            '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+            '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453'),
+           (11, 'auction', 'auction_bid_placed', '0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768',
+            1004, interval '20 hours',
+            '{"buyer": "0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f", "value": "110000000000", "value3": {"payment_token": "0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14"}}',
+            '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+            '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453'),
+           (12, 'auction', 'auction_bid_placed', '0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768',
+            1005, interval '6 hours',
+            '{"buyer": "0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e", "value": "125000000000", "value3": {"payment_token": "0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14"}}',
+            '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+            '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453'),
+           -- Oak Spirit auction, won by carol
+           (13, 'auction', 'auction_complete', '0:f6f677fabfa22fd09810e3a566d642d94a9941c3a5cf3ad8dffcf5e05080f973',
+            1995, interval '3 days',
+            json_build_object(
+                    'buyer', '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+                    'value', '240000000000',
+                    'value2', json_build_object(
+                            'subject_owner', '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f',
+                            'payment_token', '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+                            'start_time', extract(epoch from now() - interval '8 days')::bigint,
+                            'end_time', extract(epoch from now() - interval '3 days')::bigint,
+                            'duration', 432000))::text,
+            '0:bda22d45dab103e6bcd526e91d214f73cbe6d3bf3a22720e08ce418501dde1ad',
+            '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45'),
+           (14, 'nft', 'nft_owner_changed', '0:bda22d45dab103e6bcd526e91d214f73cbe6d3bf3a22720e08ce418501dde1ad',
+            1996, interval '3 days',
+            '{"old_owner": "0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f", "new_owner": "0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e"}',
+            '0:bda22d45dab103e6bcd526e91d214f73cbe6d3bf3a22720e08ce418501dde1ad',
+            '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45'),
+           -- sells
+           (15, 'direct_sell', 'direct_sell_state_changed',
+            '0:c26728be5ca4f779cc91bb778b84fceb8d1e2d5a92a2d7646f7e1127f01daafb', 1013, interval '3 days',
+            json_build_object('value2', json_build_object(
+                    'creator', '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+                    'token', '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+                    '_price', '150000000000',
+                    'start', extract(epoch from now() - interval '3 days')::bigint,
+                    'end', 0,
+                    'status', 2))::text,
+            '0:676d3cc00846d00090b5ea5e6d15f640402b34f8fcdf14e85c3a95625f758e32',
+            '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453'),
+           (16, 'direct_sell', 'direct_sell_state_changed',
+            '0:57ea541902650ae21dcf63411a0843462bf4c7a46753089742ec15711c5da5a7', 2009, interval '5 days',
+            json_build_object('value2', json_build_object(
+                    'creator', '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f',
+                    'token', '0:a519f99bb5d6d51ef958ed24d337ad75a1c770885dcd42d51d6663f9fcdacfb2',
+                    '_price', '8000000',
+                    'start', extract(epoch from now() - interval '7 days')::bigint,
+                    'end', 0,
+                    'status', 3))::text,
+            '0:ce7dd71b290ad778b8855c31cf0b52643b9c25dfadaa5b7d5458c1cf297ee103',
+            '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45'),
+           (17, 'nft', 'nft_owner_changed', '0:ce7dd71b290ad778b8855c31cf0b52643b9c25dfadaa5b7d5458c1cf297ee103',
+            2010, interval '5 days',
+            '{"old_owner": "0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f", "new_owner": "0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e"}',
+            '0:ce7dd71b290ad778b8855c31cf0b52643b9c25dfadaa5b7d5458c1cf297ee103',
+            '0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45'),
+           (18, 'direct_sell', 'direct_sell_state_changed',
+            '0:3d4254526b0204cee4aebff14be4aa447b36b55c75f0b30bdadc302293dd1372', 1019, interval '9 days',
+            json_build_object('value2', json_build_object(
+                    'creator', '0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f',
+                    'token', '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+                    '_price', '500000000000',
+                    'start', extract(epoch from now() - interval '12 days')::bigint,
+                    'end', 0,
+                    'status', 4))::text,
+            '0:3c7e02f42822cb4e58defe4a28f85bbfb2c94662b52014f592f6a9670617373e',
+            '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453'),
+           -- buys
+           (19, 'direct_buy', 'direct_buy_state_changed',
+            '0:c9f273544893e6f7a8be3fcbf9601140ad6c0885a2c82b84233a77a7a1ae1c90', 1022, interval '2 days',
+            json_build_object('value2', json_build_object(
+                    'creator', '0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e',
+                    'spent_token', '0:2c3a2ff6443af741ce653ae4ef2c85c2d52a9df84944bbe14d702c3131da3f14',
+                    '_price', '90000000000',
+                    'start_time_buy', extract(epoch from now() - interval '2 days')::bigint,
+                    'end_time_buy', extract(epoch from now() + interval '28 days')::bigint,
+                    'duration_time', 2592000,
+                    'status', 2))::text,
+            '0:3c7e02f42822cb4e58defe4a28f85bbfb2c94662b52014f592f6a9670617373e',
+            '0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453'),
+           (20, 'direct_buy', 'direct_buy_state_changed',
+            '0:2518786ce3949c569e56edd595ac86f7032e2a500fba1a87341f88c740316715', 3000, interval '10 days',
+            json_build_object('value2', json_build_object(
+                    'creator', '0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+                    'spent_token', '0:a519f99bb5d6d51ef958ed24d337ad75a1c770885dcd42d51d6663f9fcdacfb2',
+                    '_price', '4000000',
+                    'start_time_buy', extract(epoch from now() - interval '11 days')::bigint,
+                    'end_time_buy', 0,
+                    'duration_time', 0,
+                    'status', 3))::text,
+            '0:168a8b63300796da63f24f91ed0cf664781e9a8dd17334fd72096c2fb9b76f34',
+            '0:243f2e4cabfb65f1335f6605afb70a27210e96f1dde0e980e286c9b83c50ff5f'),
+           (21, 'nft', 'nft_owner_changed', '0:168a8b63300796da63f24f91ed0cf664781e9a8dd17334fd72096c2fb9b76f34',
+            3001, interval '10 days',
+            '{"old_owner": "0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e", "new_owner": "0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe"}',
+            '0:168a8b63300796da63f24f91ed0cf664781e9a8dd17334fd72096c2fb9b76f34',
+            '0:243f2e4cabfb65f1335f6605afb70a27210e96f1dde0e980e286c9b83c50ff5f') )
+         v(id, event_cat, event_type, address, created_lt, age, args, nft, collection)
+on conflict do nothing;
+
+select setval('nft_events_id_seq', ( select max(id) from nft_events ));
+
+-- profiles and partner keys
+
+insert into users (address, logo_nft, username, bio, twitter, instagram, facebook, link, email)
+values ('0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe',
+        '0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75',
+        'alice', 'Collector of punks.', 'https://twitter.com/alice', null, null, 'https://alice.example.com',
+        'alice@example.com'),
+       ('0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f', null, 'bob', 'Draws forest spirits.',
+        null, 'https://instagram.com/bob', null, null, null)
+on conflict do nothing;
+
+-- the key is `dev-local-key`
+insert into api_keys (key_hash, name, rate_per_sec, burst, daily_quota)
+values ('3700285e3c8496a57e45eb1ccd43f2424852788576961320fbb31f86f17edb61', 'local development', 100, 200, null)
+on conflict do nothing;
+
+refresh materialized view nft_verified_mv;
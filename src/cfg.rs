@@ -1,10 +1,17 @@
-use config::{self, ConfigError, Environment};
-use serde::Deserialize;
+use crate::services::rate_limit::ClientIpSource;
+use config::{self, ConfigError, Environment, File};
+use serde::{Deserialize, Serialize};
 use sqlx::{
     postgres::{PgPool, PgPoolOptions},
     Error,
 };
+use std::collections::BTreeMap;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::str::FromStr;
+use std::time::Duration;
+use ton_block::MsgAddressInt;
+
+const REDACTED: &str = "<redacted>";
 
 fn default_http_address() -> SocketAddr {
     SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 8080))
@@ -18,7 +25,134 @@ fn default_max_connections() -> u32 {
     50
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_worker_threads() -> usize {
+    16
+}
+
+fn default_tokens_manifest_url() -> String {
+    String::from("https://raw.githubusercontent.com/broxus/ton-assets/master/manifest.json")
+}
+
+fn default_flatqube_url() -> String {
+    String::from("https://api.flatqube.io/v1/currencies_usdt_prices")
+}
+
+fn default_price_providers() -> Vec<PriceProviderKind> {
+    vec![PriceProviderKind::VenomDex, PriceProviderKind::FlatQube]
+}
+
+fn default_update_interval_sec() -> u64 {
+    5 * 60
+}
+
+fn default_retry_interval_sec() -> u64 {
+    30
+}
+
+fn default_max_deviation() -> f64 {
+    0.5
+}
+
+fn default_max_outlier_updates() -> u32 {
+    3
+}
+
+fn default_max_price_age_sec() -> u64 {
+    60 * 60
+}
+
+fn default_allowed_origins() -> Vec<String> {
+    vec![String::from("*")]
+}
+
+fn default_allowed_headers() -> Vec<String> {
+    ["authority", "user-agent", "content-type", "authorization"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_allowed_methods() -> Vec<String> {
+    ["GET", "POST", "OPTIONS"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_long_ttl_sec() -> u64 {
+    5 * 60
+}
+
+fn default_medium_ttl_sec() -> u64 {
+    60
+}
+
+fn default_short_ttl_sec() -> u64 {
+    10
+}
+
+fn default_realtime_ttl_sec() -> u64 {
+    1
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_ip_bucket() -> BucketConfig {
+    BucketConfig {
+        per_sec: 10.0,
+        burst: 30,
+    }
+}
+
+fn default_route_costs() -> BTreeMap<String, u32> {
+    [
+        ("/nfts", 3),
+        ("/search", 5),
+        ("/search/suggest", 1),
+        ("/collections", 2),
+        ("/events", 2),
+        ("/metrics", 2),
+    ]
+    .into_iter()
+    .map(|(route, cost)| (route.to_string(), cost))
+    .collect()
+}
+
+fn default_api_key_header() -> String {
+    String::from("x-api-key")
+}
+
+fn default_trusted_proxy_hops() -> usize {
+    1
+}
+
+fn default_request_timeout_sec() -> u64 {
+    30
+}
+
+fn default_graphql_complexity_per_token() -> usize {
+    100
+}
+
+fn default_suggest_refresh_interval_sec() -> u64 {
+    5 * 60
+}
+
+fn default_suggest_max_nfts() -> usize {
+    500_000
+}
+
+fn default_metadata_refresh_cooldown_sec() -> u64 {
+    10 * 60
+}
+
+fn default_metadata_refresh_watch_interval_sec() -> u64 {
+    15
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DatabaseConfig {
     #[serde(default = "default_url")]
     pub url: String,
@@ -45,25 +179,496 @@ impl Default for DatabaseConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceProviderKind {
+    #[serde(rename = "flatqube")]
+    FlatQube,
+    VenomDex,
+    File,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PricesConfig {
+    /// Providers in priority order, the first one returning a sane price wins
+    #[serde(default = "default_price_providers")]
+    pub providers: Vec<PriceProviderKind>,
+
+    #[serde(default = "default_flatqube_url")]
+    pub flatqube_url: String,
+
+    /// Path to a yaml/json `{ token: usd_price }` map used by the `file` provider
+    pub file_path: Option<String>,
+
+    #[serde(default = "default_update_interval_sec")]
+    pub update_interval_sec: u64,
+
+    /// Delay before the next attempt when no provider returned anything
+    #[serde(default = "default_retry_interval_sec")]
+    pub retry_interval_sec: u64,
+
+    /// Max relative change against the last accepted price before a quote is treated as an outlier
+    #[serde(default = "default_max_deviation")]
+    pub max_deviation: f64,
+
+    /// Consecutive updates a token's quotes can be rejected as outliers, the next one is accepted
+    #[serde(default = "default_max_outlier_updates")]
+    pub max_outlier_updates: u32,
+
+    /// Age of the last accepted price after which any quote replaces it
+    #[serde(default = "default_max_price_age_sec")]
+    pub max_price_age_sec: u64,
+}
+
+impl Default for PricesConfig {
+    fn default() -> Self {
+        PricesConfig {
+            providers: default_price_providers(),
+            flatqube_url: default_flatqube_url(),
+            file_path: None,
+            update_interval_sec: default_update_interval_sec(),
+            retry_interval_sec: default_retry_interval_sec(),
+            max_deviation: default_max_deviation(),
+            max_outlier_updates: default_max_outlier_updates(),
+            max_price_age_sec: default_max_price_age_sec(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CorsConfig {
+    /// `*` allows any origin
+    #[serde(default = "default_allowed_origins")]
+    pub allowed_origins: Vec<String>,
+
+    #[serde(default = "default_allowed_headers")]
+    pub allowed_headers: Vec<String>,
+
+    #[serde(default = "default_allowed_methods")]
+    pub allowed_methods: Vec<String>,
+
+    pub max_age_sec: Option<u64>,
+}
+
+impl CorsConfig {
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|o| o == "*")
+    }
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            allowed_origins: default_allowed_origins(),
+            allowed_headers: default_allowed_headers(),
+            allowed_methods: default_allowed_methods(),
+            max_age_sec: None,
+        }
+    }
+}
+
+/// Time to live of the response caches, from the slowest changing data to the fastest one
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CacheConfig {
+    #[serde(default = "default_long_ttl_sec")]
+    pub long_ttl_sec: u64,
+
+    #[serde(default = "default_medium_ttl_sec")]
+    pub medium_ttl_sec: u64,
+
+    #[serde(default = "default_short_ttl_sec")]
+    pub short_ttl_sec: u64,
+
+    #[serde(default = "default_realtime_ttl_sec")]
+    pub realtime_ttl_sec: u64,
+}
+
+impl CacheConfig {
+    pub fn long_ttl(&self) -> Duration {
+        Duration::from_secs(self.long_ttl_sec)
+    }
+
+    pub fn medium_ttl(&self) -> Duration {
+        Duration::from_secs(self.medium_ttl_sec)
+    }
+
+    pub fn short_ttl(&self) -> Duration {
+        Duration::from_secs(self.short_ttl_sec)
+    }
+
+    pub fn realtime_ttl(&self) -> Duration {
+        Duration::from_secs(self.realtime_ttl_sec)
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            long_ttl_sec: default_long_ttl_sec(),
+            medium_ttl_sec: default_medium_ttl_sec(),
+            short_ttl_sec: default_short_ttl_sec(),
+            realtime_ttl_sec: default_realtime_ttl_sec(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct BucketConfig {
+    /// Tokens refilled per second
+    pub per_sec: f64,
+    /// Bucket capacity, i.e. the largest allowed burst
+    pub burst: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RateLimitConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Bucket of anonymous clients, keyed by ip. Api keys carry their own limits
+    #[serde(default = "default_ip_bucket")]
+    pub ip: BucketConfig,
+
+    /// Tokens taken by a request, by path prefix (the longest match wins). Unlisted routes cost 1
+    #[serde(default = "default_route_costs")]
+    pub route_costs: BTreeMap<String, u32>,
+
+    #[serde(default = "default_api_key_header")]
+    pub api_key_header: String,
+
+    /// Take the client ip from `x-forwarded-for`/`x-real-ip`, enable only behind a trusted proxy
+    #[serde(default)]
+    pub trust_forwarded_for: bool,
+
+    /// Proxies in front of the api that append to `x-forwarded-for`, the client ip is the entry
+    /// this many places from the right
+    #[serde(default = "default_trusted_proxy_hops")]
+    pub trusted_proxy_hops: usize,
+
+    #[serde(default = "default_request_timeout_sec")]
+    pub request_timeout_sec: u64,
+
+    /// `/graphql` takes one more token for every this many points of query complexity
+    #[serde(default = "default_graphql_complexity_per_token")]
+    pub graphql_complexity_per_token: usize,
+}
+
+impl RateLimitConfig {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_sec)
+    }
+
+    pub fn client_ip_source(&self) -> ClientIpSource {
+        if self.trust_forwarded_for {
+            ClientIpSource::Forwarded {
+                proxy_hops: self.trusted_proxy_hops,
+            }
+        } else {
+            ClientIpSource::Peer
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            ip: default_ip_bucket(),
+            route_costs: default_route_costs(),
+            api_key_header: default_api_key_header(),
+            trust_forwarded_for: false,
+            trusted_proxy_hops: default_trusted_proxy_hops(),
+            request_timeout_sec: default_request_timeout_sec(),
+            graphql_complexity_per_token: default_graphql_complexity_per_token(),
+        }
+    }
+}
+
+/// Names completed by `/search/suggest`, kept in memory and reloaded from the database
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SuggestConfig {
+    #[serde(default = "default_suggest_refresh_interval_sec")]
+    pub refresh_interval_sec: u64,
+
+    /// Nfts loaded into the index, those of the most owned collections first
+    #[serde(default = "default_suggest_max_nfts")]
+    pub max_nfts: usize,
+}
+
+impl SuggestConfig {
+    pub fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_interval_sec)
+    }
+}
+
+impl Default for SuggestConfig {
+    fn default() -> Self {
+        SuggestConfig {
+            refresh_interval_sec: default_suggest_refresh_interval_sec(),
+            max_nfts: default_suggest_max_nfts(),
+        }
+    }
+}
+
+/// `/nft/{address}/refresh-metadata` requests and the cache invalidation that follows them
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MetadataRefreshConfig {
+    /// Time between two refresh requests of the same nft
+    #[serde(default = "default_metadata_refresh_cooldown_sec")]
+    pub cooldown_sec: u64,
+
+    /// How often `nft_metadata` is checked for rewritten metadata of the requested nfts
+    #[serde(default = "default_metadata_refresh_watch_interval_sec")]
+    pub watch_interval_sec: u64,
+}
+
+impl MetadataRefreshConfig {
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.cooldown_sec)
+    }
+
+    pub fn watch_interval(&self) -> Duration {
+        Duration::from_secs(self.watch_interval_sec)
+    }
+}
+
+impl Default for MetadataRefreshConfig {
+    fn default() -> Self {
+        MetadataRefreshConfig {
+            cooldown_sec: default_metadata_refresh_cooldown_sec(),
+            watch_interval_sec: default_metadata_refresh_watch_interval_sec(),
+        }
+    }
+}
+
+/// `Sunset` dates (HTTP-date, e.g. `Sat, 01 May 2027 00:00:00 GMT`) announced on deprecated routes
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct VersioningConfig {
+    /// Unprefixed paths, kept as aliases of `/v1`
+    pub legacy_sunset: Option<String>,
+    /// `/v1` routes dropped from `/v2`
+    pub v1_sunset: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ApiConfig {
     #[serde(default = "default_http_address")]
     pub http_address: SocketAddr,
+    #[serde(default = "default_worker_threads")]
+    pub worker_threads: usize,
+    #[serde(default)]
     pub database: DatabaseConfig,
     pub auth_token_lifetime: u32,
     pub jwt_secret: String,
+    /// Wallets signed in with the admin role, allowed to call the `/admin` routes
+    #[serde(default)]
+    pub admin_addresses: Vec<String>,
     pub base_url: String,
     pub prices_url: String,
     pub main_token: String,
+    #[serde(default = "default_tokens_manifest_url")]
+    pub tokens_manifest_url: String,
+    #[serde(default)]
+    pub prices: PricesConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub suggest: SuggestConfig,
+    #[serde(default)]
+    pub metadata_refresh: MetadataRefreshConfig,
+    #[serde(default)]
+    pub versioning: VersioningConfig,
 }
 
 impl ApiConfig {
+    /// Reads the file pointed by `CONFIG_PATH` (if any) and applies env overrides on top
     pub fn new() -> Result<ApiConfig, ConfigError> {
+        Self::load(std::env::var("CONFIG_PATH").ok().as_deref())
+    }
+
+    /// Layers: serde defaults < yaml/toml/json file < `{PREFIX}__*` env variables
+    pub fn load(path: Option<&str>) -> Result<ApiConfig, ConfigError> {
         let prefix = std::env::var("PREFIX").unwrap_or_else(|_| String::from("indexer_api"));
-        config::Config::builder()
-            .add_source(Environment::with_prefix(&prefix).separator("__"))
+        let mut builder = config::Config::builder();
+        if let Some(path) = path {
+            builder = builder.add_source(File::with_name(path).required(true));
+        }
+        let cfg: ApiConfig = builder
+            .add_source(
+                Environment::with_prefix(&prefix)
+                    .separator("__")
+                    .list_separator(",")
+                    .with_list_parse_key("admin_addresses")
+                    .with_list_parse_key("prices.providers")
+                    .with_list_parse_key("cors.allowed_origins")
+                    .with_list_parse_key("cors.allowed_headers")
+                    .with_list_parse_key("cors.allowed_methods")
+                    .try_parsing(true),
+            )
             .build()?
-            .try_deserialize()
+            .try_deserialize()?;
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+
+        if self.worker_threads == 0 {
+            errors.push("worker_threads must be greater than 0".to_string());
+        }
+        if self.database.max_connections == 0 {
+            errors.push("database.max_connections must be greater than 0".to_string());
+        }
+        if let Err(e) = reqwest::Url::parse(&self.database.url) {
+            errors.push(format!("database.url is not a valid url: {e}"));
+        }
+        if self.auth_token_lifetime == 0 {
+            errors.push("auth_token_lifetime must be greater than 0".to_string());
+        }
+        if self.jwt_secret.trim().is_empty() {
+            errors.push("jwt_secret must not be empty".to_string());
+        }
+        for address in &self.admin_addresses {
+            // compared as is with the signed in address, which is always the raw lowercase form
+            let parsed = MsgAddressInt::from_str(address).map(|a| a.to_string());
+            if parsed.as_deref().ok() != Some(address.as_str()) {
+                errors.push(format!("admin_addresses: {address:?} is not a raw address"));
+            }
+        }
+        if self.base_url.trim().is_empty() {
+            errors.push("base_url must not be empty".to_string());
+        }
+        if self.main_token.trim().is_empty() {
+            errors.push("main_token must not be empty".to_string());
+        }
+        for (key, url) in [
+            ("prices_url", &self.prices_url),
+            ("tokens_manifest_url", &self.tokens_manifest_url),
+            ("prices.flatqube_url", &self.prices.flatqube_url),
+        ] {
+            if let Err(e) = reqwest::Url::parse(url) {
+                errors.push(format!("{key} is not a valid url ({url:?}): {e}"));
+            }
+        }
+
+        if self.prices.providers.is_empty() {
+            errors.push("prices.providers must list at least one provider".to_string());
+        }
+        if self.prices.providers.contains(&PriceProviderKind::File)
+            && self.prices.file_path.is_none()
+        {
+            errors.push("prices.file_path is required by the file price provider".to_string());
+        }
+        if self.prices.update_interval_sec == 0 || self.prices.retry_interval_sec == 0 {
+            errors.push(
+                "prices.update_interval_sec and prices.retry_interval_sec must be greater than 0"
+                    .to_string(),
+            );
+        }
+        if !(self.prices.max_deviation > 0.0 && self.prices.max_deviation.is_finite()) {
+            errors.push("prices.max_deviation must be a positive number".to_string());
+        }
+
+        if self.cors.allowed_origins.is_empty() {
+            errors.push("cors.allowed_origins must not be empty, use \"*\" to allow any".into());
+        }
+        for origin in self.cors.allowed_origins.iter().filter(|o| *o != "*") {
+            if let Err(e) = reqwest::Url::parse(origin) {
+                errors.push(format!(
+                    "cors.allowed_origins: {origin:?} is not a valid origin: {e}"
+                ));
+            }
+        }
+        for method in &self.cors.allowed_methods {
+            if http::Method::from_bytes(method.as_bytes()).is_err() {
+                errors.push(format!(
+                    "cors.allowed_methods: {method:?} is not a http method"
+                ));
+            }
+        }
+
+        let cache = &self.cache;
+        if [
+            cache.long_ttl_sec,
+            cache.medium_ttl_sec,
+            cache.short_ttl_sec,
+            cache.realtime_ttl_sec,
+        ]
+        .contains(&0)
+        {
+            errors.push("cache ttls must be greater than 0".to_string());
+        }
+
+        let rate_limit = &self.rate_limit;
+        if !(rate_limit.ip.per_sec > 0.0 && rate_limit.ip.per_sec.is_finite())
+            || rate_limit.ip.burst == 0
+        {
+            errors.push("rate_limit.ip needs positive per_sec and burst".to_string());
+        }
+        for (route, cost) in &rate_limit.route_costs {
+            if !route.starts_with('/') {
+                errors.push(format!(
+                    "rate_limit.route_costs: {route:?} must start with '/'"
+                ));
+            }
+            if *cost > rate_limit.ip.burst {
+                errors.push(format!(
+                    "rate_limit.route_costs: cost of {route:?} exceeds rate_limit.ip.burst, the route would be unreachable"
+                ));
+            }
+        }
+        if http::HeaderName::from_bytes(rate_limit.api_key_header.as_bytes()).is_err() {
+            errors.push("rate_limit.api_key_header is not a valid header name".to_string());
+        }
+        if rate_limit.request_timeout_sec == 0 {
+            errors.push("rate_limit.request_timeout_sec must be greater than 0".to_string());
+        }
+        if rate_limit.graphql_complexity_per_token == 0 {
+            errors
+                .push("rate_limit.graphql_complexity_per_token must be greater than 0".to_string());
+        }
+
+        if self.suggest.refresh_interval_sec == 0 {
+            errors.push("suggest.refresh_interval_sec must be greater than 0".to_string());
+        }
+        if self.metadata_refresh.watch_interval_sec == 0 {
+            errors.push("metadata_refresh.watch_interval_sec must be greater than 0".to_string());
+        }
+
+        for (key, sunset) in [
+            ("versioning.legacy_sunset", &self.versioning.legacy_sunset),
+            ("versioning.v1_sunset", &self.versioning.v1_sunset),
+        ] {
+            if let Some(Err(e)) = sunset.as_deref().map(chrono::DateTime::parse_from_rfc2822) {
+                errors.push(format!("{key} is not a valid HTTP-date: {e}"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Message(format!(
+                "invalid configuration:\n  - {}",
+                errors.join("\n  - ")
+            )))
+        }
+    }
+
+    /// Copy safe to print: secrets and database credentials are masked
+    pub fn redacted(&self) -> ApiConfig {
+        let mut cfg = self.clone();
+        cfg.jwt_secret = REDACTED.to_string();
+        if let Ok(mut url) = reqwest::Url::parse(&cfg.database.url) {
+            if url.password().is_some() && url.set_password(Some(REDACTED)).is_ok() {
+                cfg.database.url = url.to_string();
+            }
+        }
+        cfg
     }
 }
 
@@ -71,12 +676,66 @@ impl Default for ApiConfig {
     fn default() -> Self {
         ApiConfig {
             http_address: default_http_address(),
+            worker_threads: default_worker_threads(),
             database: DatabaseConfig::default(),
             auth_token_lifetime: 999999999,
             jwt_secret: "jwtsecret".to_string(),
+            admin_addresses: Vec::new(),
             base_url: String::default(),
             prices_url: "".to_string(),
             main_token: "".to_string(),
+            tokens_manifest_url: default_tokens_manifest_url(),
+            prices: PricesConfig::default(),
+            cors: CorsConfig::default(),
+            cache: CacheConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            suggest: SuggestConfig::default(),
+            metadata_refresh: MetadataRefreshConfig::default(),
+            versioning: VersioningConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub config_path: Option<String>,
+    pub print_config: bool,
+    /// Apply `migrations/` before serving
+    pub migrate: bool,
+    /// Sql fixtures loaded after the migrations
+    pub seed: Option<String>,
+}
+
+impl CliArgs {
+    pub fn parse() -> anyhow::Result<Self> {
+        let mut args = CliArgs::default();
+        let mut iter = std::env::args().skip(1).filter(|a| !a.is_empty());
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--config" => match iter.next() {
+                    Some(path) => args.config_path = Some(path),
+                    None => anyhow::bail!("--config requires a path"),
+                },
+                "--print-config" => args.print_config = true,
+                "--migrate" => args.migrate = true,
+                "--seed" => match iter.next() {
+                    Some(path) => args.seed = Some(path),
+                    None => anyhow::bail!("--seed requires a path"),
+                },
+                other => {
+                    if let Some(path) = other.strip_prefix("--config=") {
+                        args.config_path = Some(path.to_string());
+                    } else if let Some(path) = other.strip_prefix("--seed=") {
+                        args.seed = Some(path.to_string());
+                    } else {
+                        anyhow::bail!("unknown argument {other:?}");
+                    }
+                }
+            }
+        }
+        if args.config_path.is_none() {
+            args.config_path = std::env::var("CONFIG_PATH").ok();
         }
+        Ok(args)
     }
 }
@@ -0,0 +1,29 @@
+use anyhow::Context;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPool;
+use sqlx::Executor;
+
+/// `migrations/`, embedded at build time. The indexer records its own migrations in the same
+/// `_sqlx_migrations` table, those are skipped rather than reported missing
+pub static MIGRATOR: Migrator = Migrator {
+    ignore_missing: true,
+    ..sqlx::migrate!()
+};
+
+/// Applies the pending migrations, the read schema one is dated first and is a no-op on an
+/// indexer database
+pub async fn run_migrations(pool: &PgPool) -> anyhow::Result<()> {
+    MIGRATOR.run(pool).await.context("failed to run migrations")
+}
+
+/// Runs a sql fixtures file, e.g. `seed/dev.sql`, in a single transaction
+pub async fn load_seed(pool: &PgPool, path: &str) -> anyhow::Result<()> {
+    let sql = std::fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
+    let mut tx = pool.begin().await?;
+    (&mut tx)
+        .execute(sql.as_str())
+        .await
+        .with_context(|| format!("failed to load {path}"))?;
+    tx.commit().await?;
+    Ok(())
+}
@@ -1,7 +1,13 @@
 use std::fmt::{Display, Formatter};
 
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+
+pub use api_types::enums::{
+    AdminAction, AuctionStatus, DirectBuyState, DirectSellState, MetadataRefreshStatus,
+    ModerationStatus, ModerationTarget, NftEventType, ReportReason, ReportStatus, RootType,
+    SearchEntity, VerificationStatus, WriteAction,
+};
+
 #[derive(Clone, Debug, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "event_type", rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
@@ -46,40 +52,6 @@ pub enum EventCategory {
     Collection,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, sqlx::Type, ToSchema)]
-#[sqlx(type_name = "auction_status", rename_all = "snake_case")]
-#[serde(rename_all = "snake_case")]
-pub enum AuctionStatus {
-    Active = 0,
-    Cancelled = 1,
-    Completed = 2,
-    Expired = 3,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize, sqlx::Type, ToSchema)]
-#[sqlx(type_name = "direct_sell_state", rename_all = "snake_case")]
-#[serde(rename_all = "snake_case")]
-pub enum DirectSellState {
-    Create = 0,
-    AwaitNft = 1,
-    Active = 2,
-    Filled = 3,
-    Cancelled = 4,
-    Expired = 5,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize, sqlx::Type, ToSchema)]
-#[sqlx(type_name = "direct_buy_state", rename_all = "snake_case")]
-#[serde(rename_all = "snake_case")]
-pub enum DirectBuyState {
-    Create = 0,
-    AwaitTokens = 1,
-    Active = 2,
-    Filled = 3,
-    Cancelled = 4,
-    Expired = 5,
-}
-
 #[derive(Clone, Debug, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "nft_price_source")]
 #[serde(rename_all = "snake_case")]
@@ -92,77 +64,6 @@ pub enum NftPriceSource {
     DirectSell,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, sqlx::Type, ToSchema)]
-#[sqlx(type_name = "t_root_types")]
-#[serde(rename_all = "snake_case")]
-pub enum RootType {
-    #[sqlx(rename = "auction")]
-    Auction,
-    #[sqlx(rename = "buy")]
-    Buy,
-    #[sqlx(rename = "sell")]
-    Sell,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize, sqlx::Type, Hash, ToSchema)]
-#[sqlx(type_name = "event_kind")]
-#[serde(rename_all = "snake_case")]
-pub enum NftEventType {
-    SellActive,
-    SellPurchased,
-    SellCanceled,
-
-    OfferActive,
-    OfferFilled,
-    OfferCanceled,
-
-    AuctionActive,
-    AuctionBidPlaced,
-    AuctionCanceled,
-    AuctionComplete,
-
-    Mint,
-
-    Transfer,
-}
-
-impl ToString for NftEventType {
-    fn to_string(&self) -> String {
-        match self {
-            NftEventType::SellActive => "sell_active".to_string(),
-            NftEventType::SellPurchased => "sell_purchased".to_string(),
-            NftEventType::SellCanceled => "sell_canceled".to_string(),
-            NftEventType::OfferActive => "offer_active".to_string(),
-            NftEventType::OfferFilled => "offer_filled".to_string(),
-            NftEventType::OfferCanceled => "offer_canceled".to_string(),
-            NftEventType::AuctionActive => "auction_active".to_string(),
-            NftEventType::AuctionBidPlaced => "auction_bid_placed".to_string(),
-            NftEventType::AuctionCanceled => "auction_canceled".to_string(),
-            NftEventType::AuctionComplete => "auction_complete".to_string(),
-            NftEventType::Mint => "mint".to_string(),
-            NftEventType::Transfer => "transfer".to_string(),
-        }
-    }
-}
-
-impl Default for AuctionStatus {
-    fn default() -> Self {
-        Self::Active
-    }
-}
-
-impl Default for DirectBuyState {
-    fn default() -> Self {
-        Self::Create
-    }
-}
-
-impl Default for DirectSellState {
-    fn default() -> Self {
-        Self::Create
-    }
-}
-
 impl From<i16> for NftPriceSource {
     fn from(state: i16) -> Self {
         match state {
@@ -174,17 +75,6 @@ impl From<i16> for NftPriceSource {
     }
 }
 
-impl From<i16> for AuctionStatus {
-    fn from(state: i16) -> Self {
-        match state {
-            0 => Self::Active,
-            1 => Self::Cancelled,
-            2 => Self::Completed,
-            _ => panic!("Unknown state of DirectSell"),
-        }
-    }
-}
-
 impl From<i16> for EventType {
     fn from(state: i16) -> Self {
         match state {
@@ -220,34 +110,6 @@ impl From<i16> for EventType {
     }
 }
 
-impl From<i16> for DirectSellState {
-    fn from(state: i16) -> Self {
-        match state {
-            0 => Self::Create,
-            1 => Self::AwaitNft,
-            2 => Self::Active,
-            3 => Self::Filled,
-            4 => Self::Cancelled,
-            5 => Self::Expired,
-            _ => panic!("Unknown state of DirectSell"),
-        }
-    }
-}
-
-impl From<i16> for DirectBuyState {
-    fn from(state: i16) -> Self {
-        match state {
-            0 => Self::Create,
-            1 => Self::AwaitTokens,
-            2 => Self::Active,
-            3 => Self::Filled,
-            4 => Self::Cancelled,
-            5 => Self::Expired,
-            _ => panic!("Unknown state of DirectBuy"),
-        }
-    }
-}
-
 impl Display for EventType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let val = serde_json::to_value(self).expect("error serialize EventType");
@@ -255,27 +117,3 @@ impl Display for EventType {
         f.write_str(str)
     }
 }
-
-impl Display for AuctionStatus {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let val = serde_json::to_value(self).expect("error serialize AuctionStatus");
-        let str = val.as_str().expect("not a string");
-        f.write_str(str)
-    }
-}
-
-impl Display for DirectBuyState {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let val = serde_json::to_value(self).expect("error serialize DirectBuyState");
-        let str = val.as_str().expect("not a string");
-        f.write_str(str)
-    }
-}
-
-impl Display for DirectSellState {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let val = serde_json::to_value(self).expect("error serialize DirectBuyState");
-        let str = val.as_str().expect("not a string");
-        f.write_str(str)
-    }
-}
@@ -0,0 +1,2040 @@
+//! Repositories over plain vectors, so handlers can be exercised without Postgres.
+//! Filters follow the sql closely enough for caching, pagination and hydration tests,
+//! the usd aggregates and the search ranking are simplified. Order validity and the metadata
+//! filters of `/nfts` aren't reimplemented, rows are served as the fixtures set them and the
+//! sql is covered by `tests/db.rs`.
+#![allow(clippy::too_many_arguments, clippy::ptr_arg)]
+
+use crate::db::repo::*;
+use crate::db::*;
+use crate::model::NftEvent;
+use crate::token::TokenDict;
+use api_types::{
+    AttributeFilter, AuctionsSortOrder, CollectionListOrder, NFTListOrder, NFTListOrderField,
+    OrderDirection, PriceHistoryScale,
+};
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use serde_json::{json, Value};
+use sqlx::types::BigDecimal;
+use std::cmp::{Ordering, Reverse};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+
+const COLLECTION_CUSTOM_WRITES: &[WriteAction] = &[
+    WriteAction::CollectionCustomUpdated,
+    WriteAction::CollectionCustomReverted,
+];
+
+/// Rows served by `InMemoryRepo`, `cnt`/`total_count` columns are recomputed per query
+#[derive(Debug, Default)]
+pub struct MemoryState {
+    pub nfts: Vec<NftDetails>,
+    pub traits: Vec<(Address, NftTraitRecord)>,
+    pub nft_prices: Vec<(Address, NftPrice)>,
+    pub collections: Vec<NftCollectionDetails>,
+    pub roots: Vec<RootRecord>,
+    /// `(fee_numerator, fee_denominator)` of `market_fee_default_changed`
+    pub default_fee: Option<(i32, i32)>,
+    pub auctions: Vec<NftAuction>,
+    pub bids: Vec<NftAuctionBid>,
+    pub direct_sells: Vec<NftDirectSell>,
+    pub direct_buys: Vec<NftDirectBuy>,
+    pub events: Vec<NftEvent>,
+    pub users: Vec<UserRecord>,
+    pub token_prices: Vec<TokenUsdPrice>,
+    pub admin_audit: Vec<AdminAuditRecord>,
+    pub verification_requests: Vec<VerificationRequestRecord>,
+    pub reports: Vec<ReportRecord>,
+    pub moderation: Vec<ModerationRecord>,
+    pub write_audit: Vec<WriteAuditRecord>,
+    /// `metadata_updated` holds the value seen when the job was created, `NftDetails::updated`
+    /// stands for `nft_metadata.updated`
+    pub metadata_refresh: Vec<MetadataRefreshRecord>,
+    /// Mirrored into `NftDetails::meta_override` on every write
+    pub metadata_overrides: Vec<NftMetadataOverrideRecord>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryRepo {
+    tokens: TokenDict,
+    state: Arc<RwLock<MemoryState>>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl InMemoryRepo {
+    pub fn new(tokens: TokenDict, state: MemoryState) -> Self {
+        Self {
+            tokens,
+            state: Arc::new(RwLock::new(state)),
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Number of repository calls served so far, a cache hit doesn't add to it
+    pub fn calls(&self) -> usize {
+        self.calls.load(AtomicOrdering::SeqCst)
+    }
+
+    pub fn state(&self) -> RwLockReadGuard<'_, MemoryState> {
+        self.state.read().expect("memory state poisoned")
+    }
+
+    pub fn state_mut(&self) -> RwLockWriteGuard<'_, MemoryState> {
+        self.state.write().expect("memory state poisoned")
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, MemoryState> {
+        self.calls.fetch_add(1, AtomicOrdering::SeqCst);
+        self.state()
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, MemoryState> {
+        self.calls.fetch_add(1, AtomicOrdering::SeqCst);
+        self.state_mut()
+    }
+}
+
+impl MemoryState {
+    fn nft(&self, address: &str) -> Option<&NftDetails> {
+        self.nfts
+            .iter()
+            .find(|n| n.address.as_deref() == Some(address))
+    }
+
+    fn collection(&self, address: &str) -> Option<&NftCollectionDetails> {
+        self.collections
+            .iter()
+            .find(|c| c.address.as_deref() == Some(address))
+    }
+
+    fn auction(&self, address: &str) -> Option<&NftAuction> {
+        self.auctions
+            .iter()
+            .find(|a| a.address.as_deref() == Some(address))
+    }
+
+    fn nft_collection(&self, nft: &str) -> Option<Address> {
+        self.nft(nft).and_then(|n| n.collection.clone())
+    }
+
+    fn nft_owner(&self, nft: &str) -> Option<Address> {
+        self.nft(nft).and_then(|n| n.owner.clone())
+    }
+
+    fn is_verified(&self, collection: Option<&Address>) -> bool {
+        collection
+            .and_then(|c| self.collection(c))
+            .and_then(|c| c.verified)
+            .unwrap_or(false)
+    }
+
+    /// Like `moderation_hidden(nft, collection)`
+    fn is_hidden(&self, nft: Option<&Address>, collection: Option<&Address>) -> bool {
+        self.moderation.iter().any(|m| {
+            m.status == ModerationStatus::Hidden
+                && (nft == Some(&m.address) || collection == Some(&m.address))
+        })
+    }
+
+    /// Last price, as `token_usd_prices`
+    fn usd_price(&self, token: &str) -> Option<BigDecimal> {
+        self.token_prices
+            .iter()
+            .filter(|p| p.token == token)
+            .max_by_key(|p| p.ts)
+            .map(|p| p.usd_price.clone())
+    }
+
+    fn metadata_updated(&self, nft: &str) -> Option<NaiveDateTime> {
+        self.nft(nft).and_then(|n| n.updated)
+    }
+
+    fn with_metadata_updated(&self, job: &MetadataRefreshRecord) -> MetadataRefreshRecord {
+        MetadataRefreshRecord {
+            metadata_updated: self.metadata_updated(&job.nft),
+            ..job.clone()
+        }
+    }
+
+    fn audit(&mut self, admin: &str, action: AdminAction, target: Option<&str>, details: Value) {
+        self.admin_audit.push(AdminAuditRecord {
+            id: self.admin_audit.len() as i64 + 1,
+            admin: admin.to_string(),
+            action,
+            target: target.map(str::to_string),
+            details,
+            created_at: Utc::now().naive_utc(),
+            total_count: 0,
+        });
+    }
+
+    /// Values written by the latest history entry of the target, the table keeps no other copy
+    fn last_written(&self, target: &str, actions: &[WriteAction]) -> Option<Value> {
+        self.write_audit
+            .iter()
+            .rev()
+            .find(|w| w.target == target && actions.contains(&w.action))
+            .map(|w| w.after.clone())
+    }
+
+    fn record_write(
+        &mut self,
+        actor: &str,
+        action: WriteAction,
+        target: &str,
+        before: Option<Value>,
+        after: Value,
+        details: Value,
+        ip: Option<&str>,
+    ) {
+        self.write_audit.push(WriteAuditRecord {
+            id: self.write_audit.len() as i64 + 1,
+            actor: actor.to_string(),
+            action,
+            target: target.to_string(),
+            before,
+            after,
+            details,
+            ip: ip.map(str::to_string),
+            created_at: Utc::now().naive_utc(),
+            total_count: 0,
+        });
+    }
+
+    fn bid_ext(&self, bid: &NftAuctionBid, cnt: i64) -> NftAuctionBidExt {
+        let auction = self.auction(&bid.auction);
+        let nft = auction.and_then(|a| a.nft.clone());
+        NftAuctionBidExt {
+            auction: bid.auction.clone(),
+            buyer: bid.buyer.clone(),
+            price_token: auction.and_then(|a| a.price_token.clone()),
+            price: bid.price.clone(),
+            usd_price: bid.usd_price.clone(),
+            next_bid_value: Some(bid.next_bid_value.clone()),
+            next_bid_usd_value: bid.next_bid_usd_value.clone(),
+            created_at: bid.created_at,
+            tx_lt: Some(bid.tx_lt),
+            active: Some(bid.active),
+            collection: nft.as_deref().and_then(|n| self.nft_collection(n)),
+            nft,
+            cnt,
+        }
+    }
+}
+
+/// `true` when the filter list is empty, like `array_length($1, 1) is null`
+fn any_of<T: PartialEq>(list: &[T], value: Option<&T>) -> bool {
+    list.is_empty() || value.is_some_and(|v| list.contains(v))
+}
+
+fn page<T>(items: Vec<T>, limit: usize, offset: usize) -> Vec<T> {
+    items.into_iter().skip(offset).take(limit).collect()
+}
+
+fn directed(ordering: Ordering, direction: &OrderDirection) -> Ordering {
+    match direction {
+        OrderDirection::Asc => ordering,
+        OrderDirection::Desc => ordering.reverse(),
+    }
+}
+
+fn has_attributes(nft: &NftDetails, filters: &[AttributeFilter]) -> bool {
+    let attributes = nft.parse_meta().attributes.unwrap_or_default();
+    let attributes = attributes.as_array().cloned().unwrap_or_default();
+    filters.iter().all(|filter| {
+        attributes.iter().any(|a| {
+            a.get("trait_type").and_then(|t| t.as_str()) == Some(filter.trait_type.as_str())
+                && a.get("value")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|v| filter.trait_values.iter().any(|f| f == v))
+        })
+    })
+}
+
+fn collection_row(c: &NftCollectionDetails, cnt: i64) -> NftCollection {
+    NftCollection {
+        address: c.address.clone().unwrap_or_default(),
+        owner: c.owner.clone().unwrap_or_default(),
+        name: c.name.clone(),
+        description: c.description.clone(),
+        created: c.created.unwrap_or_default(),
+        updated: c.updated.unwrap_or_default(),
+        verified: c.verified.unwrap_or_default(),
+        wallpaper: c.wallpaper.clone(),
+        logo: c.logo.clone(),
+        owners_count: c.owners_count.map(|x| x as i32),
+        nft_count: c.nft_count.unwrap_or_default(),
+        max_price: c.max_price.clone(),
+        total_price: c.total_price.clone(),
+        cnt,
+        first_mint: c.first_mint.unwrap_or_default(),
+        social: c.social.clone(),
+    }
+}
+
+/// Values of the override kept in the history
+fn override_snapshot(o: &NftMetadataOverrideRecord) -> Value {
+    json!({
+        "name": o.name,
+        "preview": o.preview,
+        "previewMimetype": o.preview_mimetype,
+        "attributes": o.attributes,
+    })
+}
+
+/// What `nft_meta_override` returns for the row
+fn meta_override(o: &NftMetadataOverrideRecord) -> Value {
+    let mut over = serde_json::Map::new();
+    if let Some(name) = &o.name {
+        over.insert("name".to_string(), json!(name));
+    }
+    if let Some(preview) = &o.preview {
+        over.insert(
+            "preview".to_string(),
+            json!({ "source": preview, "mimetype": o.preview_mimetype }),
+        );
+    }
+    if let Some(attributes) = &o.attributes {
+        over.insert("attributes".to_string(), attributes.clone());
+    }
+    Value::Object(over)
+}
+
+fn with_total_count(mut list: Vec<NftDetails>, total_count: i64) -> Vec<NftDetails> {
+    for nft in list.iter_mut() {
+        nft.total_count = total_count;
+    }
+    list
+}
+
+macro_rules! with_cnt {
+    ($list:expr) => {{
+        let mut list = $list;
+        let cnt = list.len() as i64;
+        for row in list.iter_mut() {
+            row.cnt = cnt;
+        }
+        list
+    }};
+}
+
+impl Repo for InMemoryRepo {
+    fn tokens(&self) -> &TokenDict {
+        &self.tokens
+    }
+}
+
+#[async_trait]
+impl NftRepo for InMemoryRepo {
+    async fn search_all(
+        &self,
+        query: &str,
+        entities: &[SearchEntity],
+        collections: Option<&[Address]>,
+        after: Option<(f64, &str)>,
+        limit: usize,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<SearchResult>> {
+        let state = self.read();
+        let needle = query.trim().to_lowercase();
+        let id = needle.trim_start_matches('#');
+        let contains = |value: &Option<String>| {
+            value
+                .as_deref()
+                .is_some_and(|v| v.to_lowercase().contains(&needle))
+        };
+        let equals =
+            |value: &Option<String>| value.as_deref().is_some_and(|v| v.to_lowercase() == needle);
+        let in_scope = |collection: &Option<Address>| {
+            collections.is_none_or(|scope| collection.as_ref().is_some_and(|c| scope.contains(c)))
+        };
+
+        let mut found = Vec::new();
+        if entities.contains(&SearchEntity::Collection) {
+            for c in state.collections.iter() {
+                let score = if equals(&c.address) {
+                    20.0
+                } else if equals(&c.name) {
+                    10.0
+                } else if contains(&c.name) || contains(&c.description) {
+                    1.5
+                } else {
+                    continue;
+                };
+                if !c.verified.unwrap_or(false)
+                    || !in_scope(&c.address)
+                    || (!include_hidden && state.is_hidden(None, c.address.as_ref()))
+                {
+                    continue;
+                }
+                found.push(SearchResult {
+                    address: c.address.clone().unwrap_or_default(),
+                    object_type: "collection".to_string(),
+                    nft_name: None,
+                    collection_name: c.name.clone(),
+                    image: c.logo.clone(),
+                    collection: c.address.clone(),
+                    nft_id: None,
+                    username: None,
+                    score,
+                });
+            }
+        }
+        if entities.contains(&SearchEntity::Nft) {
+            for n in state.nfts.iter() {
+                let score = if equals(&n.address) {
+                    10.0
+                } else if equals(&n.name) {
+                    5.0
+                } else if !id.is_empty() && n.nft_id.as_deref() == Some(id) {
+                    4.0
+                } else if contains(&n.name) || contains(&n.description) {
+                    1.0
+                } else {
+                    continue;
+                };
+                if n.burned.unwrap_or(false)
+                    || !state.is_verified(n.collection.as_ref())
+                    || !in_scope(&n.collection)
+                    || (!include_hidden
+                        && state.is_hidden(n.address.as_ref(), n.collection.as_ref()))
+                {
+                    continue;
+                }
+                found.push(SearchResult {
+                    address: n.address.clone().unwrap_or_default(),
+                    object_type: "nft".to_string(),
+                    nft_name: n.name.clone(),
+                    collection_name: n
+                        .collection
+                        .as_deref()
+                        .and_then(|c| state.collection(c))
+                        .and_then(|c| c.name.clone()),
+                    image: n.parse_meta().image,
+                    collection: n.collection.clone(),
+                    nft_id: n.nft_id.clone(),
+                    username: None,
+                    score,
+                });
+            }
+        }
+        if entities.contains(&SearchEntity::User) && collections.is_none() {
+            for u in state.users.iter() {
+                let address = Some(u.address.clone());
+                let score = if equals(&address) {
+                    10.0
+                } else if equals(&u.username) {
+                    5.0
+                } else if contains(&u.username) {
+                    1.0
+                } else {
+                    continue;
+                };
+                found.push(SearchResult {
+                    address: u.address.clone(),
+                    object_type: "user".to_string(),
+                    nft_name: None,
+                    collection_name: None,
+                    image: u.avatar_url.clone(),
+                    collection: None,
+                    nft_id: None,
+                    username: u.username.clone(),
+                    score,
+                });
+            }
+        }
+
+        found.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        Ok(found
+            .into_iter()
+            .filter(|r| {
+                after.is_none_or(|(score, address)| {
+                    r.score < score || (r.score == score && r.address.as_str() > address)
+                })
+            })
+            .take(limit)
+            .collect())
+    }
+
+    async fn suggest_entries(&self, max_nfts: usize) -> sqlx::Result<Vec<SuggestRecord>> {
+        let state = self.read();
+        let live = || state.nfts.iter().filter(|n| !n.burned.unwrap_or(false));
+        let owners = |collection: Option<&Address>| {
+            let mut owners: Vec<_> = live()
+                .filter(|n| n.collection.as_ref() == collection)
+                .filter_map(|n| n.owner.as_ref())
+                .collect();
+            owners.sort();
+            owners.dedup();
+            owners.len() as i64
+        };
+
+        let mut entries = Vec::new();
+        for c in state.collections.iter() {
+            let (Some(address), Some(name)) = (&c.address, &c.name) else {
+                continue;
+            };
+            if c.verified.unwrap_or(false) && !state.is_hidden(None, Some(address)) {
+                entries.push(SuggestRecord {
+                    address: address.clone(),
+                    object_type: "collection".to_string(),
+                    name: name.clone(),
+                    image: c.logo.clone(),
+                    collection: Some(address.clone()),
+                    weight: owners(Some(address)),
+                });
+            }
+        }
+        let mut nfts: Vec<SuggestRecord> = live()
+            .filter(|n| {
+                state.is_verified(n.collection.as_ref())
+                    && !state.is_hidden(n.address.as_ref(), n.collection.as_ref())
+            })
+            .filter_map(|n| {
+                Some(SuggestRecord {
+                    address: n.address.clone()?,
+                    object_type: "nft".to_string(),
+                    name: n.name.clone().filter(|name| !name.is_empty())?,
+                    image: n.parse_meta().image,
+                    collection: n.collection.clone(),
+                    weight: owners(n.collection.as_ref()),
+                })
+            })
+            .collect();
+        nfts.sort_by(|a, b| b.weight.cmp(&a.weight).then(a.address.cmp(&b.address)));
+        entries.extend(nfts.into_iter().take(max_nfts));
+        for u in state.users.iter() {
+            let Some(name) = u.username.clone().filter(|name| !name.is_empty()) else {
+                continue;
+            };
+            if !state.is_hidden(Some(&u.address), None) {
+                entries.push(SuggestRecord {
+                    address: u.address.clone(),
+                    object_type: "user".to_string(),
+                    name,
+                    image: u.avatar_url.clone(),
+                    collection: None,
+                    weight: live()
+                        .filter(|n| n.owner.as_ref() == Some(&u.address))
+                        .count() as i64,
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn get_nft_details(&self, address: &String) -> sqlx::Result<Option<NftDetails>> {
+        let state = self.read();
+        Ok(state.nft(address).cloned())
+    }
+
+    async fn collect_nfts(&self, ids: &[String]) -> sqlx::Result<Vec<NftDetails>> {
+        let state = self.read();
+        Ok(ids.iter().filter_map(|id| state.nft(id).cloned()).collect())
+    }
+
+    async fn nft_top_search(
+        &self,
+        _from: NaiveDateTime,
+        limit: i64,
+        offset: i64,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<NftDetails>> {
+        let state = self.read();
+        let mut list: Vec<NftDetails> = state
+            .nfts
+            .iter()
+            .filter(|n| n.deal_price_usd.is_some())
+            .filter(|n| {
+                include_hidden || !state.is_hidden(n.address.as_ref(), n.collection.as_ref())
+            })
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| b.deal_price_usd.cmp(&a.deal_price_usd));
+        let total = list.len() as i64;
+        Ok(with_total_count(
+            page(list, limit as usize, offset as usize),
+            total,
+        ))
+    }
+
+    async fn nft_search(
+        &self,
+        owners: &[Address],
+        collections: &[Address],
+        forsale: Option<bool>,
+        auction: Option<bool>,
+        verified: Option<bool>,
+        limit: usize,
+        offset: usize,
+        attributes: &[AttributeFilter],
+        order: Option<NFTListOrder>,
+        with_count: bool,
+        include_hidden: bool,
+        _filters: &NftFilters,
+    ) -> sqlx::Result<Vec<NftDetails>> {
+        let state = self.read();
+        let mut list: Vec<NftDetails> = state
+            .nfts
+            .iter()
+            .filter(|n| !n.burned.unwrap_or(false))
+            .filter(|n| any_of(owners, n.owner.as_ref()))
+            .filter(|n| any_of(collections, n.collection.as_ref()))
+            .filter(|n| !forsale.unwrap_or(false) || n.forsale.is_some())
+            .filter(|n| !auction.unwrap_or(false) || n.auction.is_some())
+            .filter(|n| !verified.unwrap_or(false) || state.is_verified(n.collection.as_ref()))
+            .filter(|n| has_attributes(n, attributes))
+            .filter(|n| {
+                include_hidden || !state.is_hidden(n.address.as_ref(), n.collection.as_ref())
+            })
+            .cloned()
+            .collect();
+        match order {
+            Some(order) => list.sort_by(|a, b| {
+                let ordering = match order.field {
+                    NFTListOrderField::FloorPriceUsd => a.floor_price_usd.cmp(&b.floor_price_usd),
+                    NFTListOrderField::DealPriceUsd => a.deal_price_usd.cmp(&b.deal_price_usd),
+                    NFTListOrderField::Name => a.name.cmp(&b.name),
+                };
+                directed(ordering, &order.direction).then_with(|| a.address.cmp(&b.address))
+            }),
+            None => {
+                list.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.address.cmp(&b.address)))
+            }
+        }
+        let total = if with_count { list.len() as i64 } else { 0 };
+        Ok(with_total_count(page(list, limit, offset), total))
+    }
+
+    async fn get_traits(&self, nft: &Address) -> sqlx::Result<Vec<NftTraitRecord>> {
+        let state = self.read();
+        Ok(state
+            .traits
+            .iter()
+            .filter(|(address, _)| address == nft)
+            .map(|(_, record)| record.clone())
+            .collect())
+    }
+
+    async fn list_nft_price_history(
+        &self,
+        nft: &str,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<NftPrice>> {
+        let state = self.read();
+        Ok(state
+            .nft_prices
+            .iter()
+            .filter(|(address, price)| address == nft && price.ts >= from && price.ts <= to)
+            .map(|(_, price)| price.clone())
+            .collect())
+    }
+
+    async fn nft_random_buy(
+        &self,
+        max_price: i64,
+        limit: i32,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<NftDetails>> {
+        let state = self.read();
+        let max_price = BigDecimal::from(max_price);
+        Ok(state
+            .nfts
+            .iter()
+            .filter(|n| n.forsale_status == Some(DirectSellState::Active))
+            .filter(|n| n.floor_price.as_ref().is_some_and(|p| p <= &max_price))
+            .filter(|n| {
+                include_hidden || !state.is_hidden(n.address.as_ref(), n.collection.as_ref())
+            })
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn nft_sell_count(&self, max_price: i64) -> sqlx::Result<Option<i64>> {
+        let state = self.read();
+        let max_price = BigDecimal::from(max_price);
+        let count = state
+            .direct_sells
+            .iter()
+            .filter(|s| s.valid && s.price <= max_price)
+            .count();
+        Ok(Some(count as i64))
+    }
+}
+
+#[async_trait]
+impl CollectionRepo for InMemoryRepo {
+    async fn get_collection(&self, address: &String) -> sqlx::Result<Option<NftCollectionDetails>> {
+        Ok(self.read().collection(address).cloned().map(|mut c| {
+            c.cnt = 1;
+            c
+        }))
+    }
+
+    async fn collect_collections(&self, ids: &[String]) -> sqlx::Result<Vec<NftCollection>> {
+        let state = self.read();
+        Ok(ids
+            .iter()
+            .filter_map(|id| state.collection(id))
+            .map(|c| collection_row(c, 0))
+            .collect())
+    }
+
+    async fn list_collections_by_owner(
+        &self,
+        owner: &String,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftCollection>> {
+        let state = self.read();
+        let list: Vec<NftCollection> = state
+            .collections
+            .iter()
+            .filter(|c| c.owner.as_ref() == Some(owner))
+            .map(|c| collection_row(c, 0))
+            .collect();
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+
+    async fn list_collections(
+        &self,
+        name: Option<&String>,
+        owners: &[String],
+        verified: Option<&bool>,
+        collections: &[Address],
+        limit: usize,
+        offset: usize,
+        order: Option<CollectionListOrder>,
+    ) -> sqlx::Result<Vec<NftCollectionDetails>> {
+        let state = self.read();
+        let name = name.map(|n| n.to_lowercase());
+        let mut list: Vec<NftCollectionDetails> = state
+            .collections
+            .iter()
+            .filter(|c| match &name {
+                Some(name) => c
+                    .name
+                    .as_deref()
+                    .is_some_and(|n| n.to_lowercase().contains(name)),
+                None => true,
+            })
+            .filter(|c| any_of(owners, c.owner.as_ref()))
+            .filter(|c| !verified.copied().unwrap_or(false) || c.verified.unwrap_or(false))
+            .filter(|c| any_of(collections, c.address.as_ref()))
+            .cloned()
+            .collect();
+        match order {
+            Some(order) => list.sort_by(|a, b| {
+                directed(a.first_mint.cmp(&b.first_mint), &order.direction)
+                    .then_with(|| a.address.cmp(&b.address))
+            }),
+            None => list.sort_by_key(|c| Reverse(c.owners_count)),
+        }
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+
+    async fn list_collections_simple(
+        &self,
+        name: Option<&String>,
+        verified: Option<&bool>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftCollectionSimple>> {
+        let list = self
+            .list_collections(name, &[], verified, &[], usize::MAX, 0, None)
+            .await?;
+        let list: Vec<NftCollectionSimple> = list
+            .into_iter()
+            .map(|c| NftCollectionSimple {
+                address: c.address.unwrap_or_default(),
+                name: c.name,
+                description: c.description,
+                verified: c.verified.unwrap_or_default(),
+                logo: c.logo,
+                cnt: c.cnt,
+                nft_count: c.nft_count.unwrap_or_default(),
+            })
+            .collect();
+        Ok(page(list, limit, offset))
+    }
+
+    async fn upsert_collection_custom(
+        &self,
+        address: &Address,
+        owner: &String,
+        updated: NaiveDateTime,
+        name: Option<String>,
+        description: Option<String>,
+        wallpaper: Option<String>,
+        logo: Option<String>,
+        social: serde_json::Value,
+        ip: Option<&str>,
+    ) -> sqlx::Result<()> {
+        let mut state = self.write();
+        let after = json!({
+            "name": name,
+            "description": description,
+            "wallpaper": wallpaper,
+            "logo": logo,
+            "social": social,
+        });
+        let Some(c) = state
+            .collections
+            .iter_mut()
+            .find(|c| c.address.as_ref() == Some(address))
+        else {
+            return Ok(());
+        };
+        c.updated = Some(updated);
+        c.name = name.or(c.name.take());
+        c.description = description.or(c.description.take());
+        c.wallpaper = wallpaper.or(c.wallpaper.take());
+        c.logo = logo.or(c.logo.take());
+        c.social = Some(social);
+
+        let before = state.last_written(address, COLLECTION_CUSTOM_WRITES);
+        state.record_write(
+            owner,
+            WriteAction::CollectionCustomUpdated,
+            address,
+            before,
+            after,
+            json!({}),
+            ip,
+        );
+        Ok(())
+    }
+
+    async fn validate_owner_of_collection(
+        &self,
+        address: &String,
+        owner: &String,
+    ) -> sqlx::Result<Option<i64>> {
+        let state = self.read();
+        let owned = state
+            .collection(address)
+            .is_some_and(|c| c.owner.as_ref() == Some(owner));
+        Ok(Some(owned as i64))
+    }
+}
+
+#[async_trait]
+impl AuctionRepo for InMemoryRepo {
+    async fn list_roots(&self) -> sqlx::Result<Vec<RootRecord>> {
+        Ok(self.read().roots.clone())
+    }
+
+    /// Collection fees aren't modelled, every owner gets the default one
+    async fn get_owner_fee(
+        &self,
+        _owner: &Address,
+        _root_code: &RootType,
+    ) -> sqlx::Result<OwnerFeeRecord> {
+        let (fee_numerator, fee_denominator) =
+            self.read().default_fee.ok_or(sqlx::Error::RowNotFound)?;
+        Ok(OwnerFeeRecord {
+            fee_numerator,
+            fee_denominator,
+            collection: None,
+            nft: None,
+        })
+    }
+
+    async fn collect_auctions(&self, ids: &[String]) -> sqlx::Result<Vec<NftAuction>> {
+        let state = self.read();
+        Ok(ids
+            .iter()
+            .filter_map(|id| state.auction(id).cloned())
+            .collect())
+    }
+
+    async fn get_nft_auction(&self, address: &String) -> sqlx::Result<Option<NftAuction>> {
+        Ok(self.read().auction(address).cloned())
+    }
+
+    async fn get_nft_auction_last_bid(
+        &self,
+        auction: &String,
+    ) -> sqlx::Result<Option<NftAuctionBid>> {
+        let state = self.read();
+        Ok(state
+            .bids
+            .iter()
+            .filter(|b| &b.auction == auction)
+            .max_by(|a, b| a.created_at.cmp(&b.created_at))
+            .cloned())
+    }
+
+    async fn list_nft_auction_bids(
+        &self,
+        auction: &String,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuctionBid>> {
+        let state = self.read();
+        let mut list: Vec<NftAuctionBid> = state
+            .bids
+            .iter()
+            .filter(|b| &b.auction == auction)
+            .cloned()
+            .collect();
+        list.sort_by_key(|b| Reverse(b.created_at));
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+
+    async fn list_nft_auctions(
+        &self,
+        owners: &[Address],
+        collections: &[Address],
+        tokens: &[Address],
+        sort: &AuctionsSortOrder,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuction>> {
+        let state = self.read();
+        let mut list: Vec<NftAuction> = state
+            .auctions
+            .iter()
+            .filter(|a| {
+                let nft = a.nft.as_deref().unwrap_or_default();
+                any_of(owners, state.nft_owner(nft).as_ref())
+                    && any_of(collections, state.nft_collection(nft).as_ref())
+                    // the sql matches `tokens` against the nft column
+                    && any_of(tokens, a.nft.as_ref())
+            })
+            .cloned()
+            .collect();
+        match sort {
+            AuctionsSortOrder::BidsCount => list.sort_by_key(|a| a.bids_count),
+            _ => list.sort_by_key(|a| Reverse(a.created_at)),
+        }
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+
+    async fn list_owner_auction_bids_out(
+        &self,
+        owner: &String,
+        collections: &[String],
+        lastbid: &Option<bool>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuctionBidExt>> {
+        let state = self.read();
+        let bids: Vec<&NftAuctionBid> = state
+            .bids
+            .iter()
+            .filter(|b| &b.buyer == owner)
+            .filter(|b| !lastbid.unwrap_or(false) || b.active)
+            .collect();
+        let list: Vec<NftAuctionBidExt> = bids
+            .into_iter()
+            .map(|b| state.bid_ext(b, 0))
+            .filter(|b| any_of(collections, b.collection.as_ref()))
+            .collect();
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+
+    async fn list_owner_auction_bids_in(
+        &self,
+        owner: &String,
+        collections: &[String],
+        lastbid: &Option<bool>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuctionBidExt>> {
+        let state = self.read();
+        let list: Vec<NftAuctionBidExt> = state
+            .bids
+            .iter()
+            .filter(|b| !lastbid.unwrap_or(false) || b.active)
+            .map(|b| state.bid_ext(b, 0))
+            .filter(|b| b.nft.as_deref().and_then(|n| state.nft_owner(n)).as_ref() == Some(owner))
+            .filter(|b| any_of(collections, b.collection.as_ref()))
+            .collect();
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+
+    async fn get_direct_sell(&self, address: &String) -> sqlx::Result<Option<NftDirectSell>> {
+        let state = self.read();
+        Ok(state
+            .direct_sells
+            .iter()
+            .find(|s| &s.address == address)
+            .cloned())
+    }
+
+    async fn collect_direct_sell(&self, ids: &[String]) -> sqlx::Result<Vec<NftDirectSell>> {
+        let state = self.read();
+        Ok(state
+            .direct_sells
+            .iter()
+            .filter(|s| ids.contains(&s.address))
+            .cloned()
+            .collect())
+    }
+
+    async fn list_owner_direct_sell(
+        &self,
+        owner: &String,
+        collections: &[String],
+        status: &[DirectSellState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectSell>> {
+        let state = self.read();
+        let list: Vec<NftDirectSell> = state
+            .direct_sells
+            .iter()
+            .filter(|s| s.seller.as_ref() == Some(owner))
+            .filter(|s| any_of(collections, s.collection.as_ref()))
+            .filter(|s| any_of(status, Some(&s.state)))
+            .cloned()
+            .collect();
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+
+    async fn collect_direct_buy(&self, ids: &[String]) -> sqlx::Result<Vec<NftDirectBuy>> {
+        let state = self.read();
+        Ok(state
+            .direct_buys
+            .iter()
+            .filter(|b| ids.contains(&b.address))
+            .cloned()
+            .collect())
+    }
+
+    async fn list_nft_direct_buy(
+        &self,
+        nft: &String,
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>> {
+        let state = self.read();
+        let list: Vec<NftDirectBuy> = state
+            .direct_buys
+            .iter()
+            .filter(|b| &b.nft == nft)
+            .filter(|b| any_of(status, Some(&b.state)))
+            .cloned()
+            .collect();
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+
+    async fn list_owner_direct_buy(
+        &self,
+        owner: &String,
+        collections: &[String],
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>> {
+        let state = self.read();
+        let list: Vec<NftDirectBuy> = state
+            .direct_buys
+            .iter()
+            .filter(|b| b.buyer.as_ref() == Some(owner))
+            .filter(|b| any_of(collections, b.collection.as_ref()))
+            .filter(|b| any_of(status, Some(&b.state)))
+            .cloned()
+            .collect();
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+
+    async fn list_owner_direct_buy_in(
+        &self,
+        owner: &String,
+        collections: &[String],
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>> {
+        let state = self.read();
+        let list: Vec<NftDirectBuy> = state
+            .direct_buys
+            .iter()
+            .filter(|b| state.nft_owner(&b.nft).as_ref() == Some(owner))
+            .filter(|b| any_of(collections, b.collection.as_ref()))
+            .filter(|b| any_of(status, Some(&b.state)))
+            .cloned()
+            .collect();
+        Ok(page(with_cnt!(list), limit, offset))
+    }
+}
+
+#[async_trait]
+impl EventRepo for InMemoryRepo {
+    async fn list_events(
+        &self,
+        nft: Option<&String>,
+        collections: &[String],
+        owner: Option<&String>,
+        event_type: &[NftEventType],
+        offset: usize,
+        limit: usize,
+        with_count: bool,
+        verified: Option<bool>,
+        include_hidden: bool,
+    ) -> sqlx::Result<NftEventsRecord> {
+        let state = self.read();
+        let mut list: Vec<&NftEvent> = state
+            .events
+            .iter()
+            .filter(|e| nft.is_none() || nft == Some(&e.address))
+            .filter(|e| any_of(collections, state.nft_collection(&e.address).as_ref()))
+            .filter(|e| owner.is_none() || state.nft_owner(&e.address).as_ref() == owner)
+            .filter(|e| any_of(event_type, Some(&e.event_type)))
+            .filter(|e| {
+                !verified.unwrap_or(false)
+                    || state.is_verified(state.nft_collection(&e.address).as_ref())
+            })
+            .filter(|e| {
+                include_hidden
+                    || !state.is_hidden(Some(&e.address), state.nft_collection(&e.address).as_ref())
+            })
+            .collect();
+        list.sort_by(|a, b| b.datetime.cmp(&a.datetime).then(b.id.cmp(&a.id)));
+        let total_rows = if with_count { list.len() as i64 } else { 0 };
+        let data = page(list, limit, offset);
+        Ok(NftEventsRecord {
+            nft: nft.cloned(),
+            content: Some(serde_json::json!({ "data": data, "totalRows": total_rows })),
+        })
+    }
+
+    async fn get_metrics_summary(
+        &self,
+        _from: NaiveDateTime,
+        _to: NaiveDateTime,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<MetricsSummaryRecord>> {
+        let state = self.read();
+        let mut list: Vec<&NftCollectionDetails> = state.collections.iter().collect();
+        list.sort_by(|a, b| b.total_volume_usd.cmp(&a.total_volume_usd));
+        let total_rows_count = list.len() as i32;
+        Ok(page(list, limit as usize, offset as usize)
+            .into_iter()
+            .map(|c| MetricsSummaryRecord {
+                collection: c.address.clone().unwrap_or_default(),
+                name: c.name.clone(),
+                logo: c.logo.clone(),
+                floor_price: c.floor_price_usd.clone(),
+                total_volume_usd_now: c.total_volume_usd.clone().unwrap_or_default(),
+                total_volume_usd_previous: BigDecimal::default(),
+                owners_count: c.owners_count.unwrap_or_default() as i32,
+                nfts_count: c.nft_count.unwrap_or_default() as i32,
+                total_rows_count,
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl UserRepo for InMemoryRepo {
+    async fn get_user_by_address(&self, address: &Address) -> sqlx::Result<Option<UserRecord>> {
+        let state = self.read();
+        Ok(state.users.iter().find(|u| &u.address == address).cloned())
+    }
+
+    async fn collect_users(&self, addresses: &[Address]) -> sqlx::Result<Vec<UserRecord>> {
+        let state = self.read();
+        Ok(state
+            .users
+            .iter()
+            .filter(|u| addresses.contains(&u.address))
+            .cloned()
+            .collect())
+    }
+
+    async fn upsert_user(
+        &self,
+        address: Address,
+        bio: Option<String>,
+        username: Option<String>,
+        logo_nft: Option<Address>,
+        twitter: Option<String>,
+        instagram: Option<String>,
+        facebook: Option<String>,
+        link: Option<String>,
+        email: Option<String>,
+        ip: Option<&str>,
+    ) -> sqlx::Result<()> {
+        let mut state = self.write();
+        let snapshot = |u: &UserRecord| {
+            json!({
+                "logoNft": u.logo_nft,
+                "username": u.username,
+                "bio": u.bio,
+                "twitter": u.twitter,
+                "instagram": u.instagram,
+                "facebook": u.facebook,
+                "link": u.link,
+                "email": u.email,
+            })
+        };
+        let user = UserRecord {
+            address,
+            logo_nft,
+            username,
+            bio,
+            twitter,
+            instagram,
+            facebook,
+            link,
+            email,
+            avatar_url: None,
+        };
+        let after = snapshot(&user);
+        let address = user.address.clone();
+        let before = match state.users.iter_mut().find(|u| u.address == user.address) {
+            Some(existing) => Some(snapshot(&std::mem::replace(existing, user))),
+            None => {
+                state.users.push(user);
+                None
+            }
+        };
+        state.record_write(
+            &address,
+            WriteAction::UserUpdated,
+            &address,
+            before,
+            after,
+            json!({}),
+            ip,
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PriceRepo for InMemoryRepo {
+    /// Stored points are returned as is, without bucketing by `scale`
+    async fn list_token_price_history(
+        &self,
+        token: &Address,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        _scale: Option<&PriceHistoryScale>,
+    ) -> sqlx::Result<Vec<TokenUsdPrice>> {
+        let state = self.read();
+        Ok(state
+            .token_prices
+            .iter()
+            .filter(|p| &p.token == token && p.ts >= from && p.ts <= to)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Root expiry isn't modelled, `expiry_date` only ends up in the audit details
+#[async_trait]
+impl AdminRepo for InMemoryRepo {
+    async fn set_collection_verified(
+        &self,
+        admin: &str,
+        collection: &str,
+        verified: bool,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<bool>> {
+        let mut state = self.write();
+        let Some(row) = state
+            .collections
+            .iter_mut()
+            .find(|c| c.address.as_deref() == Some(collection))
+        else {
+            return Ok(None);
+        };
+        let previous = row.verified.unwrap_or_default();
+        row.verified = Some(verified);
+
+        let action = if verified {
+            AdminAction::CollectionVerified
+        } else {
+            AdminAction::CollectionUnverified
+        };
+        let details = json!({ "verified": verified, "previous": previous, "comment": comment });
+        state.audit(admin, action, Some(collection), details);
+        Ok(Some(previous))
+    }
+
+    async fn upsert_root(
+        &self,
+        admin: &str,
+        address: &str,
+        code: RootType,
+        expiry_date: Option<NaiveDateTime>,
+        comment: Option<&str>,
+    ) -> sqlx::Result<()> {
+        let mut state = self.write();
+        let code = serde_json::to_value(code)
+            .ok()
+            .and_then(|c| c.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let previous = state.roots.iter().position(|r| r.address == address);
+        let previous = previous.map(|i| state.roots.remove(i).code);
+        state.roots.push(RootRecord {
+            address: address.to_string(),
+            code: code.clone(),
+        });
+
+        let details = json!({
+            "code": code,
+            "expiryDate": expiry_date.map(|d| d.and_utc().timestamp()),
+            "previous": previous.map(|code| json!({ "code": code, "expiryDate": null })),
+            "comment": comment,
+        });
+        state.audit(admin, AdminAction::RootUpserted, Some(address), details);
+        Ok(())
+    }
+
+    async fn remove_root(
+        &self,
+        admin: &str,
+        address: &str,
+        comment: Option<&str>,
+    ) -> sqlx::Result<bool> {
+        let mut state = self.write();
+        let Some(i) = state.roots.iter().position(|r| r.address == address) else {
+            return Ok(false);
+        };
+        let removed = state.roots.remove(i);
+
+        let details = json!({
+            "previous": { "code": removed.code, "expiryDate": null },
+            "comment": comment,
+        });
+        state.audit(admin, AdminAction::RootRemoved, Some(address), details);
+        Ok(true)
+    }
+
+    /// Verified nfts are filtered on the fly, there is nothing to rebuild
+    async fn refresh_nft_verified(&self, admin: &str) -> sqlx::Result<Duration> {
+        let mut state = self.write();
+        let details = json!({ "elapsedMs": 0 });
+        state.audit(admin, AdminAction::NftVerifiedRefreshed, None, details);
+        Ok(Duration::ZERO)
+    }
+
+    async fn list_admin_audit(
+        &self,
+        admin: Option<&str>,
+        target: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<AdminAuditRecord>> {
+        let state = self.read();
+        let mut list: Vec<AdminAuditRecord> = state
+            .admin_audit
+            .iter()
+            .filter(|a| admin.is_none() || admin == Some(a.admin.as_str()))
+            .filter(|a| target.is_none() || target == a.target.as_deref())
+            .cloned()
+            .collect();
+        list.sort_by_key(|a| Reverse(a.id));
+        let total_count = list.len() as i64;
+        let mut list = page(list, limit, offset);
+        for row in list.iter_mut() {
+            row.total_count = total_count;
+        }
+        Ok(list)
+    }
+}
+
+#[async_trait]
+impl VerificationRepo for InMemoryRepo {
+    async fn create_verification_request(
+        &self,
+        collection: &str,
+        applicant: &str,
+        description: &str,
+        links: &[String],
+    ) -> sqlx::Result<Option<VerificationRequestRecord>> {
+        let mut state = self.write();
+        let pending = state
+            .verification_requests
+            .iter()
+            .any(|r| r.collection == collection && r.status == VerificationStatus::Pending);
+        if pending {
+            return Ok(None);
+        }
+        let request = VerificationRequestRecord {
+            id: state.verification_requests.len() as i64 + 1,
+            collection: collection.to_string(),
+            applicant: applicant.to_string(),
+            description: description.to_string(),
+            links: json!(links),
+            status: VerificationStatus::Pending,
+            reason: None,
+            reviewer: None,
+            created_at: Utc::now().naive_utc(),
+            reviewed_at: None,
+            total_count: 1,
+        };
+        state.verification_requests.push(request.clone());
+        Ok(Some(request))
+    }
+
+    async fn list_verification_requests(
+        &self,
+        applicant: Option<&str>,
+        collection: Option<&str>,
+        status: Option<VerificationStatus>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<VerificationRequestRecord>> {
+        let state = self.read();
+        let mut list: Vec<VerificationRequestRecord> = state
+            .verification_requests
+            .iter()
+            .filter(|r| applicant.is_none() || applicant == Some(r.applicant.as_str()))
+            .filter(|r| collection.is_none() || collection == Some(r.collection.as_str()))
+            .filter(|r| status.is_none() || status == Some(r.status))
+            .cloned()
+            .collect();
+        list.sort_by_key(|r| Reverse(r.id));
+        let total_count = list.len() as i64;
+        let mut list = page(list, limit, offset);
+        for row in list.iter_mut() {
+            row.total_count = total_count;
+        }
+        Ok(list)
+    }
+
+    async fn review_verification_request(
+        &self,
+        admin: &str,
+        id: i64,
+        approve: bool,
+        reason: Option<&str>,
+    ) -> sqlx::Result<Option<VerificationRequestRecord>> {
+        let mut state = self.write();
+        let Some(request) = state
+            .verification_requests
+            .iter_mut()
+            .find(|r| r.id == id && r.status == VerificationStatus::Pending)
+        else {
+            return Ok(None);
+        };
+        request.status = if approve {
+            VerificationStatus::Approved
+        } else {
+            VerificationStatus::Rejected
+        };
+        request.reason = reason.map(str::to_string);
+        request.reviewer = Some(admin.to_string());
+        request.reviewed_at = Some(Utc::now().naive_utc());
+        let request = request.clone();
+
+        if approve {
+            let collection = state
+                .collections
+                .iter_mut()
+                .find(|c| c.address.as_deref() == Some(request.collection.as_str()));
+            let previous = collection.map(|c| c.verified.replace(true).unwrap_or_default());
+            let details = json!({
+                "verified": true,
+                "previous": previous,
+                "request": id,
+                "comment": reason,
+            });
+            let target = Some(request.collection.as_str());
+            state.audit(admin, AdminAction::CollectionVerified, target, details);
+        } else {
+            let details = json!({ "request": id, "comment": reason });
+            let target = Some(request.collection.as_str());
+            state.audit(admin, AdminAction::VerificationRejected, target, details);
+        }
+        Ok(Some(request))
+    }
+}
+
+#[async_trait]
+impl ModerationRepo for InMemoryRepo {
+    async fn create_report(
+        &self,
+        reporter: &str,
+        target_type: ModerationTarget,
+        target: &str,
+        reason: ReportReason,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<ReportRecord>> {
+        let mut state = self.write();
+        let open = state.reports.iter().any(|r| {
+            r.target == target && r.reporter == reporter && r.status == ReportStatus::Open
+        });
+        if open {
+            return Ok(None);
+        }
+        let report = ReportRecord {
+            id: state.reports.len() as i64 + 1,
+            target_type,
+            target: target.to_string(),
+            reason,
+            comment: comment.map(str::to_string),
+            reporter: reporter.to_string(),
+            status: ReportStatus::Open,
+            resolution: None,
+            resolver: None,
+            created_at: Utc::now().naive_utc(),
+            resolved_at: None,
+            total_count: 1,
+        };
+        state.reports.push(report.clone());
+        Ok(Some(report))
+    }
+
+    async fn list_reports(
+        &self,
+        status: Option<ReportStatus>,
+        target_type: Option<ModerationTarget>,
+        target: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<ReportRecord>> {
+        let state = self.read();
+        let list: Vec<ReportRecord> = state
+            .reports
+            .iter()
+            .filter(|r| status.is_none() || status == Some(r.status))
+            .filter(|r| target_type.is_none() || target_type == Some(r.target_type))
+            .filter(|r| target.is_none() || target == Some(r.target.as_str()))
+            .cloned()
+            .collect();
+        let total_count = list.len() as i64;
+        let mut list = page(list, limit, offset);
+        for row in list.iter_mut() {
+            row.total_count = total_count;
+        }
+        Ok(list)
+    }
+
+    async fn set_moderation(
+        &self,
+        admin: &str,
+        target_type: ModerationTarget,
+        target: &str,
+        status: ModerationStatus,
+        reason: Option<&str>,
+    ) -> sqlx::Result<Option<u64>> {
+        let mut state = self.write();
+        let exists = match target_type {
+            ModerationTarget::Nft => state.nft(target).is_some(),
+            ModerationTarget::Collection => state.collection(target).is_some(),
+            ModerationTarget::User => false,
+        };
+        if !exists {
+            return Ok(None);
+        }
+
+        let record = ModerationRecord {
+            address: target.to_string(),
+            target_type,
+            status,
+            reason: reason.map(str::to_string),
+            moderator: admin.to_string(),
+            updated_at: Utc::now().naive_utc(),
+            total_count: 0,
+        };
+        let previous = match state.moderation.iter_mut().find(|m| m.address == target) {
+            Some(existing) => Some(std::mem::replace(existing, record).status),
+            None => {
+                state.moderation.push(record);
+                None
+            }
+        };
+
+        let mut resolved = 0;
+        for report in state.reports.iter_mut() {
+            if report.target == target && report.status == ReportStatus::Open {
+                report.status = ReportStatus::Resolved;
+                report.resolution = reason.map(str::to_string);
+                report.resolver = Some(admin.to_string());
+                report.resolved_at = Some(Utc::now().naive_utc());
+                resolved += 1;
+            }
+        }
+
+        let details = json!({
+            "targetType": target_type,
+            "status": status,
+            "previous": previous,
+            "resolvedReports": resolved,
+            "comment": reason,
+        });
+        state.audit(admin, AdminAction::ModerationChanged, Some(target), details);
+        Ok(Some(resolved))
+    }
+
+    async fn dismiss_report(
+        &self,
+        admin: &str,
+        id: i64,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<ReportRecord>> {
+        let mut state = self.write();
+        let Some(report) = state
+            .reports
+            .iter_mut()
+            .find(|r| r.id == id && r.status == ReportStatus::Open)
+        else {
+            return Ok(None);
+        };
+        report.status = ReportStatus::Dismissed;
+        report.resolution = comment.map(str::to_string);
+        report.resolver = Some(admin.to_string());
+        report.resolved_at = Some(Utc::now().naive_utc());
+        let report = report.clone();
+
+        let details = json!({ "report": id, "comment": comment });
+        let target = Some(report.target.as_str());
+        state.audit(admin, AdminAction::ReportDismissed, target, details);
+        Ok(Some(report))
+    }
+
+    async fn list_moderation(
+        &self,
+        status: Option<ModerationStatus>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<ModerationRecord>> {
+        let state = self.read();
+        let mut list: Vec<ModerationRecord> = state
+            .moderation
+            .iter()
+            .filter(|m| status.is_none() || status == Some(m.status))
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| {
+            b.updated_at
+                .cmp(&a.updated_at)
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        let total_count = list.len() as i64;
+        let mut list = page(list, limit, offset);
+        for row in list.iter_mut() {
+            row.total_count = total_count;
+        }
+        Ok(list)
+    }
+}
+
+#[async_trait]
+impl WriteAuditRepo for InMemoryRepo {
+    async fn revert_collection_custom(
+        &self,
+        actor: &str,
+        address: &str,
+        id: i64,
+        ip: Option<&str>,
+    ) -> sqlx::Result<bool> {
+        let mut state = self.write();
+        let Some(after) = state
+            .write_audit
+            .iter()
+            .find(|w| {
+                w.id == id && w.target == address && COLLECTION_CUSTOM_WRITES.contains(&w.action)
+            })
+            .map(|w| w.after.clone())
+        else {
+            return Ok(false);
+        };
+        let before = state.last_written(address, COLLECTION_CUSTOM_WRITES);
+        if let Some(c) = state
+            .collections
+            .iter_mut()
+            .find(|c| c.address.as_deref() == Some(address))
+        {
+            let text = |field: &str| after[field].as_str().map(str::to_string);
+            c.updated = Some(Utc::now().naive_utc());
+            c.name = text("name");
+            c.description = text("description");
+            c.wallpaper = text("wallpaper");
+            c.logo = text("logo");
+            c.social = Some(after["social"].clone()).filter(|v| !v.is_null());
+        }
+        state.record_write(
+            actor,
+            WriteAction::CollectionCustomReverted,
+            address,
+            before,
+            after,
+            json!({ "revertedTo": id }),
+            ip,
+        );
+        Ok(true)
+    }
+
+    async fn list_write_audit(
+        &self,
+        actor: Option<&str>,
+        target: Option<&str>,
+        action: Option<WriteAction>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<WriteAuditRecord>> {
+        let state = self.read();
+        let list: Vec<WriteAuditRecord> = state
+            .write_audit
+            .iter()
+            .rev()
+            .filter(|w| actor.is_none() || actor == Some(w.actor.as_str()))
+            .filter(|w| target.is_none() || target == Some(w.target.as_str()))
+            .filter(|w| action.is_none() || action == Some(w.action))
+            .cloned()
+            .collect();
+        let total_count = list.len() as i64;
+        let mut list = page(list, limit, offset);
+        for row in list.iter_mut() {
+            row.total_count = total_count;
+        }
+        Ok(list)
+    }
+}
+
+#[async_trait]
+impl MetadataRefreshRepo for InMemoryRepo {
+    async fn latest_metadata_refresh(
+        &self,
+        nft: &str,
+    ) -> sqlx::Result<Option<MetadataRefreshRecord>> {
+        let state = self.read();
+        Ok(state
+            .metadata_refresh
+            .iter()
+            .filter(|j| j.nft == nft)
+            .max_by_key(|j| j.id)
+            .map(|j| state.with_metadata_updated(j)))
+    }
+
+    async fn enqueue_metadata_refresh(
+        &self,
+        nft: &str,
+        requested_by: &str,
+    ) -> sqlx::Result<Option<MetadataRefreshRecord>> {
+        let mut state = self.write();
+        let Some(collection) = state.nft(nft).map(|n| n.collection.clone()) else {
+            return Ok(None);
+        };
+        if state
+            .metadata_refresh
+            .iter()
+            .any(|j| j.nft == nft && j.status.is_open())
+        {
+            return Ok(None);
+        }
+        let job = MetadataRefreshRecord {
+            id: state.metadata_refresh.len() as i64 + 1,
+            nft: nft.to_string(),
+            collection,
+            requested_by: requested_by.to_string(),
+            status: MetadataRefreshStatus::Pending,
+            error: None,
+            created_at: Utc::now().naive_utc(),
+            finished_at: None,
+            metadata_updated: state.metadata_updated(nft),
+        };
+        state.metadata_refresh.push(job.clone());
+        Ok(Some(job))
+    }
+
+    async fn enqueue_collection_metadata_refresh(
+        &self,
+        collection: &str,
+        requested_by: &str,
+        cooldown: Duration,
+    ) -> sqlx::Result<MetadataRefreshBatchRecord> {
+        let now = Utc::now().naive_utc();
+        let mut state = self.write();
+        let nfts: Vec<(Address, Option<NaiveDateTime>)> = state
+            .nfts
+            .iter()
+            .filter(|n| n.collection.as_deref() == Some(collection))
+            .filter(|n| !n.burned.unwrap_or(false))
+            .filter_map(|n| Some((n.address.clone()?, n.updated)))
+            .collect();
+        let mut queued = 0;
+        for (nft, updated) in &nfts {
+            let busy = state.metadata_refresh.iter().any(|j| {
+                &j.nft == nft
+                    && (j.status.is_open()
+                        || (now - j.created_at).to_std().unwrap_or_default() < cooldown)
+            });
+            if busy {
+                continue;
+            }
+            let job = MetadataRefreshRecord {
+                id: state.metadata_refresh.len() as i64 + 1,
+                nft: nft.clone(),
+                collection: Some(collection.to_string()),
+                requested_by: requested_by.to_string(),
+                status: MetadataRefreshStatus::Pending,
+                error: None,
+                created_at: now,
+                finished_at: None,
+                metadata_updated: *updated,
+            };
+            state.metadata_refresh.push(job);
+            queued += 1;
+        }
+        Ok(MetadataRefreshBatchRecord {
+            queued,
+            skipped: nfts.len() as i64 - queued,
+        })
+    }
+
+    async fn changed_metadata(
+        &self,
+        since: Option<NaiveDateTime>,
+        window: Duration,
+    ) -> sqlx::Result<Vec<ChangedMetadataRecord>> {
+        let now = Utc::now().naive_utc();
+        let state = self.read();
+        let mut changed: Vec<ChangedMetadataRecord> = Vec::new();
+        for job in &state.metadata_refresh {
+            let Some(updated) = state.metadata_updated(&job.nft) else {
+                continue;
+            };
+            let recent = (now - job.created_at).to_std().unwrap_or_default() < window;
+            if !recent
+                || Some(updated) == job.metadata_updated
+                || since.is_some_and(|since| updated <= since)
+                || changed.iter().any(|c| c.nft == job.nft)
+            {
+                continue;
+            }
+            changed.push(ChangedMetadataRecord {
+                nft: job.nft.clone(),
+                updated,
+            });
+        }
+        Ok(changed)
+    }
+}
+
+#[async_trait]
+impl MetadataOverrideRepo for InMemoryRepo {
+    async fn list_metadata_overrides(
+        &self,
+        collection: &str,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftMetadataOverrideRecord>> {
+        let state = self.read();
+        let mut list: Vec<NftMetadataOverrideRecord> = state
+            .metadata_overrides
+            .iter()
+            .filter(|o| o.collection == collection)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.nft.cmp(&b.nft));
+        let total_count = list.len() as i64;
+        let mut list = page(list, limit, offset);
+        for row in list.iter_mut() {
+            row.total_count = total_count;
+        }
+        Ok(list)
+    }
+
+    async fn write_metadata_overrides(
+        &self,
+        collection: &str,
+        actor: &str,
+        items: &[NftMetadataOverrideWrite],
+        ip: Option<&str>,
+    ) -> sqlx::Result<Vec<Address>> {
+        let mut state = self.write();
+        let mut written = Vec::with_capacity(items.len());
+        for item in items {
+            if state.nft_collection(&item.nft).as_deref() != Some(collection) {
+                continue;
+            }
+            let record = NftMetadataOverrideRecord {
+                nft: item.nft.clone(),
+                collection: collection.to_string(),
+                name: item.name.clone(),
+                preview: item.preview.clone(),
+                preview_mimetype: item.preview_mimetype.clone(),
+                attributes: item.attributes.clone(),
+                updated_by: actor.to_string(),
+                updated: Utc::now().naive_utc(),
+                total_count: 0,
+            };
+            let before = state
+                .metadata_overrides
+                .iter()
+                .position(|o| o.nft == item.nft)
+                .map(|i| override_snapshot(&state.metadata_overrides.remove(i)));
+            let after = override_snapshot(&record);
+            if let Some(nft) = state
+                .nfts
+                .iter_mut()
+                .find(|n| n.address.as_deref() == Some(item.nft.as_str()))
+            {
+                nft.meta_override = Some(meta_override(&record));
+            }
+            state.metadata_overrides.push(record);
+            state.record_write(
+                actor,
+                WriteAction::NftMetadataOverrideUpdated,
+                &item.nft,
+                before,
+                after,
+                json!({ "collection": collection }),
+                ip,
+            );
+            written.push(item.nft.clone());
+        }
+        Ok(written)
+    }
+
+    async fn remove_metadata_override(
+        &self,
+        collection: &str,
+        nft: &str,
+        actor: &str,
+        ip: Option<&str>,
+    ) -> sqlx::Result<bool> {
+        let mut state = self.write();
+        let Some(i) = state
+            .metadata_overrides
+            .iter()
+            .position(|o| o.nft == nft && o.collection == collection)
+        else {
+            return Ok(false);
+        };
+        let removed = state.metadata_overrides.remove(i);
+        if let Some(details) = state
+            .nfts
+            .iter_mut()
+            .find(|n| n.address.as_deref() == Some(nft))
+        {
+            details.meta_override = None;
+        }
+        state.record_write(
+            actor,
+            WriteAction::NftMetadataOverrideRemoved,
+            nft,
+            Some(override_snapshot(&removed)),
+            json!({}),
+            json!({ "collection": collection }),
+            ip,
+        );
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl MetadataReportRepo for InMemoryRepo {
+    async fn collection_nft_meta(
+        &self,
+        collection: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> sqlx::Result<Vec<NftMetaRecord>> {
+        let state = self.read();
+        let mut list: Vec<NftMetaRecord> = state
+            .nfts
+            .iter()
+            .filter(|n| n.collection.as_deref() == Some(collection) && n.burned != Some(true))
+            .filter(|n| after.is_none() || n.address.as_deref() > after)
+            .filter_map(|n| {
+                Some(NftMetaRecord {
+                    address: n.address.clone()?,
+                    name: n.name.clone(),
+                    meta: n.meta.clone(),
+                    meta_override: n.meta_override.clone(),
+                })
+            })
+            .collect();
+        list.sort_by(|a, b| a.address.cmp(&b.address));
+        list.truncate(limit);
+        Ok(list)
+    }
+}
+
+/// Collection fees aren't modelled, every root charges `default_fee`
+#[async_trait]
+impl QuoteRepo for InMemoryRepo {
+    async fn root_fees(
+        &self,
+        _collection: &Address,
+        root_code: &RootType,
+    ) -> sqlx::Result<Vec<RootFeeRecord>> {
+        let state = self.read();
+        let code = serde_json::to_value(root_code).expect("root type serializes");
+        let mut list: Vec<RootFeeRecord> = state
+            .roots
+            .iter()
+            .filter(|r| code.as_str() == Some(r.code.as_str()))
+            .map(|r| RootFeeRecord {
+                root: r.address.clone(),
+                fee_numerator: state.default_fee.map(|(n, _)| n),
+                fee_denominator: state.default_fee.map(|(_, d)| d),
+                collection_fee: false,
+            })
+            .collect();
+        list.sort_by(|a, b| a.root.cmp(&b.root));
+        Ok(list)
+    }
+
+    async fn token_usd_price(&self, token: &Address) -> sqlx::Result<Option<BigDecimal>> {
+        Ok(self.read().usd_price(token))
+    }
+}
+
+#[async_trait]
+impl OrderBookRepo for InMemoryRepo {
+    async fn collection_orders(&self, collection: &Address) -> sqlx::Result<Vec<OrderBookRecord>> {
+        let state = self.read();
+        let listed = |nft: &str| {
+            state.nft(nft).is_some_and(|n| {
+                n.collection.as_ref() == Some(collection)
+                    && n.burned != Some(true)
+                    && !state.is_hidden(n.address.as_ref(), n.collection.as_ref())
+            })
+        };
+        let record = |address: &Address, bid, auction, token: &Address, price: &BigDecimal| {
+            OrderBookRecord {
+                address: address.clone(),
+                bid,
+                auction,
+                price_token: token.clone(),
+                price: price.clone(),
+                price_usd: state.usd_price(token).map(|usd| price * usd),
+            }
+        };
+        let now = Utc::now().naive_utc();
+        let auctions = state.auctions.iter().filter_map(|a| {
+            let running = a.status == Some(AuctionStatus::Active)
+                && a.finished_at
+                    .is_some_and(|t| t == NaiveDateTime::default() || t > now)
+                && a.nft.as_deref().is_some_and(listed);
+            match (running, &a.address, &a.price_token, &a.min_bid) {
+                (true, Some(address), Some(token), Some(price)) => {
+                    Some(record(address, false, true, token, price))
+                }
+                _ => None,
+            }
+        });
+        let sells = state
+            .direct_sells
+            .iter()
+            .filter(|s| s.valid && listed(&s.nft))
+            .map(|s| record(&s.address, false, false, &s.price_token, &s.price));
+        let buys = state
+            .direct_buys
+            .iter()
+            .filter(|b| b.valid && listed(&b.nft))
+            .map(|b| record(&b.address, true, false, &b.price_token, &b.price));
+        Ok(auctions.chain(sells).chain(buys).collect())
+    }
+}
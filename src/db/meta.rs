@@ -0,0 +1,430 @@
+use super::NftDetails;
+use api_types::{
+    Fee, MetaRoyalty, MetadataIssue, NftAttribute, NftAttributeKind, NftMediaFile, NftMediaRole,
+    NftMetadata, NftOverrideField,
+};
+use serde_json::{Map, Value};
+
+const SOURCE_KEYS: &[&str] = &["source", "src", "uri", "url"];
+const MIMETYPE_KEYS: &[&str] = &["mimetype", "mimeType", "mime_type", "mime", "type"];
+const IMAGE_KEYS: &[&str] = &["image", "image_url", "imageUrl"];
+const ANIMATION_KEYS: &[&str] = &["animation_url", "animationUrl", "animation"];
+const AUDIO_KEYS: &[&str] = &["audio", "audio_url", "audioUrl"];
+const MODEL_KEYS: &[&str] = &["model", "model_url", "modelUrl", "3d_model"];
+const EXTERNAL_URL_KEYS: &[&str] = &[
+    "external_url",
+    "externalUrl",
+    "external_link",
+    "externalLink",
+];
+const MEDIA_SCHEMES: &[&str] = &["https://", "http://", "ipfs://", "ar://", "data:"];
+const ATTRIBUTES_KEYS: &[&str] = &["attributes", "traits"];
+const ROYALTY_KEYS: &[&str] = &["royalty", "royalties"];
+const RECIPIENT_KEYS: &[&str] = &["recipient", "receiver", "address", "wallet"];
+const TRAIT_TYPE_KEYS: &[&str] = &["trait_type", "traitType", "trait", "type", "name", "key"];
+const TRAIT_VALUE_KEYS: &[&str] = &["value", "trait_value", "traitValue"];
+
+/// Fields of the metadata used by the nft lists
+#[derive(Clone, Debug, Default)]
+pub struct MetaParsed {
+    pub image: Option<String>,
+    pub mimetype: Option<String>,
+    pub full_image: Option<String>,
+    pub full_image_mimetype: Option<String>,
+    pub attributes: Option<Value>,
+    pub typ: Option<String>,
+    pub royalty: Option<MetaRoyalty>,
+}
+
+impl NftDetails {
+    pub fn parse_meta(&self) -> MetaParsed {
+        let Some(meta) = self.merged_meta() else {
+            return MetaParsed::default();
+        };
+        let metadata = parse_metadata(&meta);
+        let preview = metadata
+            .files
+            .iter()
+            .find(|f| f.role == NftMediaRole::Preview)
+            .or_else(|| {
+                metadata
+                    .files
+                    .iter()
+                    .find(|f| f.role == NftMediaRole::Image)
+            });
+        let full_image = metadata
+            .files
+            .iter()
+            .find(|f| f.role != NftMediaRole::Preview);
+
+        MetaParsed {
+            image: preview.map(|f| f.source.clone()),
+            mimetype: preview.and_then(|f| f.mimetype.clone()),
+            full_image: full_image.map(|f| f.source.clone()),
+            full_image_mimetype: full_image.and_then(|f| f.mimetype.clone()),
+            attributes: meta.get("attributes").cloned(),
+            typ: meta.get("type").and_then(text),
+            royalty: parse_royalty(&meta),
+        }
+    }
+
+    pub fn metadata(&self) -> NftMetadata {
+        self.merged_meta()
+            .map(|meta| parse_metadata(&meta))
+            .unwrap_or_default()
+    }
+
+    /// The name set by the collection owner, else the indexed one
+    pub fn display_name(&self) -> Option<String> {
+        self.meta_override
+            .as_ref()
+            .and_then(|o| o.get("name"))
+            .and_then(text)
+            .or_else(|| self.name.clone())
+    }
+
+    pub fn overridden(&self) -> Vec<NftOverrideField> {
+        let Some(over) = self.meta_override.as_ref().and_then(root) else {
+            return vec![];
+        };
+        [
+            ("name", NftOverrideField::Name),
+            ("preview", NftOverrideField::Preview),
+            ("attributes", NftOverrideField::Attributes),
+        ]
+        .into_iter()
+        .filter(|(key, _)| over.get(*key).is_some_and(|v| !v.is_null()))
+        .map(|(_, field)| field)
+        .collect()
+    }
+
+    fn merged_meta(&self) -> Option<Map<String, Value>> {
+        merge_override(self.meta.as_ref(), self.meta_override.as_ref())
+    }
+}
+
+/// Metadata with the preview and the attributes of the override in place of its own
+fn merge_override(
+    meta: Option<&Value>,
+    meta_override: Option<&Value>,
+) -> Option<Map<String, Value>> {
+    let meta = meta.and_then(root);
+    let Some(over) = meta_override.and_then(root) else {
+        return meta;
+    };
+    let mut meta = meta.unwrap_or_default();
+    if let Some(preview) = over.get("preview") {
+        meta.insert("preview".to_string(), preview.clone());
+    }
+    if let Some(attributes) = over.get("attributes") {
+        for key in ATTRIBUTES_KEYS {
+            meta.remove(*key);
+        }
+        meta.insert("attributes".to_string(), attributes.clone());
+    }
+    Some(meta)
+}
+
+/// Metadata object, also when it was stored as a json string
+fn root(meta: &Value) -> Option<Map<String, Value>> {
+    match meta {
+        Value::Object(object) => Some(object.clone()),
+        Value::String(s) => match serde_json::from_str(s) {
+            Ok(Value::Object(object)) => Some(object),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Issues of the metadata as shown, with the override merged in, and what could be read of it.
+/// Names and traits are compared across the collection by the caller
+pub fn check_metadata(
+    meta: Option<&Value>,
+    meta_override: Option<&Value>,
+) -> (Vec<MetadataIssue>, NftMetadata) {
+    let mut issues = Vec::new();
+    match meta {
+        None | Some(Value::Null) => issues.push(MetadataIssue::MissingMetadata),
+        Some(meta) if root(meta).is_none() => issues.push(MetadataIssue::UnparsableMetadata),
+        Some(_) => {}
+    }
+    let Some(meta) = merge_override(meta, meta_override) else {
+        return (issues, NftMetadata::default());
+    };
+    let metadata = parse_metadata(&meta);
+
+    if metadata.files.is_empty() {
+        issues.push(MetadataIssue::MissingFiles);
+    }
+    if !metadata
+        .files
+        .iter()
+        .any(|f| matches!(f.role, NftMediaRole::Preview | NftMediaRole::Image))
+    {
+        issues.push(MetadataIssue::MissingPreview);
+    }
+    if metadata.files.iter().any(|f| {
+        !MEDIA_SCHEMES
+            .iter()
+            .any(|scheme| f.source.starts_with(scheme))
+    }) {
+        issues.push(MetadataIssue::InvalidFileUrl);
+    }
+    match field(&meta, ATTRIBUTES_KEYS) {
+        Some(Value::Array(items)) if metadata.attributes.len() < items.len() => {
+            issues.push(MetadataIssue::MalformedAttributes)
+        }
+        Some(Value::Array(_)) | None => {}
+        Some(_) => issues.push(MetadataIssue::AttributesNotArray),
+    }
+    (issues, metadata)
+}
+
+/// Reads the TIP-4.2 layout (`preview`, `files`) as well as the OpenSea one (`image`,
+/// `animation_url`, `external_url`), skipping whatever doesn't fit instead of failing
+pub fn parse_metadata(meta: &Map<String, Value>) -> NftMetadata {
+    let mut files = Vec::new();
+    if let Some(preview) = meta.get("preview") {
+        files.extend(media(preview, Some(NftMediaRole::Preview)));
+    }
+    for (keys, role) in [
+        (&["files"][..], None),
+        (IMAGE_KEYS, Some(NftMediaRole::Image)),
+        (ANIMATION_KEYS, Some(NftMediaRole::Animation)),
+        (AUDIO_KEYS, Some(NftMediaRole::Audio)),
+        (MODEL_KEYS, Some(NftMediaRole::Model)),
+    ] {
+        if let Some(value) = field(meta, keys) {
+            files.extend(media(value, role));
+        }
+    }
+    let mut unique = Vec::with_capacity(files.len());
+    for file in files {
+        if !unique
+            .iter()
+            .any(|f: &NftMediaFile| f.source == file.source && f.role == file.role)
+        {
+            unique.push(file);
+        }
+    }
+
+    NftMetadata {
+        files: unique,
+        external_url: field(meta, EXTERNAL_URL_KEYS).and_then(text),
+        attributes: field(meta, ATTRIBUTES_KEYS)
+            .map(parse_attributes)
+            .unwrap_or_default(),
+    }
+}
+
+/// `royalty` as a fraction, a percent or basis points, or OpenSea's `seller_fee_basis_points`.
+/// Shares outside of `0..=1` are dropped
+pub fn parse_royalty(meta: &Map<String, Value>) -> Option<MetaRoyalty> {
+    let royalty = match field(meta, ROYALTY_KEYS) {
+        Some(Value::Array(items)) => items.first().and_then(Value::as_object),
+        Some(value) => value.as_object(),
+        None => None,
+    };
+    let fee = royalty
+        .and_then(royalty_fee)
+        .or_else(|| {
+            let bps = number(field(
+                meta,
+                &["seller_fee_basis_points", "sellerFeeBasisPoints"],
+            )?)?;
+            fraction(bps, 10_000.0)
+        })
+        .filter(|fee| fee.numerator >= 0 && fee.numerator <= fee.denominator);
+    let recipient = royalty
+        .and_then(|r| field(r, RECIPIENT_KEYS))
+        .or_else(|| field(meta, &["fee_recipient", "feeRecipient"]))
+        .and_then(text);
+    let description = royalty.and_then(|r| r.get("description")).and_then(text);
+    let royalty_type = royalty
+        .and_then(|r| field(r, &["royalty_type", "royaltyType", "type"]))
+        .and_then(text);
+    if royalty.is_none() && fee.is_none() {
+        return None;
+    }
+    Some(MetaRoyalty {
+        description,
+        royalty_type,
+        fee,
+        recipient,
+    })
+}
+
+fn royalty_fee(royalty: &Map<String, Value>) -> Option<Fee> {
+    let royalty = field(royalty, &["fee"])
+        .and_then(Value::as_object)
+        .unwrap_or(royalty);
+    if let (Some(numerator), Some(denominator)) = (
+        royalty.get("numerator").and_then(number),
+        royalty.get("denominator").and_then(number),
+    ) {
+        return fraction(numerator, denominator);
+    }
+    if let Some(bps) = field(royalty, &["basis_points", "basisPoints", "bps"]).and_then(number) {
+        return fraction(bps, 10_000.0);
+    }
+    let percent = field(royalty, &["percent", "percentage", "value"]).and_then(number)?;
+    fraction(percent * 100.0, 10_000.0)
+}
+
+/// A number or a numeric string, `"5%"` included
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().trim_end_matches('%').trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn fraction(numerator: f64, denominator: f64) -> Option<Fee> {
+    if !numerator.is_finite() || denominator < 1.0 || denominator > i32::MAX as f64 {
+        return None;
+    }
+    Some(Fee {
+        numerator: numerator.round() as i32,
+        denominator: denominator.round() as i32,
+    })
+}
+
+/// First of `keys` holding a value
+fn field<'a>(object: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter()
+        .filter_map(|key| object.get(*key))
+        .find(|value| !value.is_null())
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// A file object, a bare url or a list of either
+fn media(value: &Value, role: Option<NftMediaRole>) -> Vec<NftMediaFile> {
+    let (source, mimetype) = match value {
+        Value::Array(items) => return items.iter().flat_map(|v| media(v, role)).collect(),
+        Value::String(_) => (text(value), None),
+        Value::Object(object) => (
+            field(object, SOURCE_KEYS).and_then(text),
+            field(object, MIMETYPE_KEYS).and_then(text),
+        ),
+        _ => (None, None),
+    };
+    let Some(source) = source else {
+        return vec![];
+    };
+    let mimetype = mimetype
+        .map(|m| m.to_lowercase())
+        .or_else(|| guess_mimetype(&source).map(str::to_string));
+    let by_type = role_of(mimetype.as_deref());
+    let role = match role {
+        None => by_type,
+        // a gif or a page given as `animation_url` stays an animation
+        Some(NftMediaRole::Animation) => match by_type {
+            NftMediaRole::Video | NftMediaRole::Audio | NftMediaRole::Model => by_type,
+            _ => NftMediaRole::Animation,
+        },
+        Some(role) => role,
+    };
+    vec![NftMediaFile {
+        source,
+        mimetype,
+        role,
+    }]
+}
+
+pub fn guess_mimetype(source: &str) -> Option<&'static str> {
+    if let Some(data) = source.strip_prefix("data:") {
+        let mimetype = data.split([';', ',']).next()?;
+        return [
+            "image/png",
+            "image/jpeg",
+            "image/gif",
+            "image/webp",
+            "image/svg+xml",
+            "video/mp4",
+            "audio/mpeg",
+            "text/html",
+        ]
+        .into_iter()
+        .find(|m| m.eq_ignore_ascii_case(mimetype));
+    }
+    let path = source.split(['?', '#']).next().unwrap_or_default();
+    let extension = path.rsplit_once('.')?.1.to_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "glb" => "model/gltf-binary",
+        "gltf" => "model/gltf+json",
+        "html" | "htm" => "text/html",
+        _ => return None,
+    })
+}
+
+fn role_of(mimetype: Option<&str>) -> NftMediaRole {
+    match mimetype.and_then(|m| m.split_once('/')) {
+        Some(("image", _)) => NftMediaRole::Image,
+        Some(("video", _)) => NftMediaRole::Video,
+        Some(("audio", _)) => NftMediaRole::Audio,
+        Some(("model", _)) => NftMediaRole::Model,
+        Some(("text", "html")) => NftMediaRole::Animation,
+        _ => NftMediaRole::Other,
+    }
+}
+
+/// `[{"trait_type": .., "value": ..}]` and its spellings, or a `{trait: value}` map
+pub fn parse_attributes(value: &Value) -> Vec<NftAttribute> {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| {
+                let object = item.as_object()?;
+                let mut attribute = attribute(
+                    field(object, TRAIT_TYPE_KEYS).and_then(text)?,
+                    field(object, TRAIT_VALUE_KEYS)?,
+                )?;
+                attribute.display_type =
+                    field(object, &["display_type", "displayType"]).and_then(text);
+                attribute.max_value = field(object, &["max_value", "maxValue"]).and_then(text);
+                Some(attribute)
+            })
+            .collect(),
+        Value::Object(object) => object
+            .iter()
+            .filter_map(|(trait_type, value)| attribute(trait_type.clone(), value))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn attribute(trait_type: String, value: &Value) -> Option<NftAttribute> {
+    let kind = match value {
+        Value::Number(_) => NftAttributeKind::Number,
+        Value::Bool(_) => NftAttributeKind::Boolean,
+        _ => NftAttributeKind::Text,
+    };
+    Some(NftAttribute {
+        trait_type,
+        value: text(value)?,
+        kind,
+        display_type: None,
+        max_value: None,
+    })
+}
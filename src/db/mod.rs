@@ -1,6 +1,11 @@
+pub mod bootstrap;
+pub mod memory;
 pub mod queries;
+pub mod repo;
 
+mod meta;
 mod types;
+pub use self::meta::*;
 pub use self::types::*;
 pub mod enums;
 
@@ -0,0 +1,210 @@
+use crate::db::queries::Queries;
+use crate::db::{AdminAction, AdminAuditRecord, RootType};
+use chrono::NaiveDateTime;
+use serde_json::{json, Value};
+use sqlx::{Executor, Postgres};
+use std::time::{Duration, Instant};
+
+impl Queries {
+    /// Previous flag, `None` when the collection isn't indexed
+    pub async fn set_collection_verified(
+        &self,
+        admin: &str,
+        collection: &str,
+        verified: bool,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<bool>> {
+        let mut tx = self.db.begin().await?;
+        let previous = sqlx::query_scalar!(
+            "select verified from nft_collection where address = $1 for update",
+            collection as _
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        let Some(previous) = previous else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            "update nft_collection set verified = $2 where address = $1",
+            collection as _,
+            verified
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let action = if verified {
+            AdminAction::CollectionVerified
+        } else {
+            AdminAction::CollectionUnverified
+        };
+        let details = json!({ "verified": verified, "previous": previous, "comment": comment });
+        insert_admin_audit(&mut tx, admin, action, Some(collection), details).await?;
+        tx.commit().await?;
+        Ok(Some(previous))
+    }
+
+    pub async fn upsert_root(
+        &self,
+        admin: &str,
+        address: &str,
+        code: RootType,
+        expiry_date: Option<NaiveDateTime>,
+        comment: Option<&str>,
+    ) -> sqlx::Result<()> {
+        let mut tx = self.db.begin().await?;
+        let previous = sqlx::query!(
+            r#"select code::text as "code!", expiry_date from roots where address = $1 for update"#,
+            address as _
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            insert into roots (address, code, expiry_date)
+            values ($1, $2, $3)
+            on conflict (address) do update set code        = excluded.code,
+                                                expiry_date = excluded.expiry_date
+            "#,
+            address as _,
+            &code as &RootType,
+            expiry_date
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let details = json!({
+            "code": code,
+            "expiryDate": expiry_date.map(|d| d.and_utc().timestamp()),
+            "previous": previous.map(|previous| json!({
+                "code": previous.code,
+                "expiryDate": previous.expiry_date.map(|d| d.and_utc().timestamp()),
+            })),
+            "comment": comment,
+        });
+        insert_admin_audit(
+            &mut tx,
+            admin,
+            AdminAction::RootUpserted,
+            Some(address),
+            details,
+        )
+        .await?;
+        tx.commit().await
+    }
+
+    /// `false` when the address isn't a root
+    pub async fn remove_root(
+        &self,
+        admin: &str,
+        address: &str,
+        comment: Option<&str>,
+    ) -> sqlx::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let removed = sqlx::query!(
+            r#"delete from roots where address = $1 returning code::text as "code!", expiry_date"#,
+            address as _
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        let Some(removed) = removed else {
+            return Ok(false);
+        };
+
+        let details = json!({
+            "previous": {
+                "code": removed.code,
+                "expiryDate": removed.expiry_date.map(|d| d.and_utc().timestamp()),
+            },
+            "comment": comment,
+        });
+        insert_admin_audit(
+            &mut tx,
+            admin,
+            AdminAction::RootRemoved,
+            Some(address),
+            details,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Rebuilds `nft_verified_mv` without blocking readers, returns how long it took
+    pub async fn refresh_nft_verified(&self, admin: &str) -> sqlx::Result<Duration> {
+        let started = Instant::now();
+        sqlx::query!("refresh materialized view concurrently nft_verified_mv")
+            .execute(self.db.as_ref())
+            .await?;
+        let elapsed = started.elapsed();
+
+        let details = json!({ "elapsedMs": elapsed.as_millis() as u64 });
+        insert_admin_audit(
+            self.db.as_ref(),
+            admin,
+            AdminAction::NftVerifiedRefreshed,
+            None,
+            details,
+        )
+        .await?;
+        Ok(elapsed)
+    }
+
+    pub async fn list_admin_audit(
+        &self,
+        admin: Option<&str>,
+        target: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<AdminAuditRecord>> {
+        sqlx::query_as!(
+            AdminAuditRecord,
+            r#"
+            select a.id,
+                   a.admin,
+                   a.action         as "action: _",
+                   a.target,
+                   a.details,
+                   a.created_at,
+                   count(1) over () as "total_count!"
+            from admin_audit a
+            where ($1::varchar is null or a.admin = $1)
+              and ($2::varchar is null or a.target = $2)
+            order by a.id desc
+            limit $3 offset $4
+            "#,
+            admin,
+            target,
+            limit as i64,
+            offset as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+}
+
+pub(super) async fn insert_admin_audit<'e, E>(
+    executor: E,
+    admin: &str,
+    action: AdminAction,
+    target: Option<&str>,
+    details: Value,
+) -> sqlx::Result<()>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query!(
+        r#"
+        insert into admin_audit (admin, action, target, details)
+        values ($1, $2, $3, $4)
+        "#,
+        admin as _,
+        action as _,
+        target as _,
+        details
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
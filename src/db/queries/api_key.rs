@@ -0,0 +1,40 @@
+use crate::db::queries::Queries;
+use crate::db::ApiKeyRecord;
+
+impl Queries {
+    pub async fn get_api_key(&self, key_hash: &str) -> sqlx::Result<Option<ApiKeyRecord>> {
+        sqlx::query_as!(
+            ApiKeyRecord,
+            r#"
+            select k.key_hash,
+                   k.name,
+                   k.rate_per_sec,
+                   k.burst,
+                   k.daily_quota,
+                   k.active,
+                   coalesce(u.requests, 0) as "requests_today!"
+            from api_keys k
+                     left join api_key_usage u on u.key_hash = k.key_hash and u.day = current_date
+            where k.key_hash = $1
+            "#,
+            key_hash
+        )
+        .fetch_optional(self.db.as_ref())
+        .await
+    }
+
+    pub async fn add_api_key_usage(&self, key_hash: &str, requests: i64) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            insert into api_key_usage (key_hash, day, requests)
+            values ($1, current_date, $2)
+            on conflict (key_hash, day) do update set requests = api_key_usage.requests + excluded.requests
+            "#,
+            key_hash,
+            requests
+        )
+        .execute(self.db.as_ref())
+        .await?;
+        Ok(())
+    }
+}
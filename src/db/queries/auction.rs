@@ -264,6 +264,84 @@ impl Queries {
         .await
     }
 
+    /// A page of auctions per nft owner, newest first, in one round trip
+    pub async fn list_owners_auctions(
+        &self,
+        owners: &[Address],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<(Address, NftAuction)>> {
+        let rows = sqlx::query!(
+            r#"
+            select k.owner as "owner!", a.*
+            from unnest($1::varchar[]) as k(owner)
+                     cross join lateral (
+                select a.address,
+                       a.nft,
+                       a.wallet_for_bids,
+                       a.price_token,
+                       a.start_price,
+                       a.max_bid,
+                       a.min_bid,
+                       a.start_usd_price,
+                       a.max_usd_bid,
+                       a.min_usd_bid,
+                       a."status: _" as "status: AuctionStatus",
+                       a.created_at,
+                       a.finished_at,
+                       a.tx_lt,
+                       a.bids_count,
+                       a.last_bid_from,
+                       a.last_bid_ts,
+                       a.last_bid_value,
+                       a.last_bid_usd_value,
+                       a.fee_numerator,
+                       a.fee_denominator,
+                       count(1) over () as "cnt!"
+                from nft_auction_search a
+                where a.nft_owner = k.owner
+                order by a.created_at desc
+                limit $2 offset $3
+                ) a
+            "#,
+            owners,
+            limit as i64,
+            offset as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let auction = NftAuction {
+                    address: r.address,
+                    nft: r.nft,
+                    wallet_for_bids: r.wallet_for_bids,
+                    price_token: r.price_token,
+                    start_price: r.start_price,
+                    max_bid: r.max_bid,
+                    min_bid: r.min_bid,
+                    start_usd_price: r.start_usd_price,
+                    max_usd_bid: r.max_usd_bid,
+                    min_usd_bid: r.min_usd_bid,
+                    status: r.status,
+                    created_at: r.created_at,
+                    finished_at: r.finished_at,
+                    tx_lt: r.tx_lt,
+                    bids_count: r.bids_count,
+                    last_bid_from: r.last_bid_from,
+                    last_bid_ts: r.last_bid_ts,
+                    last_bid_value: r.last_bid_value,
+                    last_bid_usd_value: r.last_bid_usd_value,
+                    cnt: r.cnt,
+                    fee_numerator: r.fee_numerator,
+                    fee_denominator: r.fee_denominator,
+                };
+                (r.owner, auction)
+            })
+            .collect())
+    }
+
     pub async fn list_nft_auctions(
         &self,
         owners: &[Address],
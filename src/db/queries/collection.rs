@@ -24,8 +24,8 @@ impl Queries {
                    c.logo,
                    c.owners_count,
                    c.nft_count,
-                   c.floor_price_usd,
-                   c.total_volume_usd,
+                   collection_floor_usd(c.address) as floor_price_usd,
+                   volume.total_volume_usd,
                    c.attributes,
                    c.first_mint,
                    c.social,
@@ -34,6 +34,10 @@ impl Queries {
                    1::bigint     as "cnt!",
                    '[]'::json    as "previews!"
             from nft_collection_details c
+                     left join lateral ( select sum(d.price_usd) as total_volume_usd
+                                         from nft_price_history_usd d
+                                         where d.collection = c.address
+                                           and d.is_deal ) volume on true
             where c.address = $1
             "#,
             address
@@ -141,8 +145,8 @@ impl Queries {
                    c.logo,
                    c.owners_count,
                    c.nft_count,
-                   c.floor_price_usd,
-                   c.total_volume_usd,
+                   collection_floor_usd(c.address) as floor_price_usd,
+                   volume.total_volume_usd,
                    c.attributes,
                    c.first_mint,
                    case when $4::boolean is false then c.total_count else c.verified_count end as "cnt",
@@ -151,6 +155,10 @@ impl Queries {
                    null::numeric                                                               as total_price,
                    c.social                                                                    as "social"
             from nft_collection_details c
+                     left join lateral ( select sum(d.price_usd) as total_volume_usd
+                                         from nft_price_history_usd d
+                                         where d.collection = c.address
+                                           and d.is_deal ) volume on true
                      left join lateral ( select json_agg(ag2.preview_url) as previews
                                          from ( select ag.preview_url
                                                 from ( select nm.meta -> 'preview' as preview_url
@@ -1,8 +1,11 @@
+use crate::db::queries::write_audit::{collection_custom_snapshot, insert_write_audit};
 use crate::db::queries::Queries;
-use crate::db::Address;
+use crate::db::{Address, WriteAction};
 use chrono::NaiveDateTime;
+use serde_json::json;
 
 impl Queries {
+    /// Writes the customization of an owned collection and records it in `write_audit`
     #[allow(clippy::too_many_arguments)]
     pub async fn upsert_collection_custom(
         &self,
@@ -14,8 +17,12 @@ impl Queries {
         wallpaper: Option<String>,
         logo: Option<String>,
         social: serde_json::Value,
+        ip: Option<&str>,
     ) -> sqlx::Result<()> {
-        sqlx::query!(
+        let mut tx = self.db.begin().await?;
+        let before = collection_custom_snapshot(&mut tx, address).await?;
+
+        let written = sqlx::query!(
             r#"
                 insert into nft_collection_custom(address, updated, name, description, wallpaper, logo, social)
                 select address, $2, $3, $4, $5, $6, $7 from nft_collection
@@ -36,13 +43,35 @@ impl Queries {
             description,
             wallpaper as _,
             logo as _,
-            social as serde_json::Value,
+            &social as &serde_json::Value,
             owner as _
         )
-        .execute(self.db.as_ref())
-        .await?;
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+        if written == 0 {
+            return Ok(());
+        }
 
-        Ok(())
+        let after = json!({
+            "name": name,
+            "description": description,
+            "wallpaper": wallpaper,
+            "logo": logo,
+            "social": social,
+        });
+        insert_write_audit(
+            &mut tx,
+            owner,
+            WriteAction::CollectionCustomUpdated,
+            address,
+            before,
+            after,
+            json!({}),
+            ip,
+        )
+        .await?;
+        tx.commit().await
     }
 
     pub async fn validate_owner_of_collection(
@@ -24,6 +24,7 @@ impl Queries {
                    case when s.state = 'active'::direct_buy_state and to_timestamp(0::double precision) < s.expired_at and
                              s.expired_at < now()::timestamp then 'expired'::direct_buy_state
                         else s.state end as "state!: _",
+                   direct_buy_valid(s)   as "valid!",
                    1::bigint             as "cnt!",
                    fee_numerator,
                    fee_denominator
@@ -62,6 +63,7 @@ impl Queries {
                    case when s.state = 'active'::direct_buy_state and to_timestamp(0::double precision) < s.expired_at and
                              s.expired_at < now()::timestamp then 'expired'::direct_buy_state
                         else s.state end as "state!: _",
+                   direct_buy_valid(s)   as "valid!",
                    1::bigint             as "cnt!",
                    fee_numerator,
                    fee_denominator
@@ -87,45 +89,62 @@ impl Queries {
         status: &[DirectBuyState],
         limit: usize,
         offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>> {
+        self.list_nfts_direct_buy(std::slice::from_ref(nft), status, limit, offset)
+            .await
+    }
+
+    /// A page of offers per nft, in one round trip
+    pub async fn list_nfts_direct_buy(
+        &self,
+        nfts: &[Address],
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
     ) -> sqlx::Result<Vec<NftDirectBuy>> {
         let status_str: Vec<String> = status.iter().map(|x| x.to_string()).collect();
         sqlx::query_as!(
             NftDirectBuy,
             r#"
-            select s.address             as "address!",
-                   s.created             as "created!",
-                   s.updated             as "updated!",
-                   s.tx_lt               as "tx_lt!",
-                   s.nft                 as "nft!",
-                   s.collection          as "collection?",
-                   s.buyer               as "buyer?",
-                   s.price_token         as "price_token!",
-                   s.price               as "price!",
-                   s.price * p.usd_price as "usd_price?",
-                   s.finished_at         as "finished_at?",
-                   s.expired_at          as "expired_at?",
-                   case when s.state = 'active'::direct_buy_state and to_timestamp(0::double precision) < s.expired_at and
-                             s.expired_at < now()::timestamp then 'expired'::direct_buy_state
-                        else s.state end as "state!: _",
-                   count(1) over ()      as "cnt!",
-                   fee_numerator,
-                   fee_denominator
-            from nft_direct_buy s
-                     join offers_whitelist ow on ow.address = s.address
-                     left join token_usd_prices p on s.price_token = p.token
-                     left join lateral ( select ((ne.args -> 'fee') -> 'numerator')::integer   as fee_numerator,
-                                                ((ne.args -> 'fee') -> 'denominator')::integer as fee_denominator
-                                         from nft_events ne
-                                         where ne.event_type = 'market_fee_changed'::event_type
-                                           and (ne.args ->> 'auction') = s.address) ev on true
-            where s.nft = $1
-              and s.state = 'active'::direct_buy_state
-              and (to_timestamp(0::double precision) = s.expired_at or s.expired_at > now()::timestamp)
-              and (array_length($2::varchar[], 1) is null or s.state::varchar = any ($2))
-            order by s.updated desc
-            limit $3 offset $4
+            select s.*
+            from unnest($1::varchar[]) as k(nft)
+                     cross join lateral (
+                select s.address             as "address!",
+                       s.created             as "created!",
+                       s.updated             as "updated!",
+                       s.tx_lt               as "tx_lt!",
+                       s.nft                 as "nft!",
+                       s.collection          as "collection?",
+                       s.buyer               as "buyer?",
+                       s.price_token         as "price_token!",
+                       s.price               as "price!",
+                       s.price * p.usd_price as "usd_price?",
+                       s.finished_at         as "finished_at?",
+                       s.expired_at          as "expired_at?",
+                       case when s.state = 'active'::direct_buy_state and to_timestamp(0::double precision) < s.expired_at and
+                                 s.expired_at < now()::timestamp then 'expired'::direct_buy_state
+                            else s.state end as "state!: _",
+                       direct_buy_valid(s)   as "valid!",
+                       count(1) over ()      as "cnt!",
+                       fee_numerator,
+                       fee_denominator
+                from nft_direct_buy s
+                         join offers_whitelist ow on ow.address = s.address
+                         left join token_usd_prices p on s.price_token = p.token
+                         left join lateral ( select ((ne.args -> 'fee') -> 'numerator')::integer   as fee_numerator,
+                                                    ((ne.args -> 'fee') -> 'denominator')::integer as fee_denominator
+                                             from nft_events ne
+                                             where ne.event_type = 'market_fee_changed'::event_type
+                                               and (ne.args ->> 'auction') = s.address) ev on true
+                where s.nft = k.nft
+                  and s.state = 'active'::direct_buy_state
+                  and (to_timestamp(0::double precision) = s.expired_at or s.expired_at > now()::timestamp)
+                  and (array_length($2::varchar[], 1) is null or s.state::varchar = any ($2))
+                order by s.updated desc
+                limit $3 offset $4
+                ) s
             "#,
-            nft,
+            nfts,
             &status_str,
             limit as i64,
             offset as i64
@@ -141,44 +160,68 @@ impl Queries {
         status: &[DirectBuyState],
         limit: usize,
         offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>> {
+        self.list_owners_direct_buy(
+            std::slice::from_ref(owner),
+            collections,
+            status,
+            limit,
+            offset,
+        )
+        .await
+    }
+
+    /// A page of offers per buyer, in one round trip
+    pub async fn list_owners_direct_buy(
+        &self,
+        owners: &[Address],
+        collections: &[String],
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
     ) -> sqlx::Result<Vec<NftDirectBuy>> {
         let status_str: Vec<String> = status.iter().map(|x| x.to_string()).collect();
         sqlx::query_as!(
             NftDirectBuy,
             r#"
-            select s.address             as "address!",
-                   s.created             as "created!",
-                   s.updated             as "updated!",
-                   s.tx_lt               as "tx_lt!",
-                   s.nft                 as "nft!",
-                   s.collection          as "collection?",
-                   s.buyer               as "buyer?",
-                   s.price_token         as "price_token!",
-                   s.price               as "price!",
-                   s.price * p.usd_price as "usd_price?",
-                   s.finished_at         as "finished_at?",
-                   s.expired_at          as "expired_at?",
-                   case when s.state = 'active'::direct_buy_state and to_timestamp(0::double precision) < s.expired_at and
-                             s.expired_at < now()::timestamp then 'expired'::direct_buy_state
-                        else s.state end as "state!: _",
-                   count(1) over ()      as "cnt!",
-                   fee_numerator,
-                   fee_denominator
-            from nft_direct_buy s
-                     join offers_whitelist ow on ow.address = s.address
-                     left join token_usd_prices p on s.price_token = p.token
-                     left join lateral ( select ((ne.args -> 'fee') -> 'numerator')::integer   as fee_numerator,
-                                                ((ne.args -> 'fee') -> 'denominator')::integer as fee_denominator
-                                         from nft_events ne
-                                         where ne.event_type = 'market_fee_changed'::event_type
-                                           and (ne.args ->> 'auction') = s.address) ev on true
-            where s.buyer = $1
-              and (s.collection = any ($2) or array_length($2::varchar[], 1) is null)
-              and (array_length($3::varchar[], 1) is null or s.state::varchar = any ($3))
-            order by s.updated desc
-            limit $4 offset $5
+            select s.*
+            from unnest($1::varchar[]) as k(buyer)
+                     cross join lateral (
+                select s.address             as "address!",
+                       s.created             as "created!",
+                       s.updated             as "updated!",
+                       s.tx_lt               as "tx_lt!",
+                       s.nft                 as "nft!",
+                       s.collection          as "collection?",
+                       s.buyer               as "buyer?",
+                       s.price_token         as "price_token!",
+                       s.price               as "price!",
+                       s.price * p.usd_price as "usd_price?",
+                       s.finished_at         as "finished_at?",
+                       s.expired_at          as "expired_at?",
+                       case when s.state = 'active'::direct_buy_state and to_timestamp(0::double precision) < s.expired_at and
+                                 s.expired_at < now()::timestamp then 'expired'::direct_buy_state
+                            else s.state end as "state!: _",
+                       direct_buy_valid(s)   as "valid!",
+                       count(1) over ()      as "cnt!",
+                       fee_numerator,
+                       fee_denominator
+                from nft_direct_buy s
+                         join offers_whitelist ow on ow.address = s.address
+                         left join token_usd_prices p on s.price_token = p.token
+                         left join lateral ( select ((ne.args -> 'fee') -> 'numerator')::integer   as fee_numerator,
+                                                    ((ne.args -> 'fee') -> 'denominator')::integer as fee_denominator
+                                             from nft_events ne
+                                             where ne.event_type = 'market_fee_changed'::event_type
+                                               and (ne.args ->> 'auction') = s.address) ev on true
+                where s.buyer = k.buyer
+                  and (s.collection = any ($2) or array_length($2::varchar[], 1) is null)
+                  and (array_length($3::varchar[], 1) is null or s.state::varchar = any ($3))
+                order by s.updated desc
+                limit $4 offset $5
+                ) s
             "#,
-            owner,
+            owners,
             collections,
             &status_str,
             limit as i64,
@@ -215,6 +258,7 @@ impl Queries {
                    case when s.state = 'active'::direct_buy_state and to_timestamp(0::double precision) < s.expired_at and
                              s.expired_at < now()::timestamp then 'expired'::direct_buy_state
                         else s.state end as "state!: _",
+                   direct_buy_valid(s)   as "valid!",
                    count(1) over ()      as "cnt!",
                    fee_numerator,
                    fee_denominator
@@ -14,7 +14,61 @@ impl Queries {
         limit: usize,
         with_count: bool,
         verified: Option<bool>,
+        include_hidden: bool,
     ) -> sqlx::Result<NftEventsRecord> {
+        self.events_of(
+            &[nft.cloned()],
+            collections,
+            owner,
+            event_type,
+            offset,
+            limit,
+            with_count,
+            verified,
+            include_hidden,
+        )
+        .await?
+        .pop()
+        .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// A page of events per nft, in one round trip
+    pub async fn list_nfts_events(
+        &self,
+        nfts: &[Address],
+        event_type: &[NftEventType],
+        offset: usize,
+        limit: usize,
+    ) -> sqlx::Result<Vec<NftEventsRecord>> {
+        let nfts: Vec<Option<Address>> = nfts.iter().cloned().map(Some).collect();
+        self.events_of(
+            &nfts,
+            &[],
+            None,
+            event_type,
+            offset,
+            limit,
+            true,
+            Some(false),
+            false,
+        )
+        .await
+    }
+
+    /// One record per element of `nfts`, `None` stands for the events of every nft
+    #[allow(clippy::too_many_arguments)]
+    async fn events_of(
+        &self,
+        nfts: &[Option<Address>],
+        collections: &[String],
+        owner: Option<&String>,
+        event_type: &[NftEventType],
+        offset: usize,
+        limit: usize,
+        with_count: bool,
+        verified: Option<bool>,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<NftEventsRecord>> {
         let event_types_slice = &event_type
             .iter()
             .map(|x| x.to_string())
@@ -25,14 +79,15 @@ impl Queries {
             "src/db/sql/activities.sql",
             event_types_slice as _,
             owner as _,
-            nft as _,
+            nfts as _,
             collections as _,
             limit as i64,
             offset as i64,
             with_count,
-            verified
+            verified,
+            include_hidden
         )
-        .fetch_one(self.db.as_ref())
+        .fetch_all(self.db.as_ref())
         .await
     }
 }
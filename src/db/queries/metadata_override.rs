@@ -0,0 +1,167 @@
+use crate::db::queries::write_audit::insert_write_audit;
+use crate::db::queries::Queries;
+use crate::db::{Address, NftMetadataOverrideRecord, NftMetadataOverrideWrite, WriteAction};
+use serde_json::{json, Value};
+use sqlx::{Executor, Postgres};
+
+impl Queries {
+    pub async fn list_metadata_overrides(
+        &self,
+        collection: &str,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftMetadataOverrideRecord>> {
+        sqlx::query_as!(
+            NftMetadataOverrideRecord,
+            r#"
+            select o.nft,
+                   o.collection,
+                   o.name,
+                   o.preview,
+                   o.preview_mimetype,
+                   o.attributes,
+                   o.updated_by,
+                   o.updated,
+                   count(1) over () as "total_count!"
+            from nft_metadata_override o
+            where o.collection = $1
+            order by o.nft
+            limit $2 offset $3
+            "#,
+            collection as _,
+            limit as i64,
+            offset as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+
+    /// Every write is recorded in `write_audit`, nfts of other collections are left out
+    pub async fn write_metadata_overrides(
+        &self,
+        collection: &str,
+        actor: &str,
+        items: &[NftMetadataOverrideWrite],
+        ip: Option<&str>,
+    ) -> sqlx::Result<Vec<Address>> {
+        let mut tx = self.db.begin().await?;
+        let mut written = Vec::with_capacity(items.len());
+        for item in items {
+            let before = metadata_override_snapshot(&mut tx, &item.nft).await?;
+            let rows = sqlx::query!(
+                r#"
+                insert into nft_metadata_override (nft, collection, name, preview, preview_mimetype,
+                                                   attributes, updated_by, updated)
+                select n.address, n.collection, $3, $4, $5, $6, $7, now()
+                from nft n
+                where n.address = $1
+                  and n.collection = $2
+                on conflict (nft)
+                do update set collection       = excluded.collection,
+                              name             = excluded.name,
+                              preview          = excluded.preview,
+                              preview_mimetype = excluded.preview_mimetype,
+                              attributes       = excluded.attributes,
+                              updated_by       = excluded.updated_by,
+                              updated          = excluded.updated
+                "#,
+                &item.nft as _,
+                collection as _,
+                item.name,
+                item.preview,
+                item.preview_mimetype,
+                item.attributes,
+                actor as _
+            )
+            .execute(&mut tx)
+            .await?
+            .rows_affected();
+            if rows == 0 {
+                continue;
+            }
+
+            let after = json!({
+                "name": item.name,
+                "preview": item.preview,
+                "previewMimetype": item.preview_mimetype,
+                "attributes": item.attributes,
+            });
+            insert_write_audit(
+                &mut tx,
+                actor,
+                WriteAction::NftMetadataOverrideUpdated,
+                &item.nft,
+                before,
+                after,
+                json!({ "collection": collection }),
+                ip,
+            )
+            .await?;
+            written.push(item.nft.clone());
+        }
+        tx.commit().await?;
+        Ok(written)
+    }
+
+    pub async fn remove_metadata_override(
+        &self,
+        collection: &str,
+        nft: &str,
+        actor: &str,
+        ip: Option<&str>,
+    ) -> sqlx::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let before = metadata_override_snapshot(&mut tx, nft).await?;
+        let removed = sqlx::query!(
+            r#"
+            delete
+            from nft_metadata_override
+            where nft = $1
+              and collection = $2
+            "#,
+            nft as _,
+            collection as _
+        )
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+        if removed == 0 {
+            return Ok(false);
+        }
+
+        insert_write_audit(
+            &mut tx,
+            actor,
+            WriteAction::NftMetadataOverrideRemoved,
+            nft,
+            before,
+            json!({}),
+            json!({ "collection": collection }),
+            ip,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+}
+
+/// Values of `nft_metadata_override` kept in the history, `None` without override
+async fn metadata_override_snapshot<'e, E>(executor: E, nft: &str) -> sqlx::Result<Option<Value>>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_scalar!(
+        r#"
+        select jsonb_build_object('name', name,
+                                  'preview', preview,
+                                  'previewMimetype', preview_mimetype,
+                                  'attributes', attributes) as "snapshot!"
+        from nft_metadata_override
+        where nft = $1
+        for update
+        "#,
+        nft as _
+    )
+    .fetch_optional(executor)
+    .await
+}
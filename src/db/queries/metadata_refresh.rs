@@ -0,0 +1,133 @@
+use crate::db::queries::Queries;
+use crate::db::{ChangedMetadataRecord, MetadataRefreshBatchRecord, MetadataRefreshRecord};
+use chrono::NaiveDateTime;
+use std::time::Duration;
+
+impl Queries {
+    pub async fn latest_metadata_refresh(
+        &self,
+        nft: &str,
+    ) -> sqlx::Result<Option<MetadataRefreshRecord>> {
+        sqlx::query_as!(
+            MetadataRefreshRecord,
+            r#"
+            select j.id,
+                   j.nft,
+                   j.collection,
+                   j.requested_by,
+                   j.status  as "status: _",
+                   j.error,
+                   j.created_at,
+                   j.finished_at,
+                   m.updated as metadata_updated
+            from metadata_refresh_jobs j
+                     left join nft_metadata m on m.nft = j.nft
+            where j.nft = $1
+            order by j.id desc
+            limit 1
+            "#,
+            nft as _
+        )
+        .fetch_optional(self.db.as_ref())
+        .await
+    }
+
+    /// `None` when the nft is unknown or already has an open job
+    pub async fn enqueue_metadata_refresh(
+        &self,
+        nft: &str,
+        requested_by: &str,
+    ) -> sqlx::Result<Option<MetadataRefreshRecord>> {
+        sqlx::query_as!(
+            MetadataRefreshRecord,
+            r#"
+            with j as (
+                insert into metadata_refresh_jobs (nft, collection, requested_by, meta_updated)
+                select n.address, n.collection, $2, md.updated
+                from nft n
+                         left join nft_metadata md on md.nft = n.address
+                where n.address = $1
+                on conflict (nft) where status in ('pending', 'processing') do nothing
+                returning *
+            )
+            select j.id           as "id!",
+                   j.nft          as "nft!",
+                   j.collection,
+                   j.requested_by as "requested_by!",
+                   j.status       as "status!: _",
+                   j.error,
+                   j.created_at   as "created_at!",
+                   j.finished_at,
+                   m.updated      as metadata_updated
+            from j
+                     left join nft_metadata m on m.nft = j.nft
+            "#,
+            nft as _,
+            requested_by as _
+        )
+        .fetch_optional(self.db.as_ref())
+        .await
+    }
+
+    pub async fn enqueue_collection_metadata_refresh(
+        &self,
+        collection: &str,
+        requested_by: &str,
+        cooldown: Duration,
+    ) -> sqlx::Result<MetadataRefreshBatchRecord> {
+        sqlx::query_as!(
+            MetadataRefreshBatchRecord,
+            r#"
+            with nfts as (
+                select n.address, n.collection, md.updated
+                from nft n
+                         left join nft_metadata md on md.nft = n.address
+                where n.collection = $1
+                  and not n.burned
+            ),
+                 queued as (
+                     insert into metadata_refresh_jobs (nft, collection, requested_by, meta_updated)
+                     select n.address, n.collection, $2, n.updated
+                     from nfts n
+                     where not exists(select 1
+                                      from metadata_refresh_jobs j
+                                      where j.nft = n.address
+                                        and (j.status in ('pending', 'processing')
+                                          or j.created_at > now()::timestamp - make_interval(secs => $3)))
+                     on conflict (nft) where status in ('pending', 'processing') do nothing
+                     returning 1
+                 )
+            select (select count(1) from queued)                               as "queued!",
+                   (select count(1) from nfts) - (select count(1) from queued) as "skipped!"
+            "#,
+            collection as _,
+            requested_by as _,
+            cooldown.as_secs_f64()
+        )
+        .fetch_one(self.db.as_ref())
+        .await
+    }
+
+    pub async fn changed_metadata(
+        &self,
+        since: Option<NaiveDateTime>,
+        window: Duration,
+    ) -> sqlx::Result<Vec<ChangedMetadataRecord>> {
+        sqlx::query_as!(
+            ChangedMetadataRecord,
+            r#"
+            select j.nft, max(m.updated) as "updated!"
+            from metadata_refresh_jobs j
+                     join nft_metadata m on m.nft = j.nft
+            where j.created_at > now()::timestamp - make_interval(secs => $2)
+              and m.updated is distinct from j.meta_updated
+              and ($1::timestamp is null or m.updated > $1)
+            group by j.nft
+            "#,
+            since,
+            window.as_secs_f64()
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+}
@@ -0,0 +1,33 @@
+use crate::db::queries::Queries;
+use crate::db::NftMetaRecord;
+
+impl Queries {
+    pub async fn collection_nft_meta(
+        &self,
+        collection: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> sqlx::Result<Vec<NftMetaRecord>> {
+        sqlx::query_as!(
+            NftMetaRecord,
+            r#"
+            select n.address,
+                   n.name::text                 as name,
+                   m.meta,
+                   nft_meta_override(n.address) as meta_override
+            from nft n
+                     left join nft_metadata m on m.nft = n.address
+            where n.collection = $1
+              and not n.burned
+              and ($2::varchar is null or n.address > $2)
+            order by n.address
+            limit $3
+            "#,
+            collection as _,
+            after,
+            limit as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+}
@@ -1,11 +1,22 @@
+mod admin;
+mod api_key;
 mod auction;
 mod collection;
 mod collection_custom;
 mod direct_buy;
 mod direct_sell;
 mod event;
+mod metadata_override;
+mod metadata_refresh;
+mod metadata_report;
+mod moderation;
 mod nft;
+mod orderbook;
+mod quote;
+mod token;
 mod user;
+mod verification;
+mod write_audit;
 
 pub use self::auction::*;
 pub use self::collection::*;
@@ -33,10 +44,12 @@ impl Queries {
         Self { db, tokens }
     }
 
+    /// Current prices and their history are written in one transaction, they can't diverge
     pub async fn update_token_usd_prices(
         &self,
         mut prices: Vec<TokenUsdPrice>,
     ) -> sqlx::Result<()> {
+        let mut tx = self.db.begin().await?;
         for price in prices.drain(..) {
             sqlx::query!(
                 r#"
@@ -49,10 +62,23 @@ impl Queries {
                 price.usd_price,
                 price.ts
             )
-            .execute(self.db.as_ref())
+            .execute(&mut tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                insert into token_usd_prices_history (token, usd_price, ts)
+                values ($1::varchar, $2, $3)
+                on conflict (token, ts) do nothing
+                "#,
+                price.token,
+                price.usd_price,
+                price.ts
+            )
+            .execute(&mut tx)
             .await?;
         }
-        Ok(())
+        tx.commit().await
     }
 
     pub async fn get_metrics_summary(
@@ -0,0 +1,264 @@
+use crate::db::queries::admin::insert_admin_audit;
+use crate::db::queries::Queries;
+use crate::db::{
+    AdminAction, ModerationRecord, ModerationStatus, ModerationTarget, ReportReason, ReportRecord,
+    ReportStatus,
+};
+use serde_json::json;
+
+impl Queries {
+    /// `None` when the reporter already has an open report on the target
+    pub async fn create_report(
+        &self,
+        reporter: &str,
+        target_type: ModerationTarget,
+        target: &str,
+        reason: ReportReason,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<ReportRecord>> {
+        sqlx::query_as!(
+            ReportRecord,
+            r#"
+            insert into moderation_reports as r (target_type, target, reason, comment, reporter)
+            values ($1, $2, $3, $4, $5)
+            on conflict (target, reporter) where status = 'open' do nothing
+            returning r.id,
+                      r.target_type as "target_type: _",
+                      r.target,
+                      r.reason      as "reason: _",
+                      r.comment,
+                      r.reporter,
+                      r.status      as "status: _",
+                      r.resolution,
+                      r.resolver,
+                      r.created_at,
+                      r.resolved_at,
+                      1::bigint     as "total_count!"
+            "#,
+            target_type as _,
+            target as _,
+            reason as _,
+            comment,
+            reporter as _
+        )
+        .fetch_optional(self.db.as_ref())
+        .await
+    }
+
+    pub async fn list_reports(
+        &self,
+        status: Option<ReportStatus>,
+        target_type: Option<ModerationTarget>,
+        target: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<ReportRecord>> {
+        sqlx::query_as!(
+            ReportRecord,
+            r#"
+            select r.id,
+                   r.target_type    as "target_type: _",
+                   r.target,
+                   r.reason         as "reason: _",
+                   r.comment,
+                   r.reporter,
+                   r.status         as "status: _",
+                   r.resolution,
+                   r.resolver,
+                   r.created_at,
+                   r.resolved_at,
+                   count(1) over () as "total_count!"
+            from moderation_reports r
+            where ($1::t_report_status is null or r.status = $1)
+              and ($2::t_moderation_target is null or r.target_type = $2)
+              and ($3::varchar is null or r.target = $3)
+            order by r.id
+            limit $4 offset $5
+            "#,
+            status as _,
+            target_type as _,
+            target,
+            limit as i64,
+            offset as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+
+    /// Sets the status of an nft or a collection and resolves the open reports on it.
+    /// `None` when the target isn't indexed.
+    pub async fn set_moderation(
+        &self,
+        admin: &str,
+        target_type: ModerationTarget,
+        target: &str,
+        status: ModerationStatus,
+        reason: Option<&str>,
+    ) -> sqlx::Result<Option<u64>> {
+        let mut tx = self.db.begin().await?;
+        let exists = match target_type {
+            ModerationTarget::Nft => {
+                sqlx::query_scalar!(
+                    r#"select exists(select 1 from nft where address = $1) as "exists!""#,
+                    target
+                )
+                .fetch_one(&mut tx)
+                .await?
+            }
+            ModerationTarget::Collection => sqlx::query_scalar!(
+                r#"select exists(select 1 from nft_collection where address = $1) as "exists!""#,
+                target
+            )
+            .fetch_one(&mut tx)
+            .await?,
+            ModerationTarget::User => return Ok(None),
+        };
+        if !exists {
+            return Ok(None);
+        }
+
+        let previous = sqlx::query_scalar!(
+            r#"select status as "status: ModerationStatus" from moderation where address = $1 for update"#,
+            target
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            insert into moderation (address, target_type, status, reason, moderator)
+            values ($1, $2, $3, $4, $5)
+            on conflict (address) do update set status     = excluded.status,
+                                                reason     = excluded.reason,
+                                                moderator  = excluded.moderator,
+                                                updated_at = now()
+            "#,
+            target as _,
+            target_type as _,
+            status as _,
+            reason,
+            admin as _
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let resolved = sqlx::query!(
+            r#"
+            update moderation_reports
+            set status      = 'resolved',
+                resolution  = $2,
+                resolver    = $3,
+                resolved_at = now()::timestamp
+            where target = $1
+              and status = 'open'
+            "#,
+            target as _,
+            reason,
+            admin as _
+        )
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+
+        let details = json!({
+            "targetType": target_type,
+            "status": status,
+            "previous": previous,
+            "resolvedReports": resolved,
+            "comment": reason,
+        });
+        insert_admin_audit(
+            &mut tx,
+            admin,
+            AdminAction::ModerationChanged,
+            Some(target),
+            details,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(resolved))
+    }
+
+    /// `None` when there is no open report with this id
+    pub async fn dismiss_report(
+        &self,
+        admin: &str,
+        id: i64,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<ReportRecord>> {
+        let mut tx = self.db.begin().await?;
+        let report = sqlx::query_as!(
+            ReportRecord,
+            r#"
+            update moderation_reports r
+            set status      = 'dismissed',
+                resolution  = $2,
+                resolver    = $3,
+                resolved_at = now()::timestamp
+            where r.id = $1
+              and r.status = 'open'
+            returning r.id,
+                      r.target_type as "target_type: _",
+                      r.target,
+                      r.reason      as "reason: _",
+                      r.comment,
+                      r.reporter,
+                      r.status      as "status: _",
+                      r.resolution,
+                      r.resolver,
+                      r.created_at,
+                      r.resolved_at,
+                      1::bigint     as "total_count!"
+            "#,
+            id,
+            comment,
+            admin as _
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        let Some(report) = report else {
+            return Ok(None);
+        };
+
+        let details = json!({ "report": id, "comment": comment });
+        insert_admin_audit(
+            &mut tx,
+            admin,
+            AdminAction::ReportDismissed,
+            Some(&report.target),
+            details,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(report))
+    }
+
+    pub async fn list_moderation(
+        &self,
+        status: Option<ModerationStatus>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<ModerationRecord>> {
+        sqlx::query_as!(
+            ModerationRecord,
+            r#"
+            select m.address,
+                   m.target_type    as "target_type: _",
+                   m.status         as "status: _",
+                   m.reason,
+                   m.moderator,
+                   m.updated_at,
+                   count(1) over () as "total_count!"
+            from moderation m
+            where ($1::t_moderation_status is null or m.status = $1)
+            order by m.updated_at desc, m.address
+            limit $2 offset $3
+            "#,
+            status as _,
+            limit as i64,
+            offset as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+}
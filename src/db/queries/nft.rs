@@ -7,70 +7,37 @@ use super::*;
 use crate::handlers::nft::{AttributeFilter, NFTListOrder, NFTListOrderField};
 
 use crate::model::OrderDirection;
+use sqlx::types::BigDecimal;
 use sqlx::{self};
 
 impl Queries {
-    pub async fn search_all(&self, search_str: &String) -> sqlx::Result<Vec<SearchResult>> {
-        sqlx::query_as!(
-            SearchResult,
-                        r#"
-                        with nft_top as (
-                select n.address,
-                       n.name                                                                           nft_name,
-                       nc.name                                                                          collection_name,
-                       'nft'                                                                         as object_type,
-                       case when m.meta is not null then m.meta::jsonb -> 'preview' ->> 'source' end as "image",
-                       case
-                           when lower(n.address) = lower($1) then 10
-                           when lower(n.name) = lower($1) then 9
-                           when n.name like '' || $1 || ' %' then 7.9
-                           when n.name like '% ' || $1 || '' then 7.86
-                           when n.name like '%' || $1 || '' then 7.855
-                           when n.name like '' || $1 || '%' then 7.85
-                           when n.name like '% ' || $1 || ' %' then 7.7
-                           when n.name like '%' || $1 || '%' then 7
-                           when n.address ilike '%' || $1 || '%' then 5
-                           else 1 end                                                                   priority
-                from nft_verified_mv n
-                         left join nft_metadata m on n.address = m.nft
-                         join nft_collection nc on n.collection = nc.address
-                where (n.name ilike '%' || $1 || '%' or n.description ilike '%' || $1 || '%' or n.address ilike '%' || $1 || '%')
-                  and not n.burned
-                order by priority desc
-                limit 20
-            )
+    /// Ranked by relevance, `after` is the score and address of the last result of the previous page
+    pub async fn search_all(
+        &self,
+        query: &str,
+        entities: &[SearchEntity],
+        collections: Option<&[Address]>,
+        after: Option<(f64, &str)>,
+        limit: usize,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<SearchResult>> {
+        let entities: Vec<&str> = entities.iter().map(SearchEntity::as_str).collect();
+        sqlx::query_as(include_str!("../sql/search.sql"))
+            .bind(query)
+            .bind(entities)
+            .bind(collections)
+            .bind(after.map(|(score, _)| score))
+            .bind(after.map(|(_, address)| address))
+            .bind(limit as i64)
+            .bind(include_hidden)
+            .fetch_all(self.db.as_ref())
+            .await
+    }
 
-            select ag.address as "address!", nft_name, collection_name, object_type as "object_type!", image
-            from (
-                     select *
-                     from nft_top
-                     union all
-
-                     select c.address,
-                            null            nft_name,
-                            c.name          collection_name,
-                            'collection' as object_type,
-                            c.logo          "image",
-                            case
-                                when lower(c.address) = lower($1) then 20
-                                when lower(c.name) = lower($1) then 19
-                                when c.name like '' || $1 || ' %' then 8.9
-                                when c.name like '% ' || $1 || '' then 8.86
-                                when c.name like '%' || $1 || '' then 8.855
-                                when c.name like '' || $1 || '%' then 8.85
-
-                                when c.name like '% ' || $1 || ' %' then 8.7
-                                when c.address ilike '%' || $1 || '%' then 6
-                                else 2 end  priority
-                     from nft_collection c
-                     where (c.name ilike '%' || $1 || '%' or c.description ilike '%' || $1 || '%' or
-                            c.address ilike '%' || $1 || '%')
-                       and c.verified) ag
-            order by ag.priority desc
-            limit 20
-            "#,
-            search_str
-        )
+    /// Every verified collection and user name, nfts of the most owned collections first
+    pub async fn suggest_entries(&self, max_nfts: usize) -> sqlx::Result<Vec<SuggestRecord>> {
+        sqlx::query_as(include_str!("../sql/suggest.sql"))
+            .bind(max_nfts as i64)
             .fetch_all(self.db.as_ref())
             .await
     }
@@ -93,43 +60,25 @@ impl Queries {
                                      auc."auction_status: _",
                                      sale.forsale,
                                      sale."forsale_status: _",
-                                     ( select distinct on (s.address) first_value(s.address) over w
-                                       from nft_direct_buy s
-                                                left join token_usd_prices tup on tup.token = s.price_token
-                                       where state = 'active'
-                                         and nft = n.address
-                                       window w as (partition by nft order by s.price * tup.usd_price desc)
-                                       limit 1 )                           as best_offer,
-                                     least(auc.price_usd, sale.price_usd)  as floor_price_usd,
-                                     last_deal.last_price                  as deal_price_usd,
-                                     case when least(auc.price_usd, sale.price_usd) = auc.price_usd then auc.min_bid
-                                          when least(auc.price_usd, sale.price_usd) = sale.price_usd then sale.price
-                                          else null::numeric end           as floor_price,
-                                     case when least(auc.price_usd, sale.price_usd) = auc.price_usd
-                                              then auc.token::character varying
-                                          when least(auc.price_usd, sale.price_usd) = sale.price_usd
-                                              then sale.token::character varying
-                                          else null::character varying end as floor_price_token,
+                                     nft_best_offer(n.address)             as best_offer,
+                                     floor.price_usd                       as floor_price_usd,
+                                     last_deal.price_usd                   as deal_price_usd,
+                                     floor.price                           as floor_price,
+                                     floor.token                           as floor_price_token,
                                      n.id::text                            as nft_id
                               from nft n
-                                       left join lateral ( select nph.price * tup.usd_price as last_price
-                                                           from nft_price_history nph
-                                                                    join offers_whitelist ow on ow.address = nph.source
-                                                                    left join token_usd_prices tup on tup.token = nph.price_token
-                                                           where nph.nft = n.address
-                                                           order by nph.ts desc
+                                       left join lateral ( select d.price_usd
+                                                           from nft_price_history_usd d
+                                                           where d.nft = n.address
+                                                           order by d.ts desc
                                                            limit 1 ) last_deal on true
-                                       left join lateral ( select a.address                 as auction,
+                                       left join lateral ( select a.address              as auction,
                                                                   case when a.status = 'active' and
                                                                             to_timestamp(0) < a.finished_at and
                                                                             a.finished_at < now() then 'expired'
-                                                                       else a.status end    as "auction_status: _",
-                                                                  a.min_bid * tup.usd_price as price_usd,
-                                                                  tup.token,
-                                                                  a.min_bid
+                                                                       else a.status end as "auction_status: _"
                                                            from nft_auction a
                                                                     join offers_whitelist ow on ow.address = a.address
-                                                                    left join token_usd_prices tup on tup.token = a.price_token
                                                            where a.nft = n.address
                                                              and a.status in ('active', 'expired')
                                                            limit 1 ) auc on true
@@ -137,39 +86,37 @@ impl Queries {
                                        left join lateral ( select s.address                                as forsale,
                                                                   case when s.state = 'active' and
                                                                             to_timestamp(0) < s.expired_at and s.expired_at < now()
-                                                                           then 'expired' else s.state end as "forsale_status: _",
-                                                                  s.price * tup.usd_price                  as price_usd,
-                                                                  s.price,
-                                                                  tup.token
+                                                                           then 'expired' else s.state end as "forsale_status: _"
                                                            from nft_direct_sell s
                                                                     join offers_whitelist ow on ow.address = s.address
-                                                                    left join token_usd_prices tup on tup.token = s.price_token
                                                            where s.nft = n.address
                                                              and s.state in ('active', 'expired')
                                                            limit 1 ) sale on true
+                                       left join lateral nft_floor(n.address) floor on true
                               where not n.burned
                                 and n.address = $1 )
-            select n.address           as "address?",
-                   n.collection        as "collection?",
-                   n.owner             as "owner?",
-                   n.manager           as "manager?",
-                   n.name              as "name?",
-                   n.description       as "description?",
-                   n.burned            as "burned?",
-                   n.updated           as "updated?",
-                   n.tx_lt             as "tx_lt?",
-                   n.meta              as "meta?",
-                   n.auction           as "auction?",
+            select n.address                    as "address?",
+                   n.collection                 as "collection?",
+                   n.owner                      as "owner?",
+                   n.manager                    as "manager?",
+                   n.name                       as "name?",
+                   n.description                as "description?",
+                   n.burned                     as "burned?",
+                   n.updated                    as "updated?",
+                   n.tx_lt                      as "tx_lt?",
+                   n.meta                       as "meta?",
+                   n.auction                    as "auction?",
                    n."auction_status: _",
-                   n.forsale           as "forsale?",
+                   n.forsale                    as "forsale?",
                    n."forsale_status: _",
-                   n.best_offer        as "best_offer?",
-                   n.floor_price_usd   as "floor_price_usd?",
-                   n.deal_price_usd    as "deal_price_usd?",
-                   n.floor_price       as "floor_price?",
-                   n.floor_price_token as "floor_price_token?",
-                   n.nft_id            as "nft_id?",
-                   1::bigint           as "total_count!"
+                   n.best_offer                 as "best_offer?",
+                   n.floor_price_usd            as "floor_price_usd?",
+                   n.deal_price_usd             as "deal_price_usd?",
+                   n.floor_price                as "floor_price?",
+                   n.floor_price_token          as "floor_price_token?",
+                   n.nft_id                     as "nft_id?",
+                   nft_meta_override(n.address) as "meta_override?",
+                   1::bigint                    as "total_count!"
             from details n;
             "#,
             address
@@ -182,8 +129,35 @@ impl Queries {
         sqlx::query_as!(
             NftDetails,
             r#"
-            select n.*, 1::bigint as "total_count!"
+            select n.address                    as "address?",
+                   n.collection                 as "collection?",
+                   n.owner                      as "owner?",
+                   n.manager                    as "manager?",
+                   n.name                       as "name?",
+                   n.description                as "description?",
+                   n.burned                     as "burned?",
+                   n.updated                    as "updated?",
+                   n.tx_lt                      as "tx_lt?",
+                   n.meta                       as "meta?",
+                   n.auction                    as "auction?",
+                   n."auction_status: _",
+                   n.forsale                    as "forsale?",
+                   n."forsale_status: _",
+                   nft_best_offer(n.address)    as "best_offer?",
+                   floor.price_usd              as "floor_price_usd?",
+                   last_deal.price_usd          as "deal_price_usd?",
+                   floor.price                  as "floor_price?",
+                   floor.token                  as "floor_price_token?",
+                   n.nft_id                     as "nft_id?",
+                   nft_meta_override(n.address) as "meta_override?",
+                   1::bigint                    as "total_count!"
             from nft_details n
+                     left join lateral nft_floor(n.address) floor on true
+                     left join lateral ( select d.price_usd
+                                         from nft_price_history_usd d
+                                         where d.nft = n.address
+                                         order by d.ts desc
+                                         limit 1 ) last_deal on true
             where n.address = any ($1)
             "#,
             ids
@@ -192,16 +166,105 @@ impl Queries {
         .await
     }
 
+    /// A page of nfts per owner, in one round trip. Hidden, burned and unverified nfts are skipped
+    pub async fn list_owners_nfts(
+        &self,
+        owners: &[Address],
+        collections: &[Address],
+        forsale: bool,
+        auction: bool,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDetails>> {
+        sqlx::query_as!(
+            NftDetails,
+            r#"
+            select n.*
+            from unnest($1::varchar[]) as k(owner)
+                     cross join lateral (
+                select n.address                    as "address?",
+                       n.collection                 as "collection?",
+                       n.owner                      as "owner?",
+                       n.manager                    as "manager?",
+                       n.name                       as "name?",
+                       n.description                as "description?",
+                       n.burned                     as "burned?",
+                       n.updated                    as "updated?",
+                       n.tx_lt                      as "tx_lt?",
+                       n.meta                       as "meta?",
+                       n.auction                    as "auction?",
+                       n."auction_status: _",
+                       n.forsale                    as "forsale?",
+                       n."forsale_status: _",
+                       nft_best_offer(n.address)    as "best_offer?",
+                       floor.price_usd              as "floor_price_usd?",
+                       last_deal.price_usd          as "deal_price_usd?",
+                       floor.price                  as "floor_price?",
+                       floor.token                  as "floor_price_token?",
+                       n.nft_id                     as "nft_id?",
+                       nft_meta_override(n.address) as "meta_override?",
+                       count(1) over ()             as "total_count!"
+                from nft_verified_mv nvm
+                         join nft_details n on n.address = nvm.address
+                         left join lateral nft_floor(n.address) floor on true
+                         left join lateral ( select d.price_usd
+                                             from nft_price_history_usd d
+                                             where d.nft = n.address
+                                             order by d.ts desc
+                                             limit 1 ) last_deal on true
+                where nvm.owner = k.owner
+                  and (nvm.collection = any ($2) or array_length($2::varchar[], 1) is null)
+                  and not nvm.burned
+                  and not moderation_hidden(nvm.address, nvm.collection)
+                  and (not $3 or n.forsale is not null)
+                  and (not $4 or n.auction is not null)
+                order by n.name, n.address
+                limit $5 offset $6
+                ) n
+            "#,
+            owners,
+            collections,
+            forsale,
+            auction,
+            limit as i64,
+            offset as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+
     pub async fn nft_top_search(
         &self,
         from: NaiveDateTime,
         limit: i64,
         offset: i64,
+        include_hidden: bool,
     ) -> sqlx::Result<Vec<NftDetails>> {
         sqlx::query_as!(
             NftDetails,
             r#"
-                select n.*, count(1) over () as "total_count!"
+                select n.address                    as "address?",
+                       n.collection                 as "collection?",
+                       n.owner                      as "owner?",
+                       n.manager                    as "manager?",
+                       n.name                       as "name?",
+                       n.description                as "description?",
+                       n.burned                     as "burned?",
+                       n.updated                    as "updated?",
+                       n.tx_lt                      as "tx_lt?",
+                       n.meta                       as "meta?",
+                       n.auction                    as "auction?",
+                       n."auction_status: _",
+                       n.forsale                    as "forsale?",
+                       n."forsale_status: _",
+                       nft_best_offer(n.address)    as "best_offer?",
+                       floor.price_usd              as "floor_price_usd?",
+                       last_deal.price_usd          as "deal_price_usd?",
+                       floor.price                  as "floor_price?",
+                       floor.token                  as "floor_price_token?",
+                       n.nft_id                     as "nft_id?",
+                       nft_meta_override(n.address) as "meta_override?",
+                       count(1) over ()             as "total_count!"
                 from (
                          select *
                          from nft_verified_mv nvm
@@ -214,14 +277,22 @@ impl Queries {
                              ) offers on true
                          where nvm.updated > $1
                            and offers.cnt > 0
+                           and ($4::bool or not moderation_hidden(nvm.address, nvm.collection))
                          order by offers.cnt desc, nvm.updated desc, nvm.address desc
                          limit $2 offset $3) ag
                          join nft_details n
                               on ag.address = n.address
+                         left join lateral nft_floor(n.address) floor on true
+                         left join lateral ( select d.price_usd
+                                             from nft_price_history_usd d
+                                             where d.nft = n.address
+                                             order by d.ts desc
+                                             limit 1 ) last_deal on true
             "#,
             from,
             limit,
-            offset
+            offset,
+            include_hidden
         )
         .fetch_all(self.db.as_ref())
         .await
@@ -240,6 +311,8 @@ impl Queries {
         _attributes: &[AttributeFilter],
         order: Option<NFTListOrder>,
         with_count: bool,
+        include_hidden: bool,
+        filters: &NftFilters,
     ) -> sqlx::Result<Vec<NftDetails>> {
         let sql: &str = include_str!("../sql/nfts.sql");
         let forsale = forsale.unwrap_or(false);
@@ -341,6 +414,17 @@ impl Queries {
             .bind(offset as i64)
             .bind(with_count)
             .bind(with_optimized)
+            .bind(include_hidden)
+            .bind(
+                (!filters.media.is_empty())
+                    .then(|| filters.media.iter().map(|m| m.as_str()).collect::<Vec<_>>()),
+            )
+            .bind(filters.text_query())
+            .bind(filters.minted_from)
+            .bind(filters.minted_to)
+            .bind(filters.nft_id_from.map(BigDecimal::from))
+            .bind(filters.nft_id_to.map(BigDecimal::from))
+            .bind(filters.manager.as_ref())
             .fetch_all(self.db.as_ref())
             .await
 
@@ -370,36 +454,59 @@ impl Queries {
     }
 
     pub async fn get_traits(&self, nft: &Address) -> sqlx::Result<Vec<NftTraitRecord>> {
-        sqlx::query_as!(
-            NftTraitRecord,
+        let traits = self.collect_traits(std::slice::from_ref(nft)).await?;
+        Ok(traits.into_iter().map(|(_, t)| t).collect())
+    }
+
+    /// Traits of several nfts with the number of nfts sharing each of them in the collection
+    pub async fn collect_traits(
+        &self,
+        nfts: &[Address],
+    ) -> sqlx::Result<Vec<(Address, NftTraitRecord)>> {
+        let rows = sqlx::query!(
             r#"
-            with nft_attributes as ( select jsonb_array_elements(nm.meta -> 'attributes') -> 'trait_type' as trait_type,
-                                            jsonb_array_elements(nm.meta -> 'attributes') -> 'value'      as trait_value,
-                                            nm.meta,
-                                            n.collection                                                  as nft_collection,
-                                            nm.nft
-                                     from nft_metadata nm
-                                              join nft n on n.address = nm.nft
-                                     where nm.meta -> 'attributes' is not null
-                                       and nm.nft = $1 ),
-                 nft_attributes_col as ( select jsonb_array_elements(nm.meta -> 'attributes') -> 'trait_type' as trait_type,
+            select k.nft as "nft!", t.trait_type, t.trait_value, t.cnt as "cnt!"
+            from unnest($1::varchar[]) as k(nft)
+                     cross join lateral (
+                with nft_attributes as ( select jsonb_array_elements(nm.meta -> 'attributes') -> 'trait_type' as trait_type,
                                                 jsonb_array_elements(nm.meta -> 'attributes') -> 'value'      as trait_value,
+                                                nm.meta,
+                                                n.collection                                                  as nft_collection,
                                                 nm.nft
                                          from nft_metadata nm
-                                         where nm.nft in ( select n2.address
-                                                           from nft n2
-                                                                    join nft n3 on n3.address = $1 and n2.collection = n3.collection ) )
-            select (na.trait_type #>> '{}')::text  as trait_type,
-                   (na.trait_value #>> '{}')::text as trait_value,
-                   count(*)                        as "cnt!"
-            from nft_attributes na
-                     left join nft_attributes_col na2 on na.trait_type = na2.trait_type and na.trait_value = na2.trait_value
-            group by na.trait_type, na.trait_value
+                                                  join nft n on n.address = nm.nft
+                                         where nm.meta -> 'attributes' is not null
+                                           and nm.nft = k.nft ),
+                     nft_attributes_col as ( select jsonb_array_elements(nm.meta -> 'attributes') -> 'trait_type' as trait_type,
+                                                    jsonb_array_elements(nm.meta -> 'attributes') -> 'value'      as trait_value,
+                                                    nm.nft
+                                             from nft_metadata nm
+                                             where nm.nft in ( select n2.address
+                                                               from nft n2
+                                                                        join nft n3 on n3.address = k.nft and n2.collection = n3.collection ) )
+                select (na.trait_type #>> '{}')::text  as trait_type,
+                       (na.trait_value #>> '{}')::text as trait_value,
+                       count(*)                        as cnt
+                from nft_attributes na
+                         left join nft_attributes_col na2 on na.trait_type = na2.trait_type and na.trait_value = na2.trait_value
+                group by na.trait_type, na.trait_value
+                ) t
             "#,
-            nft
+            nfts
         )
-            .fetch_all(self.db.as_ref())
-            .await
+        .fetch_all(self.db.as_ref())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let record = NftTraitRecord {
+                    trait_type: r.trait_type,
+                    trait_value: r.trait_value,
+                    cnt: r.cnt,
+                };
+                (r.nft, record)
+            })
+            .collect())
     }
 
     pub async fn list_nft_price_history(
@@ -411,13 +518,13 @@ impl Queries {
         sqlx::query_as!(
             NftPrice,
             r#"
-            select ts, usd_price as "usd_price!"
-            from nft_price_history nph
-                     inner join offers_whitelist ow on ow.address = nph.source
-            where nft = $1
-              and ts between $2 and $3
-              and usd_price is not null
-            order by ts
+            select ag.ts as "ts!", ag.usd_price as "usd_price!"
+            from ( select d.ts, coalesce(d.price_usd, d.usd_price) as usd_price
+                   from nft_price_history_usd d
+                   where d.nft = $1
+                     and d.ts between $2 and $3 ) ag
+            where ag.usd_price is not null
+            order by ag.ts
             "#,
             nft,
             from,
@@ -431,6 +538,7 @@ impl Queries {
         &self,
         max_price: i64,
         limit: i32,
+        include_hidden: bool,
     ) -> sqlx::Result<Vec<NftDetails>> {
         sqlx::query_as(
             r#"
@@ -459,8 +567,8 @@ impl Queries {
                              join offers_whitelist ow on ow.address = s.address
                              left join token_usd_prices tup on tup.token = s.price_token
                     where s.price <= $1
-                      and s.state = 'active'::direct_sell_state
-                      and (s.expired_at = to_timestamp(0) or s.expired_at > now())
+                      and direct_sell_valid(s)
+                      and ($3::bool or not moderation_hidden(n.address, n.collection))
                     order by random()
                     limit $2
                 )
@@ -479,39 +587,31 @@ impl Queries {
                        n.auction_status::auction_status    as auction_status,
                        n.forsale           as forsale,
                        n.forsale_status::direct_sell_state    as forsale_status,
-                       best_offer.address  as best_offer,
+                       nft_best_offer(n.address) as best_offer,
                        n.floor_price_usd      floor_price_usd,
                        last_deal.last_price   deal_price_usd,
                        n.floor_price       as floor_price,
                        n.floor_price_token as floor_price_token,
                        n.id::text          as nft_id,
+                       nft_meta_override(n.address) as meta_override,
                        0::int8                   as total_count
                 from deals n
                          left join nft_metadata m on m.nft = n.address
-                         left join lateral ( SELECT s.address
-                                             FROM nft_direct_buy s
-                                                      JOIN offers_whitelist ow ON ow.address = s.address
-                                                      JOIN token_usd_prices tup ON tup.token = s.price_token
-                                             WHERE s.state = 'active'
-                                               AND s.nft = n.address
-                                             group by s.address, (s.price * tup.usd_price)
-                                             HAVING (s.price * tup.usd_price) = MAX(s.price * tup.usd_price)
-                                             LIMIT 1 ) best_offer on true
-
-
-                         left join lateral ( select nph.price * tup.usd_price as last_price
-                                             from nft_price_history nph
-                                                      join offers_whitelist ow on ow.address = nph.source
-                                                      join token_usd_prices tup on tup.token = nph.price_token
-                                             where nph.nft = n.address
-                                             order by nph.ts desc
+
+                         left join lateral ( select d.price_usd as last_price
+                                             from nft_price_history_usd d
+                                             where d.nft = n.address
+                                             order by d.ts desc
                                              limit 1 ) last_deal on true
 
 
-            "#
-        ).bind(max_price).bind(limit)
-            .fetch_all(self.db.as_ref())
-            .await
+            "#,
+        )
+        .bind(max_price)
+        .bind(limit)
+        .bind(include_hidden)
+        .fetch_all(self.db.as_ref())
+        .await
     }
 
     pub async fn nft_sell_count(&self, max_price: i64) -> sqlx::Result<Option<i64>> {
@@ -521,16 +621,15 @@ impl Queries {
             from nft n
                      join nft_collection c on n.collection = c.address
                      join nft_direct_sell nds on nds.nft = n.address and nds.created <= now() and
-                                                 (now() <= nds.expired_at or nds.expired_at = to_timestamp(0)) and
-                                                 nds.state = 'active' and nds.price <= $1::int8
+                                                 direct_sell_valid(nds) and nds.price <= $1::int8
                      join offers_whitelist ow on ow.address = nds.address
             where n.burned is false
               and c.verified is true
            "#,
             max_price
         )
-            .fetch_one(self.db.as_ref())
-            .await
+        .fetch_one(self.db.as_ref())
+        .await
     }
 
     pub async fn nft_attributes_dictionary(&self) -> sqlx::Result<Vec<TraitDef>> {
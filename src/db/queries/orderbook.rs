@@ -0,0 +1,68 @@
+use crate::db::queries::Queries;
+use crate::db::{Address, OrderBookRecord};
+
+impl Queries {
+    /// Whitelisted orders that can be filled, of nfts not hidden by moderation
+    pub async fn collection_orders(
+        &self,
+        collection: &Address,
+    ) -> sqlx::Result<Vec<OrderBookRecord>> {
+        sqlx::query_as!(
+            OrderBookRecord,
+            r#"
+            select a.address                 as "address!",
+                   false                     as "bid!",
+                   true                      as "auction!",
+                   a.price_token             as "price_token!",
+                   a.min_bid                 as "price!",
+                   a.min_bid * tup.usd_price as price_usd
+            from nft_auction a
+                     join nft n on n.address = a.nft
+                     join offers_whitelist ow on ow.address = a.address
+                     left join token_usd_prices tup on tup.token = a.price_token
+            where n.collection = $1
+              and not n.burned
+              and a.status = 'active'::auction_status
+              and (a.finished_at = to_timestamp(0) or a.finished_at > now()::timestamp)
+              and a.min_bid is not null
+              and a.price_token is not null
+              and not moderation_hidden(n.address, n.collection)
+
+            union all
+
+            select s.address,
+                   false                   as bid,
+                   false                   as auction,
+                   s.price_token,
+                   s.price,
+                   s.price * tup.usd_price as price_usd
+            from nft_direct_sell s
+                     join nft n on n.address = s.nft
+                     join offers_whitelist ow on ow.address = s.address
+                     left join token_usd_prices tup on tup.token = s.price_token
+            where n.collection = $1
+              and direct_sell_valid(s)
+              and not moderation_hidden(n.address, n.collection)
+
+            union all
+
+            select s.address,
+                   true                    as bid,
+                   false                   as auction,
+                   s.price_token,
+                   s.price,
+                   s.price * tup.usd_price as price_usd
+            from nft_direct_buy s
+                     join nft n on n.address = s.nft
+                     join offers_whitelist ow on ow.address = s.address
+                     left join token_usd_prices tup on tup.token = s.price_token
+            where n.collection = $1
+              and direct_buy_valid(s)
+              and not moderation_hidden(n.address, n.collection)
+            "#,
+            collection as _
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+}
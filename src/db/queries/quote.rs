@@ -0,0 +1,49 @@
+use crate::db::queries::Queries;
+use crate::db::{Address, RootFeeRecord, RootType};
+use sqlx::types::BigDecimal;
+
+impl Queries {
+    /// The collection fee when set, as in `get_owner_fee`, else the last default of each root
+    pub async fn root_fees(
+        &self,
+        collection: &Address,
+        root_code: &RootType,
+    ) -> sqlx::Result<Vec<RootFeeRecord>> {
+        sqlx::query_as!(
+            RootFeeRecord,
+            r#"
+            select r.address                                       as root,
+                   coalesce(nc.fee_numerator, d.fee_numerator)     as fee_numerator,
+                   coalesce(nc.fee_denominator, d.fee_denominator) as fee_denominator,
+                   nc.address is not null                          as "collection_fee!"
+            from roots r
+                     left join nft_collection nc
+                               on nc.address = $1 and nc.fee_numerator is not null and
+                                  nc.fee_denominator is not null
+                     left join lateral ( select (ne.args -> 'fee' -> 'numerator')::int   as fee_numerator,
+                                                (ne.args -> 'fee' -> 'denominator')::int as fee_denominator
+                                         from nft_events ne
+                                         where ne.event_type = 'market_fee_default_changed'
+                                           and ne.address = r.address
+                                         order by ne.created_at desc, ne.created_lt desc, ne.id desc
+                                         limit 1 ) as d on true
+            where r.code = $2::t_root_types
+              and (r.expiry_date is null or now()::timestamp < r.expiry_date)
+            order by r.address
+            "#,
+            collection as &Address,
+            root_code as &RootType
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+
+    pub async fn token_usd_price(&self, token: &Address) -> sqlx::Result<Option<BigDecimal>> {
+        sqlx::query_scalar!(
+            "select usd_price from token_usd_prices where token = $1",
+            token as &Address
+        )
+        .fetch_optional(self.db.as_ref())
+        .await
+    }
+}
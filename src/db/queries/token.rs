@@ -0,0 +1,34 @@
+use crate::db::queries::Queries;
+use crate::db::{Address, TokenUsdPrice};
+use crate::handlers::nft::PriceHistoryScale;
+use chrono::NaiveDateTime;
+
+impl Queries {
+    pub async fn list_token_price_history(
+        &self,
+        token: &Address,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        scale: Option<&PriceHistoryScale>,
+    ) -> sqlx::Result<Vec<TokenUsdPrice>> {
+        sqlx::query_as!(
+            TokenUsdPrice,
+            r#"
+            select ph.token                                                                  as "token!",
+                   avg(ph.usd_price)                                                         as "usd_price!",
+                   case when $4::text is null then ph.ts else date_trunc($4::text, ph.ts) end as "ts!"
+            from token_usd_prices_history ph
+            where ph.token = $1
+              and ph.ts between $2 and $3
+            group by ph.token, 3
+            order by 3
+            "#,
+            token,
+            from,
+            to,
+            scale.map(|s| s.to_string())
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+}
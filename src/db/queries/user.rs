@@ -1,5 +1,7 @@
+use crate::db::queries::write_audit::insert_write_audit;
 use crate::db::queries::Queries;
-use crate::db::{Address, UserRecord};
+use crate::db::{Address, UserRecord, WriteAction};
+use serde_json::json;
 
 impl Queries {
     pub async fn get_user_by_address(&self, address: &Address) -> sqlx::Result<Option<UserRecord>> {
@@ -27,6 +29,32 @@ impl Queries {
         .await
     }
 
+    pub async fn collect_users(&self, addresses: &[Address]) -> sqlx::Result<Vec<UserRecord>> {
+        sqlx::query_as!(
+            UserRecord,
+            r#"
+               select u.address,
+               u.logo_nft,
+               u.username,
+               u.bio,
+               u.twitter,
+               u.instagram,
+               u.facebook,
+               u.link,
+               u.email,
+               nm.meta -> 'preview' ->> 'source' as avatar_url
+                from users u
+                         left join nft n on n.address = u.logo_nft and n.owner = u.address
+                         left join nft_metadata nm on n.address = nm.nft
+                where u.address = any ($1)
+            "#,
+            addresses as _
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+
+    /// Writes the profile and records the change in `write_audit`
     #[allow(clippy::too_many_arguments)]
     pub async fn upsert_user(
         &self,
@@ -39,8 +67,29 @@ impl Queries {
         facebook: Option<String>,
         link: Option<String>,
         email: Option<String>,
+        ip: Option<&str>,
     ) -> sqlx::Result<()> {
-        let _ = sqlx::query!(
+        let mut tx = self.db.begin().await?;
+        let before = sqlx::query_scalar!(
+            r#"
+                select jsonb_build_object('logoNft', logo_nft,
+                                          'username', username,
+                                          'bio', bio,
+                                          'twitter', twitter,
+                                          'instagram', instagram,
+                                          'facebook', facebook,
+                                          'link', link,
+                                          'email', email) as "before!"
+                from users
+                where address = $1
+                for update
+            "#,
+            address as _
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        sqlx::query!(
             r#"
                 insert into users(address, logo_nft, username, bio, twitter, instagram, facebook, link, email)
                 values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
@@ -55,19 +104,40 @@ impl Queries {
                               email     = $9
 
             "#,
-            address as Address,
-            logo_nft as Option<Address>,
+            &address as &Address,
+            &logo_nft as &Option<Address>,
             username,
             bio,
             twitter,
             instagram,
             facebook,
             link,
-            email as Option<String>
+            &email as &Option<String>
         )
-        .execute(self.db.as_ref())
-        .await;
+        .execute(&mut tx)
+        .await?;
 
-        Ok(())
+        let after = json!({
+            "logoNft": logo_nft,
+            "username": username,
+            "bio": bio,
+            "twitter": twitter,
+            "instagram": instagram,
+            "facebook": facebook,
+            "link": link,
+            "email": email,
+        });
+        insert_write_audit(
+            &mut tx,
+            &address,
+            WriteAction::UserUpdated,
+            &address,
+            before,
+            after,
+            json!({}),
+            ip,
+        )
+        .await?;
+        tx.commit().await
     }
 }
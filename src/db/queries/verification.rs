@@ -0,0 +1,172 @@
+use crate::db::queries::admin::insert_admin_audit;
+use crate::db::queries::Queries;
+use crate::db::{AdminAction, VerificationRequestRecord, VerificationStatus};
+use serde_json::json;
+
+impl Queries {
+    /// `None` when the collection already has a pending request
+    pub async fn create_verification_request(
+        &self,
+        collection: &str,
+        applicant: &str,
+        description: &str,
+        links: &[String],
+    ) -> sqlx::Result<Option<VerificationRequestRecord>> {
+        sqlx::query_as!(
+            VerificationRequestRecord,
+            r#"
+            insert into verification_requests as r (collection, applicant, description, links)
+            values ($1, $2, $3, $4)
+            on conflict (collection) where status = 'pending' do nothing
+            returning r.id,
+                   r.collection,
+                   r.applicant,
+                   r.description,
+                   r.links,
+                   r.status         as "status: _",
+                   r.reason,
+                   r.reviewer,
+                   r.created_at,
+                   r.reviewed_at,
+                   1::bigint        as "total_count!"
+            "#,
+            collection as _,
+            applicant as _,
+            description,
+            json!(links)
+        )
+        .fetch_optional(self.db.as_ref())
+        .await
+    }
+
+    pub async fn list_verification_requests(
+        &self,
+        applicant: Option<&str>,
+        collection: Option<&str>,
+        status: Option<VerificationStatus>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<VerificationRequestRecord>> {
+        sqlx::query_as!(
+            VerificationRequestRecord,
+            r#"
+            select r.id,
+                   r.collection,
+                   r.applicant,
+                   r.description,
+                   r.links,
+                   r.status         as "status: _",
+                   r.reason,
+                   r.reviewer,
+                   r.created_at,
+                   r.reviewed_at,
+                   count(1) over () as "total_count!"
+            from verification_requests r
+            where ($1::varchar is null or r.applicant = $1)
+              and ($2::varchar is null or r.collection = $2)
+              and ($3::t_verification_status is null or r.status = $3)
+            order by r.id desc
+            limit $4 offset $5
+            "#,
+            applicant,
+            collection,
+            status as _,
+            limit as i64,
+            offset as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+
+    /// Closes a pending request, an approval also verifies the collection.
+    /// `None` when there is no pending request with this id.
+    pub async fn review_verification_request(
+        &self,
+        admin: &str,
+        id: i64,
+        approve: bool,
+        reason: Option<&str>,
+    ) -> sqlx::Result<Option<VerificationRequestRecord>> {
+        let status = if approve {
+            VerificationStatus::Approved
+        } else {
+            VerificationStatus::Rejected
+        };
+
+        let mut tx = self.db.begin().await?;
+        let request = sqlx::query_as!(
+            VerificationRequestRecord,
+            r#"
+            update verification_requests r
+            set status      = $2,
+                reason      = $3,
+                reviewer    = $4,
+                reviewed_at = now()::timestamp
+            where r.id = $1
+              and r.status = 'pending'
+            returning r.id,
+                   r.collection,
+                   r.applicant,
+                   r.description,
+                   r.links,
+                   r.status         as "status: _",
+                   r.reason,
+                   r.reviewer,
+                   r.created_at,
+                   r.reviewed_at,
+                   1::bigint        as "total_count!"
+            "#,
+            id,
+            status as _,
+            reason,
+            admin as _
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        let Some(request) = request else {
+            return Ok(None);
+        };
+
+        if approve {
+            let previous = sqlx::query_scalar!(
+                "select verified from nft_collection where address = $1 for update",
+                &request.collection as _
+            )
+            .fetch_optional(&mut tx)
+            .await?;
+            sqlx::query!(
+                "update nft_collection set verified = true where address = $1",
+                &request.collection as _
+            )
+            .execute(&mut tx)
+            .await?;
+            let details = json!({
+                "verified": true,
+                "previous": previous,
+                "request": id,
+                "comment": reason,
+            });
+            insert_admin_audit(
+                &mut tx,
+                admin,
+                AdminAction::CollectionVerified,
+                Some(&request.collection),
+                details,
+            )
+            .await?;
+        } else {
+            let details = json!({ "request": id, "comment": reason });
+            insert_admin_audit(
+                &mut tx,
+                admin,
+                AdminAction::VerificationRejected,
+                Some(&request.collection),
+                details,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(Some(request))
+    }
+}
@@ -0,0 +1,161 @@
+use crate::db::queries::Queries;
+use crate::db::{WriteAction, WriteAuditRecord};
+use serde_json::{json, Value};
+use sqlx::{Executor, Postgres};
+
+impl Queries {
+    /// Restores the values written by a history entry of the collection,
+    /// `false` when the entry doesn't belong to it
+    pub async fn revert_collection_custom(
+        &self,
+        actor: &str,
+        address: &str,
+        id: i64,
+        ip: Option<&str>,
+    ) -> sqlx::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let version = sqlx::query_scalar!(
+            r#"
+            select after
+            from write_audit
+            where id = $1
+              and target = $2
+              and action in ('collection_custom_updated', 'collection_custom_reverted')
+            "#,
+            id,
+            address as _
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        let Some(after) = version else {
+            return Ok(false);
+        };
+
+        let before = collection_custom_snapshot(&mut tx, address).await?;
+        sqlx::query!(
+            r#"
+            update nft_collection_custom
+            set updated     = now(),
+                name        = $2::jsonb ->> 'name',
+                description = $2::jsonb ->> 'description',
+                wallpaper   = $2::jsonb ->> 'wallpaper',
+                logo        = $2::jsonb ->> 'logo',
+                social      = $2::jsonb -> 'social'
+            where address = $1
+            "#,
+            address as _,
+            after
+        )
+        .execute(&mut tx)
+        .await?;
+
+        insert_write_audit(
+            &mut tx,
+            actor,
+            WriteAction::CollectionCustomReverted,
+            address,
+            before,
+            after,
+            json!({ "revertedTo": id }),
+            ip,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn list_write_audit(
+        &self,
+        actor: Option<&str>,
+        target: Option<&str>,
+        action: Option<WriteAction>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<WriteAuditRecord>> {
+        sqlx::query_as!(
+            WriteAuditRecord,
+            r#"
+            select w.id,
+                   w.actor,
+                   w.action         as "action: _",
+                   w.target,
+                   w.before,
+                   w.after,
+                   w.details,
+                   w.ip,
+                   w.created_at,
+                   count(1) over () as "total_count!"
+            from write_audit w
+            where ($1::varchar is null or w.actor = $1)
+              and ($2::varchar is null or w.target = $2)
+              and ($3::t_write_action is null or w.action = $3)
+            order by w.id desc
+            limit $4 offset $5
+            "#,
+            actor,
+            target,
+            action as _,
+            limit as i64,
+            offset as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+    }
+}
+
+/// Values of `nft_collection_custom` kept in the history, `None` before the first write
+pub(super) async fn collection_custom_snapshot<'e, E>(
+    executor: E,
+    address: &str,
+) -> sqlx::Result<Option<Value>>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_scalar!(
+        r#"
+        select jsonb_build_object('name', name,
+                                  'description', description,
+                                  'wallpaper', wallpaper,
+                                  'logo', logo,
+                                  'social', social) as "snapshot!"
+        from nft_collection_custom
+        where address = $1
+        for update
+        "#,
+        address as _
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn insert_write_audit<'e, E>(
+    executor: E,
+    actor: &str,
+    action: WriteAction,
+    target: &str,
+    before: Option<Value>,
+    after: Value,
+    details: Value,
+    ip: Option<&str>,
+) -> sqlx::Result<()>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query!(
+        r#"
+        insert into write_audit (actor, action, target, before, after, details, ip)
+        values ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        actor as _,
+        action as _,
+        target as _,
+        before,
+        after,
+        details,
+        ip
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,1168 @@
+//! Data access used by the handlers, split per domain. `Queries` implements every trait
+//! on Postgres, `memory::InMemoryRepo` implements them for tests.
+#![allow(clippy::too_many_arguments, clippy::ptr_arg)]
+
+use crate::db::queries::Queries;
+use crate::db::*;
+use crate::token::TokenDict;
+use api_types::{
+    AttributeFilter, AuctionsSortOrder, CollectionListOrder, NFTListOrder, PriceHistoryScale,
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::types::BigDecimal;
+use std::time::Duration;
+
+/// Shared by every repository, handlers format prices with the token dictionary
+pub trait Repo: Clone + Send + Sync + 'static {
+    fn tokens(&self) -> &TokenDict;
+}
+
+#[async_trait]
+pub trait NftRepo: Repo {
+    async fn search_all(
+        &self,
+        query: &str,
+        entities: &[SearchEntity],
+        collections: Option<&[Address]>,
+        after: Option<(f64, &str)>,
+        limit: usize,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<SearchResult>>;
+    async fn suggest_entries(&self, max_nfts: usize) -> sqlx::Result<Vec<SuggestRecord>>;
+    async fn get_nft_details(&self, address: &String) -> sqlx::Result<Option<NftDetails>>;
+    async fn collect_nfts(&self, ids: &[String]) -> sqlx::Result<Vec<NftDetails>>;
+    async fn nft_top_search(
+        &self,
+        from: NaiveDateTime,
+        limit: i64,
+        offset: i64,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<NftDetails>>;
+    async fn nft_search(
+        &self,
+        owners: &[Address],
+        collections: &[Address],
+        forsale: Option<bool>,
+        auction: Option<bool>,
+        verified: Option<bool>,
+        limit: usize,
+        offset: usize,
+        attributes: &[AttributeFilter],
+        order: Option<NFTListOrder>,
+        with_count: bool,
+        include_hidden: bool,
+        filters: &NftFilters,
+    ) -> sqlx::Result<Vec<NftDetails>>;
+    async fn get_traits(&self, nft: &Address) -> sqlx::Result<Vec<NftTraitRecord>>;
+    async fn list_nft_price_history(
+        &self,
+        nft: &str,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<NftPrice>>;
+    async fn nft_random_buy(
+        &self,
+        max_price: i64,
+        limit: i32,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<NftDetails>>;
+    async fn nft_sell_count(&self, max_price: i64) -> sqlx::Result<Option<i64>>;
+}
+
+#[async_trait]
+pub trait CollectionRepo: Repo {
+    async fn get_collection(&self, address: &String) -> sqlx::Result<Option<NftCollectionDetails>>;
+    async fn collect_collections(&self, ids: &[String]) -> sqlx::Result<Vec<NftCollection>>;
+    async fn list_collections_by_owner(
+        &self,
+        owner: &String,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftCollection>>;
+    async fn list_collections(
+        &self,
+        name: Option<&String>,
+        owners: &[String],
+        verified: Option<&bool>,
+        collections: &[Address],
+        limit: usize,
+        offset: usize,
+        order: Option<CollectionListOrder>,
+    ) -> sqlx::Result<Vec<NftCollectionDetails>>;
+    async fn list_collections_simple(
+        &self,
+        name: Option<&String>,
+        verified: Option<&bool>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftCollectionSimple>>;
+    async fn upsert_collection_custom(
+        &self,
+        address: &Address,
+        owner: &String,
+        updated: NaiveDateTime,
+        name: Option<String>,
+        description: Option<String>,
+        wallpaper: Option<String>,
+        logo: Option<String>,
+        social: serde_json::Value,
+        ip: Option<&str>,
+    ) -> sqlx::Result<()>;
+    async fn validate_owner_of_collection(
+        &self,
+        address: &String,
+        owner: &String,
+    ) -> sqlx::Result<Option<i64>>;
+}
+
+/// Market contracts: auctions, direct sells, direct buys and their roots
+#[async_trait]
+pub trait AuctionRepo: Repo {
+    async fn list_roots(&self) -> sqlx::Result<Vec<RootRecord>>;
+    async fn get_owner_fee(
+        &self,
+        owner: &Address,
+        root_code: &RootType,
+    ) -> sqlx::Result<OwnerFeeRecord>;
+    async fn collect_auctions(&self, ids: &[String]) -> sqlx::Result<Vec<NftAuction>>;
+    async fn get_nft_auction(&self, address: &String) -> sqlx::Result<Option<NftAuction>>;
+    async fn get_nft_auction_last_bid(
+        &self,
+        auction: &String,
+    ) -> sqlx::Result<Option<NftAuctionBid>>;
+    async fn list_nft_auction_bids(
+        &self,
+        auction: &String,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuctionBid>>;
+    async fn list_nft_auctions(
+        &self,
+        owners: &[Address],
+        collections: &[Address],
+        tokens: &[Address],
+        sort: &AuctionsSortOrder,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuction>>;
+    async fn list_owner_auction_bids_out(
+        &self,
+        owner: &String,
+        collections: &[String],
+        lastbid: &Option<bool>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuctionBidExt>>;
+    async fn list_owner_auction_bids_in(
+        &self,
+        owner: &String,
+        collections: &[String],
+        lastbid: &Option<bool>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuctionBidExt>>;
+    async fn get_direct_sell(&self, address: &String) -> sqlx::Result<Option<NftDirectSell>>;
+    async fn collect_direct_sell(&self, ids: &[String]) -> sqlx::Result<Vec<NftDirectSell>>;
+    async fn list_owner_direct_sell(
+        &self,
+        owner: &String,
+        collections: &[String],
+        status: &[DirectSellState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectSell>>;
+    async fn collect_direct_buy(&self, ids: &[String]) -> sqlx::Result<Vec<NftDirectBuy>>;
+    async fn list_nft_direct_buy(
+        &self,
+        nft: &String,
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>>;
+    async fn list_owner_direct_buy(
+        &self,
+        owner: &String,
+        collections: &[String],
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>>;
+    async fn list_owner_direct_buy_in(
+        &self,
+        owner: &String,
+        collections: &[String],
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>>;
+}
+
+#[async_trait]
+pub trait EventRepo: Repo {
+    async fn list_events(
+        &self,
+        nft: Option<&String>,
+        collections: &[String],
+        owner: Option<&String>,
+        event_type: &[NftEventType],
+        offset: usize,
+        limit: usize,
+        with_count: bool,
+        verified: Option<bool>,
+        include_hidden: bool,
+    ) -> sqlx::Result<NftEventsRecord>;
+    async fn get_metrics_summary(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<MetricsSummaryRecord>>;
+}
+
+#[async_trait]
+pub trait UserRepo: Repo {
+    async fn get_user_by_address(&self, address: &Address) -> sqlx::Result<Option<UserRecord>>;
+    async fn collect_users(&self, addresses: &[Address]) -> sqlx::Result<Vec<UserRecord>>;
+    async fn upsert_user(
+        &self,
+        address: Address,
+        bio: Option<String>,
+        username: Option<String>,
+        logo_nft: Option<Address>,
+        twitter: Option<String>,
+        instagram: Option<String>,
+        facebook: Option<String>,
+        link: Option<String>,
+        email: Option<String>,
+        ip: Option<&str>,
+    ) -> sqlx::Result<()>;
+}
+
+#[async_trait]
+pub trait PriceRepo: Repo {
+    async fn list_token_price_history(
+        &self,
+        token: &Address,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        scale: Option<&PriceHistoryScale>,
+    ) -> sqlx::Result<Vec<TokenUsdPrice>>;
+}
+
+/// Verification and whitelist changes, every write is recorded in `admin_audit`
+#[async_trait]
+pub trait AdminRepo: Repo {
+    async fn set_collection_verified(
+        &self,
+        admin: &str,
+        collection: &str,
+        verified: bool,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<bool>>;
+    async fn upsert_root(
+        &self,
+        admin: &str,
+        address: &str,
+        code: RootType,
+        expiry_date: Option<NaiveDateTime>,
+        comment: Option<&str>,
+    ) -> sqlx::Result<()>;
+    async fn remove_root(
+        &self,
+        admin: &str,
+        address: &str,
+        comment: Option<&str>,
+    ) -> sqlx::Result<bool>;
+    async fn refresh_nft_verified(&self, admin: &str) -> sqlx::Result<Duration>;
+    async fn list_admin_audit(
+        &self,
+        admin: Option<&str>,
+        target: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<AdminAuditRecord>>;
+}
+
+/// Applications of collection owners for verification, reviewed by admins
+#[async_trait]
+pub trait VerificationRepo: Repo {
+    async fn create_verification_request(
+        &self,
+        collection: &str,
+        applicant: &str,
+        description: &str,
+        links: &[String],
+    ) -> sqlx::Result<Option<VerificationRequestRecord>>;
+    async fn list_verification_requests(
+        &self,
+        applicant: Option<&str>,
+        collection: Option<&str>,
+        status: Option<VerificationStatus>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<VerificationRequestRecord>>;
+    async fn review_verification_request(
+        &self,
+        admin: &str,
+        id: i64,
+        approve: bool,
+        reason: Option<&str>,
+    ) -> sqlx::Result<Option<VerificationRequestRecord>>;
+}
+
+/// User reports and the moderation status of nfts and collections, admin writes are audited
+#[async_trait]
+pub trait ModerationRepo: Repo {
+    async fn create_report(
+        &self,
+        reporter: &str,
+        target_type: ModerationTarget,
+        target: &str,
+        reason: ReportReason,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<ReportRecord>>;
+    async fn list_reports(
+        &self,
+        status: Option<ReportStatus>,
+        target_type: Option<ModerationTarget>,
+        target: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<ReportRecord>>;
+    async fn set_moderation(
+        &self,
+        admin: &str,
+        target_type: ModerationTarget,
+        target: &str,
+        status: ModerationStatus,
+        reason: Option<&str>,
+    ) -> sqlx::Result<Option<u64>>;
+    async fn dismiss_report(
+        &self,
+        admin: &str,
+        id: i64,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<ReportRecord>>;
+    async fn list_moderation(
+        &self,
+        status: Option<ModerationStatus>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<ModerationRecord>>;
+}
+
+/// History of the writes made with user tokens, see `write_audit`
+#[async_trait]
+pub trait WriteAuditRepo: Repo {
+    async fn revert_collection_custom(
+        &self,
+        actor: &str,
+        address: &str,
+        id: i64,
+        ip: Option<&str>,
+    ) -> sqlx::Result<bool>;
+    async fn list_write_audit(
+        &self,
+        actor: Option<&str>,
+        target: Option<&str>,
+        action: Option<WriteAction>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<WriteAuditRecord>>;
+}
+
+/// Metadata refresh jobs, picked up by the indexer
+#[async_trait]
+pub trait MetadataRefreshRepo: Repo {
+    /// Most recent job of the nft
+    async fn latest_metadata_refresh(
+        &self,
+        nft: &str,
+    ) -> sqlx::Result<Option<MetadataRefreshRecord>>;
+    /// `None` when the nft is unknown or already has an open job
+    async fn enqueue_metadata_refresh(
+        &self,
+        nft: &str,
+        requested_by: &str,
+    ) -> sqlx::Result<Option<MetadataRefreshRecord>>;
+    /// Every nft of the collection without an open job or a job younger than `cooldown`
+    async fn enqueue_collection_metadata_refresh(
+        &self,
+        collection: &str,
+        requested_by: &str,
+        cooldown: Duration,
+    ) -> sqlx::Result<MetadataRefreshBatchRecord>;
+    /// Nfts with a job younger than `window` whose metadata was written since the request,
+    /// and after `since` when given
+    async fn changed_metadata(
+        &self,
+        since: Option<NaiveDateTime>,
+        window: Duration,
+    ) -> sqlx::Result<Vec<ChangedMetadataRecord>>;
+}
+
+/// Per nft overrides of the metadata, written by the collection owner
+#[async_trait]
+pub trait MetadataOverrideRepo: Repo {
+    async fn list_metadata_overrides(
+        &self,
+        collection: &str,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftMetadataOverrideRecord>>;
+    /// Replaces the overrides of the nfts of the collection, returns the nfts written
+    async fn write_metadata_overrides(
+        &self,
+        collection: &str,
+        actor: &str,
+        items: &[NftMetadataOverrideWrite],
+        ip: Option<&str>,
+    ) -> sqlx::Result<Vec<Address>>;
+    /// `false` when the nft had no override in the collection
+    async fn remove_metadata_override(
+        &self,
+        collection: &str,
+        nft: &str,
+        actor: &str,
+        ip: Option<&str>,
+    ) -> sqlx::Result<bool>;
+}
+
+#[async_trait]
+pub trait MetadataReportRepo: Repo {
+    /// Nfts of the collection that aren't burned, by address, the first `limit` ones after `after`
+    async fn collection_nft_meta(
+        &self,
+        collection: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> sqlx::Result<Vec<NftMetaRecord>>;
+}
+
+#[async_trait]
+pub trait QuoteRepo: Repo {
+    /// Roots of the type still in use, by address
+    async fn root_fees(
+        &self,
+        collection: &Address,
+        root_code: &RootType,
+    ) -> sqlx::Result<Vec<RootFeeRecord>>;
+    async fn token_usd_price(&self, token: &Address) -> sqlx::Result<Option<BigDecimal>>;
+}
+
+#[async_trait]
+pub trait OrderBookRepo: Repo {
+    async fn collection_orders(&self, collection: &Address) -> sqlx::Result<Vec<OrderBookRecord>>;
+}
+
+/// Every repository, what the routes of all handler modules are built from
+pub trait ApiRepo:
+    NftRepo
+    + CollectionRepo
+    + AuctionRepo
+    + EventRepo
+    + UserRepo
+    + PriceRepo
+    + AdminRepo
+    + VerificationRepo
+    + ModerationRepo
+    + WriteAuditRepo
+    + MetadataRefreshRepo
+    + MetadataOverrideRepo
+    + MetadataReportRepo
+    + QuoteRepo
+    + OrderBookRepo
+{
+}
+
+impl<T> ApiRepo for T where
+    T: NftRepo
+        + CollectionRepo
+        + AuctionRepo
+        + EventRepo
+        + UserRepo
+        + PriceRepo
+        + AdminRepo
+        + VerificationRepo
+        + ModerationRepo
+        + WriteAuditRepo
+        + MetadataRefreshRepo
+        + MetadataOverrideRepo
+        + MetadataReportRepo
+        + QuoteRepo
+        + OrderBookRepo
+{
+}
+
+impl Repo for Queries {
+    fn tokens(&self) -> &TokenDict {
+        &self.tokens
+    }
+}
+
+#[async_trait]
+impl NftRepo for Queries {
+    async fn search_all(
+        &self,
+        query: &str,
+        entities: &[SearchEntity],
+        collections: Option<&[Address]>,
+        after: Option<(f64, &str)>,
+        limit: usize,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<SearchResult>> {
+        Queries::search_all(
+            self,
+            query,
+            entities,
+            collections,
+            after,
+            limit,
+            include_hidden,
+        )
+        .await
+    }
+
+    async fn suggest_entries(&self, max_nfts: usize) -> sqlx::Result<Vec<SuggestRecord>> {
+        Queries::suggest_entries(self, max_nfts).await
+    }
+
+    async fn get_nft_details(&self, address: &String) -> sqlx::Result<Option<NftDetails>> {
+        Queries::get_nft_details(self, address).await
+    }
+
+    async fn collect_nfts(&self, ids: &[String]) -> sqlx::Result<Vec<NftDetails>> {
+        Queries::collect_nfts(self, ids).await
+    }
+
+    async fn nft_top_search(
+        &self,
+        from: NaiveDateTime,
+        limit: i64,
+        offset: i64,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<NftDetails>> {
+        Queries::nft_top_search(self, from, limit, offset, include_hidden).await
+    }
+
+    async fn nft_search(
+        &self,
+        owners: &[Address],
+        collections: &[Address],
+        forsale: Option<bool>,
+        auction: Option<bool>,
+        verified: Option<bool>,
+        limit: usize,
+        offset: usize,
+        attributes: &[AttributeFilter],
+        order: Option<NFTListOrder>,
+        with_count: bool,
+        include_hidden: bool,
+        filters: &NftFilters,
+    ) -> sqlx::Result<Vec<NftDetails>> {
+        Queries::nft_search(
+            self,
+            owners,
+            collections,
+            forsale,
+            auction,
+            verified,
+            limit,
+            offset,
+            attributes,
+            order,
+            with_count,
+            include_hidden,
+            filters,
+        )
+        .await
+    }
+
+    async fn get_traits(&self, nft: &Address) -> sqlx::Result<Vec<NftTraitRecord>> {
+        Queries::get_traits(self, nft).await
+    }
+
+    async fn list_nft_price_history(
+        &self,
+        nft: &str,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<NftPrice>> {
+        Queries::list_nft_price_history(self, nft, from, to).await
+    }
+
+    async fn nft_random_buy(
+        &self,
+        max_price: i64,
+        limit: i32,
+        include_hidden: bool,
+    ) -> sqlx::Result<Vec<NftDetails>> {
+        Queries::nft_random_buy(self, max_price, limit, include_hidden).await
+    }
+
+    async fn nft_sell_count(&self, max_price: i64) -> sqlx::Result<Option<i64>> {
+        Queries::nft_sell_count(self, max_price).await
+    }
+}
+
+#[async_trait]
+impl CollectionRepo for Queries {
+    async fn get_collection(&self, address: &String) -> sqlx::Result<Option<NftCollectionDetails>> {
+        Queries::get_collection(self, address).await
+    }
+
+    async fn collect_collections(&self, ids: &[String]) -> sqlx::Result<Vec<NftCollection>> {
+        Queries::collect_collections(self, ids).await
+    }
+
+    async fn list_collections_by_owner(
+        &self,
+        owner: &String,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftCollection>> {
+        Queries::list_collections_by_owner(self, owner, limit, offset).await
+    }
+
+    async fn list_collections(
+        &self,
+        name: Option<&String>,
+        owners: &[String],
+        verified: Option<&bool>,
+        collections: &[Address],
+        limit: usize,
+        offset: usize,
+        order: Option<CollectionListOrder>,
+    ) -> sqlx::Result<Vec<NftCollectionDetails>> {
+        Queries::list_collections(
+            self,
+            name,
+            owners,
+            verified,
+            collections,
+            limit,
+            offset,
+            order,
+        )
+        .await
+    }
+
+    async fn list_collections_simple(
+        &self,
+        name: Option<&String>,
+        verified: Option<&bool>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftCollectionSimple>> {
+        Queries::list_collections_simple(self, name, verified, limit, offset).await
+    }
+
+    async fn upsert_collection_custom(
+        &self,
+        address: &Address,
+        owner: &String,
+        updated: NaiveDateTime,
+        name: Option<String>,
+        description: Option<String>,
+        wallpaper: Option<String>,
+        logo: Option<String>,
+        social: serde_json::Value,
+        ip: Option<&str>,
+    ) -> sqlx::Result<()> {
+        Queries::upsert_collection_custom(
+            self,
+            address,
+            owner,
+            updated,
+            name,
+            description,
+            wallpaper,
+            logo,
+            social,
+            ip,
+        )
+        .await
+    }
+
+    async fn validate_owner_of_collection(
+        &self,
+        address: &String,
+        owner: &String,
+    ) -> sqlx::Result<Option<i64>> {
+        Queries::validate_owner_of_collection(self, address, owner).await
+    }
+}
+
+#[async_trait]
+impl AuctionRepo for Queries {
+    async fn list_roots(&self) -> sqlx::Result<Vec<RootRecord>> {
+        Queries::list_roots(self).await
+    }
+
+    async fn get_owner_fee(
+        &self,
+        owner: &Address,
+        root_code: &RootType,
+    ) -> sqlx::Result<OwnerFeeRecord> {
+        Queries::get_owner_fee(self, owner, root_code).await
+    }
+
+    async fn collect_auctions(&self, ids: &[String]) -> sqlx::Result<Vec<NftAuction>> {
+        Queries::collect_auctions(self, ids).await
+    }
+
+    async fn get_nft_auction(&self, address: &String) -> sqlx::Result<Option<NftAuction>> {
+        Queries::get_nft_auction(self, address).await
+    }
+
+    async fn get_nft_auction_last_bid(
+        &self,
+        auction: &String,
+    ) -> sqlx::Result<Option<NftAuctionBid>> {
+        Queries::get_nft_auction_last_bid(self, auction).await
+    }
+
+    async fn list_nft_auction_bids(
+        &self,
+        auction: &String,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuctionBid>> {
+        Queries::list_nft_auction_bids(self, auction, limit, offset).await
+    }
+
+    async fn list_nft_auctions(
+        &self,
+        owners: &[Address],
+        collections: &[Address],
+        tokens: &[Address],
+        sort: &AuctionsSortOrder,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuction>> {
+        Queries::list_nft_auctions(self, owners, collections, tokens, sort, limit, offset).await
+    }
+
+    async fn list_owner_auction_bids_out(
+        &self,
+        owner: &String,
+        collections: &[String],
+        lastbid: &Option<bool>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuctionBidExt>> {
+        Queries::list_owner_auction_bids_out(self, owner, collections, lastbid, limit, offset).await
+    }
+
+    async fn list_owner_auction_bids_in(
+        &self,
+        owner: &String,
+        collections: &[String],
+        lastbid: &Option<bool>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftAuctionBidExt>> {
+        Queries::list_owner_auction_bids_in(self, owner, collections, lastbid, limit, offset).await
+    }
+
+    async fn get_direct_sell(&self, address: &String) -> sqlx::Result<Option<NftDirectSell>> {
+        Queries::get_direct_sell(self, address).await
+    }
+
+    async fn collect_direct_sell(&self, ids: &[String]) -> sqlx::Result<Vec<NftDirectSell>> {
+        Queries::collect_direct_sell(self, ids).await
+    }
+
+    async fn list_owner_direct_sell(
+        &self,
+        owner: &String,
+        collections: &[String],
+        status: &[DirectSellState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectSell>> {
+        Queries::list_owner_direct_sell(self, owner, collections, status, limit, offset).await
+    }
+
+    async fn collect_direct_buy(&self, ids: &[String]) -> sqlx::Result<Vec<NftDirectBuy>> {
+        Queries::collect_direct_buy(self, ids).await
+    }
+
+    async fn list_nft_direct_buy(
+        &self,
+        nft: &String,
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>> {
+        Queries::list_nft_direct_buy(self, nft, status, limit, offset).await
+    }
+
+    async fn list_owner_direct_buy(
+        &self,
+        owner: &String,
+        collections: &[String],
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>> {
+        Queries::list_owner_direct_buy(self, owner, collections, status, limit, offset).await
+    }
+
+    async fn list_owner_direct_buy_in(
+        &self,
+        owner: &String,
+        collections: &[String],
+        status: &[DirectBuyState],
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftDirectBuy>> {
+        Queries::list_owner_direct_buy_in(self, owner, collections, status, limit, offset).await
+    }
+}
+
+#[async_trait]
+impl EventRepo for Queries {
+    async fn list_events(
+        &self,
+        nft: Option<&String>,
+        collections: &[String],
+        owner: Option<&String>,
+        event_type: &[NftEventType],
+        offset: usize,
+        limit: usize,
+        with_count: bool,
+        verified: Option<bool>,
+        include_hidden: bool,
+    ) -> sqlx::Result<NftEventsRecord> {
+        Queries::list_events(
+            self,
+            nft,
+            collections,
+            owner,
+            event_type,
+            offset,
+            limit,
+            with_count,
+            verified,
+            include_hidden,
+        )
+        .await
+    }
+
+    async fn get_metrics_summary(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<MetricsSummaryRecord>> {
+        Queries::get_metrics_summary(self, from, to, limit, offset).await
+    }
+}
+
+#[async_trait]
+impl UserRepo for Queries {
+    async fn get_user_by_address(&self, address: &Address) -> sqlx::Result<Option<UserRecord>> {
+        Queries::get_user_by_address(self, address).await
+    }
+
+    async fn collect_users(&self, addresses: &[Address]) -> sqlx::Result<Vec<UserRecord>> {
+        Queries::collect_users(self, addresses).await
+    }
+
+    async fn upsert_user(
+        &self,
+        address: Address,
+        bio: Option<String>,
+        username: Option<String>,
+        logo_nft: Option<Address>,
+        twitter: Option<String>,
+        instagram: Option<String>,
+        facebook: Option<String>,
+        link: Option<String>,
+        email: Option<String>,
+        ip: Option<&str>,
+    ) -> sqlx::Result<()> {
+        Queries::upsert_user(
+            self, address, bio, username, logo_nft, twitter, instagram, facebook, link, email, ip,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl PriceRepo for Queries {
+    async fn list_token_price_history(
+        &self,
+        token: &Address,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        scale: Option<&PriceHistoryScale>,
+    ) -> sqlx::Result<Vec<TokenUsdPrice>> {
+        Queries::list_token_price_history(self, token, from, to, scale).await
+    }
+}
+
+#[async_trait]
+impl AdminRepo for Queries {
+    async fn set_collection_verified(
+        &self,
+        admin: &str,
+        collection: &str,
+        verified: bool,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<bool>> {
+        Queries::set_collection_verified(self, admin, collection, verified, comment).await
+    }
+
+    async fn upsert_root(
+        &self,
+        admin: &str,
+        address: &str,
+        code: RootType,
+        expiry_date: Option<NaiveDateTime>,
+        comment: Option<&str>,
+    ) -> sqlx::Result<()> {
+        Queries::upsert_root(self, admin, address, code, expiry_date, comment).await
+    }
+
+    async fn remove_root(
+        &self,
+        admin: &str,
+        address: &str,
+        comment: Option<&str>,
+    ) -> sqlx::Result<bool> {
+        Queries::remove_root(self, admin, address, comment).await
+    }
+
+    async fn refresh_nft_verified(&self, admin: &str) -> sqlx::Result<Duration> {
+        Queries::refresh_nft_verified(self, admin).await
+    }
+
+    async fn list_admin_audit(
+        &self,
+        admin: Option<&str>,
+        target: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<AdminAuditRecord>> {
+        Queries::list_admin_audit(self, admin, target, limit, offset).await
+    }
+}
+
+#[async_trait]
+impl VerificationRepo for Queries {
+    async fn create_verification_request(
+        &self,
+        collection: &str,
+        applicant: &str,
+        description: &str,
+        links: &[String],
+    ) -> sqlx::Result<Option<VerificationRequestRecord>> {
+        Queries::create_verification_request(self, collection, applicant, description, links).await
+    }
+
+    async fn list_verification_requests(
+        &self,
+        applicant: Option<&str>,
+        collection: Option<&str>,
+        status: Option<VerificationStatus>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<VerificationRequestRecord>> {
+        Queries::list_verification_requests(self, applicant, collection, status, limit, offset)
+            .await
+    }
+
+    async fn review_verification_request(
+        &self,
+        admin: &str,
+        id: i64,
+        approve: bool,
+        reason: Option<&str>,
+    ) -> sqlx::Result<Option<VerificationRequestRecord>> {
+        Queries::review_verification_request(self, admin, id, approve, reason).await
+    }
+}
+
+#[async_trait]
+impl ModerationRepo for Queries {
+    async fn create_report(
+        &self,
+        reporter: &str,
+        target_type: ModerationTarget,
+        target: &str,
+        reason: ReportReason,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<ReportRecord>> {
+        Queries::create_report(self, reporter, target_type, target, reason, comment).await
+    }
+
+    async fn list_reports(
+        &self,
+        status: Option<ReportStatus>,
+        target_type: Option<ModerationTarget>,
+        target: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<ReportRecord>> {
+        Queries::list_reports(self, status, target_type, target, limit, offset).await
+    }
+
+    async fn set_moderation(
+        &self,
+        admin: &str,
+        target_type: ModerationTarget,
+        target: &str,
+        status: ModerationStatus,
+        reason: Option<&str>,
+    ) -> sqlx::Result<Option<u64>> {
+        Queries::set_moderation(self, admin, target_type, target, status, reason).await
+    }
+
+    async fn dismiss_report(
+        &self,
+        admin: &str,
+        id: i64,
+        comment: Option<&str>,
+    ) -> sqlx::Result<Option<ReportRecord>> {
+        Queries::dismiss_report(self, admin, id, comment).await
+    }
+
+    async fn list_moderation(
+        &self,
+        status: Option<ModerationStatus>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<ModerationRecord>> {
+        Queries::list_moderation(self, status, limit, offset).await
+    }
+}
+
+#[async_trait]
+impl WriteAuditRepo for Queries {
+    async fn revert_collection_custom(
+        &self,
+        actor: &str,
+        address: &str,
+        id: i64,
+        ip: Option<&str>,
+    ) -> sqlx::Result<bool> {
+        Queries::revert_collection_custom(self, actor, address, id, ip).await
+    }
+
+    async fn list_write_audit(
+        &self,
+        actor: Option<&str>,
+        target: Option<&str>,
+        action: Option<WriteAction>,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<WriteAuditRecord>> {
+        Queries::list_write_audit(self, actor, target, action, limit, offset).await
+    }
+}
+
+#[async_trait]
+impl MetadataRefreshRepo for Queries {
+    async fn latest_metadata_refresh(
+        &self,
+        nft: &str,
+    ) -> sqlx::Result<Option<MetadataRefreshRecord>> {
+        Queries::latest_metadata_refresh(self, nft).await
+    }
+
+    async fn enqueue_metadata_refresh(
+        &self,
+        nft: &str,
+        requested_by: &str,
+    ) -> sqlx::Result<Option<MetadataRefreshRecord>> {
+        Queries::enqueue_metadata_refresh(self, nft, requested_by).await
+    }
+
+    async fn enqueue_collection_metadata_refresh(
+        &self,
+        collection: &str,
+        requested_by: &str,
+        cooldown: Duration,
+    ) -> sqlx::Result<MetadataRefreshBatchRecord> {
+        Queries::enqueue_collection_metadata_refresh(self, collection, requested_by, cooldown).await
+    }
+
+    async fn changed_metadata(
+        &self,
+        since: Option<NaiveDateTime>,
+        window: Duration,
+    ) -> sqlx::Result<Vec<ChangedMetadataRecord>> {
+        Queries::changed_metadata(self, since, window).await
+    }
+}
+
+#[async_trait]
+impl MetadataOverrideRepo for Queries {
+    async fn list_metadata_overrides(
+        &self,
+        collection: &str,
+        limit: usize,
+        offset: usize,
+    ) -> sqlx::Result<Vec<NftMetadataOverrideRecord>> {
+        Queries::list_metadata_overrides(self, collection, limit, offset).await
+    }
+
+    async fn write_metadata_overrides(
+        &self,
+        collection: &str,
+        actor: &str,
+        items: &[NftMetadataOverrideWrite],
+        ip: Option<&str>,
+    ) -> sqlx::Result<Vec<Address>> {
+        Queries::write_metadata_overrides(self, collection, actor, items, ip).await
+    }
+
+    async fn remove_metadata_override(
+        &self,
+        collection: &str,
+        nft: &str,
+        actor: &str,
+        ip: Option<&str>,
+    ) -> sqlx::Result<bool> {
+        Queries::remove_metadata_override(self, collection, nft, actor, ip).await
+    }
+}
+
+#[async_trait]
+impl MetadataReportRepo for Queries {
+    async fn collection_nft_meta(
+        &self,
+        collection: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> sqlx::Result<Vec<NftMetaRecord>> {
+        Queries::collection_nft_meta(self, collection, after, limit).await
+    }
+}
+
+#[async_trait]
+impl QuoteRepo for Queries {
+    async fn root_fees(
+        &self,
+        collection: &Address,
+        root_code: &RootType,
+    ) -> sqlx::Result<Vec<RootFeeRecord>> {
+        Queries::root_fees(self, collection, root_code).await
+    }
+
+    async fn token_usd_price(&self, token: &Address) -> sqlx::Result<Option<BigDecimal>> {
+        Queries::token_usd_price(self, token).await
+    }
+}
+
+#[async_trait]
+impl OrderBookRepo for Queries {
+    async fn collection_orders(&self, collection: &Address) -> sqlx::Result<Vec<OrderBookRecord>> {
+        Queries::collection_orders(self, collection).await
+    }
+}
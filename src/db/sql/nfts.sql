@@ -24,6 +24,17 @@ with nfts as (
       and not $4
       and ((nvm.collection = any ($2) or $2 = '{}') and (nvm.owner = any ($1) or $1 = '{}'))
       and not burned
+      and ($9::bool or not moderation_hidden(nvm.address, nvm.collection))
+      and ($10::text[] is null or exists(select 1 from nft_metadata md where md.nft = nvm.address and nft_media_kinds(md.meta) && $10))
+      and ($11::text is null or to_tsvector('simple', coalesce(nvm.name, '') || ' ' || coalesce(nvm.description, '')) @@ to_tsquery('simple', $11))
+      and (($12::int8 is null and $13::int8 is null) or exists(select 1
+                                                               from nft_events e
+                                                               where e.nft = nvm.address
+                                                                 and e.event_type = 'nft_created'
+                                                                 and e.created_at >= coalesce($12, 0)
+                                                                 and e.created_at <= coalesce($13, 9223372036854775807)))
+      and ($14::numeric is null or nvm.id >= $14) and ($15::numeric is null or nvm.id <= $15)
+      and ($16::text is null or nvm.manager = $16)
     order by nvm.name #NFTS_DIRECTION_BASE#, nvm.address
 ),
 
@@ -76,6 +87,17 @@ with nfts as (
                     and (a.finished_at = to_timestamp(0) or a.finished_at > now()::timestamp)
                     and ($1 = '{}' or n.owner = any ($1::text[]))
                     and ($2 = '{}' or n.collection = any ($2))
+                    and ($9::bool or not moderation_hidden(n.address, n.collection))
+                    and ($10::text[] is null or exists(select 1 from nft_metadata md where md.nft = n.address and nft_media_kinds(md.meta) && $10))
+                    and ($11::text is null or to_tsvector('simple', coalesce(n.name, '') || ' ' || coalesce(n.description, '')) @@ to_tsquery('simple', $11))
+                    and (($12::int8 is null and $13::int8 is null) or exists(select 1
+                                                                             from nft_events e
+                                                                             where e.nft = n.address
+                                                                               and e.event_type = 'nft_created'
+                                                                               and e.created_at >= coalesce($12, 0)
+                                                                               and e.created_at <= coalesce($13, 9223372036854775807)))
+                    and ($14::numeric is null or n.id >= $14) and ($15::numeric is null or n.id <= $15)
+                    and ($16::text is null or n.manager = $16)
 
 
                   union all
@@ -104,10 +126,20 @@ with nfts as (
                            join offers_whitelist ow on ow.address = s.address
                            left join token_usd_prices tup on tup.token = s.price_token
                   where ($4::bool or $8::bool)
-                    and s.state = 'active'::direct_sell_state
-                    and (s.expired_at = to_timestamp(0) or s.expired_at > now())
+                    and direct_sell_valid(s)
                     and ($1 = '{}' or n.owner = any ($1::text[]))
                     and ($2 = '{}' or n.collection = any ($2))
+                    and ($9::bool or not moderation_hidden(n.address, n.collection))
+                    and ($10::text[] is null or exists(select 1 from nft_metadata md where md.nft = n.address and nft_media_kinds(md.meta) && $10))
+                    and ($11::text is null or to_tsvector('simple', coalesce(n.name, '') || ' ' || coalesce(n.description, '')) @@ to_tsquery('simple', $11))
+                    and (($12::int8 is null and $13::int8 is null) or exists(select 1
+                                                                             from nft_events e
+                                                                             where e.nft = n.address
+                                                                               and e.event_type = 'nft_created'
+                                                                               and e.created_at >= coalesce($12, 0)
+                                                                               and e.created_at <= coalesce($13, 9223372036854775807)))
+                    and ($14::numeric is null or n.id >= $14) and ($15::numeric is null or n.id <= $15)
+                    and ($16::text is null or n.manager = $16)
               ) ag
 
          order by #DEALS_ORDER_FIELD# #ORDER_DIRECTION#
@@ -135,7 +167,7 @@ select n.address,
        coalesce(n.auction_status, auc.status)::auction_status              as auction_status,
        coalesce(n.forsale, sale.forsale)                                   as forsale,
        coalesce(n.forsale_status, sale.status)::direct_sell_state          as forsale_status,
-       best_offer.address                                                  as best_offer,
+       nft_best_offer(n.address)                                           as best_offer,
        coalesce(n.floor_price_usd, least(auc.price_usd, sale.price_usd))      floor_price_usd,
        last_deal.last_price                                                   deal_price_usd,
        coalesce(n.floor_price, case
@@ -149,19 +181,10 @@ select n.address,
                                              then sale.token::character varying
                                          else null::character varying end) as floor_price_token,
        n.id::text                                                          as nft_id,
+       nft_meta_override(n.address)                                        as meta_override,
        case when $7 then count(1) over () else 0 end                         total_count
 from res n
          left join nft_metadata m on m.nft = n.address
-         left join lateral ( SELECT s.address
-                             FROM nft_direct_buy s
-                                      JOIN offers_whitelist ow ON ow.address = s.address
-                                      JOIN token_usd_prices tup ON tup.token = s.price_token
-                             WHERE s.state = 'active'
-                               AND s.nft = n.address
-                             group by s.address, (s.price * tup.usd_price)
-                             HAVING (s.price * tup.usd_price) = MAX(s.price * tup.usd_price)
-                             LIMIT 1 ) best_offer on true
-
          left join lateral ( select a.address                 as auction,
                                     a.status                  as status,
                                     a.min_bid * tup.usd_price as price_usd,
@@ -186,16 +209,13 @@ from res n
                                       left join token_usd_prices tup on tup.token = s.price_token
                              where n.forsale is null
                                and s.nft = n.address
-                               and s.state = 'active'::direct_sell_state
-                               and (s.expired_at = to_timestamp(0) or s.expired_at > now())
+                               and direct_sell_valid(s)
                              limit 1 ) sale on true
 
-         left join lateral ( select nph.price * tup.usd_price as last_price
-                             from nft_price_history nph
-                                      join offers_whitelist ow on ow.address = nph.source
-                                      join token_usd_prices tup on tup.token = nph.price_token
-                             where nph.nft = n.address
-                             order by nph.ts desc
+         left join lateral ( select d.price_usd as last_price
+                             from nft_price_history_usd d
+                             where d.nft = n.address
+                             order by d.ts desc
                              limit 1 ) last_deal on true
 
 #ORDER_RESULT#
@@ -0,0 +1,109 @@
+-- $1 query, $2 object types, $3 collections, $4/$5 score and address of the cursor, $6 limit, $7 include hidden
+with q as ( select lower(trim($1::text))                                     as text,
+                   ( select to_tsquery('simple', string_agg(w || ':*', ' & '))
+                     from regexp_split_to_table(lower($1::text), '[^[:alnum:]]+') w
+                     where w <> '' )                                         as ts,
+                   case
+                       when ltrim(trim($1::text), '#') ~ '^[0-9]{1,30}$'
+                           then ltrim(trim($1::text), '#')::numeric end      as id ),
+     hits as ( select n.address,
+                      'nft'                                      as object_type,
+                      coalesce(mo.name, n.name::text)            as nft_name,
+                      c.name                                     as collection_name,
+                      coalesce(mo.preview, m.meta -> 'preview' ->> 'source') as image,
+                      n.collection,
+                      n.id::text                                 as nft_id,
+                      null::text                                 as username,
+                      case
+                          when n.address = q.text then 10
+                          when lower(n.name) = q.text then 5
+                          when n.id = q.id then 4
+                          else coalesce(ts_rank_cd(to_tsvector('simple', coalesce(n.name, '') || ' ' || coalesce(n.description, '')), q.ts), 0)
+                              + coalesce(greatest(word_similarity(q.text, lower(n.name)), similarity(q.text, lower(n.name))), 0)
+                          end                                    as score
+               from q,
+                    nft_verified_mv n
+                        join nft_collection c on c.address = n.collection
+                        left join nft_metadata m on m.nft = n.address
+                        left join nft_metadata_override mo on mo.nft = n.address
+               where 'nft' = any ($2::text[])
+                 and ($3::varchar[] is null or n.collection = any ($3::varchar[]))
+                 and not n.burned
+                 and ($7::bool or not moderation_hidden(n.address, n.collection))
+                 and (n.address = q.text
+                   or to_tsvector('simple', coalesce(n.name, '') || ' ' || coalesce(n.description, '')) @@ q.ts
+                   or q.text <% lower(n.name)
+                   or lower(n.name) % q.text
+                   or n.id = q.id)
+
+               union all
+
+               -- collections come first on equal matches, as they did with the old ladder
+               select c.address,
+                      'collection',
+                      null,
+                      c.name,
+                      c.logo,
+                      c.address,
+                      null,
+                      null,
+                      case
+                          when c.address = q.text then 20
+                          when lower(c.name) = q.text then 10
+                          else coalesce(ts_rank_cd(to_tsvector('simple', coalesce(c.name, '') || ' ' || coalesce(c.description, '')), q.ts), 0)
+                              + coalesce(greatest(word_similarity(q.text, lower(c.name)), similarity(q.text, lower(c.name))), 0) + 0.5
+                          end
+               from q,
+                    nft_collection c
+               where 'collection' = any ($2::text[])
+                 and ($3::varchar[] is null or c.address = any ($3::varchar[]))
+                 and c.verified
+                 and ($7::bool or not moderation_hidden(null, c.address))
+                 and (c.address = q.text
+                   or to_tsvector('simple', coalesce(c.name, '') || ' ' || coalesce(c.description, '')) @@ q.ts
+                   or q.text <% lower(c.name)
+                   or lower(c.name) % q.text)
+
+               union all
+
+               select u.address,
+                      'user',
+                      null,
+                      null,
+                      coalesce(mo.preview, nm.meta -> 'preview' ->> 'source'),
+                      null,
+                      null,
+                      u.username,
+                      case
+                          when u.address = q.text then 10
+                          when lower(u.username) = q.text then 5
+                          else coalesce(greatest(word_similarity(q.text, lower(u.username)), similarity(q.text, lower(u.username))), 0)
+                          end
+               from q,
+                    users u
+                        left join nft n on n.address = u.logo_nft and n.owner = u.address
+                        left join nft_metadata nm on nm.nft = n.address
+                        left join nft_metadata_override mo on mo.nft = n.address
+               where 'user' = any ($2::text[])
+                 and $3::varchar[] is null
+                 and (u.address = q.text
+                   or starts_with(lower(u.username), q.text)
+                   or q.text <% lower(u.username)
+                   or lower(u.username) % q.text) ),
+     ranked as ( select h.*, round(h.score::numeric, 6)::float8 as rank
+                 from hits h )
+select r.address,
+       r.object_type,
+       r.nft_name,
+       r.collection_name,
+       r.image,
+       r.collection,
+       r.nft_id,
+       r.username,
+       r.rank as score
+from ranked r
+where $4::float8 is null
+   or r.rank < $4::float8
+   or (r.rank = $4::float8 and r.address > $5::varchar)
+order by r.rank desc, r.address
+limit $6
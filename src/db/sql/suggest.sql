@@ -0,0 +1,49 @@
+-- names of /search/suggest, $1 max nfts. Weight is the owners of the collection, the nfts held by a user
+with owners as ( select n.collection, count(distinct n.owner) as cnt
+                 from nft_verified_mv n
+                 where not n.burned
+                 group by n.collection )
+select c.address,
+       'collection'               as object_type,
+       c.name,
+       c.logo                     as image,
+       c.address                  as collection,
+       coalesce(o.cnt, 0)::int8   as weight
+from nft_collection c
+         left join owners o on o.collection = c.address
+where c.verified
+  and coalesce(c.name, '') <> ''
+  and not moderation_hidden(null, c.address)
+
+union all
+
+( select n.address,
+         'nft',
+         coalesce(mo.name, n.name::text),
+         coalesce(mo.preview, m.meta -> 'preview' ->> 'source'),
+         n.collection,
+         coalesce(o.cnt, 0)::int8
+  from nft_verified_mv n
+           left join owners o on o.collection = n.collection
+           left join nft_metadata m on m.nft = n.address
+           left join nft_metadata_override mo on mo.nft = n.address
+  where not n.burned
+    and coalesce(mo.name, n.name, '') <> ''
+    and not moderation_hidden(n.address, n.collection)
+  order by o.cnt desc nulls last, n.address
+  limit $1 )
+
+union all
+
+select u.address,
+       'user',
+       u.username,
+       coalesce(mo.preview, nm.meta -> 'preview' ->> 'source'),
+       null,
+       ( select count(1) from nft h where h.owner = u.address and not h.burned )
+from users u
+         left join nft n on n.address = u.logo_nft and n.owner = u.address
+         left join nft_metadata nm on nm.nft = n.address
+         left join nft_metadata_override mo on mo.nft = n.address
+where coalesce(u.username, '') <> ''
+  and not moderation_hidden(u.address, null)
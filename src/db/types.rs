@@ -1,10 +1,8 @@
 use super::*;
+pub use api_types::{Address, MetaRoyalty, Social};
 use chrono::NaiveDateTime;
-use log::error;
 use serde::{Deserialize, Serialize};
 use sqlx::types::BigDecimal;
-use utoipa::ToSchema;
-pub type Address = String;
 
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct SearchResult {
@@ -13,6 +11,22 @@ pub struct SearchResult {
     pub nft_name: Option<String>,
     pub collection_name: Option<String>,
     pub image: Option<String>,
+    pub collection: Option<Address>,
+    pub nft_id: Option<String>,
+    pub username: Option<String>,
+    pub score: f64,
+}
+
+/// Name completed by `/search/suggest`
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SuggestRecord {
+    pub address: Address,
+    pub object_type: String,
+    pub name: String,
+    pub image: Option<String>,
+    pub collection: Option<Address>,
+    /// Owners of the collection, nfts held by a user
+    pub weight: i64,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -38,6 +52,47 @@ pub struct NftDetails {
     pub floor_price: Option<BigDecimal>,
     pub floor_price_token: Option<Address>,
     pub nft_id: Option<Address>,
+    /// `nft_meta_override`, merged over `meta`
+    pub meta_override: Option<serde_json::Value>,
+}
+
+/// Metadata filters of `/nfts`, `None` and empty lists match every nft
+#[derive(Debug, Clone, Default)]
+pub struct NftFilters {
+    pub media: Vec<api_types::NftMediaKind>,
+    /// Words of the name or the description, each matched as a prefix
+    pub text: Option<String>,
+    /// Unix time of the mint event
+    pub minted_from: Option<i64>,
+    pub minted_to: Option<i64>,
+    pub nft_id_from: Option<u64>,
+    pub nft_id_to: Option<u64>,
+    pub manager: Option<Address>,
+}
+
+impl NftFilters {
+    /// `text` as a prefix tsquery, `None` without words
+    pub fn text_query(&self) -> Option<String> {
+        let words = self.words();
+        (!words.is_empty()).then(|| {
+            words
+                .iter()
+                .map(|w| format!("{w}:*"))
+                .collect::<Vec<_>>()
+                .join(" & ")
+        })
+    }
+
+    /// Words of `text`, lowercase
+    pub fn words(&self) -> Vec<String> {
+        self.text
+            .as_deref()
+            .unwrap_or_default()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -195,6 +250,8 @@ pub struct NftDirectSell {
     pub price: BigDecimal,
     pub usd_price: Option<BigDecimal>,
     pub state: DirectSellState,
+    /// `direct_sell_valid`
+    pub valid: bool,
     pub created: NaiveDateTime,
     pub updated: NaiveDateTime,
     pub finished_at: Option<NaiveDateTime>,
@@ -215,6 +272,8 @@ pub struct NftDirectBuy {
     pub price: BigDecimal,
     pub usd_price: Option<BigDecimal>,
     pub state: DirectBuyState,
+    /// `direct_buy_valid`
+    pub valid: bool,
     pub created: NaiveDateTime,
     pub updated: NaiveDateTime,
     pub finished_at: Option<NaiveDateTime>,
@@ -237,7 +296,7 @@ pub struct NftPriceHistory {
     pub is_deal: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, sqlx::FromRow)]
 pub struct NftPrice {
     pub ts: NaiveDateTime,
     pub usd_price: BigDecimal,
@@ -262,85 +321,17 @@ pub struct TraitDef {
     pub values: Option<serde_json::Value>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, sqlx::FromRow)]
 pub struct TokenUsdPrice {
     pub token: Address,
     pub usd_price: BigDecimal,
     pub ts: NaiveDateTime,
 }
 
-#[derive(Clone, Debug, Default)]
-pub struct MetaParsed {
-    pub image: Option<String>,
-    pub mimetype: Option<String>,
-    pub full_image: Option<String>,
-    pub full_image_mimetype: Option<String>,
-    pub attributes: Option<serde_json::Value>,
-    pub typ: Option<String>,
-    pub royalty: Option<MetaRoyalty>,
-}
-#[derive(Deserialize, Clone, Debug)]
-struct MetaFile {
-    pub source: Option<String>,
-    pub mimetype: Option<String>,
-}
-
-#[derive(Deserialize, Serialize, Clone, Debug, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct MetaRoyalty {
-    pub description: Option<String>,
-    pub royalty_type: Option<String>,
-}
-
-#[derive(Deserialize, Clone, Debug)]
-struct MetaJson {
-    pub files: Vec<MetaFile>,
-    pub preview: Option<MetaFile>,
-    #[serde(rename = "type")]
-    pub typ: Option<String>,
-    pub attributes: Option<serde_json::Value>,
-    pub royalty: Option<MetaRoyalty>,
-}
-
-impl NftDetails {
-    pub fn parse_meta(&self) -> MetaParsed {
-        match self.meta.clone() {
-            Some(meta) => {
-                let meta_json = serde_json::from_value::<MetaJson>(meta);
-                match meta_json {
-                    Ok(meta_json) => {
-                        let typ = meta_json.typ;
-                        let preview = meta_json
-                            .preview
-                            .map_or((None, None), |p| (p.source, p.mimetype));
-                        let full_image = meta_json
-                            .files
-                            .first()
-                            .map_or((None, None), |p| (p.source.clone(), p.mimetype.clone()));
-
-                        MetaParsed {
-                            image: preview.0,
-                            mimetype: preview.1,
-                            full_image: full_image.0,
-                            full_image_mimetype: full_image.1,
-                            attributes: meta_json.attributes,
-                            typ,
-                            royalty: meta_json.royalty,
-                        }
-                    }
-                    Err(e) => {
-                        error!("Failed to parse meta {:#?} {e}", self.meta);
-                        MetaParsed::default()
-                    }
-                }
-            }
-            None => MetaParsed::default(),
-        }
-    }
-}
-
 #[derive(Deserialize, Debug, Serialize, sqlx::FromRow)]
 pub struct NftEventsRecord {
+    /// Nft the events were asked for, `None` when they weren't filtered by nft
+    pub nft: Option<Address>,
     pub content: Option<serde_json::Value>,
 }
 
@@ -365,6 +356,16 @@ pub struct OwnerFeeRecord {
     pub nft: Option<String>,
 }
 
+/// Fee of a market root for the nfts of a collection
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RootFeeRecord {
+    pub root: Address,
+    pub fee_numerator: Option<i32>,
+    pub fee_denominator: Option<i32>,
+    /// Set on the collection, in place of the root default
+    pub collection_fee: bool,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow, Default)]
 pub struct UserRecord {
     pub address: String,
@@ -379,18 +380,152 @@ pub struct UserRecord {
     pub avatar_url: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub struct Social {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub twitter: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub telegram: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub discord: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub youtube: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub facebook: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub medium: Option<String>,
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApiKeyRecord {
+    pub key_hash: String,
+    pub name: String,
+    pub rate_per_sec: f64,
+    pub burst: i32,
+    pub daily_quota: Option<i64>,
+    pub active: bool,
+    pub requests_today: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AdminAuditRecord {
+    pub id: i64,
+    pub admin: Address,
+    pub action: AdminAction,
+    pub target: Option<Address>,
+    pub details: serde_json::Value,
+    pub created_at: NaiveDateTime,
+    pub total_count: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct VerificationRequestRecord {
+    pub id: i64,
+    pub collection: Address,
+    pub applicant: Address,
+    pub description: String,
+    pub links: serde_json::Value,
+    pub status: VerificationStatus,
+    pub reason: Option<String>,
+    pub reviewer: Option<Address>,
+    pub created_at: NaiveDateTime,
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub total_count: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ReportRecord {
+    pub id: i64,
+    pub target_type: ModerationTarget,
+    pub target: Address,
+    pub reason: ReportReason,
+    pub comment: Option<String>,
+    pub reporter: Address,
+    pub status: ReportStatus,
+    pub resolution: Option<String>,
+    pub resolver: Option<Address>,
+    pub created_at: NaiveDateTime,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub total_count: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct MetadataRefreshRecord {
+    pub id: i64,
+    pub nft: Address,
+    pub collection: Option<Address>,
+    pub requested_by: Address,
+    pub status: MetadataRefreshStatus,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+    /// Current `nft_metadata.updated`
+    pub metadata_updated: Option<NaiveDateTime>,
+}
+
+/// Nfts of a collection-wide refresh request
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct MetadataRefreshBatchRecord {
+    pub queued: i64,
+    pub skipped: i64,
+}
+
+/// Metadata written after a refresh was requested
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChangedMetadataRecord {
+    pub nft: Address,
+    pub updated: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct NftMetadataOverrideRecord {
+    pub nft: Address,
+    pub collection: Address,
+    pub name: Option<String>,
+    pub preview: Option<String>,
+    pub preview_mimetype: Option<String>,
+    pub attributes: Option<serde_json::Value>,
+    pub updated_by: Address,
+    pub updated: NaiveDateTime,
+    pub total_count: i64,
+}
+
+/// Valid order of a collection, auctions at their min bid
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OrderBookRecord {
+    pub address: Address,
+    /// A direct buy, everything else is an ask
+    pub bid: bool,
+    pub auction: bool,
+    pub price_token: Address,
+    pub price: BigDecimal,
+    pub price_usd: Option<BigDecimal>,
+}
+
+/// Metadata of an nft checked by the metadata report
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct NftMetaRecord {
+    pub address: Address,
+    pub name: Option<String>,
+    pub meta: Option<serde_json::Value>,
+    pub meta_override: Option<serde_json::Value>,
+}
+
+/// Override written for an nft, the mimetype is already filled in
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NftMetadataOverrideWrite {
+    pub nft: Address,
+    pub name: Option<String>,
+    pub preview: Option<String>,
+    pub preview_mimetype: Option<String>,
+    pub attributes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ModerationRecord {
+    pub address: Address,
+    pub target_type: ModerationTarget,
+    pub status: ModerationStatus,
+    pub reason: Option<String>,
+    pub moderator: Address,
+    pub updated_at: NaiveDateTime,
+    pub total_count: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WriteAuditRecord {
+    pub id: i64,
+    pub actor: Address,
+    pub action: WriteAction,
+    pub target: Address,
+    pub before: Option<serde_json::Value>,
+    pub after: serde_json::Value,
+    pub details: serde_json::Value,
+    pub ip: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub total_count: i64,
 }
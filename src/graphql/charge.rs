@@ -0,0 +1,124 @@
+//! Rate limiting of queries by their complexity. The route cost pays for the request,
+//! the rest is taken once the query is validated and before any resolver runs
+use crate::services::error::Error;
+use crate::services::rate_limit::{RateLimiter, RemoteAddr};
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextValidation};
+use async_graphql::{ServerError, ValidationResult};
+use std::sync::{Arc, Mutex};
+use warp::http::HeaderMap;
+
+/// Client of a request, put into the request data by the handler
+pub struct ComplexityCharge {
+    limiter: RateLimiter,
+    headers: HeaderMap,
+    remote: Option<RemoteAddr>,
+    /// Set when the client can't pay, the handler replies with the usual rate limit error
+    refused: Mutex<Option<Error>>,
+}
+
+impl ComplexityCharge {
+    pub fn new(limiter: RateLimiter, headers: HeaderMap, remote: Option<RemoteAddr>) -> Self {
+        ComplexityCharge {
+            limiter,
+            headers,
+            remote,
+            refused: Mutex::new(None),
+        }
+    }
+
+    pub fn refused(&self) -> Option<Error> {
+        self.refused
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+}
+
+/// Charges the [`ComplexityCharge`] of the request, requests without one are free
+pub struct ChargeComplexity;
+
+impl ExtensionFactory for ChargeComplexity {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ChargeComplexityExtension)
+    }
+}
+
+struct ChargeComplexityExtension;
+
+#[async_trait::async_trait]
+impl Extension for ChargeComplexityExtension {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+        let Some(charge) = ctx.data_opt::<Arc<ComplexityCharge>>() else {
+            return Ok(result);
+        };
+        let paid = charge
+            .limiter
+            .charge_complexity(&charge.headers, charge.remote, result.complexity)
+            .await;
+        match paid {
+            Ok(()) => Ok(result),
+            Err(e) => {
+                let error = ServerError::new(e.to_string(), None);
+                *charge.refused.lock().unwrap_or_else(|e| e.into_inner()) = Some(e);
+                Err(vec![error])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfg::{BucketConfig, RateLimitConfig};
+    use crate::db::queries::Queries;
+    use crate::graphql::build_schema;
+    use crate::token::TokenDict;
+
+    #[tokio::test]
+    async fn a_query_is_refused_by_complexity_before_it_runs() {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/test")
+            .expect("lazy pool");
+        let db = Queries::new(Arc::new(pool), TokenDict::new(vec![]));
+        let schema = build_schema(db.clone());
+        let limiter = RateLimiter::new(
+            db,
+            RateLimitConfig {
+                ip: BucketConfig {
+                    per_sec: 0.001,
+                    burst: 10,
+                },
+                graphql_complexity_per_token: 10,
+                ..Default::default()
+            },
+        );
+        let execute = |query: &'static str| {
+            let charge = Arc::new(ComplexityCharge::new(
+                limiter.clone(),
+                HeaderMap::new(),
+                None,
+            ));
+            let request = async_graphql::Request::new(query).data(charge.clone());
+            let schema = schema.clone();
+            async move { (schema.execute(request).await, charge.refused()) }
+        };
+
+        let (response, refused) = execute("{ __typename }").await;
+        assert!(response.errors.is_empty() && refused.is_none());
+
+        // leaves a single token
+        assert!(limiter
+            .charge_complexity(&HeaderMap::new(), None, 90)
+            .await
+            .is_ok());
+        let (response, refused) = execute("{ nfts(limit: 50) { items { name } } }").await;
+        assert!(matches!(refused, Some(Error::TooManyRequests { .. })));
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.data == async_graphql::Value::Null);
+    }
+}
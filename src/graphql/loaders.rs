@@ -0,0 +1,329 @@
+use crate::db::queries::Queries;
+use crate::db::{
+    Address, DirectBuyState, DirectSellState, NftAuction, NftDetails, NftDirectBuy, NftDirectSell,
+    NftEventType,
+};
+use crate::handlers::auction::collect_auctions;
+use crate::handlers::collection::collect_collections;
+use crate::handlers::nft::{collect_direct_buy, collect_direct_sell, collect_nfts};
+use crate::model::{Auction, Collection, DirectBuy, DirectSell, NftEvents, NftTrait, UserDto, NFT};
+use async_graphql::dataloader::Loader;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Loader errors are shared between all the resolvers waiting for the batch
+pub type LoadError = Arc<anyhow::Error>;
+
+/// A page of a list hanging off an nft or a user. Keys asking for the same page of
+/// different addresses are loaded with one query
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PageKey<F> {
+    pub address: Address,
+    pub filter: F,
+    pub limit: usize,
+    pub offset: usize,
+}
+
+/// Addresses of the keys grouped by the page they ask for
+fn pages<F: Clone + Eq + Hash>(keys: &[PageKey<F>]) -> HashMap<(F, usize, usize), Vec<Address>> {
+    let mut pages: HashMap<_, Vec<Address>> = HashMap::new();
+    for key in keys {
+        pages
+            .entry((key.filter.clone(), key.limit, key.offset))
+            .or_default()
+            .push(key.address.clone());
+    }
+    pages
+}
+
+/// Hands the rows of one page query out to the keys that asked for it,
+/// addresses without rows get an empty list
+fn share<F: Clone + Eq + Hash, V>(
+    loaded: &mut HashMap<PageKey<F>, Vec<V>>,
+    (filter, limit, offset): &(F, usize, usize),
+    addresses: Vec<Address>,
+    rows: impl IntoIterator<Item = (Address, V)>,
+) {
+    let mut by_address: HashMap<Address, Vec<V>> = HashMap::new();
+    for (address, row) in rows {
+        by_address.entry(address).or_default().push(row);
+    }
+    for address in addresses {
+        let rows = by_address.remove(&address).unwrap_or_default();
+        let key = PageKey {
+            address,
+            filter: filter.clone(),
+            limit: *limit,
+            offset: *offset,
+        };
+        loaded.insert(key, rows);
+    }
+}
+
+pub struct NftLoader(pub Queries);
+
+impl Loader<Address> for NftLoader {
+    type Value = NFT;
+    type Error = LoadError;
+
+    async fn load(&self, keys: &[Address]) -> Result<HashMap<Address, NFT>, LoadError> {
+        collect_nfts(&self.0, keys).await.map_err(Arc::new)
+    }
+}
+
+pub struct CollectionLoader(pub Queries);
+
+impl Loader<Address> for CollectionLoader {
+    type Value = Collection;
+    type Error = LoadError;
+
+    async fn load(&self, keys: &[Address]) -> Result<HashMap<Address, Collection>, LoadError> {
+        collect_collections(&self.0, &keys.to_vec())
+            .await
+            .map_err(Arc::new)
+    }
+}
+
+pub struct AuctionLoader(pub Queries);
+
+impl Loader<Address> for AuctionLoader {
+    type Value = Auction;
+    type Error = LoadError;
+
+    async fn load(&self, keys: &[Address]) -> Result<HashMap<Address, Auction>, LoadError> {
+        collect_auctions(&self.0, keys).await.map_err(Arc::new)
+    }
+}
+
+pub struct DirectSellLoader(pub Queries);
+
+impl Loader<Address> for DirectSellLoader {
+    type Value = DirectSell;
+    type Error = LoadError;
+
+    async fn load(&self, keys: &[Address]) -> Result<HashMap<Address, DirectSell>, LoadError> {
+        collect_direct_sell(&self.0, keys).await.map_err(Arc::new)
+    }
+}
+
+pub struct DirectBuyLoader(pub Queries);
+
+impl Loader<Address> for DirectBuyLoader {
+    type Value = DirectBuy;
+    type Error = LoadError;
+
+    async fn load(&self, keys: &[Address]) -> Result<HashMap<Address, DirectBuy>, LoadError> {
+        collect_direct_buy(&self.0, keys).await.map_err(Arc::new)
+    }
+}
+
+pub struct UserLoader(pub Queries);
+
+impl Loader<Address> for UserLoader {
+    type Value = UserDto;
+    type Error = LoadError;
+
+    async fn load(&self, keys: &[Address]) -> Result<HashMap<Address, UserDto>, LoadError> {
+        let users = self
+            .0
+            .collect_users(keys)
+            .await
+            .map_err(|e| Arc::new(e.into()))?;
+        Ok(users
+            .into_iter()
+            .map(|user| (user.address.clone(), UserDto::from(user)))
+            .collect())
+    }
+}
+
+pub struct NftTraitsLoader(pub Queries);
+
+impl Loader<Address> for NftTraitsLoader {
+    type Value = Vec<NftTrait>;
+    type Error = LoadError;
+
+    async fn load(&self, keys: &[Address]) -> Result<HashMap<Address, Vec<NftTrait>>, LoadError> {
+        let traits = self
+            .0
+            .collect_traits(keys)
+            .await
+            .map_err(|e| Arc::new(e.into()))?;
+        let mut loaded: HashMap<Address, Vec<NftTrait>> =
+            keys.iter().map(|k| (k.clone(), vec![])).collect();
+        for (nft, record) in traits {
+            loaded.entry(nft).or_default().push(record.into());
+        }
+        Ok(loaded)
+    }
+}
+
+pub struct NftEventsLoader(pub Queries);
+
+impl Loader<PageKey<Vec<NftEventType>>> for NftEventsLoader {
+    type Value = NftEvents;
+    type Error = LoadError;
+
+    async fn load(
+        &self,
+        keys: &[PageKey<Vec<NftEventType>>],
+    ) -> Result<HashMap<PageKey<Vec<NftEventType>>, NftEvents>, LoadError> {
+        let mut loaded = HashMap::new();
+        for ((types, limit, offset), nfts) in pages(keys) {
+            let records = self
+                .0
+                .list_nfts_events(&nfts, &types, offset, limit)
+                .await
+                .map_err(|e| Arc::new(e.into()))?;
+            // one record per asked nft
+            for record in records {
+                let Some(nft) = record.nft else { continue };
+                let events = match record.content {
+                    None => NftEvents::default(),
+                    Some(value) => serde_json::from_value(value).map_err(|e| Arc::new(e.into()))?,
+                };
+                let key = PageKey {
+                    address: nft,
+                    filter: types.clone(),
+                    limit,
+                    offset,
+                };
+                loaded.insert(key, events);
+            }
+        }
+        Ok(loaded)
+    }
+}
+
+pub struct NftDirectBuysLoader(pub Queries);
+
+impl Loader<PageKey<Vec<DirectBuyState>>> for NftDirectBuysLoader {
+    type Value = Vec<NftDirectBuy>;
+    type Error = LoadError;
+
+    async fn load(
+        &self,
+        keys: &[PageKey<Vec<DirectBuyState>>],
+    ) -> Result<HashMap<PageKey<Vec<DirectBuyState>>, Vec<NftDirectBuy>>, LoadError> {
+        let mut loaded = HashMap::new();
+        for (page, nfts) in pages(keys) {
+            let (status, limit, offset) = &page;
+            let list = self
+                .0
+                .list_nfts_direct_buy(&nfts, status, *limit, *offset)
+                .await
+                .map_err(|e| Arc::new(e.into()))?;
+            share(
+                &mut loaded,
+                &page,
+                nfts,
+                list.into_iter().map(|x| (x.nft.clone(), x)),
+            );
+        }
+        Ok(loaded)
+    }
+}
+
+/// Filters of `User.nfts`: collections, forsale, auction
+pub type OwnerNftsFilter = (Vec<Address>, bool, bool);
+
+pub struct OwnerNftsLoader(pub Queries);
+
+impl Loader<PageKey<OwnerNftsFilter>> for OwnerNftsLoader {
+    type Value = Vec<NftDetails>;
+    type Error = LoadError;
+
+    async fn load(
+        &self,
+        keys: &[PageKey<OwnerNftsFilter>],
+    ) -> Result<HashMap<PageKey<OwnerNftsFilter>, Vec<NftDetails>>, LoadError> {
+        let mut loaded = HashMap::new();
+        for (page, owners) in pages(keys) {
+            let ((collections, forsale, auction), limit, offset) = &page;
+            let list = self
+                .0
+                .list_owners_nfts(&owners, collections, *forsale, *auction, *limit, *offset)
+                .await
+                .map_err(|e| Arc::new(e.into()))?;
+            let rows = list.into_iter().filter_map(|x| Some((x.owner.clone()?, x)));
+            share(&mut loaded, &page, owners, rows);
+        }
+        Ok(loaded)
+    }
+}
+
+pub struct OwnerAuctionsLoader(pub Queries);
+
+impl Loader<PageKey<()>> for OwnerAuctionsLoader {
+    type Value = Vec<NftAuction>;
+    type Error = LoadError;
+
+    async fn load(
+        &self,
+        keys: &[PageKey<()>],
+    ) -> Result<HashMap<PageKey<()>, Vec<NftAuction>>, LoadError> {
+        let mut loaded = HashMap::new();
+        for (page, owners) in pages(keys) {
+            let (_, limit, offset) = &page;
+            let list = self
+                .0
+                .list_owners_auctions(&owners, *limit, *offset)
+                .await
+                .map_err(|e| Arc::new(e.into()))?;
+            share(&mut loaded, &page, owners, list);
+        }
+        Ok(loaded)
+    }
+}
+
+pub struct OwnerDirectSellsLoader(pub Queries);
+
+impl Loader<PageKey<Vec<DirectSellState>>> for OwnerDirectSellsLoader {
+    type Value = Vec<NftDirectSell>;
+    type Error = LoadError;
+
+    async fn load(
+        &self,
+        keys: &[PageKey<Vec<DirectSellState>>],
+    ) -> Result<HashMap<PageKey<Vec<DirectSellState>>, Vec<NftDirectSell>>, LoadError> {
+        let mut loaded = HashMap::new();
+        for (page, owners) in pages(keys) {
+            let (status, limit, offset) = &page;
+            let list = self
+                .0
+                .list_owners_direct_sell(&owners, &[], status, *limit, *offset)
+                .await
+                .map_err(|e| Arc::new(e.into()))?;
+            let rows = list
+                .into_iter()
+                .filter_map(|x| Some((x.seller.clone()?, x)));
+            share(&mut loaded, &page, owners, rows);
+        }
+        Ok(loaded)
+    }
+}
+
+pub struct OwnerDirectBuysLoader(pub Queries);
+
+impl Loader<PageKey<Vec<DirectBuyState>>> for OwnerDirectBuysLoader {
+    type Value = Vec<NftDirectBuy>;
+    type Error = LoadError;
+
+    async fn load(
+        &self,
+        keys: &[PageKey<Vec<DirectBuyState>>],
+    ) -> Result<HashMap<PageKey<Vec<DirectBuyState>>, Vec<NftDirectBuy>>, LoadError> {
+        let mut loaded = HashMap::new();
+        for (page, owners) in pages(keys) {
+            let (status, limit, offset) = &page;
+            let list = self
+                .0
+                .list_owners_direct_buy(&owners, &[], status, *limit, *offset)
+                .await
+                .map_err(|e| Arc::new(e.into()))?;
+            let rows = list.into_iter().filter_map(|x| Some((x.buyer.clone()?, x)));
+            share(&mut loaded, &page, owners, rows);
+        }
+        Ok(loaded)
+    }
+}
@@ -0,0 +1,315 @@
+mod charge;
+mod loaders;
+mod relations;
+
+pub use self::charge::*;
+pub use self::loaders::*;
+pub use self::relations::*;
+
+use crate::db::queries::Queries;
+use crate::db::{Address, NftEventType, NftFilters};
+use crate::handlers::auction::AuctionsSortOrder;
+use crate::model::{Auction, CollectionDetails, FromDb, NftEvents, UserDto, NFT};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Object, OutputType, Schema, SimpleObject,
+};
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Upper bound of every list argument
+const MAX_LIMIT: usize = 100;
+
+pub fn build_schema(db: Queries) -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(DataLoader::new(NftLoader(db.clone()), tokio::spawn))
+        .data(DataLoader::new(CollectionLoader(db.clone()), tokio::spawn))
+        .data(DataLoader::new(AuctionLoader(db.clone()), tokio::spawn))
+        .data(DataLoader::new(DirectSellLoader(db.clone()), tokio::spawn))
+        .data(DataLoader::new(DirectBuyLoader(db.clone()), tokio::spawn))
+        .data(DataLoader::new(UserLoader(db.clone()), tokio::spawn))
+        .data(DataLoader::new(NftTraitsLoader(db.clone()), tokio::spawn))
+        .data(DataLoader::new(NftEventsLoader(db.clone()), tokio::spawn))
+        .data(DataLoader::new(
+            NftDirectBuysLoader(db.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(OwnerNftsLoader(db.clone()), tokio::spawn))
+        .data(DataLoader::new(
+            OwnerAuctionsLoader(db.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            OwnerDirectSellsLoader(db.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            OwnerDirectBuysLoader(db.clone()),
+            tokio::spawn,
+        ))
+        .data(db)
+        .extension(ChargeComplexity)
+        .limit_depth(8)
+        .limit_complexity(5000)
+        .finish()
+}
+
+#[derive(SimpleObject)]
+#[graphql(concrete(name = "NftPage", params(NftNode)))]
+#[graphql(concrete(name = "CollectionPage", params(CollectionDetailsNode)))]
+#[graphql(concrete(name = "AuctionPage", params(AuctionNode)))]
+pub struct Page<T: OutputType> {
+    pub total_count: i64,
+    pub items: Vec<T>,
+}
+
+pub(crate) fn queries<'a>(ctx: &Context<'a>) -> &'a Queries {
+    ctx.data_unchecked::<Queries>()
+}
+
+pub(crate) fn loader<'a, T: Send + Sync + 'static>(ctx: &Context<'a>) -> &'a DataLoader<T> {
+    ctx.data_unchecked::<DataLoader<T>>()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn nft_page(
+    db: &Queries,
+    owners: &[Address],
+    collections: &[Address],
+    forsale: Option<bool>,
+    auction: Option<bool>,
+    verified: Option<bool>,
+    limit: usize,
+    offset: usize,
+) -> anyhow::Result<Page<NftNode>> {
+    let list = db
+        .nft_search(
+            owners,
+            collections,
+            forsale,
+            auction,
+            verified,
+            limit.min(MAX_LIMIT),
+            offset,
+            &[],
+            None,
+            true,
+            false,
+            &NftFilters::default(),
+        )
+        .await?;
+    Ok(Page {
+        total_count: list.first().map(|x| x.total_count).unwrap_or_default(),
+        items: list.into_iter().map(|x| NFT::from(x).into()).collect(),
+    })
+}
+
+pub(crate) async fn events(
+    db: &Queries,
+    nft: Option<&Address>,
+    collections: &[Address],
+    owner: Option<&Address>,
+    types: &[NftEventType],
+    limit: usize,
+    offset: usize,
+) -> anyhow::Result<NftEvents> {
+    let verified = if nft.is_some() { Some(false) } else { None };
+    let record = db
+        .list_events(
+            nft,
+            collections,
+            owner,
+            types,
+            offset,
+            limit.min(MAX_LIMIT),
+            true,
+            verified,
+            false,
+        )
+        .await?;
+    Ok(match record.content {
+        None => NftEvents::default(),
+        Some(value) => serde_json::from_value(value)?,
+    })
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn nft(
+        &self,
+        ctx: &Context<'_>,
+        address: Address,
+    ) -> async_graphql::Result<Option<NftNode>> {
+        Ok(loader::<NftLoader>(ctx)
+            .load_one(address)
+            .await?
+            .map(Into::into))
+    }
+
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    #[allow(clippy::too_many_arguments)]
+    async fn nfts(
+        &self,
+        ctx: &Context<'_>,
+        owners: Option<Vec<Address>>,
+        collections: Option<Vec<Address>>,
+        forsale: Option<bool>,
+        auction: Option<bool>,
+        verified: Option<bool>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> async_graphql::Result<Page<NftNode>> {
+        Ok(nft_page(
+            queries(ctx),
+            &owners.unwrap_or_default(),
+            &collections.unwrap_or_default(),
+            forsale,
+            auction,
+            Some(verified.unwrap_or(true)),
+            limit,
+            offset,
+        )
+        .await?)
+    }
+
+    async fn collection(
+        &self,
+        ctx: &Context<'_>,
+        address: Address,
+    ) -> async_graphql::Result<Option<CollectionNode>> {
+        Ok(loader::<CollectionLoader>(ctx)
+            .load_one(address)
+            .await?
+            .map(Into::into))
+    }
+
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn collections(
+        &self,
+        ctx: &Context<'_>,
+        name: Option<String>,
+        owners: Option<Vec<Address>>,
+        verified: Option<bool>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> async_graphql::Result<Page<CollectionDetailsNode>> {
+        let list = queries(ctx)
+            .list_collections(
+                name.as_ref(),
+                &owners.unwrap_or_default(),
+                Some(&verified.unwrap_or(true)),
+                &[],
+                limit.min(MAX_LIMIT),
+                offset,
+                None,
+            )
+            .await?;
+        Ok(Page {
+            total_count: list.first().map(|x| x.cnt).unwrap_or_default(),
+            items: list
+                .into_iter()
+                .map(|x| CollectionDetails::try_from(x).map(Into::into))
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+
+    async fn auction(
+        &self,
+        ctx: &Context<'_>,
+        address: Address,
+    ) -> async_graphql::Result<Option<AuctionNode>> {
+        Ok(loader::<AuctionLoader>(ctx)
+            .load_one(address)
+            .await?
+            .map(Into::into))
+    }
+
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn auctions(
+        &self,
+        ctx: &Context<'_>,
+        owners: Option<Vec<Address>>,
+        collections: Option<Vec<Address>>,
+        tokens: Option<Vec<Address>>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> async_graphql::Result<Page<AuctionNode>> {
+        let db = queries(ctx);
+        let list = db
+            .list_nft_auctions(
+                &owners.unwrap_or_default(),
+                &collections.unwrap_or_default(),
+                &tokens.unwrap_or_default(),
+                &AuctionsSortOrder::StartDate,
+                limit.min(MAX_LIMIT),
+                offset,
+            )
+            .await?;
+        Ok(Page {
+            total_count: list.first().map(|x| x.cnt).unwrap_or_default(),
+            items: list
+                .iter()
+                .map(|x| Auction::from_db(x, &db.tokens).into())
+                .collect(),
+        })
+    }
+
+    async fn direct_sell(
+        &self,
+        ctx: &Context<'_>,
+        address: Address,
+    ) -> async_graphql::Result<Option<DirectSellNode>> {
+        Ok(loader::<DirectSellLoader>(ctx)
+            .load_one(address)
+            .await?
+            .map(Into::into))
+    }
+
+    async fn direct_buy(
+        &self,
+        ctx: &Context<'_>,
+        address: Address,
+    ) -> async_graphql::Result<Option<DirectBuyNode>> {
+        Ok(loader::<DirectBuyLoader>(ctx)
+            .load_one(address)
+            .await?
+            .map(Into::into))
+    }
+
+    /// Users without a profile are returned with the address only, same as `/user/{address}`
+    async fn user(&self, ctx: &Context<'_>, address: Address) -> async_graphql::Result<UserNode> {
+        let user = loader::<UserLoader>(ctx).load_one(address.clone()).await?;
+        let user = user.unwrap_or_else(|| UserDto {
+            address,
+            ..Default::default()
+        });
+        Ok(user.into())
+    }
+
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    #[allow(clippy::too_many_arguments)]
+    async fn events(
+        &self,
+        ctx: &Context<'_>,
+        owner: Option<Address>,
+        collections: Option<Vec<Address>>,
+        nft: Option<Address>,
+        types: Option<Vec<NftEventType>>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> async_graphql::Result<NftEventsNode> {
+        Ok(events(
+            queries(ctx),
+            nft.as_ref(),
+            &collections.unwrap_or_default(),
+            owner.as_ref(),
+            &types.unwrap_or_default(),
+            limit,
+            offset,
+        )
+        .await?
+        .into())
+    }
+}
@@ -0,0 +1,359 @@
+//! Fields linking the REST models together, resolved lazily through the loaders
+//!
+//! The models come from `api-types`, so every GraphQL object wraps one and carries the
+//! public type name while the flattened model keeps a `*Fields` name.
+use super::{loader, nft_page, queries, Page, MAX_LIMIT};
+use super::{
+    AuctionLoader, CollectionLoader, DirectBuyLoader, DirectSellLoader, NftDirectBuysLoader,
+    NftEventsLoader, NftLoader, NftTraitsLoader, OwnerAuctionsLoader, OwnerDirectBuysLoader,
+    OwnerDirectSellsLoader, OwnerNftsLoader, PageKey, UserLoader,
+};
+use crate::db::{Address, DirectBuyState, DirectSellState, NftEventType};
+use crate::model::{
+    Auction, Collection, CollectionDetails, DirectBuy, DirectSell, FromDb, NftEvent, NftEvents,
+    NftTrait, UserDto, NFT,
+};
+use async_graphql::{ComplexObject, Context, Result, SimpleObject};
+
+macro_rules! node {
+    ($node:ident, $name:literal, $field:ident: $model:ty) => {
+        #[derive(SimpleObject)]
+        #[graphql(name = $name, complex)]
+        pub struct $node {
+            #[graphql(flatten)]
+            pub $field: $model,
+        }
+
+        impl From<$model> for $node {
+            fn from($field: $model) -> Self {
+                Self { $field }
+            }
+        }
+    };
+}
+
+node!(NftNode, "NFT", nft: NFT);
+node!(CollectionNode, "Collection", collection: Collection);
+node!(CollectionDetailsNode, "CollectionDetails", details: CollectionDetails);
+node!(AuctionNode, "Auction", auction: Auction);
+node!(DirectSellNode, "DirectSell", direct_sell: DirectSell);
+node!(DirectBuyNode, "DirectBuy", direct_buy: DirectBuy);
+node!(NftEventNode, "NftEvent", event: NftEvent);
+node!(UserNode, "User", user: UserDto);
+
+#[derive(SimpleObject)]
+#[graphql(name = "NftEvents")]
+pub struct NftEventsNode {
+    pub data: Vec<NftEventNode>,
+    pub total_rows: i64,
+}
+
+impl From<NftEvents> for NftEventsNode {
+    fn from(events: NftEvents) -> Self {
+        Self {
+            data: events.data.into_iter().map(NftEventNode::from).collect(),
+            total_rows: events.total_rows,
+        }
+    }
+}
+
+fn page_key<F>(address: &Address, filter: F, limit: usize, offset: usize) -> PageKey<F> {
+    PageKey {
+        address: address.clone(),
+        filter,
+        limit: limit.min(MAX_LIMIT),
+        offset,
+    }
+}
+
+/// Nfts of a collection shared by `Collection` and `CollectionDetails`
+async fn collection_nfts(
+    ctx: &Context<'_>,
+    collection: &Address,
+    forsale: Option<bool>,
+    auction: Option<bool>,
+    limit: usize,
+    offset: usize,
+) -> Result<Page<NftNode>> {
+    Ok(nft_page(
+        queries(ctx),
+        &[],
+        std::slice::from_ref(collection),
+        forsale,
+        auction,
+        None,
+        limit,
+        offset,
+    )
+    .await?)
+}
+
+#[ComplexObject]
+impl NftNode {
+    #[graphql(name = "collection")]
+    async fn collection_object(&self, ctx: &Context<'_>) -> Result<Option<CollectionNode>> {
+        Ok(loader::<CollectionLoader>(ctx)
+            .load_one(self.nft.collection.clone())
+            .await?
+            .map(Into::into))
+    }
+
+    #[graphql(name = "auction")]
+    async fn auction_object(&self, ctx: &Context<'_>) -> Result<Option<AuctionNode>> {
+        match &self.nft.auction {
+            Some(address) => Ok(loader::<AuctionLoader>(ctx)
+                .load_one(address.clone())
+                .await?
+                .map(Into::into)),
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "forsale")]
+    async fn forsale_object(&self, ctx: &Context<'_>) -> Result<Option<DirectSellNode>> {
+        match &self.nft.forsale {
+            Some(address) => Ok(loader::<DirectSellLoader>(ctx)
+                .load_one(address.clone())
+                .await?
+                .map(Into::into)),
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "bestOffer")]
+    async fn best_offer_object(&self, ctx: &Context<'_>) -> Result<Option<DirectBuyNode>> {
+        match &self.nft.best_offer {
+            Some(address) => Ok(loader::<DirectBuyLoader>(ctx)
+                .load_one(address.clone())
+                .await?
+                .map(Into::into)),
+            None => Ok(None),
+        }
+    }
+
+    async fn owner_profile(&self, ctx: &Context<'_>) -> Result<Option<UserNode>> {
+        match &self.nft.contract.owner {
+            Some(address) => Ok(loader::<UserLoader>(ctx)
+                .load_one(address.clone())
+                .await?
+                .map(Into::into)),
+            None => Ok(None),
+        }
+    }
+
+    async fn traits(&self, ctx: &Context<'_>) -> Result<Vec<NftTrait>> {
+        Ok(loader::<NftTraitsLoader>(ctx)
+            .load_one(self.nft.contract.address.clone())
+            .await?
+            .unwrap_or_default())
+    }
+
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn events(
+        &self,
+        ctx: &Context<'_>,
+        types: Option<Vec<NftEventType>>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> Result<NftEventsNode> {
+        let address = &self.nft.contract.address;
+        let key = page_key(address, types.unwrap_or_default(), limit, offset);
+        Ok(loader::<NftEventsLoader>(ctx)
+            .load_one(key)
+            .await?
+            .unwrap_or_default()
+            .into())
+    }
+
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn direct_buys(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<Vec<DirectBuyState>>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> Result<Vec<DirectBuyNode>> {
+        let address = &self.nft.contract.address;
+        let key = page_key(address, status.unwrap_or_default(), limit, offset);
+        let list = loader::<NftDirectBuysLoader>(ctx).load_one(key).await?;
+        let tokens = &queries(ctx).tokens;
+        Ok(list
+            .unwrap_or_default()
+            .iter()
+            .map(|x| DirectBuy::from_db(x, tokens).into())
+            .collect())
+    }
+}
+
+#[ComplexObject]
+impl CollectionNode {
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn nfts(
+        &self,
+        ctx: &Context<'_>,
+        forsale: Option<bool>,
+        auction: Option<bool>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> Result<Page<NftNode>> {
+        let address = &self.collection.contract.address;
+        collection_nfts(ctx, address, forsale, auction, limit, offset).await
+    }
+}
+
+#[ComplexObject]
+impl CollectionDetailsNode {
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn nfts(
+        &self,
+        ctx: &Context<'_>,
+        forsale: Option<bool>,
+        auction: Option<bool>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> Result<Page<NftNode>> {
+        let address = &self.details.collection.contract.address;
+        collection_nfts(ctx, address, forsale, auction, limit, offset).await
+    }
+}
+
+#[ComplexObject]
+impl AuctionNode {
+    #[graphql(name = "nft")]
+    async fn nft_object(&self, ctx: &Context<'_>) -> Result<Option<NftNode>> {
+        Ok(loader::<NftLoader>(ctx)
+            .load_one(self.auction.nft.clone())
+            .await?
+            .map(Into::into))
+    }
+}
+
+#[ComplexObject]
+impl DirectSellNode {
+    #[graphql(name = "nft")]
+    async fn nft_object(&self, ctx: &Context<'_>) -> Result<Option<NftNode>> {
+        Ok(loader::<NftLoader>(ctx)
+            .load_one(self.direct_sell.nft.clone())
+            .await?
+            .map(Into::into))
+    }
+}
+
+#[ComplexObject]
+impl DirectBuyNode {
+    #[graphql(name = "nft")]
+    async fn nft_object(&self, ctx: &Context<'_>) -> Result<Option<NftNode>> {
+        Ok(loader::<NftLoader>(ctx)
+            .load_one(self.direct_buy.nft.clone())
+            .await?
+            .map(Into::into))
+    }
+}
+
+#[ComplexObject]
+impl NftEventNode {
+    #[graphql(name = "nft")]
+    async fn nft_object(&self, ctx: &Context<'_>) -> Result<Option<NftNode>> {
+        Ok(loader::<NftLoader>(ctx)
+            .load_one(self.event.address.clone())
+            .await?
+            .map(Into::into))
+    }
+}
+
+#[ComplexObject]
+impl UserNode {
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn nfts(
+        &self,
+        ctx: &Context<'_>,
+        collections: Option<Vec<Address>>,
+        forsale: Option<bool>,
+        auction: Option<bool>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> Result<Page<NftNode>> {
+        let filter = (
+            collections.unwrap_or_default(),
+            forsale.unwrap_or_default(),
+            auction.unwrap_or_default(),
+        );
+        let key = page_key(&self.user.address, filter, limit, offset);
+        let list = loader::<OwnerNftsLoader>(ctx)
+            .load_one(key)
+            .await?
+            .unwrap_or_default();
+        Ok(Page {
+            total_count: list.first().map(|x| x.total_count).unwrap_or_default(),
+            items: list.into_iter().map(|x| NFT::from(x).into()).collect(),
+        })
+    }
+
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn auctions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> Result<Page<AuctionNode>> {
+        let key = page_key(&self.user.address, (), limit, offset);
+        let list = loader::<OwnerAuctionsLoader>(ctx)
+            .load_one(key)
+            .await?
+            .unwrap_or_default();
+        let tokens = &queries(ctx).tokens;
+        Ok(Page {
+            total_count: list.first().map(|x| x.cnt).unwrap_or_default(),
+            items: list
+                .iter()
+                .map(|x| Auction::from_db(x, tokens).into())
+                .collect(),
+        })
+    }
+
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn direct_sells(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<Vec<DirectSellState>>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> Result<Vec<DirectSellNode>> {
+        let key = page_key(
+            &self.user.address,
+            status.unwrap_or_default(),
+            limit,
+            offset,
+        );
+        let list = loader::<OwnerDirectSellsLoader>(ctx).load_one(key).await?;
+        let tokens = &queries(ctx).tokens;
+        Ok(list
+            .unwrap_or_default()
+            .iter()
+            .map(|x| DirectSell::from_db(x, tokens).into())
+            .collect())
+    }
+
+    #[graphql(complexity = "limit.min(MAX_LIMIT) * child_complexity")]
+    async fn direct_buys(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<Vec<DirectBuyState>>,
+        #[graphql(default = 20)] limit: usize,
+        #[graphql(default)] offset: usize,
+    ) -> Result<Vec<DirectBuyNode>> {
+        let key = page_key(
+            &self.user.address,
+            status.unwrap_or_default(),
+            limit,
+            offset,
+        );
+        let list = loader::<OwnerDirectBuysLoader>(ctx).load_one(key).await?;
+        let tokens = &queries(ctx).tokens;
+        Ok(list
+            .unwrap_or_default()
+            .iter()
+            .map(|x| DirectBuy::from_db(x, tokens).into())
+            .collect())
+    }
+}
@@ -0,0 +1,392 @@
+use crate::db::repo::{AdminRepo, ApiRepo, WriteAuditRepo};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
+use crate::model::{AdminAuditEntry, VecWithTotal, WriteAuditEntry};
+use crate::schema::{VecAdminAuditWithTotal, VecWriteAuditWithTotal};
+use crate::services::auth::AuthService;
+use crate::{api_doc_addon, catch_error_401, catch_error_403, catch_error_500, response};
+pub use api_types::{
+    AdminAction, AdminAuditQuery, RemoveRootPayload, RootType, SetCollectionVerifiedPayload,
+    UpsertRootPayload, WriteAuditQuery,
+};
+use chrono::DateTime;
+use http::{HeaderMap, HeaderValue};
+use moka::future::Cache;
+use serde_json::Value;
+use std::convert::Infallible;
+use std::sync::Arc;
+use utoipa::OpenApi;
+use warp::http::{Method, StatusCode};
+use warp::Filter;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        set_collection_verified,
+        upsert_root,
+        remove_root,
+        refresh_nft_verified,
+        list_admin_audit,
+        list_write_audit
+    ),
+    components(schemas(
+        SetCollectionVerifiedPayload,
+        UpsertRootPayload,
+        RemoveRootPayload,
+        RootType,
+        AdminAuditEntry,
+        AdminAction,
+        VecAdminAuditWithTotal,
+        WriteAuditQuery,
+        VecWriteAuditWithTotal
+    )),
+    tags(
+        (name = "admin", description = "Verification and whitelist management, admin tokens only"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::POST,
+        "/admin/collections/verified",
+        set_collection_verified(
+            ctx.db.clone(),
+            ctx.auth.clone(),
+            ctx.collections_cache.clone(),
+        ),
+    )
+    .add(
+        Method::POST,
+        "/admin/roots",
+        upsert_root(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::POST,
+        "/admin/roots/remove",
+        remove_root(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::POST,
+        "/admin/nft-verified/refresh",
+        refresh_nft_verified(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/admin/audit",
+        list_admin_audit(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/admin/audit/writes",
+        list_write_audit(ctx.db.clone(), ctx.auth.clone()),
+    );
+}
+
+fn not_found(message: &'static str) -> Result<Box<dyn warp::Reply>, Infallible> {
+    Ok(Box::from(warp::reply::with_status(
+        message,
+        StatusCode::NOT_FOUND,
+    )))
+}
+
+#[utoipa::path(
+    post,
+    tag = "admin",
+    path = "/admin/collections/verified",
+    request_body(content = SetCollectionVerifiedPayload, description = "Verify or unverify a collection"),
+    responses(
+        (status = 200),
+        (status = 401),
+        (status = 403),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn set_collection_verified<D: AdminRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    collections_cache: Cache<u64, Value>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "collections" / "verified")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<SetCollectionVerifiedPayload>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and(warp::any().map(move || collections_cache.clone()))
+        .and_then(set_collection_verified_handler::<D>)
+}
+
+pub async fn set_collection_verified_handler<D: AdminRepo>(
+    payload: SetCollectionVerifiedPayload,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+    collections_cache: Cache<u64, Value>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    let admin = catch_error_403!(admin);
+
+    let previous = catch_error_500!(
+        db.set_collection_verified(
+            &admin,
+            &payload.collection,
+            payload.verified,
+            payload.comment.as_deref(),
+        )
+        .await
+    );
+    if previous.is_none() {
+        return not_found("Collection not found");
+    }
+    collections_cache.invalidate_all();
+
+    Ok(Box::from(warp::reply::with_status("", StatusCode::OK)))
+}
+
+#[utoipa::path(
+    post,
+    tag = "admin",
+    path = "/admin/roots",
+    request_body(content = UpsertRootPayload, description = "Add a market root to the offers whitelist or change its expiry"),
+    responses(
+        (status = 200),
+        (status = 400),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn upsert_root<D: AdminRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "roots")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<UpsertRootPayload>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(upsert_root_handler::<D>)
+}
+
+pub async fn upsert_root_handler<D: AdminRepo>(
+    payload: UpsertRootPayload,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    let admin = catch_error_403!(admin);
+
+    let expiry_date = match payload.expiry_date {
+        Some(ts) => match DateTime::from_timestamp(ts, 0) {
+            Some(date) => Some(date.naive_utc()),
+            None => {
+                return Ok(Box::from(warp::reply::with_status(
+                    "Invalid expiryDate",
+                    StatusCode::BAD_REQUEST,
+                )))
+            }
+        },
+        None => None,
+    };
+
+    catch_error_500!(
+        db.upsert_root(
+            &admin,
+            &payload.address,
+            payload.code,
+            expiry_date,
+            payload.comment.as_deref(),
+        )
+        .await
+    );
+
+    Ok(Box::from(warp::reply::with_status("", StatusCode::OK)))
+}
+
+#[utoipa::path(
+    post,
+    tag = "admin",
+    path = "/admin/roots/remove",
+    request_body(content = RemoveRootPayload, description = "Drop a market root and its offers from the whitelist"),
+    responses(
+        (status = 200),
+        (status = 401),
+        (status = 403),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn remove_root<D: AdminRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "roots" / "remove")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<RemoveRootPayload>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(remove_root_handler::<D>)
+}
+
+pub async fn remove_root_handler<D: AdminRepo>(
+    payload: RemoveRootPayload,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    let admin = catch_error_403!(admin);
+
+    let removed = catch_error_500!(
+        db.remove_root(&admin, &payload.address, payload.comment.as_deref())
+            .await
+    );
+    if !removed {
+        return not_found("Root not found");
+    }
+
+    Ok(Box::from(warp::reply::with_status("", StatusCode::OK)))
+}
+
+#[utoipa::path(
+    post,
+    tag = "admin",
+    path = "/admin/nft-verified/refresh",
+    responses(
+        (status = 200),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn refresh_nft_verified<D: AdminRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "nft-verified" / "refresh")
+        .and(warp::post())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(refresh_nft_verified_handler::<D>)
+}
+
+pub async fn refresh_nft_verified_handler<D: AdminRepo>(
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    let admin = catch_error_403!(admin);
+
+    let elapsed = catch_error_500!(db.refresh_nft_verified(&admin).await);
+    log::info!("nft_verified_mv refreshed by {admin} in {elapsed:?}");
+
+    Ok(Box::from(warp::reply::with_status("", StatusCode::OK)))
+}
+
+#[utoipa::path(
+    get,
+    tag = "admin",
+    path = "/admin/audit",
+    params(AdminAuditQuery),
+    responses(
+        (status = 200, body = VecAdminAuditWithTotal),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn list_admin_audit<D: AdminRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "audit")
+        .and(warp::get())
+        .and(warp::query::<AdminAuditQuery>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(list_admin_audit_handler::<D>)
+}
+
+pub async fn list_admin_audit_handler<D: AdminRepo>(
+    query: AdminAuditQuery,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    catch_error_403!(admin);
+
+    let list = catch_error_500!(
+        db.list_admin_audit(
+            query.admin.as_deref(),
+            query.target.as_deref(),
+            query.limit.unwrap_or(100),
+            query.offset.unwrap_or_default(),
+        )
+        .await
+    );
+    let count = list.first().map(|it| it.total_count).unwrap_or_default();
+    let items = list.into_iter().map(AdminAuditEntry::from).collect();
+    response!(VecWithTotal { count, items })
+}
+
+#[utoipa::path(
+    get,
+    tag = "admin",
+    path = "/admin/audit/writes",
+    params(WriteAuditQuery),
+    responses(
+        (status = 200, body = VecWriteAuditWithTotal, description = "Writes made with user tokens, most recent first"),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn list_write_audit<D: WriteAuditRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "audit" / "writes")
+        .and(warp::get())
+        .and(warp::query::<WriteAuditQuery>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(list_write_audit_handler::<D>)
+}
+
+pub async fn list_write_audit_handler<D: WriteAuditRepo>(
+    query: WriteAuditQuery,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    catch_error_403!(admin);
+
+    let list = catch_error_500!(
+        db.list_write_audit(
+            query.actor.as_deref(),
+            query.target.as_deref(),
+            query.action,
+            query.limit.unwrap_or(100),
+            query.offset.unwrap_or_default(),
+        )
+        .await
+    );
+    let count = list.first().map(|it| it.total_count).unwrap_or_default();
+    let items = list.into_iter().map(WriteAuditEntry::from).collect();
+    response!(VecWithTotal { count, items })
+}
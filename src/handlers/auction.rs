@@ -1,17 +1,22 @@
-use crate::db::queries::Queries;
-use crate::db::Address;
+use crate::db::repo::{ApiRepo, AuctionRepo, CollectionRepo, NftRepo};
+use crate::handlers::http_cache::cacheable;
 use crate::handlers::nft::collect_nft_and_collection;
+use crate::handlers::query_string::{query_params, FromQuery, QueryParams};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
+use crate::model::FromDb;
 use crate::model::{Auction, AuctionBid, Collection, VecWith, NFT};
-use crate::{api_doc_addon, catch_empty, catch_error_500, response, schema};
+use crate::{api_doc_addon, catch_empty, catch_error_400, catch_error_500, response, schema};
+pub use api_types::{AuctionBidsQuery, AuctionsQuery, AuctionsSortOrder, GetAuctionResult};
 use schema::*;
-use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use std::{collections::HashMap, convert::Infallible};
 use utoipa::OpenApi;
-use utoipa::ToSchema;
-use warp::{http::StatusCode, Filter};
+use warp::http::{Method, StatusCode};
+use warp::Filter;
 #[derive(OpenApi)]
 #[openapi(
-    paths(get_auctions, get_auction, get_auction_bids),
+    paths(get_auctions, get_auction, get_auction_by_query, get_auction_bids),
     components(schemas(
         AuctionsQuery,
         AuctionsSortOrder,
@@ -27,6 +32,21 @@ use warp::{http::StatusCode, Filter};
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(Method::POST, "/auctions", get_auctions(ctx.db.clone()))
+        .deprecated(Method::POST, "/auction", get_auction(ctx.db.clone()))
+        .add(
+            Method::GET,
+            "/auction",
+            get_auction_by_query(ctx.db.clone(), ctx.realtime_ttl),
+        )
+        .add(
+            Method::POST,
+            "/auction/bids",
+            get_auction_bids(ctx.db.clone()),
+        );
+}
+
 #[utoipa::path(
     post,
     tag = "auction",
@@ -37,19 +57,20 @@ api_doc_addon!(ApiDoc);
         (status = 500),
     ),
 )]
-pub fn get_auctions(
-    db: Queries,
+pub fn get_auctions<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("auctions")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<AuctionsQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_auctions_handler)
+        .and_then(get_auctions_handler::<D>)
 }
 
-pub async fn get_auctions_handler(
+pub async fn get_auctions_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     params: AuctionsQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let owners: &[String] = params.owners.as_deref().unwrap_or(&[]);
     let collections = params.collections.as_deref().unwrap_or(&[]);
@@ -70,7 +91,7 @@ pub async fn get_auctions_handler(
     let count = list.first().map(|it| it.cnt).unwrap_or_default();
     let ret: Vec<Auction> = list
         .iter()
-        .map(|col| Auction::from_db(col, &db.tokens))
+        .map(|col| Auction::from_db(col, db.tokens()))
         .collect();
     let nft_ids = ret.iter().map(|x| x.nft.clone()).collect();
     let (nft, collection) = catch_error_500!(collect_nft_and_collection(&db, &nft_ids).await);
@@ -86,44 +107,14 @@ pub async fn get_auctions_handler(
     response!(&ret)
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub struct AuctionsQuery {
-    pub owners: Option<Vec<Address>>,
-    pub collections: Option<Vec<Address>>,
-    pub tokens: Option<Vec<Address>>,
-    pub sort: Option<AuctionsSortOrder>,
-    pub limit: Option<usize>,
-    #[schema(example = 1001)]
-    pub offset: Option<usize>,
-}
-
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub struct AuctionBidsQuery {
-    pub auction: Address,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-}
-
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub enum AuctionsSortOrder {
-    #[serde(rename = "start-date")]
-    StartDate,
-    #[serde(rename = "bids-count")]
-    BidsCount,
-    #[serde(rename = "average")]
-    Average,
-    #[serde(rename = "average-in-hour")]
-    AverageInHour,
-    #[serde(rename = "average-in-day")]
-    AverageInDay,
-}
-
-#[derive(Debug, Clone, Serialize, ToSchema)]
-pub struct GetAuctionResult {
-    pub auction: Auction,
-    pub bid: Option<AuctionBid>,
-    pub nft: HashMap<Address, NFT>,
-    pub collection: HashMap<Address, Collection>,
+impl FromQuery for AuctionBidsQuery {
+    fn from_query(query: &QueryParams) -> Result<Self, String> {
+        Ok(AuctionBidsQuery {
+            auction: query.require("auction")?,
+            limit: query.get("limit")?,
+            offset: query.get("offset")?,
+        })
+    }
 }
 
 #[utoipa::path(
@@ -137,19 +128,56 @@ pub struct GetAuctionResult {
     ),
 )]
 
-pub fn get_auction(
-    db: Queries,
+pub fn get_auction<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("auction")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<AuctionBidsQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_auction_handler)
+        .and_then(get_auction_handler::<D>)
+}
+
+#[utoipa::path(
+    get,
+    tag = "auction",
+    path = "/auction",
+    params(
+        ("auction" = String, Query, description = "Auction address"),
+    ),
+    responses(
+        (status = 200, body = GetAuctionResult),
+        (status = 304),
+        (status = 400),
+        (status = 500),
+    ),
+)]
+pub fn get_auction_by_query<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
+    max_age: Duration,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    cacheable(
+        warp::path!("auction")
+            .and(warp::get())
+            .and(query_params())
+            .and(warp::any().map(move || db.clone()))
+            .and_then(get_auction_by_query_handler::<D>),
+        max_age,
+    )
+}
+
+pub async fn get_auction_by_query_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
+    query: QueryParams,
+    db: D,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let params = catch_error_400!(AuctionBidsQuery::from_query(&query));
+    get_auction_handler(params, db).await
 }
 
-pub async fn get_auction_handler(
+pub async fn get_auction_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     params: AuctionBidsQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let auction = catch_error_500!(db.get_nft_auction(&params.auction).await);
     let auction = catch_empty!(auction, "auction not found");
@@ -158,9 +186,9 @@ pub async fn get_auction_handler(
     let (nft, collection) = catch_error_500!(collect_nft_and_collection(&db, &nft_ids).await);
 
     let bid = catch_error_500!(db.get_nft_auction_last_bid(&params.auction).await);
-    let bid = bid.map(|b| AuctionBid::from_db(&b, &auction, &db.tokens));
+    let bid = bid.map(|b| AuctionBid::from_db((&b, &auction), db.tokens()));
 
-    let auction = Auction::from_db(&auction, &db.tokens);
+    let auction = Auction::from_db(&auction, db.tokens());
     let ret = GetAuctionResult {
         auction,
         nft,
@@ -181,19 +209,20 @@ pub async fn get_auction_handler(
         (status = 500),
     ),
 )]
-pub fn get_auction_bids(
-    db: Queries,
+pub fn get_auction_bids<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("auction" / "bids")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<AuctionBidsQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_auction_bids_handler)
+        .and_then(get_auction_bids_handler::<D>)
 }
 
-pub async fn get_auction_bids_handler(
+pub async fn get_auction_bids_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     params: AuctionBidsQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let auc = catch_error_500!(db.get_nft_auction(&params.auction).await);
     let auc = catch_empty!(auc, "auction not found");
@@ -211,7 +240,7 @@ pub async fn get_auction_bids_handler(
 
     let ret: Vec<AuctionBid> = bids
         .iter()
-        .map(|b| AuctionBid::from_db(b, &auc, &db.tokens))
+        .map(|b| AuctionBid::from_db((b, &auc), db.tokens()))
         .collect();
 
     let auction_ids: Vec<String> = ret.iter().map(|x| x.auction.clone()).collect();
@@ -230,12 +259,12 @@ pub async fn get_auction_bids_handler(
     response!(&ret)
 }
 
-pub async fn collect_auctions(
-    db: &Queries,
+pub async fn collect_auctions<D: AuctionRepo>(
+    db: &D,
     ids: &[String],
 ) -> anyhow::Result<HashMap<String, Auction>> {
     let dblist = db.collect_auctions(ids).await?;
-    let list = dblist.iter().map(|col| Auction::from_db(col, &db.tokens));
+    let list = dblist.iter().map(|col| Auction::from_db(col, db.tokens()));
     let mut map = HashMap::new();
     for item in list {
         map.insert(item.address.clone(), item.clone());
@@ -243,8 +272,8 @@ pub async fn collect_auctions(
     Ok(map)
 }
 
-pub async fn collect_auctions_nfts_collections(
-    db: &Queries,
+pub async fn collect_auctions_nfts_collections<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: &D,
     auction_ids: &[String],
 ) -> anyhow::Result<(
     HashMap<String, NFT>,
@@ -1,12 +1,14 @@
+use crate::db::repo::ApiRepo;
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
 use crate::model::LoginData;
 use crate::services::auth::AuthService;
 use crate::{api_doc_addon, catch_error_400, response};
-use serde::{Deserialize, Serialize};
+pub use api_types::SignInPayload;
 use std::convert::Infallible;
 use std::sync::Arc;
 use utoipa::OpenApi;
-use utoipa::ToSchema;
-use warp::http::StatusCode;
+use warp::http::{Method, StatusCode};
 use warp::Filter;
 #[derive(OpenApi)]
 #[openapi(paths(sign_in), components(schemas(SignInPayload)), tags(
@@ -15,15 +17,8 @@ use warp::Filter;
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct SignInPayload {
-    pub public_key: String,
-    pub address: String,
-    pub wallet_type: String,
-    pub timestamp: u64,
-    pub signature: String,
-    pub with_signature_id: Option<i32>,
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(Method::POST, "/user/sign_in", sign_in(ctx.auth.clone()));
 }
 
 #[utoipa::path(
@@ -41,6 +36,7 @@ pub fn sign_in(
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("user" / "sign_in")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<SignInPayload>())
         .and(warp::any().map(move || auth_service.clone()))
         .and_then(sign_in_handler)
@@ -1,23 +1,27 @@
-use crate::db::queries::Queries;
-use crate::db::Address;
-use crate::handlers::{calculate_hash, OrderDirection};
+use crate::db::repo::{ApiRepo, CollectionRepo};
+use crate::handlers::http_cache::{cache_max_age, cacheable};
+use crate::handlers::query_string::{query_params, FromQuery, QueryParams};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::{calculate_hash, MAX_JSON_BODY_BYTES};
 use crate::model::{Collection, CollectionDetails, CollectionSimple, VecWithTotal};
 use crate::schema::VecCollectionSimpleWithTotal;
 use crate::schema::VecCollectionsWithTotal;
-use crate::{api_doc_addon, catch_empty, catch_error_500, response};
+use crate::{api_doc_addon, catch_empty, catch_error_400, catch_error_500, response};
+pub use api_types::{
+    CollectionListOrder, CollectionListOrderField, CollectionParam, ListCollectionsParams,
+    ListCollectionsSimpleParams, OwnerParam,
+};
 use moka::future::Cache;
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::fmt::Display;
 use std::{collections::HashMap, convert::Infallible};
 use utoipa::OpenApi;
-use utoipa::ToSchema;
-use warp::http::StatusCode;
+use warp::http::{Method, StatusCode};
 use warp::Filter;
 #[derive(OpenApi)]
 #[openapi(
     paths(
         list_collections,
+        list_collections_by_query,
         list_collections_simple,
         get_collection,
         get_collections_by_owner
@@ -41,37 +45,55 @@ use warp::Filter;
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub enum CollectionListOrderField {
-    #[serde(rename = "firstMint")]
-    FirstMint,
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.deprecated(
+        Method::POST,
+        "/collections",
+        list_collections(ctx.db.clone(), ctx.collections_cache.clone()),
+    )
+    .add(
+        Method::GET,
+        "/collections",
+        list_collections_by_query(ctx.db.clone(), ctx.collections_cache.clone()),
+    )
+    .add(
+        Method::POST,
+        "/collections/simple",
+        list_collections_simple(ctx.db.clone(), ctx.collections_cache.clone()),
+    )
+    .add(
+        Method::POST,
+        "/collection/details",
+        get_collection(ctx.db.clone(), ctx.cache_1_sec.clone()),
+    )
+    .add(
+        Method::POST,
+        "/collections/by-owner",
+        get_collections_by_owner(ctx.db.clone()),
+    );
 }
 
-impl Display for CollectionListOrderField {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CollectionListOrderField::FirstMint => write!(f, "first_mint"),
-        }
+impl FromQuery for ListCollectionsParams {
+    fn from_query(query: &QueryParams) -> Result<Self, String> {
+        let order = match query.get("order.field")? {
+            Some(field) => Some(CollectionListOrder {
+                field,
+                direction: query.require("order.direction")?,
+            }),
+            None => None,
+        };
+        Ok(ListCollectionsParams {
+            name: query.get("name")?,
+            owners: query.list("owners")?,
+            verified: query.get("verified")?,
+            collections: query.list("collections")?,
+            limit: query.get("limit")?,
+            offset: query.get("offset")?,
+            order,
+        })
     }
 }
 
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub struct CollectionListOrder {
-    pub field: CollectionListOrderField,
-    pub direction: OrderDirection,
-}
-
-#[derive(Clone, Deserialize, Hash, ToSchema)]
-pub struct ListCollectionsParams {
-    pub name: Option<String>,
-    pub owners: Option<Vec<String>>,
-    pub verified: Option<bool>,
-    pub collections: Option<Vec<String>>,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-    pub order: Option<CollectionListOrder>,
-}
-
 #[utoipa::path(
     post,
     tag = "collection",
@@ -82,21 +104,68 @@ pub struct ListCollectionsParams {
         (status = 500),
     ),
 )]
-pub fn list_collections(
-    db: Queries,
+pub fn list_collections<D: CollectionRepo>(
+    db: D,
     cache: Cache<u64, Value>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("collections")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<ListCollectionsParams>())
         .and(warp::any().map(move || db.clone()))
         .and(warp::any().map(move || cache.clone()))
-        .and_then(list_collections_handler)
+        .and_then(list_collections_handler::<D>)
+}
+
+#[utoipa::path(
+    get,
+    tag = "collection",
+    path = "/collections",
+    params(
+        ("name" = Option<String>, Query, description = "Collection name filter"),
+        ("owners" = Option<Vec<String>>, Query, description = "Repeated or comma separated"),
+        ("verified" = Option<bool>, Query, description = "Only verified collections"),
+        ("collections" = Option<Vec<String>>, Query, description = "Repeated or comma separated"),
+        ("limit" = Option<usize>, Query, description = "Page size"),
+        ("offset" = Option<usize>, Query, description = "Page offset"),
+        ("order.field" = Option<CollectionListOrderField>, Query, description = "Sort field"),
+        ("order.direction" = Option<OrderDirection>, Query, description = "Required with order.field"),
+    ),
+    responses(
+        (status = 200, body = VecCollectionsWithTotal),
+        (status = 304),
+        (status = 400),
+        (status = 500),
+    ),
+)]
+pub fn list_collections_by_query<D: CollectionRepo>(
+    db: D,
+    cache: Cache<u64, Value>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let max_age = cache_max_age(&cache);
+    cacheable(
+        warp::path!("collections")
+            .and(warp::get())
+            .and(query_params())
+            .and(warp::any().map(move || db.clone()))
+            .and(warp::any().map(move || cache.clone()))
+            .and_then(list_collections_by_query_handler::<D>),
+        max_age,
+    )
+}
+
+pub async fn list_collections_by_query_handler<D: CollectionRepo>(
+    query: QueryParams,
+    db: D,
+    cache: Cache<u64, Value>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let params = catch_error_400!(ListCollectionsParams::from_query(&query));
+    list_collections_handler(params, db, cache).await
 }
 
-pub async fn list_collections_handler(
+pub async fn list_collections_handler<D: CollectionRepo>(
     params: ListCollectionsParams,
-    db: Queries,
+    db: D,
     cache: Cache<u64, Value>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let hash = calculate_hash(&params);
@@ -128,7 +197,7 @@ pub async fn list_collections_handler(
             let count = list.first().map(|it| it.cnt).unwrap_or_default();
             let mut items = vec![];
             for collection_detail in list {
-                let detail = catch_error_500!(CollectionDetails::from_db(collection_detail));
+                let detail = catch_error_500!(CollectionDetails::try_from(collection_detail));
                 items.push(detail);
             }
             ret = VecWithTotal { count, items };
@@ -144,19 +213,6 @@ pub async fn list_collections_handler(
     response!(&ret)
 }
 
-#[derive(Debug, Clone, Deserialize, Hash, ToSchema)]
-pub struct CollectionParam {
-    pub collection: Address,
-}
-
-#[derive(Debug, Clone, Deserialize, Hash, ToSchema)]
-pub struct ListCollectionsSimpleParams {
-    pub name: Option<String>,
-    pub verified: Option<bool>,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-}
-
 #[utoipa::path(
     post,
     tag = "collection",
@@ -167,21 +223,22 @@ pub struct ListCollectionsSimpleParams {
         (status = 500),
     ),
 )]
-pub fn list_collections_simple(
-    db: Queries,
+pub fn list_collections_simple<D: CollectionRepo>(
+    db: D,
     cache: Cache<u64, Value>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("collections" / "simple")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<ListCollectionsSimpleParams>())
         .and(warp::any().map(move || db.clone()))
         .and(warp::any().map(move || cache.clone()))
-        .and_then(list_collections_simple_handler)
+        .and_then(list_collections_simple_handler::<D>)
 }
 
-pub async fn list_collections_simple_handler(
+pub async fn list_collections_simple_handler<D: CollectionRepo>(
     params: ListCollectionsSimpleParams,
-    db: Queries,
+    db: D,
     cache: Cache<u64, Value>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let hash = calculate_hash(&params);
@@ -199,7 +256,7 @@ pub async fn list_collections_simple_handler(
                     .await
             );
             let count = list.first().map(|it| it.cnt).unwrap_or_default();
-            let items = list.into_iter().map(CollectionSimple::from_db).collect();
+            let items = list.into_iter().map(CollectionSimple::from).collect();
 
             ret = VecWithTotal { count, items };
             let value_for_cache =
@@ -224,21 +281,22 @@ pub async fn list_collections_simple_handler(
         (status = 500),
     ),
 )]
-pub fn get_collection(
-    db: Queries,
+pub fn get_collection<D: CollectionRepo>(
+    db: D,
     cache: Cache<u64, Value>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("collection" / "details")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<CollectionParam>())
         .and(warp::any().map(move || db.clone()))
         .and(warp::any().map(move || cache.clone()))
-        .and_then(get_collection_handler)
+        .and_then(get_collection_handler::<D>)
 }
 
-pub async fn get_collection_handler(
+pub async fn get_collection_handler<D: CollectionRepo>(
     param: CollectionParam,
-    db: Queries,
+    db: D,
     cache: Cache<u64, Value>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let hash = calculate_hash(&param);
@@ -248,7 +306,7 @@ pub async fn get_collection_handler(
         None => {
             let col = catch_error_500!(db.get_collection(&param.collection).await);
             let col = catch_empty!(col, "");
-            ret = catch_error_500!(CollectionDetails::from_db(col));
+            ret = catch_error_500!(CollectionDetails::try_from(col));
             let value_for_cache =
                 serde_json::to_value(ret.clone()).expect("Failed serializing cached value");
             cache.insert(hash, value_for_cache).await;
@@ -260,13 +318,6 @@ pub async fn get_collection_handler(
     response!(&ret)
 }
 
-#[derive(Debug, Clone, Deserialize, ToSchema)]
-pub struct OwnerParam {
-    pub owner: Address,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-}
-
 #[utoipa::path(
     post,
     tag = "collection",
@@ -277,19 +328,20 @@ pub struct OwnerParam {
         (status = 500),
     ),
 )]
-pub fn get_collections_by_owner(
-    db: Queries,
+pub fn get_collections_by_owner<D: CollectionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("collections" / "by-owner")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<OwnerParam>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_collections_by_owner_handler)
+        .and_then(get_collections_by_owner_handler::<D>)
 }
 
-pub async fn get_collections_by_owner_handler(
+pub async fn get_collections_by_owner_handler<D: CollectionRepo>(
     params: OwnerParam,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let owner = params.owner;
     let limit = params.limit.unwrap_or(100);
@@ -298,18 +350,18 @@ pub async fn get_collections_by_owner_handler(
     let list = catch_error_500!(db.list_collections_by_owner(&owner, limit, offset).await);
 
     let count = list.first().map(|it| it.cnt).unwrap_or_default();
-    let ret: Vec<Collection> = list.into_iter().map(Collection::from_db).collect();
+    let ret: Vec<Collection> = list.into_iter().map(Collection::from).collect();
     let ret = VecWithTotal { count, items: ret };
     response!(&ret)
 }
 
 #[allow(clippy::ptr_arg)]
-pub async fn collect_collections(
-    db: &Queries,
+pub async fn collect_collections<D: CollectionRepo>(
+    db: &D,
     ids: &Vec<String>,
 ) -> anyhow::Result<HashMap<String, Collection>> {
     let dblist = db.collect_collections(ids).await?;
-    let list = dblist.into_iter().map(Collection::from_db);
+    let list = dblist.into_iter().map(Collection::from);
     let mut map = HashMap::new();
     for item in list {
         map.insert(item.contract.address.clone(), item.clone());
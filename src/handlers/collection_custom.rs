@@ -1,24 +1,38 @@
-use crate::db::queries::Queries;
-use crate::db::{Address, Social};
+use crate::db::repo::{ApiRepo, CollectionRepo, WriteAuditRepo};
+use crate::db::Social;
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
+use crate::model::{VecWithTotal, WriteAuditEntry};
+use crate::schema::VecWriteAuditWithTotal;
 use crate::services::auth::AuthService;
-use crate::{api_doc_addon, catch_empty, catch_error_401, catch_error_403, catch_error_500};
+use crate::services::rate_limit::{client_ip, ClientIpSource};
+use crate::{
+    api_doc_addon, catch_empty, catch_error_401, catch_error_403, catch_error_500, response,
+};
+pub use api_types::{
+    CustomHistoryQuery, RevertCollectionCustomPayload, UpsertCollectionCustomPayload, WriteAction,
+};
 use http::{HeaderMap, HeaderValue};
-use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
 use std::sync::Arc;
 use utoipa::OpenApi;
-use utoipa::ToSchema;
-use warp::http::StatusCode;
+use warp::http::{Method, StatusCode};
 use warp::Filter;
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
-        upsert_collection_custom
+        upsert_collection_custom,
+        get_collection_custom_history,
+        revert_collection_custom
     ),
     components(schemas(
         UpsertCollectionCustomPayload,
-        Social
+        Social,
+        RevertCollectionCustomPayload,
+        WriteAuditEntry,
+        WriteAction,
+        VecWriteAuditWithTotal
     )),
     tags(
         (name = "collection-custom", description = "Collection-custom handlers"),
@@ -27,43 +41,57 @@ use warp::Filter;
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub struct UpsertCollectionCustomPayload {
-    pub address: Address,
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub wallpaper: Option<String>,
-    pub logo: Option<String>,
-    pub social: Option<Social>,
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::POST,
+        "/collections-custom",
+        upsert_collection_custom(ctx.db.clone(), ctx.auth.clone(), ctx.client_ip),
+    )
+    .add(
+        Method::GET,
+        "/collection/{address}/custom/history",
+        get_collection_custom_history(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::POST,
+        "/collection/{address}/custom/revert",
+        revert_collection_custom(ctx.db.clone(), ctx.auth.clone(), ctx.client_ip),
+    );
 }
 
 #[utoipa::path(
     post,
     tag = "collection",
     path = "/collections-custom",
-    request_body(content = UpsertCollectionCustomPayload, description = "Upsert collection"),
+    request_body(content = UpsertCollectionCustomPayload, description = "Upsert collection, the previous values are kept in the history"),
     responses(
         (status = 200),
+        (status = 401),
+        (status = 403),
         (status = 500),
     ),
 )]
-pub fn upsert_collection_custom(
-    db: Queries,
+pub fn upsert_collection_custom<D: CollectionRepo>(
+    db: D,
     auth_service: Arc<AuthService>,
+    ip_source: ClientIpSource,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("collections-custom")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<UpsertCollectionCustomPayload>())
         .and(warp::header::headers_cloned())
+        .and(client_ip(ip_source))
         .and(warp::any().map(move || db.clone()))
         .and(warp::any().map(move || auth_service.clone()))
-        .and_then(upsert_collection_custom_handler)
+        .and_then(upsert_collection_custom_handler::<D>)
 }
 
-pub async fn upsert_collection_custom_handler(
+pub async fn upsert_collection_custom_handler<D: CollectionRepo>(
     payload: UpsertCollectionCustomPayload,
     headers: HeaderMap<HeaderValue>,
-    db: Queries,
+    ip: Option<String>,
+    db: D,
     auth_service: Arc<AuthService>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let address = catch_error_401!(auth_service.authenticate(headers));
@@ -95,9 +123,135 @@ pub async fn upsert_collection_custom_handler(
             payload.wallpaper,
             payload.logo,
             serde_json::to_value(payload.social).expect("Failed parsing social medias"),
+            ip.as_deref(),
         )
         .await
     );
 
     Ok(Box::from(warp::reply::with_status("", StatusCode::OK)))
 }
+
+#[utoipa::path(
+    get,
+    tag = "collection",
+    path = "/collection/{address}/custom/history",
+    params(
+        ("address" = String, Path, description = "Collection address"),
+        CustomHistoryQuery
+    ),
+    responses(
+        (status = 200, body = VecWriteAuditWithTotal, description = "Most recent first, client ips are only shown to admins"),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn get_collection_custom_history<D: CollectionRepo + WriteAuditRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("collection" / String / "custom" / "history")
+        .and(warp::get())
+        .and(warp::query::<CustomHistoryQuery>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(get_collection_custom_history_handler::<D>)
+}
+
+pub async fn get_collection_custom_history_handler<D: CollectionRepo + WriteAuditRepo>(
+    address: String,
+    query: CustomHistoryQuery,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let caller = catch_error_401!(auth_service.authenticate(headers.clone()));
+    let is_admin = matches!(auth_service.authenticate_admin(headers), Ok(Some(_)));
+    if !is_admin {
+        let owned = catch_error_500!(db.validate_owner_of_collection(&address, &caller).await);
+        catch_error_403!(owned.filter(|count| *count > 0));
+    }
+
+    let list = catch_error_500!(
+        db.list_write_audit(
+            None,
+            Some(&address),
+            None,
+            query.limit.unwrap_or(100),
+            query.offset.unwrap_or_default(),
+        )
+        .await
+    );
+    let count = list.first().map(|it| it.total_count).unwrap_or_default();
+    let items = list
+        .into_iter()
+        .map(WriteAuditEntry::from)
+        .map(|mut entry| {
+            if !is_admin {
+                entry.ip = None;
+            }
+            entry
+        })
+        .collect();
+    response!(VecWithTotal { count, items })
+}
+
+#[utoipa::path(
+    post,
+    tag = "collection",
+    path = "/collection/{address}/custom/revert",
+    params(("address" = String, Path, description = "Collection address")),
+    request_body(content = RevertCollectionCustomPayload, description = "Restore the values written by a history entry"),
+    responses(
+        (status = 200),
+        (status = 401),
+        (status = 403),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn revert_collection_custom<D: CollectionRepo + WriteAuditRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    ip_source: ClientIpSource,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("collection" / String / "custom" / "revert")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<RevertCollectionCustomPayload>())
+        .and(warp::header::headers_cloned())
+        .and(client_ip(ip_source))
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(revert_collection_custom_handler::<D>)
+}
+
+pub async fn revert_collection_custom_handler<D: CollectionRepo + WriteAuditRepo>(
+    address: String,
+    payload: RevertCollectionCustomPayload,
+    headers: HeaderMap<HeaderValue>,
+    ip: Option<String>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let caller = catch_error_401!(auth_service.authenticate(headers.clone()));
+    let is_admin = matches!(auth_service.authenticate_admin(headers), Ok(Some(_)));
+    if !is_admin {
+        let owned = catch_error_500!(db.validate_owner_of_collection(&address, &caller).await);
+        catch_error_403!(owned.filter(|count| *count > 0));
+    }
+
+    let reverted = catch_error_500!(
+        db.revert_collection_custom(&caller, &address, payload.id, ip.as_deref())
+            .await
+    );
+    if !reverted {
+        return Ok(Box::from(warp::reply::with_status(
+            "History entry not found",
+            StatusCode::NOT_FOUND,
+        )));
+    }
+
+    Ok(Box::from(warp::reply::with_status("", StatusCode::OK)))
+}
@@ -0,0 +1,417 @@
+use crate::db::enums::{AuctionStatus, DirectBuyState, DirectSellState, NftEventType};
+use crate::handlers::router::RouterRegistry;
+use crate::model::{
+    Attribute, Auction, AuctionBid, Collection, CollectionDetails, CollectionDetailsPreviewMeta,
+    Contract, DirectBuy, DirectSell, Fee, OrderDirection, Price, NFT,
+};
+use crate::schema::{
+    Address, VecCollectionSimpleWithTotal, VecCollectionsWithTotal, VecWithAuction,
+    VecWithAuctionBids, VecWithDirectBuy, VecWithDirectSell, VecWithNFT,
+};
+use std::sync::Arc;
+use utoipa::openapi::{Deprecated, PathItemType};
+use utoipa::OpenApi;
+use warp::http::Method;
+use warp::Filter;
+
+use super::admin::ApiDocAddon as AdminApiDocAddon;
+use super::auction::ApiDocAddon as AuctionApiDocAddon;
+use super::auth::ApiDocAddon as AuthApiDocAddon;
+use super::collection::ApiDocAddon as CollectionApiDocAddon;
+use super::collection_custom::ApiDocAddon as CollectionCustomAddon;
+use super::events::ApiDocAddon as EventApiDocAddon;
+use super::graphql::ApiDocAddon as GraphqlApiDocAddon;
+use super::metadata_override::ApiDocAddon as MetadataOverrideApiDocAddon;
+use super::metadata_refresh::ApiDocAddon as MetadataRefreshApiDocAddon;
+use super::metadata_report::ApiDocAddon as MetadataReportApiDocAddon;
+use super::metrics::ApiDocAddon as MetricsApiDocAddon;
+use super::moderation::ApiDocAddon as ModerationApiDocAddon;
+use super::nft::ApiDocAddon as NftApiDocAddon;
+use super::orderbook::ApiDocAddon as OrderBookApiDocAddon;
+use super::owner::ApiDocAddon as OwnerApiDocAddon;
+use super::quote::ApiDocAddon as QuoteApiDocAddon;
+use super::token::ApiDocAddon as TokenApiDocAddon;
+use super::user::ApiDocAddon as UserApiDocAddon;
+use super::verification::ApiDocAddon as VerificationApiDocAddon;
+use super::ApiDocAddon as ModuleApiDocAddon;
+
+#[derive(OpenApi)]
+#[openapi(
+    components(schemas(
+        Address,
+        Auction,
+        Collection,
+        DirectBuy,
+        DirectSell,
+        Fee,
+        DirectBuyState,
+        NFT,
+        Contract,
+        Price,
+        AuctionBid,
+        DirectSellState,
+        AuctionStatus,
+        OrderDirection,
+        CollectionDetails,
+        CollectionDetailsPreviewMeta, NftEventType, Attribute,
+        VecWithAuction,
+        VecWithAuctionBids,
+        VecWithDirectBuy,
+        VecWithDirectSell,
+        VecWithNFT,
+        VecCollectionsWithTotal,
+        VecCollectionSimpleWithTotal
+    )),
+    info(
+        title = "Marketplace API",
+        description = "Paths are served under `/v1`, `/v2` and unprefixed as aliases of `/v1`. Deprecated operations are not available in `/v2`."
+    ),
+    modifiers(
+        &AdminApiDocAddon,
+        &AuctionApiDocAddon,
+        &AuthApiDocAddon,
+        &CollectionApiDocAddon,
+        &MetadataOverrideApiDocAddon,
+        &MetadataRefreshApiDocAddon,
+        &MetadataReportApiDocAddon,
+        &MetricsApiDocAddon,
+        &ModerationApiDocAddon,
+        &EventApiDocAddon,
+        &GraphqlApiDocAddon,
+        &NftApiDocAddon,
+        &OrderBookApiDocAddon,
+        &OwnerApiDocAddon,
+        &QuoteApiDocAddon,
+        &TokenApiDocAddon,
+        &UserApiDocAddon,
+        &VerificationApiDocAddon,
+        &ModuleApiDocAddon,
+        &CollectionCustomAddon
+    )
+)]
+struct ApiDoc;
+
+/// Spec of the api with the operations of deprecated routes marked as such
+pub fn api_spec(registry: &RouterRegistry) -> utoipa::openapi::OpenApi {
+    let mut spec = ApiDoc::openapi();
+    for route in registry.routes().filter(|r| r.deprecated) {
+        let operation = path_item_type(&route.method).and_then(|typ| {
+            spec.paths
+                .paths
+                .get_mut(route.path)?
+                .operations
+                .get_mut(&typ)
+        });
+        if let Some(operation) = operation {
+            operation.deprecated = Some(Deprecated::True);
+        }
+    }
+    spec
+}
+
+pub fn path_item_type(method: &Method) -> Option<PathItemType> {
+    Some(match *method {
+        Method::GET => PathItemType::Get,
+        Method::POST => PathItemType::Post,
+        Method::PUT => PathItemType::Put,
+        Method::DELETE => PathItemType::Delete,
+        Method::PATCH => PathItemType::Patch,
+        Method::HEAD => PathItemType::Head,
+        Method::OPTIONS => PathItemType::Options,
+        _ => return None,
+    })
+}
+
+/// `/swagger.json`, Swagger UI at `/docs` and ReDoc at `/docs/redoc`
+pub fn routes(
+    spec: utoipa::openapi::OpenApi,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let spec = Arc::new(spec);
+    let swagger_json = warp::path!("swagger.json")
+        .and(warp::get())
+        .map(move || warp::reply::json(spec.as_ref()));
+    let swagger_ui_html = swagger_ui_html();
+    let swagger_ui = warp::path!("docs")
+        .and(warp::get())
+        .map(move || warp::reply::html(swagger_ui_html.clone()));
+    let redoc_html = redoc_html();
+    let redoc = warp::path!("docs" / "redoc")
+        .and(warp::get())
+        .map(move || warp::reply::html(redoc_html.clone()));
+    let assets = warp::path!("docs" / "assets" / String)
+        .and(warp::get())
+        .and_then(|name: String| async move {
+            let (_, content_type, content) = assets::FILES
+                .iter()
+                .find(|(file, ..)| *file == name)
+                .ok_or_else(warp::reject::not_found)?;
+            let reply = warp::reply::with_header(*content, "content-type", *content_type);
+            Ok::<_, warp::Rejection>(warp::reply::with_header(
+                reply,
+                "cache-control",
+                "public, max-age=86400",
+            ))
+        });
+
+    swagger_json.or(swagger_ui).or(redoc).or(assets)
+}
+
+/// Scripts and styles of the doc pages. The `bundled-docs` feature serves them from the
+/// binary under `/docs/assets`, run `assets/docs/fetch.sh` before building with it
+#[cfg(feature = "bundled-docs")]
+mod assets {
+    pub const SWAGGER_UI_CSS: &str = "/docs/assets/swagger-ui.css";
+    pub const SWAGGER_UI_JS: &str = "/docs/assets/swagger-ui-bundle.js";
+    pub const REDOC_JS: &str = "/docs/assets/redoc.standalone.js";
+
+    /// File name, content type and content
+    pub const FILES: &[(&str, &str, &[u8])] = &[
+        (
+            "swagger-ui.css",
+            "text/css",
+            include_bytes!("../../assets/docs/swagger-ui.css"),
+        ),
+        (
+            "swagger-ui-bundle.js",
+            "application/javascript",
+            include_bytes!("../../assets/docs/swagger-ui-bundle.js"),
+        ),
+        (
+            "redoc.standalone.js",
+            "application/javascript",
+            include_bytes!("../../assets/docs/redoc.standalone.js"),
+        ),
+    ];
+}
+
+/// Same versions as `assets/docs/fetch.sh`, loaded by the browser from their CDNs
+#[cfg(not(feature = "bundled-docs"))]
+mod assets {
+    pub const SWAGGER_UI_CSS: &str = "https://unpkg.com/swagger-ui-dist@5.17.14/swagger-ui.css";
+    pub const SWAGGER_UI_JS: &str =
+        "https://unpkg.com/swagger-ui-dist@5.17.14/swagger-ui-bundle.js";
+    pub const REDOC_JS: &str = "https://cdn.redoc.ly/redoc/v2.1.5/bundles/redoc.standalone.js";
+
+    pub const FILES: &[(&str, &str, &[u8])] = &[];
+}
+
+fn swagger_ui_html() -> String {
+    format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>Marketplace API</title>
+  <link rel="stylesheet" href="{css}" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="{js}" crossorigin></script>
+  <script>
+    window.ui = SwaggerUIBundle({{ url: "/swagger.json", dom_id: "#swagger-ui" }});
+  </script>
+</body>
+</html>
+"##,
+        css = assets::SWAGGER_UI_CSS,
+        js = assets::SWAGGER_UI_JS,
+    )
+}
+
+fn redoc_html() -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>Marketplace API</title>
+</head>
+<body>
+  <redoc spec-url="/swagger.json"></redoc>
+  <script src="{js}"></script>
+</body>
+</html>
+"#,
+        js = assets::REDOC_JS,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfg::{RateLimitConfig, VersioningConfig};
+    use crate::db::queries::Queries;
+    use crate::graphql::build_schema;
+    use crate::handlers::register_routes;
+    use crate::handlers::router::{BoxedRoute, RouteContext, RouteInfo};
+    use crate::services::auth::AuthService;
+    use crate::services::error::Error;
+    use crate::services::rate_limit::{ClientIpSource, RateLimiter};
+    use crate::services::suggest::SuggestIndex;
+    use crate::token::TokenDict;
+    use moka::future::Cache;
+    use serde_json::Value;
+    use std::time::Duration;
+    use warp::filters::body::BodyDeserializeError;
+
+    fn registry() -> RouterRegistry {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(200))
+            .connect_lazy("postgres://localhost/test")
+            .expect("lazy pool");
+        let db = Queries::new(Arc::new(pool), TokenDict::new(vec![]));
+        let ctx = RouteContext {
+            db: db.clone(),
+            auth: Arc::new(AuthService::new(60, "secret".into(), String::new())),
+            graphql: build_schema(db.clone()),
+            rate_limiter: RateLimiter::new(db, RateLimitConfig::default()),
+            cache_5_minutes: Cache::new(1),
+            cache_minute: Cache::new(1),
+            cache_10_sec: Cache::new(1),
+            cache_1_sec: Cache::new(1),
+            collections_cache: Cache::new(1),
+            realtime_ttl: Duration::from_secs(1),
+            client_ip: ClientIpSource::Peer,
+            suggest: SuggestIndex::new(0),
+            metadata_refresh_cooldown: Duration::from_secs(1),
+        };
+        let mut registry = RouterRegistry::default();
+        register_routes(&ctx, &mut registry);
+        registry
+    }
+
+    /// Whether the router has a route for the method and the OpenAPI path,
+    /// path parameters are filled with a sample address
+    async fn is_served(router: &BoxedRoute, method: &Method, path: &str) -> bool {
+        let path = path
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with('{') {
+                    "0:0000000000000000000000000000000000000000000000000000000000000000"
+                } else {
+                    segment
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let served = warp::test::request()
+            .method(method.as_str())
+            .path(&format!("/v1{path}"))
+            .json(&serde_json::json!({}))
+            .filter(router)
+            .await;
+        match served {
+            Ok(_) => true,
+            // a route that matched and rejected the request is combined with the
+            // `MethodNotAllowed` of the routes at the same path with other methods
+            Err(rejection) => {
+                !rejection.is_not_found()
+                    && (rejection.find::<warp::reject::MethodNotAllowed>().is_none()
+                        || rejection.find::<Error>().is_some()
+                        || rejection.find::<BodyDeserializeError>().is_some()
+                        || rejection.find::<warp::reject::InvalidQuery>().is_some()
+                        || rejection.find::<warp::reject::MissingHeader>().is_some()
+                        || rejection.find::<warp::reject::InvalidHeader>().is_some())
+            }
+        }
+    }
+
+    fn documented(spec: &utoipa::openapi::OpenApi, method: &Method, path: &str) -> bool {
+        let typ = path_item_type(method).expect("standard method");
+        spec.paths
+            .paths
+            .get(path)
+            .is_some_and(|item| item.operations.contains_key(&typ))
+    }
+
+    #[tokio::test]
+    async fn every_route_is_documented() {
+        let registry = registry();
+        let spec = api_spec(&registry);
+        let routes: Vec<RouteInfo> = registry.routes().cloned().collect();
+        let router = registry.build(&VersioningConfig::default());
+        let mut missing = Vec::new();
+        for route in routes {
+            let name = format!("{} {}", route.method, route.path);
+            assert!(
+                is_served(&router, &route.method, route.path).await,
+                "{name} is registered at a path the router doesn't serve"
+            );
+            if !documented(&spec, &route.method, route.path) {
+                missing.push(name);
+            }
+        }
+        assert!(
+            missing.is_empty(),
+            "routes missing from the spec: {missing:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn every_documented_operation_is_served() {
+        let registry = registry();
+        let spec = api_spec(&registry);
+        let router = registry.build(&VersioningConfig::default());
+        let mut unserved = Vec::new();
+        for (path, item) in &spec.paths.paths {
+            for typ in item.operations.keys() {
+                let method = serde_json::to_value(typ).expect("method serializes");
+                let method = method.as_str().expect("method name").to_uppercase();
+                let method = Method::from_bytes(method.as_bytes()).expect("http method");
+                if !is_served(&router, &method, path).await {
+                    unserved.push(format!("{method} {path}"));
+                }
+            }
+        }
+        assert!(
+            unserved.is_empty(),
+            "documented operations without a route: {unserved:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn doc_pages_assets_are_served() {
+        let docs = routes(api_spec(&registry()));
+        for (name, ..) in assets::FILES {
+            let response = warp::test::request()
+                .path(&format!("/docs/assets/{name}"))
+                .reply(&docs)
+                .await;
+            assert_eq!(response.status(), 200, "{name}");
+        }
+        let response = warp::test::request()
+            .path("/docs/assets/unknown.js")
+            .reply(&docs)
+            .await;
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn every_schema_reference_resolves() {
+        let spec = serde_json::to_value(api_spec(&registry())).expect("spec serializes");
+        let schemas = &spec["components"]["schemas"];
+        let mut refs = Vec::new();
+        collect_refs(&spec, &mut refs);
+        let dangling: Vec<&String> = refs
+            .iter()
+            .filter(|r| {
+                let name = r.trim_start_matches("#/components/schemas/");
+                schemas.get(name).is_none()
+            })
+            .collect();
+        assert!(dangling.is_empty(), "unknown schemas: {dangling:?}");
+    }
+
+    fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(r)) = map.get("$ref") {
+                    refs.push(r.clone());
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+}
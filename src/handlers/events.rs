@@ -1,6 +1,9 @@
-use crate::db::queries::Queries;
-use crate::db::NftEventType;
-use crate::handlers::calculate_hash;
+use crate::db::repo::{ApiRepo, EventRepo, NftRepo};
+use crate::handlers::http_cache::{cache_max_age, cacheable};
+use crate::handlers::moderation::{private_reply, wants_hidden};
+use crate::handlers::query_string::{query_params, FromQuery, QueryParams};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::{calculate_hash, MAX_JSON_BODY_BYTES};
 use crate::model::AuctionActive;
 use crate::model::AuctionBidPlaced;
 use crate::model::AuctionCanceled;
@@ -12,23 +15,36 @@ use crate::model::NftEventDirectSell;
 use crate::model::NftEventMint;
 use crate::model::NftEventTransfer;
 use crate::model::NftEvents;
-use crate::{api_doc_addon, catch_error_500, model::SearchResult, response};
+use crate::services::auth::AuthService;
+use crate::services::suggest::SuggestIndex;
+use crate::{
+    api_doc_addon, catch_error_400, catch_error_401, catch_error_403, catch_error_500,
+    model::{SearchResult, Suggestion},
+    response,
+};
+pub use api_types::{EventsQuery, SearchEntity, SearchQuery, SearchRes, SuggestQuery, SuggestRes};
+use http::{HeaderMap, HeaderValue};
 use moka::future::Cache;
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
 use utoipa::OpenApi;
-use utoipa::ToSchema;
-use warp::http::StatusCode;
+use warp::http::header::CONTENT_TYPE;
+use warp::http::{Method, StatusCode};
 use warp::hyper::body::Bytes;
 use warp::Filter;
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(search_all, get_events),
+    paths(search_all, search_suggest, get_events, get_events_by_query),
     components(schemas(
         SearchResult,
         SearchRes,
+        SearchQuery,
+        SearchEntity,
+        SuggestRes,
+        Suggestion,
         EventsQuery,
         NftEvents,
         NftEvent,
@@ -50,35 +66,211 @@ use warp::Filter;
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
+const SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+const MAX_SEARCH_CHARS: usize = 200;
+const SUGGEST_LIMIT: usize = 5;
+const MAX_SUGGEST_LIMIT: usize = 20;
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.deprecated(
+        Method::POST,
+        "/events",
+        get_events(ctx.db.clone(), ctx.cache_10_sec.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/events",
+        get_events_by_query(ctx.db.clone(), ctx.cache_10_sec.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::POST,
+        "/search",
+        search_all(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/search/suggest",
+        search_suggest(ctx.db.clone(), ctx.suggest.clone(), ctx.realtime_ttl),
+    );
+}
+
 #[utoipa::path(
     post,
     tag = "event",
     path = "/search",
-    request_body(content = String, description = "Search events"),
+    request_body(content = SearchQuery, description = "Matched against names, descriptions, addresses, usernames and nft ids, typos included. A `text/plain` body is the search text alone"),
+    params(
+        ("X-Include-Hidden" = Option<bool>, Header, description = "Admins only, keep hidden nfts and collections"),
+    ),
     responses(
-        (status = 200, body = SearchRes),
+        (status = 200, body = SearchRes, description = "Most relevant first"),
+        (status = 400),
+        (status = 401),
+        (status = 403),
         (status = 500),
     ),
 )]
-pub fn search_all(
-    db: Queries,
+pub fn search_all<D: NftRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("search")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::bytes())
+        .and(warp::header::headers_cloned())
         .and(warp::any().map(move || db.clone()))
-        .and_then(search_all_handler)
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(search_all_handler::<D>)
 }
 
-pub async fn search_all_handler(
-    query: Bytes,
-    db: Queries,
+pub async fn search_all_handler<D: NftRepo>(
+    body: Bytes,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
-    let query = String::from_utf8(query.into()).expect("err converting to String");
-    let items = catch_error_500!(db.search_all(&query).await);
-    let items: Vec<SearchResult> = items.into_iter().map(SearchResult::from_db).collect();
+    let include_hidden = wants_hidden(&headers);
+    let query = catch_error_400!(search_query(&headers, &body));
+    let after = catch_error_400!(query.cursor.as_deref().map(parse_search_cursor).transpose());
+    if include_hidden {
+        let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+        catch_error_403!(admin);
+    }
+
+    let entities = match query.types.is_empty() {
+        true => &SearchEntity::ALL[..],
+        false => &query.types[..],
+    };
+    let limit = query
+        .limit
+        .unwrap_or(SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let mut items = catch_error_500!(
+        db.search_all(
+            query.query.trim(),
+            entities,
+            query.collections.as_deref(),
+            after
+                .as_ref()
+                .map(|(score, address)| (*score, address.as_str())),
+            limit + 1,
+            include_hidden,
+        )
+        .await
+    );
+    let next_cursor = match items.len() > limit {
+        true => {
+            items.truncate(limit);
+            items
+                .last()
+                .map(|last| format!("{}:{}", last.score, last.address))
+        }
+        false => None,
+    };
+    let items: Vec<SearchResult> = items.into_iter().map(SearchResult::from).collect();
     let count = items.len();
-    response!(&SearchRes { items, count })
+    let response = SearchRes {
+        items,
+        count,
+        next_cursor,
+    };
+    if include_hidden {
+        return private_reply(&response);
+    }
+    response!(&response)
+}
+
+/// `application/json` bodies are a [`SearchQuery`], anything else is the search text
+fn search_query(headers: &HeaderMap<HeaderValue>, body: &[u8]) -> anyhow::Result<SearchQuery> {
+    let is_json = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let query = match is_json {
+        true => serde_json::from_slice(body)?,
+        false => SearchQuery {
+            query: String::from_utf8(body.to_vec())?,
+            ..Default::default()
+        },
+    };
+    anyhow::ensure!(
+        query.query.chars().count() <= MAX_SEARCH_CHARS,
+        "query must be at most {MAX_SEARCH_CHARS} characters"
+    );
+    Ok(query)
+}
+
+/// `nextCursor` is the score and the address of the last result
+fn parse_search_cursor(cursor: &str) -> anyhow::Result<(f64, String)> {
+    let (score, address) = cursor
+        .split_once(':')
+        .ok_or_else(|| anyhow::anyhow!("Invalid cursor"))?;
+    let score = score
+        .parse::<f64>()
+        .map_err(|_| anyhow::anyhow!("Invalid cursor"))?;
+    Ok((score, address.to_string()))
+}
+
+#[utoipa::path(
+    get,
+    tag = "event",
+    path = "/search/suggest",
+    params(
+        ("q" = String, Query, description = "Start of a collection, nft or user name, or of any of its words"),
+        ("limit" = Option<usize>, Query, description = "Per type, 5 by default, at most 20"),
+    ),
+    responses(
+        (status = 200, body = SuggestRes, description = "Served from memory, reloaded every few minutes"),
+        (status = 304),
+        (status = 400),
+        (status = 500),
+    ),
+)]
+pub fn search_suggest<D: NftRepo>(
+    db: D,
+    index: SuggestIndex,
+    max_age: Duration,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    cacheable(
+        warp::path!("search" / "suggest")
+            .and(warp::get())
+            .and(query_params())
+            .and(warp::any().map(move || db.clone()))
+            .and(warp::any().map(move || index.clone()))
+            .and_then(search_suggest_handler::<D>),
+        max_age,
+    )
+}
+
+pub async fn search_suggest_handler<D: NftRepo>(
+    query: QueryParams,
+    db: D,
+    index: SuggestIndex,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let query = catch_error_400!(SuggestQuery::from_query(&query));
+    if query.q.chars().count() > MAX_SEARCH_CHARS {
+        return Ok(Box::from(warp::reply::with_status(
+            format!("q must be at most {MAX_SEARCH_CHARS} characters"),
+            StatusCode::BAD_REQUEST,
+        )));
+    }
+    let limit = query
+        .limit
+        .unwrap_or(SUGGEST_LIMIT)
+        .clamp(1, MAX_SUGGEST_LIMIT);
+    let response = catch_error_500!(index.suggest(&db, &query.q, limit).await);
+    response!(&response)
+}
+
+impl FromQuery for SuggestQuery {
+    fn from_query(query: &QueryParams) -> Result<Self, String> {
+        Ok(SuggestQuery {
+            q: query.require("q")?,
+            limit: query.get("limit")?,
+        })
+    }
 }
 
 #[utoipa::path(
@@ -86,29 +278,100 @@ pub async fn search_all_handler(
     tag = "event",
     path = "/events",
     request_body(content = EventsQuery, description = "List events"),
+    params(
+        ("X-Include-Hidden" = Option<bool>, Header, description = "Admins only, keep events of hidden nfts and collections"),
+    ),
     responses(
         (status = 200, body = NftEvents),
+        (status = 401),
+        (status = 403),
         (status = 500),
     ),
 )]
-pub fn get_events(
-    db: Queries,
+pub fn get_events<D: EventRepo>(
+    db: D,
     cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("events")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<EventsQuery>())
+        .and(warp::header::headers_cloned())
         .and(warp::any().map(move || db.clone()))
         .and(warp::any().map(move || cache.clone()))
-        .and_then(get_events_handler)
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(get_events_handler::<D>)
 }
 
-pub async fn get_events_handler(
+#[utoipa::path(
+    get,
+    tag = "event",
+    path = "/events",
+    params(
+        ("owner" = Option<String>, Query, description = "Owner address"),
+        ("collections" = Option<Vec<String>>, Query, description = "Repeated or comma separated"),
+        ("nft" = Option<String>, Query, description = "NFT address"),
+        ("types" = Option<Vec<NftEventType>>, Query, description = "Repeated or comma separated"),
+        ("limit" = Option<usize>, Query, description = "Page size"),
+        ("offset" = Option<usize>, Query, description = "Page offset"),
+        ("withCount" = Option<bool>, Query, description = "Count the exact total"),
+        ("verified" = Option<bool>, Query, description = "Only verified collections"),
+        ("X-Include-Hidden" = Option<bool>, Header, description = "Admins only, keep events of hidden nfts and collections"),
+    ),
+    responses(
+        (status = 200, body = NftEvents),
+        (status = 304),
+        (status = 400),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn get_events_by_query<D: EventRepo>(
+    db: D,
+    cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let max_age = cache_max_age(&cache);
+    cacheable(
+        warp::path!("events")
+            .and(warp::get())
+            .and(query_params())
+            .and(warp::header::headers_cloned())
+            .and(warp::any().map(move || db.clone()))
+            .and(warp::any().map(move || cache.clone()))
+            .and(warp::any().map(move || auth_service.clone()))
+            .and_then(get_events_by_query_handler::<D>),
+        max_age,
+    )
+}
+
+pub async fn get_events_by_query_handler<D: EventRepo>(
+    query: QueryParams,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let query = catch_error_400!(EventsQuery::from_query(&query));
+    get_events_handler(query, headers, db, cache, auth_service).await
+}
+
+pub async fn get_events_handler<D: EventRepo>(
     query: EventsQuery,
-    db: Queries,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
     cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
-    let hash = calculate_hash(&query);
+    let include_hidden = wants_hidden(&headers);
+    if include_hidden {
+        let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+        catch_error_403!(admin);
+    }
+
+    let hash = calculate_hash(&(&query, include_hidden));
     let cached_value = cache.get(&hash);
 
     let response;
@@ -140,6 +403,7 @@ pub async fn get_events_handler(
                     final_limit,
                     with_count,
                     verified,
+                    include_hidden,
                 )
                 .await
             );
@@ -170,25 +434,23 @@ pub async fn get_events_handler(
         }
     }
 
+    if include_hidden {
+        return private_reply(&response);
+    }
     response!(&response)
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub struct EventsQuery {
-    pub owner: Option<String>,
-    pub collections: Option<Vec<String>>,
-    pub nft: Option<String>,
-    #[serde(rename = "types")]
-    pub event_type: Option<Vec<NftEventType>>,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-    #[serde(rename = "withCount")]
-    pub with_count: Option<bool>,
-    pub verified: Option<bool>,
-}
-
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub struct SearchRes {
-    pub items: Vec<SearchResult>,
-    pub count: usize,
+impl FromQuery for EventsQuery {
+    fn from_query(query: &QueryParams) -> Result<Self, String> {
+        Ok(EventsQuery {
+            owner: query.get("owner")?,
+            collections: query.list("collections")?,
+            nft: query.get("nft")?,
+            event_type: query.list("types")?,
+            limit: query.get("limit")?,
+            offset: query.get("offset")?,
+            with_count: query.get("withCount")?,
+            verified: query.get("verified")?,
+        })
+    }
 }
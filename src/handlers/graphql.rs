@@ -0,0 +1,89 @@
+use crate::api_doc_addon;
+use crate::db::repo::ApiRepo;
+use crate::graphql::{ApiSchema, ComplexityCharge};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
+use crate::services::error::limit_reply;
+use crate::services::rate_limit::{RateLimiter, RemoteAddr};
+pub use api_types::{GraphQLRequest, GraphQLResponse};
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
+use std::convert::Infallible;
+use std::sync::Arc;
+use utoipa::OpenApi;
+use warp::http::{HeaderMap, Method};
+use warp::Filter;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(graphql, graphql_playground),
+    components(schemas(GraphQLRequest, GraphQLResponse)),
+    tags(
+        (name = "graphql", description = "GraphQL endpoint"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::POST,
+        "/graphql",
+        graphql(ctx.graphql.clone(), ctx.rate_limiter.clone()),
+    )
+    .add(Method::GET, "/graphql", graphql_playground());
+}
+
+#[utoipa::path(
+    post,
+    tag = "graphql",
+    path = "/graphql",
+    request_body(content = GraphQLRequest, description = "GraphQL query"),
+    responses(
+        (status = 200, body = GraphQLResponse, description = "Errors are returned in the `errors` field"),
+        (status = 429, description = "The query complexity exceeds the remaining rate limit tokens"),
+    ),
+)]
+pub fn graphql(
+    schema: ApiSchema,
+    limiter: RateLimiter,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("graphql")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<async_graphql::Request>())
+        .and(warp::header::headers_cloned())
+        .and(warp::ext::optional::<RemoteAddr>())
+        .and(warp::any().map(move || schema.clone()))
+        .and(warp::any().map(move || limiter.clone()))
+        .and_then(graphql_handler)
+}
+
+pub async fn graphql_handler(
+    request: async_graphql::Request,
+    headers: HeaderMap,
+    remote: Option<RemoteAddr>,
+    schema: ApiSchema,
+    limiter: RateLimiter,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let charge = Arc::new(ComplexityCharge::new(limiter, headers, remote));
+    let response = schema.execute(request.data(charge.clone())).await;
+    if let Some(reply) = charge.refused().as_ref().and_then(limit_reply) {
+        return Ok(reply);
+    }
+    Ok(Box::new(warp::reply::json(&response)))
+}
+
+#[utoipa::path(
+    get,
+    tag = "graphql",
+    path = "/graphql",
+    responses(
+        (status = 200, content_type = "text/html", description = "GraphQL playground"),
+    ),
+)]
+pub fn graphql_playground(
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("graphql")
+        .and(warp::get())
+        .map(|| warp::reply::html(playground_source(GraphQLPlaygroundConfig::new("/graphql"))))
+}
@@ -0,0 +1,88 @@
+use moka::future::Cache;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+use std::time::Duration;
+use warp::http::{header, HeaderValue, StatusCode};
+use warp::hyper::body::{to_bytes, Body, Bytes};
+use warp::reply::Response;
+use warp::{Filter, Rejection, Reply};
+
+/// Browser/CDN max age matching the server side cache, so clients never keep
+/// a response longer than we do
+pub fn cache_max_age(cache: &Cache<u64, Value>) -> Duration {
+    cache.policy().time_to_live().unwrap_or_default()
+}
+
+/// Adds `Cache-Control`, unless the reply sets its own, and a strong `ETag` to successful
+/// replies of `filter`, answering `304 Not Modified` when the client already has the same body
+pub fn cacheable<F>(
+    filter: F,
+    max_age: Duration,
+) -> impl Filter<Extract = (Box<dyn Reply>,), Error = Rejection> + Clone
+where
+    F: Filter<Extract = (Box<dyn Reply>,), Error = Rejection> + Clone + Send + Sync + 'static,
+{
+    filter
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and_then(
+            move |reply: Box<dyn Reply>, if_none_match: Option<String>| {
+                with_etag(reply, if_none_match, max_age)
+            },
+        )
+}
+
+async fn with_etag(
+    reply: Box<dyn Reply>,
+    if_none_match: Option<String>,
+    max_age: Duration,
+) -> Result<Box<dyn Reply>, Infallible> {
+    let response = reply.into_response();
+    if response.status() != StatusCode::OK {
+        return Ok(Box::new(response));
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match to_bytes(body).await {
+        Ok(body) => body,
+        Err(e) => {
+            log::error!("failed to read response body: {e}");
+            return Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+    };
+
+    // cache hits are re-serialized from a `Value` with sorted keys, and maps come in
+    // random order, so the same content is hashed and sent in one canonical form
+    let body = match serde_json::from_slice::<Value>(&body) {
+        Ok(value) => serde_json::to_vec(&value).map(Bytes::from).unwrap_or(body),
+        Err(_) => body,
+    };
+
+    let etag = format!("\"{}\"", hex::encode(&Sha256::digest(&body)[..16]));
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        parts.headers.insert(header::ETAG, value);
+    }
+    // replies with their own policy, e.g. admin views of hidden items, keep it
+    if !parts.headers.contains_key(header::CACHE_CONTROL) {
+        let cache_control = format!("public, max-age={}", max_age.as_secs());
+        if let Ok(value) = HeaderValue::from_str(&cache_control) {
+            parts.headers.insert(header::CACHE_CONTROL, value);
+        }
+    }
+
+    if if_none_match.is_some_and(|tags| etag_matches(&tags, &etag)) {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_TYPE);
+        parts.headers.remove(header::CONTENT_LENGTH);
+        return Ok(Box::new(Response::from_parts(parts, Body::empty())));
+    }
+
+    Ok(Box::new(Response::from_parts(parts, Body::from(body))))
+}
+
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
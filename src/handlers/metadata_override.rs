@@ -0,0 +1,492 @@
+use crate::db::repo::{ApiRepo, CollectionRepo, MetadataOverrideRepo, NftRepo};
+use crate::db::{guess_mimetype, parse_attributes, NftMetadataOverrideWrite};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
+use crate::model::{NftMetadataOverride, NftMetadataOverrideBatch, VecWithTotal};
+use crate::schema::VecNftMetadataOverrideWithTotal;
+use crate::services::auth::AuthService;
+use crate::services::rate_limit::{client_ip, ClientIpSource};
+use crate::{
+    api_doc_addon, catch_error_400, catch_error_401, catch_error_403, catch_error_500, response,
+};
+pub use api_types::{
+    CustomHistoryQuery, NftMetadataOverrideItem, NftMetadataOverridePayload, NftOverrideField,
+};
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, HeaderValue};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::Arc;
+use utoipa::OpenApi;
+use warp::http::{Method, StatusCode};
+use warp::hyper::body::Bytes;
+use warp::Filter;
+
+/// Rows of a single bulk write
+const MAX_OVERRIDE_ITEMS: usize = 1000;
+const MAX_OVERRIDES_BODY_BYTES: u64 = 1024 * 1024;
+const MAX_NAME_CHARS: usize = 200;
+const MAX_PREVIEW_CHARS: usize = 2048;
+const MAX_ATTRIBUTES: usize = 100;
+const PREVIEW_SCHEMES: &[&str] = &["https://", "http://", "ipfs://", "ar://"];
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_metadata_overrides,
+        write_metadata_overrides,
+        write_metadata_override,
+        remove_metadata_override
+    ),
+    components(schemas(
+        NftMetadataOverride,
+        NftMetadataOverrideBatch,
+        NftMetadataOverrideItem,
+        NftMetadataOverridePayload,
+        NftOverrideField,
+        VecNftMetadataOverrideWithTotal
+    )),
+    tags(
+        (name = "metadata-override", description = "Metadata fixed by the collection owner"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::GET,
+        "/collection/{address}/metadata-overrides",
+        list_metadata_overrides(ctx.db.clone()),
+    )
+    .add(
+        Method::POST,
+        "/collection/{address}/metadata-overrides",
+        write_metadata_overrides(ctx.db.clone(), ctx.auth.clone(), ctx.client_ip),
+    )
+    .add(
+        Method::POST,
+        "/nft/{address}/metadata-override",
+        write_metadata_override(ctx.db.clone(), ctx.auth.clone(), ctx.client_ip),
+    )
+    .add(
+        Method::POST,
+        "/nft/{address}/metadata-override/remove",
+        remove_metadata_override(ctx.db.clone(), ctx.auth.clone(), ctx.client_ip),
+    );
+}
+
+#[utoipa::path(
+    get,
+    tag = "metadata-override",
+    path = "/collection/{address}/metadata-overrides",
+    params(
+        ("address" = String, Path, description = "Collection address"),
+        CustomHistoryQuery
+    ),
+    responses(
+        (status = 200, body = VecNftMetadataOverrideWithTotal),
+        (status = 500),
+    ),
+)]
+pub fn list_metadata_overrides<D: MetadataOverrideRepo>(
+    db: D,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("collection" / String / "metadata-overrides")
+        .and(warp::get())
+        .and(warp::query::<CustomHistoryQuery>())
+        .and(warp::any().map(move || db.clone()))
+        .and_then(list_metadata_overrides_handler::<D>)
+}
+
+pub async fn list_metadata_overrides_handler<D: MetadataOverrideRepo>(
+    address: String,
+    query: CustomHistoryQuery,
+    db: D,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let list = catch_error_500!(
+        db.list_metadata_overrides(
+            &address,
+            query.limit.unwrap_or(100),
+            query.offset.unwrap_or_default(),
+        )
+        .await
+    );
+    let count = list.first().map(|it| it.total_count).unwrap_or_default();
+    let items = list.into_iter().map(NftMetadataOverride::from).collect();
+    response!(VecWithTotal { count, items })
+}
+
+#[utoipa::path(
+    post,
+    tag = "metadata-override",
+    path = "/collection/{address}/metadata-overrides",
+    params(("address" = String, Path, description = "Collection address")),
+    request_body(content = Vec<NftMetadataOverrideItem>, description = "Replaces the overrides of the listed nfts. A `text/csv` body has a header line naming the columns `nft`, `name`, `preview`, `previewMimetype` and `attributes`, given as json or as `Trait=Value;Trait=Value`"),
+    responses(
+        (status = 200, body = NftMetadataOverrideBatch),
+        (status = 400),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn write_metadata_overrides<D: CollectionRepo + MetadataOverrideRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    ip_source: ClientIpSource,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("collection" / String / "metadata-overrides")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_OVERRIDES_BODY_BYTES))
+        .and(warp::body::bytes())
+        .and(warp::header::headers_cloned())
+        .and(client_ip(ip_source))
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(write_metadata_overrides_handler::<D>)
+}
+
+pub async fn write_metadata_overrides_handler<D: CollectionRepo + MetadataOverrideRepo>(
+    address: String,
+    body: Bytes,
+    headers: HeaderMap<HeaderValue>,
+    ip: Option<String>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let caller = catch_error_401!(auth_service.authenticate(headers.clone()));
+    let items = catch_error_400!(override_items(&headers, &body));
+    let owned = catch_error_500!(db.validate_owner_of_collection(&address, &caller).await);
+    catch_error_403!(owned.filter(|count| *count > 0));
+
+    let written = catch_error_500!(
+        db.write_metadata_overrides(&address, &caller, &items, ip.as_deref())
+            .await
+    );
+    let skipped = items
+        .into_iter()
+        .map(|item| item.nft)
+        .filter(|nft| !written.contains(nft))
+        .collect();
+    response!(NftMetadataOverrideBatch {
+        collection: address,
+        written: written.len(),
+        skipped,
+    })
+}
+
+#[utoipa::path(
+    post,
+    tag = "metadata-override",
+    path = "/nft/{address}/metadata-override",
+    params(("address" = String, Path, description = "Nft address")),
+    request_body(content = NftMetadataOverridePayload, description = "Replaces the override of the nft, the previous values are kept in the history"),
+    responses(
+        (status = 200),
+        (status = 400),
+        (status = 401),
+        (status = 403),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn write_metadata_override<D: NftRepo + CollectionRepo + MetadataOverrideRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    ip_source: ClientIpSource,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("nft" / String / "metadata-override")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<NftMetadataOverridePayload>())
+        .and(warp::header::headers_cloned())
+        .and(client_ip(ip_source))
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(write_metadata_override_handler::<D>)
+}
+
+pub async fn write_metadata_override_handler<D: NftRepo + CollectionRepo + MetadataOverrideRepo>(
+    address: String,
+    payload: NftMetadataOverridePayload,
+    headers: HeaderMap<HeaderValue>,
+    ip: Option<String>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let caller = catch_error_401!(auth_service.authenticate(headers));
+    let item = catch_error_400!(override_write(NftMetadataOverrideItem {
+        nft: address.clone(),
+        name: payload.name,
+        preview: payload.preview,
+        preview_mimetype: payload.preview_mimetype,
+        attributes: payload.attributes,
+    }));
+    let Some(collection) = catch_error_500!(nft_collection(&db, &address).await) else {
+        return Ok(Box::from(warp::reply::with_status(
+            "Nft not found",
+            StatusCode::NOT_FOUND,
+        )));
+    };
+    let owned = catch_error_500!(db.validate_owner_of_collection(&collection, &caller).await);
+    catch_error_403!(owned.filter(|count| *count > 0));
+
+    catch_error_500!(
+        db.write_metadata_overrides(&collection, &caller, &[item], ip.as_deref())
+            .await
+    );
+    Ok(Box::from(warp::reply::with_status("", StatusCode::OK)))
+}
+
+#[utoipa::path(
+    post,
+    tag = "metadata-override",
+    path = "/nft/{address}/metadata-override/remove",
+    params(("address" = String, Path, description = "Nft address")),
+    responses(
+        (status = 200),
+        (status = 401),
+        (status = 403),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn remove_metadata_override<D: NftRepo + CollectionRepo + MetadataOverrideRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    ip_source: ClientIpSource,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("nft" / String / "metadata-override" / "remove")
+        .and(warp::post())
+        .and(warp::header::headers_cloned())
+        .and(client_ip(ip_source))
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(remove_metadata_override_handler::<D>)
+}
+
+pub async fn remove_metadata_override_handler<
+    D: NftRepo + CollectionRepo + MetadataOverrideRepo,
+>(
+    address: String,
+    headers: HeaderMap<HeaderValue>,
+    ip: Option<String>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let caller = catch_error_401!(auth_service.authenticate(headers));
+    let Some(collection) = catch_error_500!(nft_collection(&db, &address).await) else {
+        return Ok(Box::from(warp::reply::with_status(
+            "Nft not found",
+            StatusCode::NOT_FOUND,
+        )));
+    };
+    let owned = catch_error_500!(db.validate_owner_of_collection(&collection, &caller).await);
+    catch_error_403!(owned.filter(|count| *count > 0));
+
+    let removed = catch_error_500!(
+        db.remove_metadata_override(&collection, &address, &caller, ip.as_deref())
+            .await
+    );
+    if !removed {
+        return Ok(Box::from(warp::reply::with_status(
+            "The nft has no override",
+            StatusCode::NOT_FOUND,
+        )));
+    }
+    Ok(Box::from(warp::reply::with_status("", StatusCode::OK)))
+}
+
+async fn nft_collection<D: NftRepo>(db: &D, nft: &String) -> sqlx::Result<Option<String>> {
+    Ok(db.get_nft_details(nft).await?.and_then(|n| n.collection))
+}
+
+/// Rows of a json or a `text/csv` body, checked like a single write
+fn override_items(
+    headers: &HeaderMap<HeaderValue>,
+    body: &[u8],
+) -> anyhow::Result<Vec<NftMetadataOverrideWrite>> {
+    let is_csv = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/csv"));
+    let items: Vec<NftMetadataOverrideItem> = match is_csv {
+        true => csv_items(std::str::from_utf8(body)?)?,
+        false => serde_json::from_slice(body)?,
+    };
+    anyhow::ensure!(!items.is_empty(), "no overrides given");
+    anyhow::ensure!(
+        items.len() <= MAX_OVERRIDE_ITEMS,
+        "at most {MAX_OVERRIDE_ITEMS} overrides at once"
+    );
+    let mut seen = HashSet::new();
+    items
+        .into_iter()
+        .map(|item| {
+            anyhow::ensure!(seen.insert(item.nft.clone()), "{} is given twice", item.nft);
+            override_write(item)
+        })
+        .collect()
+}
+
+fn override_write(item: NftMetadataOverrideItem) -> anyhow::Result<NftMetadataOverrideWrite> {
+    let nft = item.nft;
+    let name = non_empty(item.name);
+    let preview = non_empty(item.preview);
+    let attributes = item.attributes.filter(|v| !v.is_null());
+    anyhow::ensure!(
+        name.is_some() || preview.is_some() || attributes.is_some(),
+        "{nft}: nothing to override, remove the override instead"
+    );
+    if let Some(name) = &name {
+        anyhow::ensure!(
+            name.chars().count() <= MAX_NAME_CHARS,
+            "{nft}: name must be at most {MAX_NAME_CHARS} characters"
+        );
+    }
+    if let Some(preview) = &preview {
+        anyhow::ensure!(
+            preview.len() <= MAX_PREVIEW_CHARS
+                && PREVIEW_SCHEMES.iter().any(|s| preview.starts_with(s)),
+            "{nft}: preview must be an http, ipfs or arweave url"
+        );
+    }
+    let preview_mimetype = match (&preview, non_empty(item.preview_mimetype)) {
+        (None, _) => None,
+        (Some(_), Some(mimetype)) => {
+            anyhow::ensure!(mimetype.contains('/'), "{nft}: invalid mimetype {mimetype}");
+            Some(mimetype.to_lowercase())
+        }
+        (Some(preview), None) => guess_mimetype(preview).map(str::to_string),
+    };
+    if let Some(attributes) = &attributes {
+        let given = match attributes {
+            Value::Array(items) => items.len(),
+            Value::Object(object) => object.len(),
+            _ => anyhow::bail!("{nft}: attributes must be a list or a map"),
+        };
+        anyhow::ensure!(
+            given <= MAX_ATTRIBUTES,
+            "{nft}: at most {MAX_ATTRIBUTES} attributes"
+        );
+        anyhow::ensure!(
+            parse_attributes(attributes).len() == given,
+            "{nft}: every attribute needs a trait type and a value"
+        );
+    }
+
+    Ok(NftMetadataOverrideWrite {
+        nft,
+        name,
+        preview,
+        preview_mimetype,
+        attributes,
+    })
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// The header line names the columns, `nft` is required
+fn csv_items(text: &str) -> anyhow::Result<Vec<NftMetadataOverrideItem>> {
+    let mut rows = csv_rows(text)?.into_iter();
+    let (_, header) = rows
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("missing csv header"))?;
+    let header: Vec<String> = header
+        .iter()
+        .map(|column| column.trim().to_lowercase().replace('_', ""))
+        .collect();
+    let column = |name: &str| header.iter().position(|c| c == name);
+    let nft = column("nft").ok_or_else(|| anyhow::anyhow!("missing csv column nft"))?;
+    let (name, preview, mimetype, attributes) = (
+        column("name"),
+        column("preview"),
+        column("previewmimetype"),
+        column("attributes"),
+    );
+
+    rows.filter(|(_, row)| row.iter().any(|cell| !cell.trim().is_empty()))
+        .map(|(line, row)| {
+            let cell = |i: Option<usize>| {
+                i.and_then(|i| row.get(i))
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty())
+            };
+            Ok(NftMetadataOverrideItem {
+                nft: cell(Some(nft)).ok_or_else(|| anyhow::anyhow!("line {line}: missing nft"))?,
+                name: cell(name),
+                preview: cell(preview),
+                preview_mimetype: cell(mimetype),
+                attributes: cell(attributes)
+                    .map(|a| csv_attributes(&a))
+                    .transpose()
+                    .map_err(|e| anyhow::anyhow!("line {line}: {e}"))?,
+            })
+        })
+        .collect()
+}
+
+/// Json, or `Trait=Value;Trait=Value`
+fn csv_attributes(cell: &str) -> anyhow::Result<Value> {
+    if cell.starts_with('[') || cell.starts_with('{') {
+        return Ok(serde_json::from_str(cell)?);
+    }
+    let mut attributes = Map::new();
+    for pair in cell.split(';').filter(|p| !p.trim().is_empty()) {
+        let (trait_type, value) = pair
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("attribute {pair} is not Trait=Value"))?;
+        attributes.insert(
+            trait_type.trim().to_string(),
+            Value::String(value.trim().to_string()),
+        );
+    }
+    Ok(Value::Object(attributes))
+}
+
+/// Comma separated, fields may be quoted with `"` and hold `""`, commas and line breaks.
+/// Rows come with the line they start on, counting the breaks inside quoted fields.
+fn csv_rows(text: &str) -> anyhow::Result<Vec<(usize, Vec<String>)>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let (mut line, mut row_line) = (1, 1);
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, '\n') => {
+                line += 1;
+                cell.push('\n');
+            }
+            (true, c) => cell.push(c),
+            (false, '"') if cell.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut cell)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut cell));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            (false, c) => cell.push(c),
+        }
+    }
+    anyhow::ensure!(!quoted, "unterminated quoted csv field");
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
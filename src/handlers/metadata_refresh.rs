@@ -0,0 +1,217 @@
+use crate::db::repo::{ApiRepo, CollectionRepo, MetadataRefreshRepo};
+use crate::db::MetadataRefreshRecord;
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::model::{MetadataRefresh, MetadataRefreshBatch};
+use crate::services::auth::AuthService;
+use crate::{api_doc_addon, catch_error_401, catch_error_403, catch_error_500, response};
+pub use api_types::MetadataRefreshStatus;
+use chrono::Utc;
+use http::{HeaderMap, HeaderValue};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::OpenApi;
+use warp::http::{Method, StatusCode};
+use warp::Filter;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        request_metadata_refresh,
+        get_metadata_refresh,
+        request_collection_metadata_refresh
+    ),
+    components(schemas(MetadataRefresh, MetadataRefreshStatus, MetadataRefreshBatch)),
+    tags(
+        (name = "metadata-refresh", description = "Metadata refresh requests, served by the indexer"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::POST,
+        "/nft/{address}/refresh-metadata",
+        request_metadata_refresh(
+            ctx.db.clone(),
+            ctx.auth.clone(),
+            ctx.metadata_refresh_cooldown,
+        ),
+    )
+    .add(
+        Method::GET,
+        "/nft/{address}/refresh-metadata",
+        get_metadata_refresh(ctx.db.clone(), ctx.metadata_refresh_cooldown),
+    )
+    .add(
+        Method::POST,
+        "/collection/{address}/refresh-metadata",
+        request_collection_metadata_refresh(
+            ctx.db.clone(),
+            ctx.auth.clone(),
+            ctx.metadata_refresh_cooldown,
+        ),
+    );
+}
+
+fn with_cooldown(record: MetadataRefreshRecord, cooldown: Duration) -> MetadataRefresh {
+    let mut job = MetadataRefresh::from(record);
+    job.next_request_at = job.created_at + cooldown.as_secs() as i64;
+    job
+}
+
+fn with_status(message: impl Into<String>, status: StatusCode) -> Box<dyn warp::Reply> {
+    Box::from(warp::reply::with_status(message.into(), status))
+}
+
+#[utoipa::path(
+    post,
+    tag = "metadata-refresh",
+    path = "/nft/{address}/refresh-metadata",
+    params(("address" = String, Path, description = "Nft address")),
+    responses(
+        (status = 200, body = MetadataRefresh, description = "Queued job, or the open one of the nft"),
+        (status = 401),
+        (status = 404),
+        (status = 429, description = "The nft was refreshed recently, see `Retry-After`"),
+        (status = 500),
+    ),
+)]
+pub fn request_metadata_refresh<D: MetadataRefreshRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    cooldown: Duration,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("nft" / String / "refresh-metadata")
+        .and(warp::post())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and(warp::any().map(move || cooldown))
+        .and_then(request_metadata_refresh_handler::<D>)
+}
+
+pub async fn request_metadata_refresh_handler<D: MetadataRefreshRepo>(
+    address: String,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+    cooldown: Duration,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let caller = catch_error_401!(auth_service.authenticate(headers));
+
+    if let Some(latest) = catch_error_500!(db.latest_metadata_refresh(&address).await) {
+        if latest.status.is_open() {
+            return response!(with_cooldown(latest, cooldown));
+        }
+        let elapsed = (Utc::now().naive_utc() - latest.created_at)
+            .to_std()
+            .unwrap_or_default();
+        if let Some(retry_after) = cooldown.checked_sub(elapsed).filter(|d| !d.is_zero()) {
+            return Ok(Box::new(warp::reply::with_header(
+                warp::reply::with_status(
+                    "Metadata of this nft was refreshed recently",
+                    StatusCode::TOO_MANY_REQUESTS,
+                ),
+                "retry-after",
+                retry_after.as_secs().max(1).to_string(),
+            )));
+        }
+    }
+
+    match catch_error_500!(db.enqueue_metadata_refresh(&address, &caller).await) {
+        Some(job) => response!(with_cooldown(job, cooldown)),
+        // queued by a concurrent request in the meantime
+        None => match catch_error_500!(db.latest_metadata_refresh(&address).await) {
+            Some(job) => response!(with_cooldown(job, cooldown)),
+            None => Ok(with_status("Nft not found", StatusCode::NOT_FOUND)),
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    tag = "metadata-refresh",
+    path = "/nft/{address}/refresh-metadata",
+    params(("address" = String, Path, description = "Nft address")),
+    responses(
+        (status = 200, body = MetadataRefresh, description = "Most recent job of the nft"),
+        (status = 404, description = "No refresh was requested"),
+        (status = 500),
+    ),
+)]
+pub fn get_metadata_refresh<D: MetadataRefreshRepo>(
+    db: D,
+    cooldown: Duration,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("nft" / String / "refresh-metadata")
+        .and(warp::get())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || cooldown))
+        .and_then(get_metadata_refresh_handler::<D>)
+}
+
+pub async fn get_metadata_refresh_handler<D: MetadataRefreshRepo>(
+    address: String,
+    db: D,
+    cooldown: Duration,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match catch_error_500!(db.latest_metadata_refresh(&address).await) {
+        Some(job) => response!(with_cooldown(job, cooldown)),
+        None => Ok(with_status("No refresh requested", StatusCode::NOT_FOUND)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    tag = "metadata-refresh",
+    path = "/collection/{address}/refresh-metadata",
+    params(("address" = String, Path, description = "Collection address")),
+    responses(
+        (status = 200, body = MetadataRefreshBatch, description = "Nfts with an open job or in their cooldown are skipped"),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn request_collection_metadata_refresh<D: CollectionRepo + MetadataRefreshRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    cooldown: Duration,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("collection" / String / "refresh-metadata")
+        .and(warp::post())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and(warp::any().map(move || cooldown))
+        .and_then(request_collection_metadata_refresh_handler::<D>)
+}
+
+pub async fn request_collection_metadata_refresh_handler<
+    D: CollectionRepo + MetadataRefreshRepo,
+>(
+    address: String,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+    cooldown: Duration,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let caller = catch_error_401!(auth_service.authenticate(headers.clone()));
+    let is_admin = matches!(auth_service.authenticate_admin(headers), Ok(Some(_)));
+    if !is_admin {
+        let owned = catch_error_500!(db.validate_owner_of_collection(&address, &caller).await);
+        catch_error_403!(owned.filter(|count| *count > 0));
+    }
+
+    let batch = catch_error_500!(
+        db.enqueue_collection_metadata_refresh(&address, &caller, cooldown)
+            .await
+    );
+    response!(MetadataRefreshBatch {
+        collection: address,
+        queued: batch.queued,
+        skipped: batch.skipped,
+    })
+}
@@ -0,0 +1,295 @@
+use crate::db::repo::{ApiRepo, CollectionRepo, MetadataReportRepo};
+use crate::db::{check_metadata, NftMetaRecord};
+use crate::handlers::calculate_hash;
+use crate::handlers::http_cache::{cache_max_age, cacheable};
+use crate::handlers::moderation::private_reply;
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::model::{
+    CollectionMetadataReport, MetadataDuplicateName, MetadataIssueNfts, MetadataTraitInconsistency,
+};
+use crate::services::auth::AuthService;
+use crate::{api_doc_addon, catch_error_500, response};
+pub use api_types::{Address, MetadataIssue, MetadataReportQuery, NftAttributeKind};
+use http::{HeaderMap, HeaderValue};
+use moka::future::Cache;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::sync::Arc;
+use utoipa::OpenApi;
+use warp::http::{Method, StatusCode};
+use warp::Filter;
+
+/// Nfts listed per issue, the counts cover all of them
+const MAX_REPORT_NFTS: usize = 1000;
+
+/// Nfts checked per request, the metadata of each one is loaded
+const MAX_CHECKED_NFTS: usize = 2000;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get_metadata_report),
+    components(schemas(
+        CollectionMetadataReport,
+        MetadataDuplicateName,
+        MetadataIssue,
+        MetadataIssueNfts,
+        MetadataTraitInconsistency
+    )),
+    tags(
+        (name = "metadata-report", description = "Metadata problems of a collection"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::GET,
+        "/collection/{address}/metadata-report",
+        get_metadata_report(ctx.db.clone(), ctx.cache_minute.clone(), ctx.auth.clone()),
+    );
+}
+
+#[utoipa::path(
+    get,
+    tag = "metadata-report",
+    path = "/collection/{address}/metadata-report",
+    params(("address" = String, Path, description = "Collection address"), MetadataReportQuery),
+    responses(
+        (status = 200, body = CollectionMetadataReport, description = "Nft lists are filled for the collection owner only"),
+        (status = 304),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn get_metadata_report<D: CollectionRepo + MetadataReportRepo>(
+    db: D,
+    cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let max_age = cache_max_age(&cache);
+    cacheable(
+        warp::path!("collection" / String / "metadata-report")
+            .and(warp::get())
+            .and(warp::query::<MetadataReportQuery>())
+            .and(warp::header::headers_cloned())
+            .and(warp::any().map(move || db.clone()))
+            .and(warp::any().map(move || cache.clone()))
+            .and(warp::any().map(move || auth_service.clone()))
+            .and_then(get_metadata_report_handler::<D>),
+        max_age,
+    )
+}
+
+pub async fn get_metadata_report_handler<D: CollectionRepo + MetadataReportRepo>(
+    address: String,
+    query: MetadataReportQuery,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let hash = calculate_hash(&("metadata-report", &address, &query.after));
+    let mut ret: CollectionMetadataReport;
+    match cache.get(&hash) {
+        None => {
+            if catch_error_500!(db.get_collection(&address).await).is_none() {
+                return Ok(Box::from(warp::reply::with_status(
+                    "Collection not found",
+                    StatusCode::NOT_FOUND,
+                )));
+            }
+            let mut nfts = catch_error_500!(
+                db.collection_nft_meta(&address, query.after.as_deref(), MAX_CHECKED_NFTS + 1)
+                    .await
+            );
+            let next = match nfts.len() > MAX_CHECKED_NFTS {
+                true => {
+                    nfts.truncate(MAX_CHECKED_NFTS);
+                    nfts.last().map(|nft| nft.address.clone())
+                }
+                false => None,
+            };
+            ret = metadata_report(address.clone(), &nfts);
+            ret.next = next;
+            let value_for_cache =
+                serde_json::to_value(ret.clone()).expect("Failed serializing cached value");
+            cache.insert(hash, value_for_cache).await;
+        }
+        Some(cached_value) => {
+            ret = serde_json::from_value(cached_value).expect("Failed parsing cached value")
+        }
+    }
+
+    // the lists are for the owner to act on, other callers get the counts
+    let owner = match auth_service.authenticate(headers) {
+        Ok(caller) => catch_error_500!(db.validate_owner_of_collection(&address, &caller).await)
+            .is_some_and(|count| count > 0),
+        Err(_) => false,
+    };
+    if owner {
+        return private_reply(&ret);
+    }
+    ret.issues.iter_mut().for_each(|i| i.nfts.clear());
+    ret.duplicate_names.iter_mut().for_each(|d| d.nfts.clear());
+    ret.inconsistent_traits
+        .iter_mut()
+        .for_each(|t| t.nfts.clear());
+    response!(&ret)
+}
+
+/// Checks each nft on its own, then compares names and trait types across the collection
+pub fn metadata_report(collection: Address, nfts: &[NftMetaRecord]) -> CollectionMetadataReport {
+    let mut issues: BTreeMap<MetadataIssue, Vec<&Address>> = BTreeMap::new();
+    let mut names: HashMap<String, (String, Vec<&Address>)> = HashMap::new();
+    let mut traits: HashMap<String, TraitUsage> = HashMap::new();
+
+    for nft in nfts {
+        let (found, metadata) = check_metadata(nft.meta.as_ref(), nft.meta_override.as_ref());
+        for issue in found {
+            issues.entry(issue).or_default().push(&nft.address);
+        }
+
+        let name = nft
+            .meta_override
+            .as_ref()
+            .and_then(|o| o.get("name"))
+            .and_then(Value::as_str)
+            .or(nft.name.as_deref())
+            .map(str::trim)
+            .filter(|name| !name.is_empty());
+        match name {
+            Some(name) => names
+                .entry(name.to_lowercase())
+                .or_insert_with(|| (name.to_string(), vec![]))
+                .1
+                .push(&nft.address),
+            None => issues
+                .entry(MetadataIssue::MissingName)
+                .or_default()
+                .push(&nft.address),
+        }
+
+        for attribute in &metadata.attributes {
+            let key: String = attribute
+                .trait_type
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect();
+            if key.is_empty() {
+                continue;
+            }
+            let usage = traits.entry(key).or_default();
+            usage
+                .spellings
+                .entry(attribute.trait_type.clone())
+                .or_default()
+                .push(&nft.address);
+            match usage.kinds.iter_mut().find(|(k, _)| *k == attribute.kind) {
+                Some((_, list)) => list.push(&nft.address),
+                None => usage.kinds.push((attribute.kind, vec![&nft.address])),
+            }
+        }
+    }
+
+    let mut duplicate_names: Vec<MetadataDuplicateName> = names
+        .into_values()
+        .filter(|(_, list)| list.len() > 1)
+        .map(|(name, list)| {
+            issues
+                .entry(MetadataIssue::DuplicateName)
+                .or_default()
+                .extend(&list);
+            MetadataDuplicateName {
+                name,
+                nfts: capped(list),
+            }
+        })
+        .collect();
+    duplicate_names.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut inconsistent_traits: Vec<MetadataTraitInconsistency> = traits
+        .into_values()
+        .filter(|usage| usage.spellings.len() > 1 || usage.kinds.len() > 1)
+        .map(|usage| {
+            // ties go to the first spelling, spellings are sorted
+            let (trait_type, _) = usage
+                .spellings
+                .iter()
+                .rev()
+                .max_by_key(|(_, list)| list.len())
+                .expect("a trait has a spelling");
+            let trait_type = trait_type.clone();
+            let main_kind = usage
+                .kinds
+                .iter()
+                .rev()
+                .max_by_key(|(_, list)| list.len())
+                .map(|(kind, _)| *kind);
+            let mut flagged: Vec<&Address> = usage
+                .spellings
+                .iter()
+                .filter(|(spelling, _)| **spelling != trait_type)
+                .chain(
+                    usage
+                        .kinds
+                        .iter()
+                        .filter(|(kind, _)| Some(*kind) != main_kind)
+                        .map(|(_, list)| (&trait_type, list)),
+                )
+                .flat_map(|(_, list)| list.iter().copied())
+                .collect();
+            flagged.sort();
+            flagged.dedup();
+            issues
+                .entry(MetadataIssue::InconsistentTraitType)
+                .or_default()
+                .extend(&flagged);
+            MetadataTraitInconsistency {
+                trait_type,
+                spellings: usage.spellings.into_keys().collect(),
+                kinds: usage.kinds.into_iter().map(|(kind, _)| kind).collect(),
+                nfts: capped(flagged),
+            }
+        })
+        .collect();
+    inconsistent_traits.sort_by(|a, b| a.trait_type.cmp(&b.trait_type));
+
+    let mut with_issues: Vec<&Address> = issues.values().flatten().copied().collect();
+    with_issues.sort();
+    with_issues.dedup();
+
+    CollectionMetadataReport {
+        collection,
+        checked: nfts.len(),
+        healthy: nfts.len() - with_issues.len(),
+        issues: issues
+            .into_iter()
+            .map(|(issue, mut list)| {
+                list.sort();
+                list.dedup();
+                MetadataIssueNfts {
+                    issue,
+                    count: list.len(),
+                    nfts: capped(list),
+                }
+            })
+            .collect(),
+        duplicate_names,
+        inconsistent_traits,
+        next: None,
+    }
+}
+
+/// Spellings of a trait type and kinds of its values, with the nfts using each
+#[derive(Default)]
+struct TraitUsage<'a> {
+    spellings: BTreeMap<String, Vec<&'a Address>>,
+    kinds: Vec<(NftAttributeKind, Vec<&'a Address>)>,
+}
+
+fn capped(list: Vec<&Address>) -> Vec<Address> {
+    list.into_iter().take(MAX_REPORT_NFTS).cloned().collect()
+}
@@ -1,17 +1,16 @@
-use crate::db::queries::Queries;
+use crate::db::repo::{ApiRepo, EventRepo};
 use crate::handlers::calculate_hash;
+use crate::handlers::router::{RouteContext, RouterRegistry};
 use crate::model::MetricsSummary;
-use crate::model::MetricsSummaryBase;
+use crate::model::{metrics_summary_page, MetricsSummaryBase};
 use crate::{api_doc_addon, catch_error_500, response};
+pub use api_types::MetricsSummaryQuery;
 use chrono::NaiveDateTime;
 use moka::future::Cache;
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::convert::Infallible;
-use utoipa::IntoParams;
 use utoipa::OpenApi;
-use utoipa::ToSchema;
-use warp::http::StatusCode;
+use warp::http::{Method, StatusCode};
 use warp::Filter;
 
 #[derive(OpenApi)]
@@ -25,13 +24,12 @@ use warp::Filter;
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
-#[derive(Debug, Clone, Deserialize, Serialize, IntoParams, ToSchema, Hash)]
-#[into_params(parameter_in = Query)]
-pub struct MetricsSummaryQuery {
-    pub from: i64,
-    pub to: i64,
-    pub limit: i64,
-    pub offset: i64,
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::GET,
+        "/metrics/summary",
+        get_metrics_summary(ctx.db.clone(), ctx.cache_minute.clone()),
+    );
 }
 
 #[utoipa::path(
@@ -44,8 +42,8 @@ pub struct MetricsSummaryQuery {
         (status = 500),
     ),
 )]
-pub fn get_metrics_summary(
-    db: Queries,
+pub fn get_metrics_summary<D: EventRepo>(
+    db: D,
     cache: Cache<u64, Value>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("metrics" / "summary")
@@ -53,12 +51,12 @@ pub fn get_metrics_summary(
         .and(warp::query::<MetricsSummaryQuery>())
         .and(warp::any().map(move || db.clone()))
         .and(warp::any().map(move || cache.clone()))
-        .and_then(metrics_summary_handler)
+        .and_then(metrics_summary_handler::<D>)
 }
 
-pub async fn metrics_summary_handler(
+pub async fn metrics_summary_handler<D: EventRepo>(
     query: MetricsSummaryQuery,
-    db: Queries,
+    db: D,
     cache: Cache<u64, Value>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let mut query = query;
@@ -79,7 +77,7 @@ pub async fn metrics_summary_handler(
                 db.get_metrics_summary(from, to, query.limit, query.offset)
                     .await
             );
-            response = MetricsSummaryBase::from(values);
+            response = metrics_summary_page(values);
             let value_for_cache =
                 serde_json::to_value(response.clone()).expect("Failed serializing cached value");
             cache.insert(hash, value_for_cache).await;
@@ -1,14 +1,27 @@
 pub mod nft;
 use std::collections::hash_map::DefaultHasher;
+pub mod admin;
 pub mod auction;
 pub mod auth;
 pub mod collection;
 pub mod collection_custom;
+pub mod docs;
 pub mod events;
+pub mod graphql;
+pub mod http_cache;
+pub mod metadata_override;
+pub mod metadata_refresh;
+pub mod metadata_report;
 pub mod metrics;
+pub mod moderation;
+pub mod orderbook;
 pub mod owner;
+pub mod query_string;
+pub mod quote;
+pub mod router;
+pub mod token;
 pub mod user;
-use utoipa::ToSchema;
+pub mod verification;
 #[macro_export]
 macro_rules! catch_error_500 {
     ($expr:expr) => {
@@ -94,17 +107,8 @@ macro_rules! response {
     };
 }
 
-use std::fmt::Display;
 use std::hash::{Hash, Hasher};
 
-use serde::{Deserialize, Serialize};
-
-lazy_static::lazy_static! {
-    static ref SWAGGER: Vec<u8> = {
-        std::fs::read("openapi.yml").expect("cannot read 'openapi.yml' from disk")
-    };
-}
-
 #[macro_export]
 macro_rules! api_doc_addon {
     ($addon:ty) => {
@@ -136,12 +140,14 @@ macro_rules! api_doc_addon {
     };
 }
 
-use crate::db::queries::Queries;
+use crate::db::repo::{ApiRepo, AuctionRepo};
+use crate::handlers::router::{RouteContext, RouterRegistry};
 use crate::model::{Root, Roots};
 use reqwest::StatusCode;
 use std::convert::Infallible;
 
 use utoipa::OpenApi;
+use warp::http::Method;
 use warp::Filter;
 
 #[derive(OpenApi)]
@@ -155,6 +161,38 @@ use warp::Filter;
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(Method::GET, "/roots", list_roots(ctx.db.clone()));
+}
+
+/// Routes of every handler module
+pub fn register_routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    for module_routes in [
+        nft::routes,
+        collection::routes,
+        collection_custom::routes,
+        owner::routes,
+        auction::routes,
+        events::routes,
+        metrics::routes,
+        token::routes,
+        user::routes,
+        auth::routes,
+        admin::routes,
+        verification::routes,
+        moderation::routes,
+        metadata_override::routes,
+        metadata_refresh::routes,
+        metadata_report::routes,
+        orderbook::routes,
+        quote::routes,
+        graphql::routes,
+        routes,
+    ] {
+        module_routes(ctx, r);
+    }
+}
+
 #[utoipa::path(
     get,
     tag = "service",
@@ -164,40 +202,26 @@ api_doc_addon!(ApiDoc);
         (status = 500),
     ),
 )]
-pub fn list_roots(
-    db: Queries,
+pub fn list_roots<D: AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("roots")
         .and(warp::get())
         .and(warp::any().map(move || db.clone()))
-        .and_then(list_roots_handler)
+        .and_then(list_roots_handler::<D>)
 }
 
-pub async fn list_roots_handler(db: Queries) -> Result<Box<dyn warp::Reply>, Infallible> {
+pub async fn list_roots_handler<D: AuctionRepo>(db: D) -> Result<Box<dyn warp::Reply>, Infallible> {
     let list = catch_error_500!(db.list_roots().await);
     let roots: Vec<Root> = list.into_iter().map(Root::from).collect();
     response!(&Roots { roots })
 }
 
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub enum OrderDirection {
-    #[serde(rename = "asc")]
-    Asc,
-    #[serde(rename = "desc")]
-    Desc,
-}
-
-impl Display for OrderDirection {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            OrderDirection::Asc => write!(f, "asc"),
-            OrderDirection::Desc => write!(f, "desc"),
-        }
-    }
-}
-
 pub fn calculate_hash<T: Hash>(t: &T) -> u64 {
     let mut s = DefaultHasher::new();
     t.hash(&mut s);
     s.finish()
 }
+
+/// Upper bound for request bodies, every filter taking a body checks it before parsing
+pub const MAX_JSON_BODY_BYTES: u64 = 64 * 1024;
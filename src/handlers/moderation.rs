@@ -0,0 +1,399 @@
+use crate::db::repo::{ApiRepo, ModerationRepo};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
+use crate::model::{Moderation, Report, VecWithTotal};
+use crate::schema::{VecModerationWithTotal, VecReportsWithTotal};
+use crate::services::auth::AuthService;
+use crate::{api_doc_addon, catch_error_401, catch_error_403, catch_error_500, response};
+pub use api_types::{
+    CreateReportPayload, DismissReportPayload, ModerationQuery, ModerationStatus, ModerationTarget,
+    ReportReason, ReportStatus, ReportsQuery, SetModerationPayload,
+};
+use http::{header, HeaderMap, HeaderValue};
+use moka::future::Cache;
+use serde::Serialize;
+use serde_json::Value;
+use std::convert::Infallible;
+use std::sync::Arc;
+use utoipa::OpenApi;
+use warp::http::{Method, StatusCode};
+use warp::Filter;
+
+/// Sent by admins to see hidden nfts and collections in the public lists
+pub const INCLUDE_HIDDEN_HEADER: &str = "x-include-hidden";
+
+const MAX_COMMENT_CHARS: usize = 1000;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        create_report,
+        list_reports,
+        set_moderation,
+        dismiss_report,
+        list_moderation
+    ),
+    components(schemas(
+        CreateReportPayload,
+        SetModerationPayload,
+        DismissReportPayload,
+        Report,
+        Moderation,
+        ModerationTarget,
+        ModerationStatus,
+        ReportReason,
+        ReportStatus,
+        VecReportsWithTotal,
+        VecModerationWithTotal
+    )),
+    tags(
+        (name = "moderation", description = "User reports and takedowns of nfts and collections"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::POST,
+        "/reports",
+        create_report(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/admin/moderation/reports",
+        list_reports(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::POST,
+        "/admin/moderation/status",
+        set_moderation(
+            ctx.db.clone(),
+            ctx.auth.clone(),
+            vec![ctx.cache_minute.clone(), ctx.cache_10_sec.clone()],
+        ),
+    )
+    .add(
+        Method::POST,
+        "/admin/moderation/reports/dismiss",
+        dismiss_report(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/admin/moderation",
+        list_moderation(ctx.db.clone(), ctx.auth.clone()),
+    );
+}
+
+/// `true` when the request carries `X-Include-Hidden: true`, the caller must then be an admin
+pub fn wants_hidden(headers: &HeaderMap<HeaderValue>) -> bool {
+    headers
+        .get(INCLUDE_HIDDEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+}
+
+/// Json reply kept out of browser and CDN caches, for lists including hidden items
+pub fn private_reply<T: Serialize>(value: &T) -> Result<Box<dyn warp::Reply>, Infallible> {
+    Ok(Box::from(warp::reply::with_header(
+        warp::reply::json(value),
+        header::CACHE_CONTROL,
+        "private, no-store",
+    )))
+}
+
+fn with_status(message: impl Into<String>, status: StatusCode) -> Box<dyn warp::Reply> {
+    Box::from(warp::reply::with_status(message.into(), status))
+}
+
+fn too_long(comment: Option<&str>) -> bool {
+    comment.is_some_and(|c| c.chars().count() > MAX_COMMENT_CHARS)
+}
+
+#[utoipa::path(
+    post,
+    tag = "moderation",
+    path = "/reports",
+    request_body(content = CreateReportPayload, description = "Report a stolen, nsfw or infringing nft, collection or user"),
+    responses(
+        (status = 200, body = Report),
+        (status = 400),
+        (status = 401),
+        (status = 409, description = "The same address already has an open report on the target"),
+        (status = 500),
+    ),
+)]
+pub fn create_report<D: ModerationRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("reports")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<CreateReportPayload>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(create_report_handler::<D>)
+}
+
+pub async fn create_report_handler<D: ModerationRepo>(
+    payload: CreateReportPayload,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let reporter = catch_error_401!(auth_service.authenticate(headers));
+    let comment = payload
+        .comment
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+    if too_long(comment) {
+        return Ok(with_status(
+            format!("comment must be at most {MAX_COMMENT_CHARS} characters"),
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    let report = catch_error_500!(
+        db.create_report(
+            &reporter,
+            payload.target_type,
+            &payload.target,
+            payload.reason,
+            comment,
+        )
+        .await
+    );
+    match report {
+        Some(report) => response!(Report::from(report)),
+        None => Ok(with_status(
+            "You already reported this target",
+            StatusCode::CONFLICT,
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    tag = "moderation",
+    path = "/admin/moderation/reports",
+    params(ReportsQuery),
+    responses(
+        (status = 200, body = VecReportsWithTotal, description = "Oldest first, `status=open` is the moderation queue"),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn list_reports<D: ModerationRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "moderation" / "reports")
+        .and(warp::get())
+        .and(warp::query::<ReportsQuery>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(list_reports_handler::<D>)
+}
+
+pub async fn list_reports_handler<D: ModerationRepo>(
+    query: ReportsQuery,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    catch_error_403!(admin);
+
+    let list = catch_error_500!(
+        db.list_reports(
+            query.status,
+            query.target_type,
+            query.target.as_deref(),
+            query.limit.unwrap_or(100),
+            query.offset.unwrap_or_default(),
+        )
+        .await
+    );
+    let count = list.first().map(|it| it.total_count).unwrap_or_default();
+    let items = list.into_iter().map(Report::from).collect();
+    response!(VecWithTotal { count, items })
+}
+
+#[utoipa::path(
+    post,
+    tag = "moderation",
+    path = "/admin/moderation/status",
+    request_body(content = SetModerationPayload, description = "Flag, hide or restore an nft or a collection, resolving its open reports"),
+    responses(
+        (status = 200),
+        (status = 400),
+        (status = 401),
+        (status = 403),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn set_moderation<D: ModerationRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    caches: Vec<Cache<u64, Value>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "moderation" / "status")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<SetModerationPayload>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and(warp::any().map(move || caches.clone()))
+        .and_then(set_moderation_handler::<D>)
+}
+
+pub async fn set_moderation_handler<D: ModerationRepo>(
+    payload: SetModerationPayload,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+    caches: Vec<Cache<u64, Value>>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    let admin = catch_error_403!(admin);
+
+    if payload.target_type == ModerationTarget::User {
+        return Ok(with_status(
+            "Only nfts and collections can be flagged or hidden",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+    if too_long(payload.reason.as_deref()) {
+        return Ok(with_status(
+            format!("reason must be at most {MAX_COMMENT_CHARS} characters"),
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    let resolved = catch_error_500!(
+        db.set_moderation(
+            &admin,
+            payload.target_type,
+            &payload.target,
+            payload.status,
+            payload.reason.as_deref(),
+        )
+        .await
+    );
+    let Some(resolved) = resolved else {
+        return Ok(with_status("Target not found", StatusCode::NOT_FOUND));
+    };
+    log::info!(
+        "{} set to {:?} by {admin}, {resolved} reports resolved",
+        payload.target,
+        payload.status
+    );
+    for cache in caches {
+        cache.invalidate_all();
+    }
+
+    Ok(Box::from(warp::reply::with_status("", StatusCode::OK)))
+}
+
+#[utoipa::path(
+    post,
+    tag = "moderation",
+    path = "/admin/moderation/reports/dismiss",
+    request_body(content = DismissReportPayload, description = "Close an open report without acting on its target"),
+    responses(
+        (status = 200, body = Report),
+        (status = 401),
+        (status = 403),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn dismiss_report<D: ModerationRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "moderation" / "reports" / "dismiss")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<DismissReportPayload>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(dismiss_report_handler::<D>)
+}
+
+pub async fn dismiss_report_handler<D: ModerationRepo>(
+    payload: DismissReportPayload,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    let admin = catch_error_403!(admin);
+
+    let report = catch_error_500!(
+        db.dismiss_report(&admin, payload.id, payload.comment.as_deref())
+            .await
+    );
+    match report {
+        Some(report) => response!(Report::from(report)),
+        None => Ok(with_status(
+            "No open report with this id",
+            StatusCode::NOT_FOUND,
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    tag = "moderation",
+    path = "/admin/moderation",
+    params(ModerationQuery),
+    responses(
+        (status = 200, body = VecModerationWithTotal, description = "Flagged and hidden items, most recent change first"),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn list_moderation<D: ModerationRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "moderation")
+        .and(warp::get())
+        .and(warp::query::<ModerationQuery>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(list_moderation_handler::<D>)
+}
+
+pub async fn list_moderation_handler<D: ModerationRepo>(
+    query: ModerationQuery,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    catch_error_403!(admin);
+
+    let list = catch_error_500!(
+        db.list_moderation(
+            query.status,
+            query.limit.unwrap_or(100),
+            query.offset.unwrap_or_default(),
+        )
+        .await
+    );
+    let count = list.first().map(|it| it.total_count).unwrap_or_default();
+    let items = list.into_iter().map(Moderation::from).collect();
+    response!(VecWithTotal { count, items })
+}
@@ -1,23 +1,37 @@
-use crate::db::queries::Queries;
-use crate::db::{MetaRoyalty, NftDetails};
-use crate::handlers::calculate_hash;
-use crate::model::{DirectBuy, NFTPrice, NftTrait, OrderDirection, VecWith, NFT};
+use crate::db::repo::{ApiRepo, AuctionRepo, CollectionRepo, NftRepo};
+use crate::db::{MetaRoyalty, NftDetails, NftFilters};
+use crate::handlers::http_cache::{cache_max_age, cacheable};
+use crate::handlers::moderation::{private_reply, wants_hidden};
+use crate::handlers::query_string::{query_params, FromQuery, QueryParams};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::{calculate_hash, MAX_JSON_BODY_BYTES};
+use crate::model::FromDb;
+use crate::model::{DirectBuy, NFTPrice, NftTrait, VecWith, NFT};
+use crate::services::auth::AuthService;
 use crate::{
-    api_doc_addon, catch_empty, catch_error_500,
-    db::{Address, DirectBuyState},
+    api_doc_addon, catch_empty, catch_error_400, catch_error_401, catch_error_403, catch_error_500,
+    db::DirectBuyState,
     model::{Auction, Collection, DirectSell},
     response,
 };
+pub use api_types::{
+    AttributeFilter, GetNFTResult, NFTListOrder, NFTListOrderField, NFTListQuery,
+    NFTListRandomBuyQuery, NFTParam, NFTSellCountQuery, NFTSellCountResponse, NFTTopListQuery,
+    NftAttribute, NftAttributeKind, NftMediaFile, NftMediaKind, NftMediaRole, NftMetadata,
+    NftPriceHistoryQuery, PriceHistoryScale,
+};
 use chrono::NaiveDateTime;
+use http::{HeaderMap, HeaderValue};
 use moka::future::Cache;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::convert::Infallible;
-use std::fmt::{Display, Formatter};
 use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::join;
-use warp::http::StatusCode;
+use warp::http::{Method, StatusCode};
 use warp::Filter;
 
 use crate::handlers::auction::collect_auctions;
@@ -25,14 +39,19 @@ use crate::handlers::collection::collect_collections;
 use crate::schema::VecWithDirectBuy;
 use crate::schema::VecWithNFT;
 use utoipa::OpenApi;
-use utoipa::ToSchema;
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(get_nft, get_nft_direct_buy, get_nft_price_history, get_nft_list, get_nft_top_list, get_nft_random_list),
+    paths(get_nft, get_nft_by_query, get_nft_direct_buy, get_nft_price_history, get_nft_list, get_nft_list_by_query, get_nft_top_list, get_nft_random_list, get_nft_sell_count),
     components(schemas(
         NFTParam,
         GetNFTResult,
+        NftMetadata,
+        NftMediaFile,
+        NftMediaRole,
+        NftMediaKind,
+        NftAttribute,
+        NftAttributeKind,
         NftTrait,
         NftPriceHistoryQuery,
         NFTPrice,
@@ -45,7 +64,8 @@ use utoipa::ToSchema;
         NFTListOrderField,
         NFTTopListQuery,
         MetaRoyalty,
-        NFTListRandomBuyQuery
+        NFTListRandomBuyQuery,
+        NFTSellCountResponse
     )),
     tags(
         (name = "nft", description = "NFT handlers"),
@@ -54,6 +74,50 @@ use utoipa::ToSchema;
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.deprecated(
+        Method::POST,
+        "/nfts",
+        get_nft_list(ctx.db.clone(), ctx.cache_10_sec.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/nfts",
+        get_nft_list_by_query(ctx.db.clone(), ctx.cache_10_sec.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::POST,
+        "/nfts/random-buy",
+        get_nft_random_list(ctx.db.clone(), ctx.cache_1_sec.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/nfts/sell-count",
+        get_nft_sell_count(ctx.db.clone(), ctx.cache_5_minutes.clone()),
+    )
+    .add(
+        Method::POST,
+        "/nfts/top",
+        get_nft_top_list(ctx.db.clone(), ctx.cache_minute.clone(), ctx.auth.clone()),
+    )
+    .deprecated(Method::POST, "/nft/details", get_nft(ctx.db.clone()))
+    .add(
+        Method::GET,
+        "/nft/details",
+        get_nft_by_query(ctx.db.clone(), ctx.realtime_ttl),
+    )
+    .add(
+        Method::POST,
+        "/nft/direct/buy",
+        get_nft_direct_buy(ctx.db.clone()),
+    )
+    .add(
+        Method::POST,
+        "/nft/price-history",
+        get_nft_price_history(ctx.db.clone()),
+    );
+}
+
 #[utoipa::path(
     post,
     tag = "nft",
@@ -64,19 +128,56 @@ api_doc_addon!(ApiDoc);
         (status = 500),
     ),
 )]
-pub fn get_nft(
-    db: Queries,
+pub fn get_nft<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("nft" / "details")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<NFTParam>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_nft_handler)
+        .and_then(get_nft_handler::<D>)
+}
+
+#[utoipa::path(
+    get,
+    tag = "nft",
+    path = "/nft/details",
+    params(
+        ("nft" = String, Query, description = "NFT address"),
+    ),
+    responses(
+        (status = 200, body = GetNFTResult),
+        (status = 304),
+        (status = 400),
+        (status = 500),
+    ),
+)]
+pub fn get_nft_by_query<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
+    max_age: Duration,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    cacheable(
+        warp::path!("nft" / "details")
+            .and(warp::get())
+            .and(query_params())
+            .and(warp::any().map(move || db.clone()))
+            .and_then(get_nft_by_query_handler::<D>),
+        max_age,
+    )
+}
+
+pub async fn get_nft_by_query_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
+    query: QueryParams,
+    db: D,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let param = catch_error_400!(NFTParam::from_query(&query));
+    get_nft_handler(param, db).await
 }
 
-pub async fn get_nft_handler(
+pub async fn get_nft_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     param: NFTParam,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let nft = catch_error_500!(db.get_nft_details(&param.nft).await);
     let nft = catch_empty!(nft, "not found");
@@ -101,7 +202,7 @@ pub async fn get_nft_handler(
     if let Some(ref auction_id) = nft.auction {
         let a = catch_error_500!(db.get_nft_auction(auction_id).await);
         if let Some(a) = a {
-            auction.insert(auction_id.clone(), Auction::from_db(&a, &db.tokens));
+            auction.insert(auction_id.clone(), Auction::from_db(&a, db.tokens()));
         }
     };
 
@@ -119,7 +220,7 @@ pub async fn get_nft_handler(
     if let Some(ref direct_sell_id) = nft.forsale {
         let a = catch_error_500!(db.get_direct_sell(direct_sell_id).await);
         if let Some(a) = a {
-            direct_sell.insert(direct_sell_id.clone(), DirectSell::from_db(&a, &db.tokens));
+            direct_sell.insert(direct_sell_id.clone(), DirectSell::from_db(&a, db.tokens()));
         }
     };
 
@@ -131,7 +232,7 @@ pub async fn get_nft_handler(
     );
 
     for x in list.drain(..) {
-        direct_buy.insert(x.address.clone(), DirectBuy::from_db(&x, &db.tokens));
+        direct_buy.insert(x.address.clone(), DirectBuy::from_db(&x, db.tokens()));
     }
 
     let traits = db.get_traits(&nft_addr).await;
@@ -146,7 +247,8 @@ pub async fn get_nft_handler(
     let traits: Vec<NftTrait> = traits.into_iter().map(NftTrait::from).collect();
 
     let ret = GetNFTResult {
-        nft: NFT::from_db(nft),
+        metadata: nft.metadata(),
+        nft: NFT::from(nft),
         collection,
         auction,
         direct_buy,
@@ -157,24 +259,15 @@ pub async fn get_nft_handler(
     response!(&ret)
 }
 
-#[derive(Debug, Clone, Serialize, ToSchema)]
-pub struct GetNFTResult {
-    pub nft: NFT,
-    pub collection: HashMap<Address, Collection>,
-    pub auction: HashMap<Address, Auction>,
-    #[serde(rename = "directSell")]
-    pub direct_sell: HashMap<Address, DirectSell>,
-    #[serde(rename = "directBuy")]
-    pub direct_buy: HashMap<Address, DirectBuy>,
-    pub traits: Vec<NftTrait>,
-}
-
-#[derive(Debug, Clone, Deserialize, ToSchema)]
-pub struct NFTParam {
-    pub nft: Address,
-    pub status: Option<Vec<DirectBuyState>>,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
+impl FromQuery for NFTParam {
+    fn from_query(query: &QueryParams) -> Result<Self, String> {
+        Ok(NFTParam {
+            nft: query.require("nft")?,
+            status: query.list("status")?,
+            limit: query.get("limit")?,
+            offset: query.get("offset")?,
+        })
+    }
 }
 
 #[utoipa::path(
@@ -187,19 +280,20 @@ pub struct NFTParam {
         (status = 500),
     ),
 )]
-pub fn get_nft_direct_buy(
-    db: Queries,
+pub fn get_nft_direct_buy<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("nft" / "direct" / "buy")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<NFTParam>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_nft_direct_buy_handler)
+        .and_then(get_nft_direct_buy_handler::<D>)
 }
 
-pub async fn get_nft_direct_buy_handler(
+pub async fn get_nft_direct_buy_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     params: NFTParam,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let limit = params.limit.unwrap_or(100);
     let offset = params.offset.unwrap_or_default();
@@ -210,7 +304,7 @@ pub async fn get_nft_direct_buy_handler(
     let count = list.first().map(|it| it.cnt).unwrap_or_default();
     let ret: Vec<DirectBuy> = list
         .iter()
-        .map(|x| DirectBuy::from_db(x, &db.tokens))
+        .map(|x| DirectBuy::from_db(x, db.tokens()))
         .collect();
     let nft_ids = ret.iter().map(|x| x.nft.clone()).collect();
 
@@ -237,24 +331,25 @@ pub async fn get_nft_direct_buy_handler(
         (status = 500),
     ),
 )]
-pub fn get_nft_price_history(
-    db: Queries,
+pub fn get_nft_price_history<D: NftRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("nft" / "price-history")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<NftPriceHistoryQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_nft_price_history_handler)
+        .and_then(get_nft_price_history_handler::<D>)
 }
 
-pub async fn get_nft_price_history_handler(
+pub async fn get_nft_price_history_handler<D: NftRepo>(
     query: NftPriceHistoryQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let from = NaiveDateTime::from_timestamp_opt(query.from, 0).expect("Failed to get datetime");
     let to = NaiveDateTime::from_timestamp_opt(query.to, 0).expect("Failed to get datetime");
     let list = catch_error_500!(db.list_nft_price_history(&query.nft, from, to).await);
-    let ret: Vec<NFTPrice> = list.into_iter().map(NFTPrice::from_db).collect();
+    let ret: Vec<NFTPrice> = list.into_iter().map(NFTPrice::from).collect();
     response!(&ret)
 }
 
@@ -263,29 +358,31 @@ pub async fn get_nft_price_history_handler(
     tag = "nft",
     path = "/nfts/top",
     request_body(content = NFTTopListQuery, description = "Get NFT top list"),
+    params(
+        ("X-Include-Hidden" = Option<bool>, Header, description = "Admins only, keep hidden nfts and collections"),
+    ),
     responses(
         (status = 200, body = VecWithNFT),
+        (status = 401),
+        (status = 403),
         (status = 500),
     ),
 )]
-pub fn get_nft_top_list(
-    db: Queries,
+pub fn get_nft_top_list<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
     cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("nfts" / "top")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<NFTTopListQuery>())
+        .and(warp::header::headers_cloned())
         .and(warp::any().map(move || db.clone()))
         .and(warp::any().map(move || cache.clone()))
-        .and_then(get_nft_top_list_handler)
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(get_nft_top_list_handler::<D>)
 }
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub struct NFTTopListQuery {
-    pub from: i64,
-    pub limit: i64,
-    pub offset: i64,
-}
-
 #[derive(Clone, Deserialize, Serialize, Hash)]
 struct NFTTopListQueryCache {
     pub limit: i64,
@@ -298,29 +395,46 @@ struct NFTTopListQueryCache {
     tag = "nft",
     path = "/nfts",
     request_body(content = NFTListQuery, description = "NFT list"),
+    params(
+        ("X-Include-Hidden" = Option<bool>, Header, description = "Admins only, keep hidden nfts and collections"),
+    ),
     responses(
         (status = 200, body = VecWithNFT),
+        (status = 401),
+        (status = 403),
         (status = 500),
     ),
 )]
-pub fn get_nft_list(
-    db: Queries,
+pub fn get_nft_list<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
     cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("nfts")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<NFTListQuery>())
+        .and(warp::header::headers_cloned())
         .and(warp::any().map(move || db.clone()))
         .and(warp::any().map(move || cache.clone()))
-        .and_then(get_nft_list_handler)
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(get_nft_list_handler::<D>)
 }
 
-pub async fn get_nft_list_handler(
+pub async fn get_nft_list_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     params: NFTListQuery,
-    db: Queries,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
     cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
-    let hash = calculate_hash(&params);
+    let include_hidden = wants_hidden(&headers);
+    if include_hidden {
+        let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+        catch_error_403!(admin);
+    }
+
+    let hash = calculate_hash(&(&params, include_hidden));
     let cached_value = cache.get(&hash);
 
     let response;
@@ -332,6 +446,15 @@ pub async fn get_nft_list_handler(
             let offset = params.offset.unwrap_or_default();
             let with_count = params.with_count.unwrap_or(false);
             let limit = params.limit.unwrap_or(100);
+            let filters = NftFilters {
+                media: params.media.clone().unwrap_or_default(),
+                text: params.text.clone(),
+                minted_from: params.minted_from,
+                minted_to: params.minted_to,
+                nft_id_from: params.nft_id_from,
+                nft_id_to: params.nft_id_to,
+                manager: params.manager.clone(),
+            };
 
             let final_limit = match with_count {
                 true => limit,
@@ -350,6 +473,8 @@ pub async fn get_nft_list_handler(
                     &params.attributes.unwrap_or_default(),
                     params.order,
                     with_count,
+                    include_hidden,
+                    &filters,
                 )
                 .await
             );
@@ -373,14 +498,77 @@ pub async fn get_nft_list_handler(
         }
     }
 
+    if include_hidden {
+        return private_reply(&response);
+    }
     response!(&response)
 }
 
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NFTListRandomBuyQuery {
-    pub max_price: i64,
-    pub limit: i32,
+#[utoipa::path(
+    get,
+    tag = "nft",
+    path = "/nfts",
+    params(
+        ("owners" = Option<Vec<String>>, Query, description = "Repeated or comma separated"),
+        ("collections" = Option<Vec<String>>, Query, description = "Repeated or comma separated"),
+        ("priceFrom" = Option<u64>, Query, description = "Min price"),
+        ("priceTo" = Option<u64>, Query, description = "Max price"),
+        ("priceToken" = Option<String>, Query, description = "Price token address"),
+        ("forsale" = Option<bool>, Query, description = "Listed for direct sale"),
+        ("auction" = Option<bool>, Query, description = "Listed on auction"),
+        ("verified" = Option<bool>, Query, description = "Only verified collections"),
+        ("limit" = Option<usize>, Query, description = "Page size"),
+        ("offset" = Option<usize>, Query, description = "Page offset"),
+        ("attributes[<traitType>]" = Option<Vec<String>>, Query, description = "Trait values, e.g. attributes[Background]=Red"),
+        ("order.field" = Option<NFTListOrderField>, Query, description = "Sort field"),
+        ("order.direction" = Option<OrderDirection>, Query, description = "Required with order.field"),
+        ("withCount" = Option<bool>, Query, description = "Count the exact total"),
+        ("media" = Option<Vec<NftMediaKind>>, Query, description = "Media kinds of the files, repeated or comma separated"),
+        ("text" = Option<String>, Query, description = "Words of the name or description, matched as prefixes"),
+        ("mintedFrom" = Option<i64>, Query, description = "Minted at or after, unix time"),
+        ("mintedTo" = Option<i64>, Query, description = "Minted at or before, unix time"),
+        ("nftIdFrom" = Option<u64>, Query, description = "Min nft id"),
+        ("nftIdTo" = Option<u64>, Query, description = "Max nft id"),
+        ("manager" = Option<String>, Query, description = "Manager address"),
+        ("X-Include-Hidden" = Option<bool>, Header, description = "Admins only, keep hidden nfts and collections"),
+    ),
+    responses(
+        (status = 200, body = VecWithNFT),
+        (status = 304),
+        (status = 400),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn get_nft_list_by_query<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
+    cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let max_age = cache_max_age(&cache);
+    cacheable(
+        warp::path!("nfts")
+            .and(warp::get())
+            .and(query_params())
+            .and(warp::header::headers_cloned())
+            .and(warp::any().map(move || db.clone()))
+            .and(warp::any().map(move || cache.clone()))
+            .and(warp::any().map(move || auth_service.clone()))
+            .and_then(get_nft_list_by_query_handler::<D>),
+        max_age,
+    )
+}
+
+pub async fn get_nft_list_by_query_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
+    query: QueryParams,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let params = catch_error_400!(NFTListQuery::from_query(&query));
+    get_nft_list_handler(params, headers, db, cache, auth_service).await
 }
 
 #[utoipa::path(
@@ -388,29 +576,46 @@ pub struct NFTListRandomBuyQuery {
     tag = "nft",
     path = "/nfts/random-buy",
     request_body(content = NFTListRandomBuyQuery, description = "NFT Random buy list"),
+    params(
+        ("X-Include-Hidden" = Option<bool>, Header, description = "Admins only, keep hidden nfts and collections"),
+    ),
     responses(
         (status = 200, body = VecWithNFT),
+        (status = 401),
+        (status = 403),
         (status = 500),
     ),
 )]
-pub fn get_nft_random_list(
-    db: Queries,
+pub fn get_nft_random_list<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
     cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("nfts" / "random-buy")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<NFTListRandomBuyQuery>())
+        .and(warp::header::headers_cloned())
         .and(warp::any().map(move || db.clone()))
         .and(warp::any().map(move || cache.clone()))
-        .and_then(get_nft_random_list_handler)
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(get_nft_random_list_handler::<D>)
 }
 
-pub async fn get_nft_random_list_handler(
+pub async fn get_nft_random_list_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     params: NFTListRandomBuyQuery,
-    db: Queries,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
     cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
-    let hash = calculate_hash(&params);
+    let include_hidden = wants_hidden(&headers);
+    if include_hidden {
+        let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+        catch_error_403!(admin);
+    }
+
+    let hash = calculate_hash(&(&params, include_hidden));
     let cached_value = cache.get(&hash);
 
     let response;
@@ -422,7 +627,7 @@ pub async fn get_nft_random_list_handler(
             }
             let max_price = params.max_price;
 
-            let list = catch_error_500!(db.nft_random_buy(max_price, limit).await);
+            let list = catch_error_500!(db.nft_random_buy(max_price, limit, include_hidden).await);
             let mut r = catch_error_500!(make_nfts_response(list, db).await);
 
             r.count = r.items.len() as i64;
@@ -437,38 +642,51 @@ pub async fn get_nft_random_list_handler(
         }
     }
 
+    if include_hidden {
+        return private_reply(&response);
+    }
     response!(&response)
 }
 
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NFTSellCountQuery {
-    pub max_price: i64,
-}
-
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NFTSellCountResponse {
-    pub count: i64,
-    pub timestamp: i64,
-}
-
-/// GET /nfts/sell-count
-pub fn get_nft_sell_count(
-    db: Queries,
+/// The json body is still accepted for clients written before the query string support
+#[utoipa::path(
+    get,
+    tag = "nft",
+    path = "/nfts/sell-count",
+    params(
+        ("maxPrice" = i64, Query, description = "Upper bound of the sell price"),
+    ),
+    responses(
+        (status = 200, body = NFTSellCountResponse),
+        (status = 304),
+        (status = 400),
+        (status = 500),
+    ),
+)]
+pub fn get_nft_sell_count<D: NftRepo>(
+    db: D,
     cache: Cache<u64, Value>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-    warp::path!("nfts" / "sell-count")
-        .and(warp::get())
-        .and(warp::body::json::<NFTSellCountQuery>())
-        .and(warp::any().map(move || db.clone()))
-        .and(warp::any().map(move || cache.clone()))
-        .and_then(get_nft_sell_count_handler)
+    let max_age = cache_max_age(&cache);
+    cacheable(
+        warp::path!("nfts" / "sell-count")
+            .and(warp::get())
+            .and(
+                warp::query::<NFTSellCountQuery>()
+                    .or(warp::body::content_length_limit(MAX_JSON_BODY_BYTES)
+                        .and(warp::body::json::<NFTSellCountQuery>()))
+                    .unify(),
+            )
+            .and(warp::any().map(move || db.clone()))
+            .and(warp::any().map(move || cache.clone()))
+            .and_then(get_nft_sell_count_handler::<D>),
+        max_age,
+    )
 }
 
-pub async fn get_nft_sell_count_handler(
+pub async fn get_nft_sell_count_handler<D: NftRepo>(
     params: NFTSellCountQuery,
-    db: Queries,
+    db: D,
     cache: Cache<u64, Value>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let hash = calculate_hash(&params);
@@ -495,16 +713,24 @@ pub async fn get_nft_sell_count_handler(
     response!(&response)
 }
 
-pub async fn get_nft_top_list_handler(
+pub async fn get_nft_top_list_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     params: NFTTopListQuery,
-    db: Queries,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
     cache: Cache<u64, Value>,
+    auth_service: Arc<AuthService>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let include_hidden = wants_hidden(&headers);
+    if include_hidden {
+        let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+        catch_error_403!(admin);
+    }
+
     let params_cache = NFTTopListQueryCache {
         limit: params.limit,
         offset: params.offset,
     };
-    let hash = calculate_hash(&params_cache);
+    let hash = calculate_hash(&(&params_cache, include_hidden));
     let cached_value = cache.get(&hash);
 
     let response;
@@ -512,7 +738,10 @@ pub async fn get_nft_top_list_handler(
         None => {
             let from =
                 NaiveDateTime::from_timestamp_opt(params.from, 0).expect("Failed to get datetime");
-            let list = catch_error_500!(db.nft_top_search(from, params.limit, params.offset).await);
+            let list = catch_error_500!(
+                db.nft_top_search(from, params.limit, params.offset, include_hidden)
+                    .await
+            );
             response = catch_error_500!(make_nfts_response(list, db).await);
             let value_for_cache =
                 serde_json::to_value(response.clone()).expect("Failed serializing cached value");
@@ -523,6 +752,9 @@ pub async fn get_nft_top_list_handler(
         }
     }
 
+    if include_hidden {
+        return private_reply(&response);
+    }
     Ok(Box::from(warp::reply::with_status(
         warp::reply::json(&response),
         StatusCode::OK,
@@ -557,13 +789,16 @@ pub async fn get_nft_top_list_handler(
 //     })
 // }
 
-async fn make_nfts_response(list: Vec<NftDetails>, db: Queries) -> anyhow::Result<VecWith<NFT>> {
+async fn make_nfts_response<D: CollectionRepo + AuctionRepo>(
+    list: Vec<NftDetails>,
+    db: D,
+) -> anyhow::Result<VecWith<NFT>> {
     let count = match list.first() {
         None => 0,
         Some(first) => first.total_count,
     };
 
-    let ret: Vec<NFT> = list.iter().map(|it| NFT::from_db(it.clone())).collect();
+    let ret: Vec<NFT> = list.iter().map(|it| NFT::from(it.clone())).collect();
 
     let collection_ids = ret.iter().map(|x| x.collection.clone()).collect();
     let collection = collect_collections(&db, &collection_ids);
@@ -591,95 +826,54 @@ async fn make_nfts_response(list: Vec<NftDetails>, db: Queries) -> anyhow::Resul
     })
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub struct AttributeFilter {
-    #[serde(rename = "traitType")]
-    pub trait_type: String,
-    #[serde(rename = "traitValues")]
-    pub trait_values: Vec<String>,
-}
-
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub struct NFTListQuery {
-    pub owners: Option<Vec<String>>,
-    pub collections: Option<Vec<String>>,
-    #[serde(rename = "priceFrom")]
-    pub price_from: Option<u64>,
-    #[serde(rename = "priceTo")]
-    pub price_to: Option<u64>,
-    #[serde(rename = "priceToken")]
-    pub price_token: Option<String>,
-    pub forsale: Option<bool>,
-    pub auction: Option<bool>,
-    pub verified: Option<bool>,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-    pub attributes: Option<Vec<AttributeFilter>>,
-    pub order: Option<NFTListOrder>,
-    #[serde(rename = "withCount")]
-    pub with_count: Option<bool>,
-}
-
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub enum NFTListOrderField {
-    #[serde(rename = "floorPriceUsd")]
-    FloorPriceUsd,
-    #[serde(rename = "dealPriceUsd")]
-    DealPriceUsd,
-    #[serde(rename = "name")]
-    Name,
-}
-
-impl Display for NFTListOrderField {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            NFTListOrderField::FloorPriceUsd => write!(f, "floor_price_usd"),
-            NFTListOrderField::DealPriceUsd => write!(f, "deal_price_usd"),
-            NFTListOrderField::Name => write!(f, "name"),
-        }
-    }
-}
-
-#[derive(Clone, Deserialize, Serialize, Hash, ToSchema)]
-pub struct NFTListOrder {
-    pub field: NFTListOrderField,
-    pub direction: OrderDirection,
-}
-
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub enum PriceHistoryScale {
-    #[serde(rename = "h")]
-    Hours,
-    #[serde(rename = "d")]
-    Days,
-}
-
-impl Display for PriceHistoryScale {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            PriceHistoryScale::Days => write!(f, "day"),
-            PriceHistoryScale::Hours => write!(f, "hour"),
-        }
+impl FromQuery for NFTListQuery {
+    fn from_query(query: &QueryParams) -> Result<Self, String> {
+        let attributes: Vec<AttributeFilter> = query
+            .grouped("attributes")
+            .into_iter()
+            .map(|(trait_type, trait_values)| AttributeFilter {
+                trait_type,
+                trait_values,
+            })
+            .collect();
+        let order = match query.get("order.field")? {
+            Some(field) => Some(NFTListOrder {
+                field,
+                direction: query.require("order.direction")?,
+            }),
+            None => None,
+        };
+        Ok(NFTListQuery {
+            owners: query.list("owners")?,
+            collections: query.list("collections")?,
+            price_from: query.get("priceFrom")?,
+            price_to: query.get("priceTo")?,
+            price_token: query.get("priceToken")?,
+            forsale: query.get("forsale")?,
+            auction: query.get("auction")?,
+            verified: query.get("verified")?,
+            limit: query.get("limit")?,
+            offset: query.get("offset")?,
+            attributes: (!attributes.is_empty()).then_some(attributes),
+            order,
+            with_count: query.get("withCount")?,
+            media: query.list("media")?,
+            text: query.get("text")?,
+            minted_from: query.get("mintedFrom")?,
+            minted_to: query.get("mintedTo")?,
+            nft_id_from: query.get("nftIdFrom")?,
+            nft_id_to: query.get("nftIdTo")?,
+            manager: query.get("manager")?,
+        })
     }
 }
 
-impl Default for PriceHistoryScale {
-    fn default() -> Self {
-        Self::Days
-    }
-}
-
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub struct NftPriceHistoryQuery {
-    pub nft: Address,
-    pub scale: Option<PriceHistoryScale>,
-    pub from: i64,
-    pub to: i64,
-}
-
-pub async fn collect_nfts(db: &Queries, ids: &[String]) -> anyhow::Result<HashMap<String, NFT>> {
+pub async fn collect_nfts<D: NftRepo>(
+    db: &D,
+    ids: &[String],
+) -> anyhow::Result<HashMap<String, NFT>> {
     let dblist = db.collect_nfts(ids).await?;
-    let list = dblist.into_iter().map(NFT::from_db);
+    let list = dblist.into_iter().map(NFT::from);
     let mut map = HashMap::new();
     for item in list {
         map.insert(item.contract.address.clone(), item.clone());
@@ -688,8 +882,8 @@ pub async fn collect_nfts(db: &Queries, ids: &[String]) -> anyhow::Result<HashMa
 }
 
 #[allow(clippy::ptr_arg)]
-pub async fn collect_nft_and_collection(
-    db: &Queries,
+pub async fn collect_nft_and_collection<D: NftRepo + CollectionRepo>(
+    db: &D,
     nft_ids: &Vec<String>,
 ) -> anyhow::Result<(HashMap<String, NFT>, HashMap<String, Collection>)> {
     let nft = collect_nfts(db, nft_ids).await?;
@@ -698,14 +892,14 @@ pub async fn collect_nft_and_collection(
     Ok((nft, collection))
 }
 
-pub async fn collect_direct_sell(
-    db: &Queries,
+pub async fn collect_direct_sell<D: AuctionRepo>(
+    db: &D,
     ids: &[String],
 ) -> anyhow::Result<HashMap<String, DirectSell>> {
     let dblist = db.collect_direct_sell(ids).await?;
     let list = dblist
         .iter()
-        .map(|col| DirectSell::from_db(col, &db.tokens));
+        .map(|col| DirectSell::from_db(col, db.tokens()));
     let mut map = HashMap::new();
     for item in list {
         map.insert(item.address.clone(), item.clone());
@@ -713,12 +907,14 @@ pub async fn collect_direct_sell(
     Ok(map)
 }
 
-pub async fn collect_direct_buy(
-    db: &Queries,
+pub async fn collect_direct_buy<D: AuctionRepo>(
+    db: &D,
     ids: &[String],
 ) -> anyhow::Result<HashMap<String, DirectBuy>> {
     let dblist = db.collect_direct_buy(ids).await?;
-    let list = dblist.iter().map(|col| DirectBuy::from_db(col, &db.tokens));
+    let list = dblist
+        .iter()
+        .map(|col| DirectBuy::from_db(col, db.tokens()));
     let mut map = HashMap::new();
     for item in list {
         map.insert(item.address.clone(), item.clone());
@@ -0,0 +1,243 @@
+use crate::db::repo::{ApiRepo, CollectionRepo, OrderBookRepo};
+use crate::db::OrderBookRecord;
+use crate::handlers::calculate_hash;
+use crate::handlers::http_cache::{cache_max_age, cacheable};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::model::{CollectionOrderBook, OrderBook, PriceLevel};
+use crate::{api_doc_addon, catch_error_400, catch_error_500, response};
+pub use api_types::{Address, OrderBookQuery};
+use moka::future::Cache;
+use serde_json::Value;
+use sqlx::types::BigDecimal;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::str::FromStr;
+use utoipa::OpenApi;
+use warp::http::{Method, StatusCode};
+use warp::Filter;
+
+const DEFAULT_LEVELS: usize = 50;
+const MAX_LEVELS: usize = 200;
+/// Bounds the work of bucketing, a step of `1e-100000000` would expand every price
+const MAX_STEP_SCALE: i64 = 36;
+const MAX_STEP_CHARS: usize = 64;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get_orderbook),
+    components(schemas(CollectionOrderBook, OrderBook, PriceLevel)),
+    tags(
+        (name = "orderbook", description = "Listing and offer depth of a collection"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::GET,
+        "/collection/{address}/orderbook",
+        get_orderbook(ctx.db.clone(), ctx.cache_minute.clone()),
+    );
+}
+
+#[utoipa::path(
+    get,
+    tag = "orderbook",
+    path = "/collection/{address}/orderbook",
+    params(("address" = String, Path, description = "Collection address"), OrderBookQuery),
+    responses(
+        (status = 200, body = CollectionOrderBook),
+        (status = 304),
+        (status = 400),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn get_orderbook<D: CollectionRepo + OrderBookRepo>(
+    db: D,
+    cache: Cache<u64, Value>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let max_age = cache_max_age(&cache);
+    cacheable(
+        warp::path!("collection" / String / "orderbook")
+            .and(warp::get())
+            .and(warp::query::<OrderBookQuery>())
+            .and(warp::any().map(move || db.clone()))
+            .and(warp::any().map(move || cache.clone()))
+            .and_then(get_orderbook_handler::<D>),
+        max_age,
+    )
+}
+
+pub async fn get_orderbook_handler<D: CollectionRepo + OrderBookRepo>(
+    address: String,
+    query: OrderBookQuery,
+    db: D,
+    cache: Cache<u64, Value>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let usd_step = catch_error_400!(query.usd_step.as_deref().map(parse_step).transpose());
+    let token_step = catch_error_400!(query.token_step.as_deref().map(parse_step).transpose());
+    let token_step = match (token_step, &query.token) {
+        (Some(step), Some(token)) => Some((token.clone(), step)),
+        (Some(_), None) => {
+            return Ok(Box::from(warp::reply::with_status(
+                "tokenStep needs the token it applies to",
+                StatusCode::BAD_REQUEST,
+            )))
+        }
+        (None, _) => None,
+    };
+    let levels = query.levels.unwrap_or(DEFAULT_LEVELS).min(MAX_LEVELS);
+
+    let hash = calculate_hash(&(
+        "orderbook",
+        &address,
+        &query.usd_step,
+        &query.token_step,
+        &query.token,
+        levels,
+    ));
+    let ret: CollectionOrderBook;
+    match cache.get(&hash) {
+        None => {
+            if catch_error_500!(db.get_collection(&address).await).is_none() {
+                return Ok(Box::from(warp::reply::with_status(
+                    "Collection not found",
+                    StatusCode::NOT_FOUND,
+                )));
+            }
+            let orders = catch_error_500!(db.collection_orders(&address).await);
+            ret = order_book(address, &orders, usd_step, token_step, levels);
+            let value_for_cache =
+                serde_json::to_value(ret.clone()).expect("Failed serializing cached value");
+            cache.insert(hash, value_for_cache).await;
+        }
+        Some(cached_value) => {
+            ret = serde_json::from_value(cached_value).expect("Failed parsing cached value")
+        }
+    }
+    response!(&ret)
+}
+
+/// A usd book over the orders with a usd price and a native book per token, `token_step`
+/// applies to the book of its token only since tokens differ in decimals
+pub fn order_book(
+    collection: Address,
+    orders: &[OrderBookRecord],
+    usd_step: Option<BigDecimal>,
+    token_step: Option<(Address, BigDecimal)>,
+    levels: usize,
+) -> CollectionOrderBook {
+    let usd: Vec<(bool, &BigDecimal)> = orders
+        .iter()
+        .filter_map(|o| Some((o.bid, o.price_usd.as_ref()?)))
+        .collect();
+    let mut by_token: BTreeMap<&Address, Vec<(bool, &BigDecimal)>> = BTreeMap::new();
+    for order in orders {
+        by_token
+            .entry(&order.price_token)
+            .or_default()
+            .push((order.bid, &order.price));
+    }
+
+    CollectionOrderBook {
+        collection,
+        direct_sells: orders.iter().filter(|o| !o.bid && !o.auction).count(),
+        auctions: orders.iter().filter(|o| o.auction).count(),
+        direct_buys: orders.iter().filter(|o| o.bid).count(),
+        usd: book(None, &usd, usd_step, levels),
+        tokens: by_token
+            .into_iter()
+            .map(|(token, prices)| {
+                let step = token_step
+                    .as_ref()
+                    .filter(|(step_token, _)| step_token == token)
+                    .map(|(_, step)| step.clone());
+                book(Some(token.clone()), &prices, step, levels)
+            })
+            .collect(),
+    }
+}
+
+fn book(
+    token: Option<Address>,
+    prices: &[(bool, &BigDecimal)],
+    step: Option<BigDecimal>,
+    levels: usize,
+) -> OrderBook {
+    let best_ask = prices.iter().filter(|(bid, _)| !bid).map(|(_, p)| *p).min();
+    let best_bid = prices.iter().filter(|(bid, _)| *bid).map(|(_, p)| *p).max();
+    let step = step.or_else(|| best_ask.or(best_bid).map(auto_step));
+
+    let (mut asks, mut bids) = (vec![], vec![]);
+    if let Some(step) = &step {
+        let mut ask_levels: BTreeMap<BigDecimal, usize> = BTreeMap::new();
+        let mut bid_levels: BTreeMap<BigDecimal, usize> = BTreeMap::new();
+        for (bid, price) in prices {
+            let level = (*price / step).with_scale(0) * step;
+            let side = if *bid {
+                &mut bid_levels
+            } else {
+                &mut ask_levels
+            };
+            *side.entry(level).or_default() += 1;
+        }
+        asks = cumulative(ask_levels.into_iter(), levels);
+        bids = cumulative(bid_levels.into_iter().rev(), levels);
+    }
+
+    OrderBook {
+        token,
+        step: step.map(|s| s.normalized().to_string()),
+        spread: best_ask
+            .zip(best_bid)
+            .map(|(ask, bid)| (ask - bid).normalized().to_string()),
+        best_ask: best_ask.map(BigDecimal::to_string),
+        best_bid: best_bid.map(BigDecimal::to_string),
+        asks,
+        bids,
+    }
+}
+
+/// Levels from the best price outwards
+fn cumulative(levels: impl Iterator<Item = (BigDecimal, usize)>, limit: usize) -> Vec<PriceLevel> {
+    let mut total = 0;
+    levels
+        .take(limit)
+        .map(|(price, count)| {
+            total += count;
+            PriceLevel {
+                price: price.normalized().to_string(),
+                count,
+                cumulative: total,
+            }
+        })
+        .collect()
+}
+
+/// Keeps two significant digits of the price, `6.75` is bucketed by `0.1`
+fn auto_step(price: &BigDecimal) -> BigDecimal {
+    if price <= &BigDecimal::default() {
+        return BigDecimal::from(1);
+    }
+    let (digits, scale) = price.normalized().as_bigint_and_exponent();
+    let exponent = digits.to_string().len() as i64 - 2 - scale;
+    BigDecimal::from_str(&format!("1e{exponent}")).expect("a power of ten parses")
+}
+
+fn parse_step(step: &str) -> anyhow::Result<BigDecimal> {
+    let step = step.trim();
+    if step.len() > MAX_STEP_CHARS {
+        anyhow::bail!("step must be at most {MAX_STEP_CHARS} characters");
+    }
+    let step = BigDecimal::from_str(step).map_err(|_| anyhow::anyhow!("step must be a number"))?;
+    if step <= BigDecimal::default() {
+        anyhow::bail!("step must be positive");
+    }
+    let (_, scale) = step.as_bigint_and_exponent();
+    if scale.abs() > MAX_STEP_SCALE {
+        anyhow::bail!("step must be between 1e-{MAX_STEP_SCALE} and 1e{MAX_STEP_SCALE}");
+    }
+    Ok(step)
+}
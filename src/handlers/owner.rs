@@ -1,23 +1,27 @@
-use crate::db::queries::Queries;
+use crate::db::repo::{ApiRepo, AuctionRepo, CollectionRepo, NftRepo};
 use crate::db::RootType;
 use crate::handlers::auction::collect_auctions_nfts_collections;
 use crate::handlers::nft::collect_nft_and_collection;
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
+use crate::model::FromDb;
 use crate::model::OwnerFee;
 use crate::schema::VecWithAuctionBids;
 use crate::schema::VecWithDirectBuy;
 use crate::schema::VecWithDirectSell;
 use crate::{
     api_doc_addon, catch_error_500,
-    db::{Address, DirectBuyState, DirectSellState},
+    db::Address,
     model::{AuctionBid, DirectBuy, DirectSell, VecWith},
     response,
 };
-use serde::{Deserialize, Serialize};
+pub use api_types::{
+    OwnerBidsInQuery, OwnerBidsOutQuery, OwnerDirectBuyQuery, OwnerDirectSellQuery, OwnerFeeQuery,
+};
+use serde::Deserialize;
 use std::convert::Infallible;
-use utoipa::IntoParams;
 use utoipa::OpenApi;
-use utoipa::ToSchema;
-use warp::http::StatusCode;
+use warp::http::{Method, StatusCode};
 use warp::Filter;
 
 #[derive(OpenApi)]
@@ -48,6 +52,35 @@ use warp::Filter;
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::POST,
+        "/owner/bids-out",
+        get_owner_bids_out(ctx.db.clone()),
+    )
+    .add(
+        Method::POST,
+        "/owner/bids-in",
+        get_owner_bids_in(ctx.db.clone()),
+    )
+    .add(
+        Method::POST,
+        "/owner/direct/buy",
+        get_owner_direct_buy(ctx.db.clone()),
+    )
+    .add(
+        Method::POST,
+        "/owner/direct/buy-in",
+        get_owner_direct_buy_in(ctx.db.clone()),
+    )
+    .add(
+        Method::POST,
+        "/owner/direct/sell",
+        get_owner_direct_sell(ctx.db.clone()),
+    )
+    .add(Method::GET, "/owner/fee", get_fee(ctx.db.clone()));
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct OwnerParam {
     pub owner: Address,
@@ -63,19 +96,20 @@ pub struct OwnerParam {
         (status = 500),
     ),
 )]
-pub fn get_owner_bids_out(
-    db: Queries,
+pub fn get_owner_bids_out<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("owner" / "bids-out")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<OwnerBidsOutQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_owner_bids_out_handler)
+        .and_then(get_owner_bids_out_handler::<D>)
 }
 
-pub async fn get_owner_bids_out_handler(
+pub async fn get_owner_bids_out_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     query: OwnerBidsOutQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let collections = query.collections.as_deref().unwrap_or(&[]);
     let owner = query.owner;
@@ -89,7 +123,7 @@ pub async fn get_owner_bids_out_handler(
     let count = list.first().map(|it| it.cnt).unwrap_or_default();
     let ret: Vec<AuctionBid> = list
         .iter()
-        .map(|x| AuctionBid::from_extended(x, &db.tokens))
+        .map(|x| AuctionBid::from_db(x, db.tokens()))
         .collect();
     let auction_ids: Vec<String> = ret.iter().map(|x| x.auction.clone()).collect();
     let (nft, collection, auctions) =
@@ -107,14 +141,6 @@ pub async fn get_owner_bids_out_handler(
     response!(&ret)
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub struct OwnerBidsOutQuery {
-    pub owner: Address,
-    pub collections: Option<Vec<Address>>,
-    pub lastbid: Option<bool>,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-}
 #[utoipa::path(
     tag = "owner",
     post,
@@ -125,19 +151,20 @@ pub struct OwnerBidsOutQuery {
         (status = 500),
     ),
 )]
-pub fn get_owner_bids_in(
-    db: Queries,
+pub fn get_owner_bids_in<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("owner" / "bids-in")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<OwnerBidsInQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_owner_bids_in_handler)
+        .and_then(get_owner_bids_in_handler::<D>)
 }
 
-pub async fn get_owner_bids_in_handler(
+pub async fn get_owner_bids_in_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     query: OwnerBidsInQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let collections = query.collections.as_deref().unwrap_or(&[]);
     let owner = query.owner;
@@ -152,7 +179,7 @@ pub async fn get_owner_bids_in_handler(
     let count = list.first().map(|it| it.cnt).unwrap_or_default();
     let ret: Vec<AuctionBid> = list
         .iter()
-        .map(|x| AuctionBid::from_extended(x, &db.tokens))
+        .map(|x| AuctionBid::from_db(x, db.tokens()))
         .collect();
     let auction_ids: Vec<String> = ret.iter().map(|x| x.auction.clone()).collect();
     let (nft, collection, auctions) =
@@ -170,15 +197,6 @@ pub async fn get_owner_bids_in_handler(
     response!(&ret)
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub struct OwnerBidsInQuery {
-    pub owner: Address,
-    pub collections: Option<Vec<Address>>,
-    pub active: Option<bool>,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-}
-
 #[utoipa::path(
     tag = "owner",
     post,
@@ -189,19 +207,20 @@ pub struct OwnerBidsInQuery {
         (status = 500),
     ),
 )]
-pub fn get_owner_direct_buy(
-    db: Queries,
+pub fn get_owner_direct_buy<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("owner" / "direct" / "buy")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<OwnerDirectBuyQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_owner_direct_buy_handler)
+        .and_then(get_owner_direct_buy_handler::<D>)
 }
 
-pub async fn get_owner_direct_buy_handler(
+pub async fn get_owner_direct_buy_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     query: OwnerDirectBuyQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let collections = query.collections.as_deref().unwrap_or(&[]);
     let owner = query.owner;
@@ -216,7 +235,7 @@ pub async fn get_owner_direct_buy_handler(
     let count = list.first().map(|it| it.cnt).unwrap_or_default();
     let ret: Vec<DirectBuy> = list
         .iter()
-        .map(|x| DirectBuy::from_db(x, &db.tokens))
+        .map(|x| DirectBuy::from_db(x, db.tokens()))
         .collect();
 
     let nft_ids = ret.iter().map(|x| x.nft.clone()).collect();
@@ -252,19 +271,20 @@ pub async fn get_owner_direct_buy_handler(
         (status = 500),
     ),
 )]
-pub fn get_owner_direct_buy_in(
-    db: Queries,
+pub fn get_owner_direct_buy_in<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("owner" / "direct" / "buy-in")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<OwnerDirectBuyQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_owner_direct_buy_in_handler)
+        .and_then(get_owner_direct_buy_in_handler::<D>)
 }
 
-pub async fn get_owner_direct_buy_in_handler(
+pub async fn get_owner_direct_buy_in_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     query: OwnerDirectBuyQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let collections = query.collections.as_deref().unwrap_or_default();
     let owner = query.owner;
@@ -279,7 +299,7 @@ pub async fn get_owner_direct_buy_in_handler(
     let count = list.first().map(|it| it.cnt).unwrap_or_default();
     let ret: Vec<DirectBuy> = list
         .iter()
-        .map(|x| DirectBuy::from_db(x, &db.tokens))
+        .map(|x| DirectBuy::from_db(x, db.tokens()))
         .collect();
     let nft_ids = ret.iter().map(|x| x.nft.clone()).collect();
     let (nft, collection) = match collect_nft_and_collection(&db, &nft_ids).await {
@@ -314,19 +334,20 @@ pub async fn get_owner_direct_buy_in_handler(
         (status = 500),
     ),
 )]
-pub fn get_owner_direct_sell(
-    db: Queries,
+pub fn get_owner_direct_sell<D: NftRepo + CollectionRepo + AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("owner" / "direct" / "sell")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<OwnerDirectSellQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_owner_direct_sell_handler)
+        .and_then(get_owner_direct_sell_handler::<D>)
 }
 
-pub async fn get_owner_direct_sell_handler(
+pub async fn get_owner_direct_sell_handler<D: NftRepo + CollectionRepo + AuctionRepo>(
     query: OwnerDirectSellQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let collections = query.collections.as_deref().unwrap_or(&[]);
     let owner = query.owner;
@@ -341,7 +362,7 @@ pub async fn get_owner_direct_sell_handler(
     let count = list.first().map(|it| it.cnt).unwrap_or_default();
     let ret: Vec<DirectSell> = list
         .iter()
-        .map(|x| DirectSell::from_db(x, &db.tokens))
+        .map(|x| DirectSell::from_db(x, db.tokens()))
         .collect();
     let nft_ids = ret.iter().map(|x| x.nft.clone()).collect();
     let (nft, collection) = catch_error_500!(collect_nft_and_collection(&db, &nft_ids).await);
@@ -358,14 +379,6 @@ pub async fn get_owner_direct_sell_handler(
     response!(&ret)
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, IntoParams)]
-#[into_params(parameter_in = Query)]
-pub struct OwnerFeeQuery {
-    pub owner: Address,
-    #[serde(rename = "rootCode")]
-    pub root_code: RootType,
-}
-
 #[utoipa::path(
 tag = "owner",
     get,
@@ -376,40 +389,22 @@ tag = "owner",
         (status = 500),
     ),
 )]
-pub fn get_fee(
-    db: Queries,
+pub fn get_fee<D: AuctionRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("owner" / "fee")
         .and(warp::get())
         .and(warp::query::<OwnerFeeQuery>())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_fee_handler)
+        .and_then(get_fee_handler::<D>)
 }
 
-pub async fn get_fee_handler(
+pub async fn get_fee_handler<D: AuctionRepo>(
     query: OwnerFeeQuery,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let fee = catch_error_500!(db.get_owner_fee(&query.owner, &query.root_code).await);
 
     let owner_fee = OwnerFee::from(fee);
     response!(&owner_fee)
 }
-
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub struct OwnerDirectSellQuery {
-    pub owner: Address,
-    pub collections: Option<Vec<Address>>,
-    pub status: Option<Vec<DirectSellState>>,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-}
-
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
-pub struct OwnerDirectBuyQuery {
-    pub owner: Address,
-    pub collections: Option<Vec<Address>>,
-    pub status: Option<Vec<DirectBuyState>>,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
-}
@@ -0,0 +1,90 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::convert::Infallible;
+use warp::Filter;
+
+/// Raw query string pairs of a GET request.
+///
+/// Keys mirror the json bodies of the POST endpoints: lists are passed as repeated keys
+/// (`owners=a&owners=b`, `owners[]=a`) or comma separated (`owners=a,b`), nested objects
+/// with dots (`order.field=name`) and attribute filters as `attributes[Trait]=Value`.
+#[derive(Debug, Clone, Default)]
+pub struct QueryParams(Vec<(String, String)>);
+
+pub fn query_params() -> impl Filter<Extract = (QueryParams,), Error = Infallible> + Clone {
+    warp::query::raw()
+        .or(warp::any().map(String::new))
+        .unify()
+        .map(|raw: String| QueryParams::parse(&raw))
+}
+
+/// Request types of the GET endpoints read from the query string
+pub trait FromQuery: Sized {
+    fn from_query(query: &QueryParams) -> Result<Self, String>;
+}
+
+impl QueryParams {
+    pub fn parse(raw: &str) -> Self {
+        QueryParams(serde_urlencoded::from_str(raw).unwrap_or_default())
+    }
+
+    fn values<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.0
+            .iter()
+            .filter(move |(k, _)| k == key || k.strip_suffix("[]") == Some(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Last value of the key, strings are tried first so `"007"` stays a string name
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, String> {
+        self.values(key)
+            .last()
+            .map(|value| parse_value(key, value))
+            .transpose()
+    }
+
+    pub fn require<T: DeserializeOwned>(&self, key: &str) -> Result<T, String> {
+        self.get(key)?
+            .ok_or_else(|| format!("missing query parameter {key}"))
+    }
+
+    pub fn list<T: DeserializeOwned>(&self, key: &str) -> Result<Option<Vec<T>>, String> {
+        let mut values = self.values(key).peekable();
+        if values.peek().is_none() {
+            return Ok(None);
+        }
+        values
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| parse_value(key, v))
+            .collect::<Result<Vec<T>, _>>()
+            .map(Some)
+    }
+
+    /// `prefix[name]=value` pairs grouped by name in the order of appearance.
+    /// Values are not split by commas since trait values are free text
+    pub fn grouped(&self, prefix: &str) -> Vec<(String, Vec<String>)> {
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        for (key, value) in &self.0 {
+            let Some(name) = key
+                .strip_prefix(prefix)
+                .and_then(|k| k.strip_prefix('['))
+                .and_then(|k| k.strip_suffix(']'))
+            else {
+                continue;
+            };
+            match groups.iter_mut().find(|(n, _)| n == name) {
+                Some((_, values)) => values.push(value.clone()),
+                None => groups.push((name.to_string(), vec![value.clone()])),
+            }
+        }
+        groups
+    }
+}
+
+fn parse_value<T: DeserializeOwned>(key: &str, value: &str) -> Result<T, String> {
+    serde_json::from_value(Value::String(value.to_string()))
+        .or_else(|_| serde_json::from_str(value))
+        .map_err(|_| format!("invalid value {value:?} of query parameter {key}"))
+}
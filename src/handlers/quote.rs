@@ -0,0 +1,155 @@
+use crate::db::repo::{ApiRepo, NftRepo, QuoteRepo};
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::model::{QuoteRoyalty, RootQuote, SaleQuote};
+use crate::{api_doc_addon, catch_error_400, catch_error_500, response};
+pub use api_types::{Fee, QuoteQuery, SaleType};
+use sqlx::types::BigDecimal;
+use std::convert::Infallible;
+use std::str::FromStr;
+use utoipa::OpenApi;
+use warp::http::{Method, StatusCode};
+use warp::Filter;
+
+/// Enough for a uint256 amount
+const MAX_PRICE_DIGITS: usize = 78;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get_quote),
+    components(schemas(QuoteRoyalty, RootQuote, SaleQuote, SaleType)),
+    tags(
+        (name = "quote", description = "Fees and proceeds of a sale"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(Method::GET, "/quote", get_quote(ctx.db.clone()));
+}
+
+#[utoipa::path(
+    get,
+    tag = "quote",
+    path = "/quote",
+    params(QuoteQuery),
+    responses(
+        (status = 200, body = SaleQuote),
+        (status = 400),
+        (status = 404),
+        (status = 500),
+    ),
+)]
+pub fn get_quote<D: NftRepo + QuoteRepo>(
+    db: D,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("quote")
+        .and(warp::get())
+        .and(warp::query::<QuoteQuery>())
+        .and(warp::any().map(move || db.clone()))
+        .and_then(get_quote_handler::<D>)
+}
+
+pub async fn get_quote_handler<D: NftRepo + QuoteRepo>(
+    query: QuoteQuery,
+    db: D,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let price = catch_error_400!(parse_price(&query.price));
+    let Some(nft) = catch_error_500!(db.get_nft_details(&query.nft).await) else {
+        return Ok(Box::from(warp::reply::with_status(
+            "Nft not found",
+            StatusCode::NOT_FOUND,
+        )));
+    };
+    if nft.burned == Some(true) {
+        return Ok(Box::from(warp::reply::with_status(
+            "The nft is burned",
+            StatusCode::BAD_REQUEST,
+        )));
+    }
+    let collection = nft.collection.clone().unwrap_or_default();
+    let roots = catch_error_500!(
+        db.root_fees(&collection, &query.sale_type.root_type())
+            .await
+    );
+    let usd_price = catch_error_500!(db.token_usd_price(&query.token).await);
+    let usd = |amount: &BigDecimal| usd_price.as_ref().map(|p| (amount * p).to_string());
+
+    let royalty = nft.parse_meta().royalty.and_then(|royalty| {
+        let fee = royalty.fee?;
+        let amount = share(&price, &fee);
+        Some((fee, royalty.recipient, amount))
+    });
+    let royalty_amount = royalty
+        .as_ref()
+        .map(|(_, _, amount)| amount.clone())
+        .unwrap_or_default();
+
+    let roots = roots
+        .into_iter()
+        .map(|root| {
+            let fee =
+                root.fee_numerator
+                    .zip(root.fee_denominator)
+                    .map(|(numerator, denominator)| Fee {
+                        numerator,
+                        denominator,
+                    });
+            let fee_amount = fee.as_ref().map(|fee| share(&price, fee));
+            // without a fee the royalty is still known, the flag tells the proceeds are partial
+            let proceeds = (&price - fee_amount.clone().unwrap_or_default() - &royalty_amount)
+                .max(BigDecimal::default());
+            RootQuote {
+                root: root.root,
+                fee_unknown: fee.is_none(),
+                fee,
+                collection_fee: root.collection_fee,
+                fee_amount_usd: fee_amount.as_ref().and_then(usd),
+                fee_amount: fee_amount.map(|a| a.to_string()),
+                proceeds_usd: usd(&proceeds),
+                proceeds: proceeds.to_string(),
+            }
+        })
+        .collect();
+
+    response!(&SaleQuote {
+        nft: query.nft,
+        collection,
+        sale_type: query.sale_type,
+        token: query.token,
+        price_usd: usd(&price),
+        price: price.to_string(),
+        royalty: royalty.map(|(fee, recipient, amount)| QuoteRoyalty {
+            fee,
+            recipient,
+            amount_usd: usd(&amount),
+            amount: amount.to_string(),
+        }),
+        roots,
+    })
+}
+
+/// A positive whole number of the smallest token units, digits only so no exponent reaches
+/// the decimal arithmetic
+fn parse_price(price: &str) -> anyhow::Result<BigDecimal> {
+    let price = price.trim();
+    if price.is_empty() || !price.bytes().all(|b| b.is_ascii_digit()) {
+        anyhow::bail!("price must be a positive whole number of the smallest token units");
+    }
+    if price.len() > MAX_PRICE_DIGITS {
+        anyhow::bail!("price must be at most {MAX_PRICE_DIGITS} digits");
+    }
+    let price = BigDecimal::from_str(price)?;
+    if price <= BigDecimal::default() {
+        anyhow::bail!("price must be a positive whole number of the smallest token units");
+    }
+    Ok(price)
+}
+
+/// `price * fee` rounded down to a whole smallest unit, as the market contracts do
+fn share(price: &BigDecimal, fee: &Fee) -> BigDecimal {
+    if fee.denominator <= 0 {
+        return BigDecimal::default();
+    }
+    (price * BigDecimal::from(fee.numerator) / BigDecimal::from(fee.denominator)).with_scale(0)
+}
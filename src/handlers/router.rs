@@ -0,0 +1,203 @@
+use crate::cfg::VersioningConfig;
+use crate::db::queries::Queries;
+use crate::graphql::ApiSchema;
+use crate::services::auth::AuthService;
+use crate::services::rate_limit::{ClientIpSource, RateLimiter};
+use crate::services::suggest::SuggestIndex;
+use moka::future::Cache;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::filters::BoxedFilter;
+use warp::http::{HeaderMap, HeaderValue, Method};
+use warp::path::FullPath;
+use warp::{Filter, Rejection, Reply};
+
+pub type BoxedRoute = BoxedFilter<(Box<dyn Reply>,)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+            ApiVersion::V2 => "v2",
+        }
+    }
+
+    /// Path without the `/v1`, `/v2` prefix, legacy paths are returned as is
+    pub fn strip_prefix(path: &str) -> &str {
+        Self::ALL
+            .iter()
+            .filter_map(|v| path.strip_prefix('/')?.strip_prefix(v.prefix()))
+            .find(|rest| rest.is_empty() || rest.starts_with('/'))
+            .unwrap_or(path)
+    }
+}
+
+/// Everything the handler modules need to build their routes, `D` is `Queries`
+/// outside of the tests
+#[derive(Clone)]
+pub struct RouteContext<D = Queries> {
+    pub db: D,
+    pub auth: Arc<AuthService>,
+    pub graphql: ApiSchema,
+    /// Charges graphql queries by complexity, on top of the route cost taken in front of the routes
+    pub rate_limiter: RateLimiter,
+    pub cache_5_minutes: Cache<u64, Value>,
+    pub cache_minute: Cache<u64, Value>,
+    pub cache_10_sec: Cache<u64, Value>,
+    pub cache_1_sec: Cache<u64, Value>,
+    /// Collection lists, dropped whenever a verification flag changes
+    pub collections_cache: Cache<u64, Value>,
+    pub realtime_ttl: Duration,
+    /// Where the client ips recorded in `write_audit` come from
+    pub client_ip: ClientIpSource,
+    pub suggest: SuggestIndex,
+    /// Time between two metadata refresh requests of the same nft
+    pub metadata_refresh_cooldown: Duration,
+}
+
+/// Method and OpenAPI path of a registered route
+#[derive(Debug, Clone)]
+pub struct RouteInfo {
+    pub method: Method,
+    pub path: &'static str,
+    pub deprecated: bool,
+}
+
+struct Registered {
+    info: RouteInfo,
+    route: BoxedRoute,
+    versions: &'static [ApiVersion],
+}
+
+/// Collects the routes of every module and mounts them under `/v1`, `/v2` and,
+/// as aliases of `/v1`, at the legacy unprefixed paths
+#[derive(Default)]
+pub struct RouterRegistry {
+    routes: Vec<Registered>,
+}
+
+impl RouterRegistry {
+    /// Route with the same behaviour in every version
+    pub fn add<F, R>(&mut self, method: Method, path: &'static str, route: F) -> &mut Self
+    where
+        F: Filter<Extract = (R,), Error = Rejection> + Clone + Send + Sync + 'static,
+        R: Reply + 'static,
+    {
+        self.register(method, path, route, &ApiVersion::ALL, false)
+    }
+
+    /// Route kept for v1 clients only and answered with `Deprecation`/`Sunset` headers
+    pub fn deprecated<F, R>(&mut self, method: Method, path: &'static str, route: F) -> &mut Self
+    where
+        F: Filter<Extract = (R,), Error = Rejection> + Clone + Send + Sync + 'static,
+        R: Reply + 'static,
+    {
+        self.register(method, path, route, &[ApiVersion::V1], true)
+    }
+
+    fn register<F, R>(
+        &mut self,
+        method: Method,
+        path: &'static str,
+        route: F,
+        versions: &'static [ApiVersion],
+        deprecated: bool,
+    ) -> &mut Self
+    where
+        F: Filter<Extract = (R,), Error = Rejection> + Clone + Send + Sync + 'static,
+        R: Reply + 'static,
+    {
+        self.routes.push(Registered {
+            info: RouteInfo {
+                method,
+                path,
+                deprecated,
+            },
+            route: route.map(|r| Box::new(r) as Box<dyn Reply>).boxed(),
+            versions,
+        });
+        self
+    }
+
+    pub fn routes(&self) -> impl Iterator<Item = &RouteInfo> {
+        self.routes.iter().map(|r| &r.info)
+    }
+
+    pub fn build(self, cfg: &VersioningConfig) -> BoxedRoute {
+        let v1_sunset = deprecation_headers(cfg.v1_sunset.as_deref());
+        let tree = |version: ApiVersion| {
+            self.routes
+                .iter()
+                .filter(|r| r.versions.contains(&version))
+                .map(|r| {
+                    if r.info.deprecated {
+                        with_headers(r.route.clone(), v1_sunset.clone())
+                    } else {
+                        r.route.clone()
+                    }
+                })
+                .reduce(|a, b| a.or(b).unify().boxed())
+                .unwrap_or_else(not_found)
+        };
+        let v1 = tree(ApiVersion::V1);
+        let v2 = tree(ApiVersion::V2);
+
+        let legacy_sunset = deprecation_headers(cfg.legacy_sunset.as_deref());
+        let legacy =
+            warp::path::full()
+                .and(v1.clone())
+                .map(move |path: FullPath, reply: Box<dyn Reply>| {
+                    let mut response = reply.into_response();
+                    response
+                        .headers_mut()
+                        .extend(legacy_sunset.as_ref().clone());
+                    let link = format!("</v1{}>; rel=\"successor-version\"", path.as_str());
+                    if let Ok(link) = HeaderValue::from_str(&link) {
+                        response.headers_mut().insert("link", link);
+                    }
+                    Box::new(response) as Box<dyn Reply>
+                });
+
+        warp::path(ApiVersion::V1.prefix())
+            .and(v1)
+            .or(warp::path(ApiVersion::V2.prefix()).and(v2))
+            .unify()
+            .or(legacy)
+            .unify()
+            .boxed()
+    }
+}
+
+fn deprecation_headers(sunset: Option<&str>) -> Arc<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Some(sunset) = sunset.and_then(|s| HeaderValue::from_str(s).ok()) {
+        headers.insert("sunset", sunset);
+    }
+    Arc::new(headers)
+}
+
+fn with_headers(route: BoxedRoute, headers: Arc<HeaderMap>) -> BoxedRoute {
+    route
+        .map(move |reply: Box<dyn Reply>| {
+            let mut response = reply.into_response();
+            response.headers_mut().extend(headers.as_ref().clone());
+            Box::new(response) as Box<dyn Reply>
+        })
+        .boxed()
+}
+
+fn not_found() -> BoxedRoute {
+    warp::any()
+        .and_then(|| async { Err::<Box<dyn Reply>, _>(warp::reject::not_found()) })
+        .boxed()
+}
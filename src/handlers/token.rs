@@ -0,0 +1,73 @@
+use crate::db::repo::{ApiRepo, PriceRepo};
+use crate::db::Address;
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::model::TokenPrice;
+use crate::{api_doc_addon, catch_error_400, catch_error_500, response};
+pub use api_types::TokenPriceHistoryQuery;
+use chrono::NaiveDateTime;
+use std::convert::Infallible;
+use utoipa::OpenApi;
+use warp::http::{Method, StatusCode};
+use warp::Filter;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get_token_price_history),
+    components(schemas(TokenPrice, TokenPriceHistoryQuery)),
+    tags(
+        (name = "token", description = "Token handlers"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::GET,
+        "/tokens/{address}/price-history",
+        get_token_price_history(ctx.db.clone()),
+    );
+}
+
+#[utoipa::path(
+    get,
+    tag = "token",
+    path = "/tokens/{address}/price-history",
+    params(
+        ("address" = String, Path, description = "Token address"),
+        TokenPriceHistoryQuery
+    ),
+    responses(
+        (status = 200, body = Vec<TokenPrice>),
+        (status = 400),
+        (status = 500),
+    ),
+)]
+pub fn get_token_price_history<D: PriceRepo>(
+    db: D,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("tokens" / String / "price-history")
+        .and(warp::get())
+        .and(warp::query::<TokenPriceHistoryQuery>())
+        .and(warp::any().map(move || db.clone()))
+        .and_then(get_token_price_history_handler::<D>)
+}
+
+pub async fn get_token_price_history_handler<D: PriceRepo>(
+    token: Address,
+    query: TokenPriceHistoryQuery,
+    db: D,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let from = catch_error_400!(
+        NaiveDateTime::from_timestamp_opt(query.from, 0).ok_or("invalid from timestamp")
+    );
+    let to = catch_error_400!(
+        NaiveDateTime::from_timestamp_opt(query.to, 0).ok_or("invalid to timestamp")
+    );
+    let list = catch_error_500!(
+        db.list_token_price_history(&token, from, to, query.scale.as_ref())
+            .await
+    );
+    let ret: Vec<TokenPrice> = list.into_iter().map(TokenPrice::from).collect();
+    response!(&ret)
+}
@@ -1,12 +1,17 @@
-use crate::db::queries::Queries;
+use crate::db::repo::{ApiRepo, UserRepo};
 use crate::db::Address;
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
 use crate::model::UserDto;
-use crate::{api_doc_addon, catch_error_500, response};
-use serde::{Deserialize, Serialize};
+use crate::services::auth::AuthService;
+use crate::services::rate_limit::{client_ip, ClientIpSource};
+use crate::{api_doc_addon, catch_error_401, catch_error_403, catch_error_500, response};
+pub use api_types::UpsertUserPayload;
+use http::{HeaderMap, HeaderValue};
 use std::convert::Infallible;
+use std::sync::Arc;
 use utoipa::OpenApi;
-use utoipa::ToSchema;
-use warp::http::StatusCode;
+use warp::http::{Method, StatusCode};
 use warp::Filter;
 
 #[derive(OpenApi)]
@@ -20,6 +25,19 @@ use warp::Filter;
 struct ApiDoc;
 api_doc_addon!(ApiDoc);
 
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::GET,
+        "/user/{address}",
+        get_user_by_address(ctx.db.clone()),
+    )
+    .add(
+        Method::POST,
+        "/user",
+        upsert_user(ctx.db.clone(), ctx.auth.clone(), ctx.client_ip),
+    );
+}
+
 #[utoipa::path(
     get,
     tag = "user",
@@ -30,18 +48,18 @@ api_doc_addon!(ApiDoc);
         (status = 500),
     )
 )]
-pub fn get_user_by_address(
-    db: Queries,
+pub fn get_user_by_address<D: UserRepo>(
+    db: D,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("user" / String)
         .and(warp::get())
         .and(warp::any().map(move || db.clone()))
-        .and_then(get_user_by_address_handler)
+        .and_then(get_user_by_address_handler::<D>)
 }
 
-async fn get_user_by_address_handler(
+async fn get_user_by_address_handler<D: UserRepo>(
     address: Address,
-    db: Queries,
+    db: D,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
     let user = catch_error_500!(db.get_user_by_address(&address).await);
     let mut user = user.unwrap_or_default();
@@ -50,43 +68,44 @@ async fn get_user_by_address_handler(
     response!(&user)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-struct UpsertUserPayload {
-    address: Address,
-    username: Option<String>,
-    bio: Option<String>,
-    logo_nft: Option<String>,
-    twitter: Option<String>,
-    instagram: Option<String>,
-    facebook: Option<String>,
-    link: Option<String>,
-    email: Option<String>,
-}
-
 #[utoipa::path(
     post,
     tag = "user",
-    path = "/user/",
-    request_body(content = UpsertUserPayload, description = "Upsert user"),
+    path = "/user",
+    request_body(content = UpsertUserPayload, description = "Upsert the profile of the signed in address"),
     responses(
     (status = 200),
+    (status = 401, description = "No jwt, required on every version since it was unauthenticated, see CHANGELOG.md"),
+    (status = 403, description = "The jwt is of another address"),
     (status = 500),
     )
 )]
-pub fn upsert_user(
-    db: Queries,
+pub fn upsert_user<D: UserRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    ip_source: ClientIpSource,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("user")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
         .and(warp::body::json::<UpsertUserPayload>())
+        .and(warp::header::headers_cloned())
+        .and(client_ip(ip_source))
         .and(warp::any().map(move || db.clone()))
-        .and_then(upsert_user_handler)
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(upsert_user_handler::<D>)
 }
 
-async fn upsert_user_handler(
+async fn upsert_user_handler<D: UserRepo>(
     payload: UpsertUserPayload,
-    db: Queries,
+    headers: HeaderMap<HeaderValue>,
+    ip: Option<String>,
+    db: D,
+    auth_service: Arc<AuthService>,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let address = catch_error_401!(auth_service.authenticate(headers));
+    catch_error_403!((address == payload.address).then_some(()));
+
     catch_error_500!(
         db.upsert_user(
             payload.address,
@@ -98,6 +117,7 @@ async fn upsert_user_handler(
             payload.facebook,
             payload.link,
             payload.email,
+            ip.as_deref(),
         )
         .await
     );
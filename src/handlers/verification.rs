@@ -0,0 +1,400 @@
+use crate::db::repo::{AdminRepo, ApiRepo, CollectionRepo, VerificationRepo};
+use crate::db::VerificationRequestRecord;
+use crate::handlers::router::{RouteContext, RouterRegistry};
+use crate::handlers::MAX_JSON_BODY_BYTES;
+use crate::model::{VecWithTotal, VerificationRequest};
+use crate::schema::VecVerificationRequestsWithTotal;
+use crate::services::auth::AuthService;
+use crate::{api_doc_addon, catch_error_401, catch_error_403, catch_error_500, response};
+pub use api_types::{
+    CreateVerificationRequestPayload, ReviewVerificationRequestPayload, VerificationRequestsQuery,
+    VerificationStatus,
+};
+use http::{HeaderMap, HeaderValue};
+use moka::future::Cache;
+use serde_json::Value;
+use std::convert::Infallible;
+use std::sync::Arc;
+use utoipa::OpenApi;
+use warp::http::{Method, StatusCode};
+use warp::Filter;
+
+const MAX_DESCRIPTION_CHARS: usize = 2000;
+const MAX_LINKS: usize = 10;
+const MAX_LINK_CHARS: usize = 512;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        create_verification_request,
+        list_own_verification_requests,
+        list_verification_requests,
+        approve_verification_request,
+        reject_verification_request
+    ),
+    components(schemas(
+        CreateVerificationRequestPayload,
+        ReviewVerificationRequestPayload,
+        VerificationRequest,
+        VerificationStatus,
+        VecVerificationRequestsWithTotal
+    )),
+    tags(
+        (name = "verification", description = "Collection verification requests"),
+    ),
+)]
+struct ApiDoc;
+api_doc_addon!(ApiDoc);
+
+pub fn routes<D: ApiRepo>(ctx: &RouteContext<D>, r: &mut RouterRegistry) {
+    r.add(
+        Method::POST,
+        "/collections/verification-requests",
+        create_verification_request(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/collections/verification-requests",
+        list_own_verification_requests(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::GET,
+        "/admin/verification-requests",
+        list_verification_requests(ctx.db.clone(), ctx.auth.clone()),
+    )
+    .add(
+        Method::POST,
+        "/admin/verification-requests/approve",
+        approve_verification_request(
+            ctx.db.clone(),
+            ctx.auth.clone(),
+            vec![
+                ctx.collections_cache.clone(),
+                ctx.cache_minute.clone(),
+                ctx.cache_10_sec.clone(),
+                ctx.cache_1_sec.clone(),
+            ],
+        ),
+    )
+    .add(
+        Method::POST,
+        "/admin/verification-requests/reject",
+        reject_verification_request(ctx.db.clone(), ctx.auth.clone()),
+    );
+}
+
+fn validate_request(payload: &CreateVerificationRequestPayload) -> Result<(), String> {
+    let description = payload.description.trim();
+    if description.is_empty() || description.chars().count() > MAX_DESCRIPTION_CHARS {
+        return Err(format!(
+            "description must be 1 to {MAX_DESCRIPTION_CHARS} characters"
+        ));
+    }
+    if payload.links.len() > MAX_LINKS {
+        return Err(format!("at most {MAX_LINKS} links are accepted"));
+    }
+    for link in &payload.links {
+        let is_http = reqwest::Url::parse(link)
+            .map(|url| matches!(url.scheme(), "http" | "https"))
+            .unwrap_or(false);
+        if !is_http || link.len() > MAX_LINK_CHARS {
+            return Err(format!("{link:?} is not an http(s) link"));
+        }
+    }
+    Ok(())
+}
+
+fn with_status(message: impl Into<String>, status: StatusCode) -> Box<dyn warp::Reply> {
+    Box::from(warp::reply::with_status(message.into(), status))
+}
+
+fn page(list: Vec<VerificationRequestRecord>) -> VecWithTotal<VerificationRequest> {
+    let count = list.first().map(|it| it.total_count).unwrap_or_default();
+    let items = list.into_iter().map(VerificationRequest::from).collect();
+    VecWithTotal { count, items }
+}
+
+#[utoipa::path(
+    post,
+    tag = "verification",
+    path = "/collections/verification-requests",
+    request_body(content = CreateVerificationRequestPayload, description = "Ask for the verification of an owned collection"),
+    responses(
+        (status = 200, body = VerificationRequest),
+        (status = 400),
+        (status = 401),
+        (status = 403),
+        (status = 409, description = "A request for the collection is already pending"),
+        (status = 500),
+    ),
+)]
+pub fn create_verification_request<D: CollectionRepo + VerificationRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("collections" / "verification-requests")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<CreateVerificationRequestPayload>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(create_verification_request_handler::<D>)
+}
+
+pub async fn create_verification_request_handler<D: CollectionRepo + VerificationRepo>(
+    payload: CreateVerificationRequestPayload,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let address = catch_error_401!(auth_service.authenticate(headers));
+    if let Err(e) = validate_request(&payload) {
+        return Ok(with_status(e, StatusCode::BAD_REQUEST));
+    }
+
+    let owned = catch_error_500!(
+        db.validate_owner_of_collection(&payload.collection, &address)
+            .await
+    );
+    catch_error_403!(owned.filter(|count| *count > 0));
+
+    let collection = catch_error_500!(db.get_collection(&payload.collection).await);
+    if collection.and_then(|c| c.verified).unwrap_or_default() {
+        return Ok(with_status(
+            "Collection is already verified",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    let request = catch_error_500!(
+        db.create_verification_request(
+            &payload.collection,
+            &address,
+            payload.description.trim(),
+            &payload.links,
+        )
+        .await
+    );
+    match request {
+        Some(request) => response!(VerificationRequest::from(request)),
+        None => Ok(with_status(
+            "A request for this collection is already pending",
+            StatusCode::CONFLICT,
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    tag = "verification",
+    path = "/collections/verification-requests",
+    params(VerificationRequestsQuery),
+    responses(
+        (status = 200, body = VecVerificationRequestsWithTotal, description = "Requests sent by the signed in address"),
+        (status = 401),
+        (status = 500),
+    ),
+)]
+pub fn list_own_verification_requests<D: VerificationRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("collections" / "verification-requests")
+        .and(warp::get())
+        .and(warp::query::<VerificationRequestsQuery>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(list_own_verification_requests_handler::<D>)
+}
+
+pub async fn list_own_verification_requests_handler<D: VerificationRepo>(
+    query: VerificationRequestsQuery,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let address = catch_error_401!(auth_service.authenticate(headers));
+    let list = catch_error_500!(
+        db.list_verification_requests(
+            Some(&address),
+            query.collection.as_deref(),
+            query.status,
+            query.limit.unwrap_or(100),
+            query.offset.unwrap_or_default(),
+        )
+        .await
+    );
+    response!(page(list))
+}
+
+#[utoipa::path(
+    get,
+    tag = "verification",
+    path = "/admin/verification-requests",
+    params(VerificationRequestsQuery),
+    responses(
+        (status = 200, body = VecVerificationRequestsWithTotal),
+        (status = 401),
+        (status = 403),
+        (status = 500),
+    ),
+)]
+pub fn list_verification_requests<D: VerificationRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "verification-requests")
+        .and(warp::get())
+        .and(warp::query::<VerificationRequestsQuery>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(list_verification_requests_handler::<D>)
+}
+
+pub async fn list_verification_requests_handler<D: VerificationRepo>(
+    query: VerificationRequestsQuery,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    catch_error_403!(admin);
+
+    let list = catch_error_500!(
+        db.list_verification_requests(
+            None,
+            query.collection.as_deref(),
+            query.status,
+            query.limit.unwrap_or(100),
+            query.offset.unwrap_or_default(),
+        )
+        .await
+    );
+    response!(page(list))
+}
+
+#[utoipa::path(
+    post,
+    tag = "verification",
+    path = "/admin/verification-requests/approve",
+    request_body(content = ReviewVerificationRequestPayload, description = "Approve a pending request and verify the collection"),
+    responses(
+        (status = 200, body = VerificationRequest),
+        (status = 401),
+        (status = 403),
+        (status = 404, description = "No pending request with this id"),
+        (status = 500),
+    ),
+)]
+pub fn approve_verification_request<D: VerificationRepo + AdminRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+    caches: Vec<Cache<u64, Value>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "verification-requests" / "approve")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<ReviewVerificationRequestPayload>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and(warp::any().map(move || caches.clone()))
+        .and_then(approve_verification_request_handler::<D>)
+}
+
+/// The collection's nfts are listed once `nft_verified_mv` is rebuilt, that happens here rather
+/// than waiting for `/admin/nft-verified/refresh`
+pub async fn approve_verification_request_handler<D: VerificationRepo + AdminRepo>(
+    payload: ReviewVerificationRequestPayload,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+    caches: Vec<Cache<u64, Value>>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    let admin = catch_error_403!(admin);
+
+    let request = catch_error_500!(
+        db.review_verification_request(&admin, payload.id, true, payload.reason.as_deref())
+            .await
+    );
+    match request {
+        Some(request) => {
+            // the approval is committed, a failed rebuild is retried by the next one
+            match db.refresh_nft_verified(&admin).await {
+                Ok(elapsed) => log::info!(
+                    "nft_verified_mv refreshed after approving request {} in {elapsed:?}",
+                    request.id
+                ),
+                Err(e) => log::error!(
+                    "nft_verified_mv refresh after approving request {} failed: {e}",
+                    request.id
+                ),
+            }
+            for cache in caches {
+                cache.invalidate_all();
+            }
+            response!(VerificationRequest::from(request))
+        }
+        None => Ok(with_status("No pending request", StatusCode::NOT_FOUND)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    tag = "verification",
+    path = "/admin/verification-requests/reject",
+    request_body(content = ReviewVerificationRequestPayload, description = "Reject a pending request, the reason is required"),
+    responses(
+        (status = 200, body = VerificationRequest),
+        (status = 400),
+        (status = 401),
+        (status = 403),
+        (status = 404, description = "No pending request with this id"),
+        (status = 500),
+    ),
+)]
+pub fn reject_verification_request<D: VerificationRepo>(
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "verification-requests" / "reject")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSON_BODY_BYTES))
+        .and(warp::body::json::<ReviewVerificationRequestPayload>())
+        .and(warp::header::headers_cloned())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(move || auth_service.clone()))
+        .and_then(reject_verification_request_handler::<D>)
+}
+
+pub async fn reject_verification_request_handler<D: VerificationRepo>(
+    payload: ReviewVerificationRequestPayload,
+    headers: HeaderMap<HeaderValue>,
+    db: D,
+    auth_service: Arc<AuthService>,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let admin = catch_error_401!(auth_service.authenticate_admin(headers));
+    let admin = catch_error_403!(admin);
+
+    let Some(reason) = payload
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+    else {
+        return Ok(with_status("reason is required", StatusCode::BAD_REQUEST));
+    };
+
+    let request = catch_error_500!(
+        db.review_verification_request(&admin, payload.id, false, Some(reason))
+            .await
+    );
+    match request {
+        Some(request) => response!(VerificationRequest::from(request)),
+        None => Ok(with_status("No pending request", StatusCode::NOT_FOUND)),
+    }
+}
@@ -16,6 +16,7 @@
 )]
 pub mod cfg;
 pub mod db;
+pub mod graphql;
 pub mod handlers;
 pub mod model;
 pub mod schema;
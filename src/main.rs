@@ -17,204 +17,241 @@
 )]
 #![recursion_limit = "256"]
 
-use api::cfg::ApiConfig;
-use api::db::enums::{AuctionStatus, DirectBuyState, DirectSellState, NftEventType};
+use api::cfg::{ApiConfig, CliArgs};
+use api::db::bootstrap;
 use api::db::queries::Queries;
-use api::handlers;
-use api::handlers::auction::{get_auction, get_auction_bids, get_auctions};
-use api::handlers::auth::sign_in;
-use api::handlers::collection::{
-    get_collection, get_collections_by_owner, list_collections, list_collections_simple,
-};
-use api::handlers::collection_custom::upsert_collection_custom;
-use api::handlers::events::{get_events, search_all};
-use api::handlers::metrics::get_metrics_summary;
-use api::handlers::nft::{
-    get_nft, get_nft_direct_buy, get_nft_list, get_nft_price_history, get_nft_random_list,
-    get_nft_sell_count, get_nft_top_list,
-};
-use api::handlers::owner::{
-    get_fee, get_owner_bids_in, get_owner_bids_out, get_owner_direct_buy, get_owner_direct_buy_in,
-    get_owner_direct_sell,
-};
-use api::handlers::user::{get_user_by_address, upsert_user};
-use api::handlers::*;
-use api::model::OrderDirection;
-use api::model::*;
-use api::schema::Address;
+use api::graphql::build_schema;
+use api::handlers::router::{RouteContext, RouterRegistry};
+use api::handlers::{docs, register_routes};
 use api::services::auth::AuthService;
+use api::services::error::handle_limit_rejection;
+use api::services::metadata_refresh::MetadataRefreshWatcher;
+use api::services::rate_limit::{RateLimiter, RemoteAddr};
+use api::services::suggest::SuggestIndex;
 use api::token::TokenDict;
 use api::usd_price::CurrencyClient;
-use handlers::auction::ApiDocAddon as AuctionApiDocAddon;
-use handlers::auth::ApiDocAddon as AuthApiDocAddon;
-use handlers::collection::ApiDocAddon as CollectionApiDocAddon;
-use handlers::collection_custom::ApiDocAddon as CollectionCustomAddon;
-use handlers::events::ApiDocAddon as EventApiDocAddon;
-use handlers::metrics::ApiDocAddon as MetricsApiDocAddon;
-use handlers::nft::ApiDocAddon as NftApiDocAddon;
-use handlers::owner::ApiDocAddon as OwnerApiDocAddon;
-use handlers::user::ApiDocAddon as UserApiDocAddon;
-use handlers::ApiDocAddon as ModuleApiDocAddon;
 use moka::future::Cache;
+use std::convert::Infallible;
 use std::sync::Arc;
 use std::time::Duration;
-use utoipa::OpenApi;
-use warp::{http::StatusCode, Filter};
-
-#[derive(OpenApi)]
-#[openapi(
-    components(schemas(
-        Address,
-        Auction,
-        Collection,
-        DirectBuy,
-        DirectSell,
-        Fee,
-        DirectBuyState,
-        NFT,
-        Contract,
-        Price,
-        AuctionBid,
-        DirectSellState,
-        AuctionStatus,
-        OrderDirection,
-        CollectionDetails,
-        CollectionDetailsPreviewMeta, NftEventType, Attribute
-    )),
-    info(title="Marketplace API"),
-    modifiers(
-        &AuctionApiDocAddon,
-        &AuthApiDocAddon,
-        &CollectionApiDocAddon,
-        &MetricsApiDocAddon,
-        &EventApiDocAddon,
-        &NftApiDocAddon,
-        &OwnerApiDocAddon,
-        &UserApiDocAddon,
-        &ModuleApiDocAddon,
-        &CollectionCustomAddon
-    )
-)]
-struct ApiDoc;
+use warp::http::{HeaderMap, HeaderValue, StatusCode};
+use warp::hyper::server::conn::AddrStream;
+use warp::hyper::service::{make_service_fn, service_fn, Service};
+use warp::hyper::{Body, Response, Server};
+use warp::Filter;
 
-#[tokio::main(flavor = "multi_thread", worker_threads = 16)]
-async fn main() {
+fn main() {
     dotenv::dotenv().ok();
     stackdriver_logger::init_with_cargo!();
+
+    let args = match CliArgs::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\nusage: application [--config <path>] [--print-config] [--migrate] [--seed <path>]");
+            std::process::exit(2);
+        }
+    };
+    let cfg = match ApiConfig::load(args.config_path.as_deref()) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("failed to load config: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if args.print_config {
+        match serde_yaml::to_string(&cfg.redacted()) {
+            Ok(s) => print!("{s}"),
+            Err(e) => {
+                eprintln!("failed to serialize config: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(cfg.worker_threads)
+        .enable_all()
+        .build()
+        .expect("err build tokio runtime")
+        .block_on(run(cfg, args));
+}
+
+async fn run(cfg: ApiConfig, args: CliArgs) {
     log::info!("INDEXER-API SERVICE");
-    let cfg = ApiConfig::new().expect("Failed to load config");
-    let tokens = TokenDict::load()
+    let db_pool = cfg.database.init().await.expect("err init database");
+    if args.migrate {
+        bootstrap::run_migrations(&db_pool)
+            .await
+            .expect("err run migrations");
+    }
+    if let Some(path) = &args.seed {
+        bootstrap::load_seed(&db_pool, path)
+            .await
+            .expect("err load seed");
+        log::info!("loaded fixtures from {path}");
+    }
+    let tokens = TokenDict::load(&cfg.tokens_manifest_url)
         .await
         .expect("error loading tokens dictionary");
-    let db_pool = cfg.database.init().await.expect("err init database");
     let db_service = Queries::new(Arc::new(db_pool), tokens);
-    let auth_service = Arc::new(AuthService::new(
-        cfg.auth_token_lifetime,
-        cfg.jwt_secret,
-        cfg.base_url,
-    ));
-
-    CurrencyClient::new(db_service.clone(), cfg.main_token, cfg.prices_url)
-        .expect("err initialize currency client")
-        .start(std::time::Duration::from_secs(5 * 60)) // 5 minutes
-        .await
-        .expect("err start currency client");
-
-    let cors = warp::cors()
-        .allow_any_origin()
-        .allow_headers(vec![
-            "authority",
-            "user-agent",
-            "content-type",
-            "authorization",
-        ])
-        .allow_methods(vec!["GET", "POST", "OPTIONS"]);
-
-    let mut cors_headers = warp::http::HeaderMap::new();
-    cors_headers.insert(
-        "access-control-allow-origin",
-        warp::http::HeaderValue::from_static("*"),
+    let auth_service = Arc::new(
+        AuthService::new(
+            cfg.auth_token_lifetime,
+            cfg.jwt_secret.clone(),
+            cfg.base_url.clone(),
+        )
+        .with_admins(cfg.admin_addresses.clone()),
     );
+
+    CurrencyClient::new(
+        db_service.clone(),
+        cfg.main_token.clone(),
+        cfg.prices_url.clone(),
+        &cfg.prices,
+    )
+    .expect("err initialize currency client")
+    .start(Duration::from_secs(cfg.prices.update_interval_sec))
+    .await
+    .expect("err start currency client");
+
+    let rate_limiter = RateLimiter::new(db_service.clone(), cfg.rate_limit.clone());
+    rate_limiter
+        .clone()
+        .start(Duration::from_secs(10))
+        .await
+        .expect("err start api key usage flush");
+
+    let suggest = SuggestIndex::new(cfg.suggest.max_nfts);
+    suggest
+        .clone()
+        .start(db_service.clone(), cfg.suggest.refresh_interval())
+        .await
+        .expect("err start search suggestions reload");
+
+    let mut cors = warp::cors()
+        .allow_headers(cfg.cors.allowed_headers.iter().map(String::as_str))
+        .allow_methods(cfg.cors.allowed_methods.iter().map(String::as_str));
+    cors = if cfg.cors.allows_any_origin() {
+        cors.allow_any_origin()
+    } else {
+        cors.allow_origins(cfg.cors.allowed_origins.iter().map(String::as_str))
+    };
+    if let Some(max_age) = cfg.cors.max_age_sec {
+        cors = cors.max_age(Duration::from_secs(max_age));
+    }
+
+    let mut cors_headers = HeaderMap::new();
+    if cfg.cors.allows_any_origin() {
+        cors_headers.insert("access-control-allow-origin", HeaderValue::from_static("*"));
+    }
     cors_headers.insert(
         "access-control-allow-methods",
-        warp::http::HeaderValue::from_static("GET, POST, OPTIONS"),
+        HeaderValue::from_str(&cfg.cors.allowed_methods.join(", "))
+            .expect("cors methods are validated on load"),
     );
 
     let cache_minute = Cache::builder()
-        .time_to_live(Duration::from_secs(60))
-        .time_to_idle(Duration::from_secs(60))
+        .time_to_live(cfg.cache.medium_ttl())
+        .time_to_idle(cfg.cache.medium_ttl())
+        .support_invalidation_closures()
         .build();
 
     let cache_5_minutes = Cache::builder()
-        .time_to_live(Duration::from_secs(60 * 5))
-        .time_to_idle(Duration::from_secs(60 * 5))
+        .time_to_live(cfg.cache.long_ttl())
+        .time_to_idle(cfg.cache.long_ttl())
+        .support_invalidation_closures()
         .build();
 
     let cache_10_sec = Cache::builder()
-        .time_to_live(Duration::from_secs(10))
-        .time_to_idle(Duration::from_secs(10))
+        .time_to_live(cfg.cache.short_ttl())
+        .time_to_idle(cfg.cache.short_ttl())
+        .support_invalidation_closures()
         .build();
 
     let cache_1_sec = Cache::builder()
-        .time_to_live(Duration::from_secs(1))
-        .time_to_idle(Duration::from_secs(1))
+        .time_to_live(cfg.cache.realtime_ttl())
+        .time_to_idle(cfg.cache.realtime_ttl())
+        .support_invalidation_closures()
+        .build();
+
+    let collections_cache = Cache::builder()
+        .time_to_live(cfg.cache.medium_ttl())
+        .time_to_idle(cfg.cache.medium_ttl())
+        .support_invalidation_closures()
         .build();
 
-    let api_doc = warp::path("swagger.json")
-        .and(warp::get())
-        .map(|| warp::reply::json(&ApiDoc::openapi()));
+    let ctx = RouteContext {
+        db: db_service.clone(),
+        auth: auth_service.clone(),
+        graphql: build_schema(db_service.clone()),
+        rate_limiter: rate_limiter.clone(),
+        cache_5_minutes,
+        cache_minute,
+        cache_10_sec,
+        cache_1_sec,
+        collections_cache,
+        realtime_ttl: cfg.cache.realtime_ttl(),
+        client_ip: cfg.rate_limit.client_ip_source(),
+        suggest,
+        metadata_refresh_cooldown: cfg.metadata_refresh.cooldown(),
+    };
+    MetadataRefreshWatcher::new(vec![
+        ctx.cache_5_minutes.clone(),
+        ctx.cache_minute.clone(),
+        ctx.cache_10_sec.clone(),
+        ctx.cache_1_sec.clone(),
+        ctx.collections_cache.clone(),
+    ])
+    .start(db_service.clone(), cfg.metadata_refresh.watch_interval())
+    .await
+    .expect("err start metadata refresh watch");
+
+    let mut registry = RouterRegistry::default();
+    register_routes(&ctx, &mut registry);
+    let docs = docs::routes(docs::api_spec(&registry));
+    let versioned = registry.build(&cfg.versioning);
 
     let api = warp::any()
+        .and(rate_limiter.filter())
         .and(
             warp::options()
                 .map(|| StatusCode::NO_CONTENT)
                 .with(warp::reply::with::headers(cors_headers))
-                .or(api_doc)
+                .or(docs)
                 .or(warp::path!("healthz").map(warp::reply))
-                .or(get_nft_list(db_service.clone(), cache_10_sec.clone()))
-                .or(get_nft_random_list(db_service.clone(), cache_1_sec.clone()))
-                .or(get_nft_sell_count(
-                    db_service.clone(),
-                    cache_5_minutes.clone(),
-                ))
-                .or(get_nft(db_service.clone()))
-                .or(get_nft_top_list(db_service.clone(), cache_minute.clone()))
-                .or(get_nft_direct_buy(db_service.clone()))
-                .or(get_nft_price_history(db_service.clone()))
-                .or(list_collections(db_service.clone(), cache_minute.clone()))
-                .or(list_collections_simple(
-                    db_service.clone(),
-                    cache_minute.clone(),
-                ))
-                .or(get_collection(db_service.clone(), cache_1_sec.clone()))
-                .or(get_collections_by_owner(db_service.clone()))
-                .or(get_owner_bids_out(db_service.clone()))
-                .or(get_owner_bids_in(db_service.clone()))
-                .or(get_owner_direct_buy_in(db_service.clone()))
-                .or(get_owner_direct_buy(db_service.clone()))
-                .or(get_owner_direct_sell(db_service.clone()))
-                .or(get_auctions(db_service.clone()))
-                .or(get_auction(db_service.clone()))
-                .or(get_auction_bids(db_service.clone()))
-                .or(get_events(db_service.clone(), cache_10_sec.clone()))
-                .or(get_metrics_summary(
-                    db_service.clone(),
-                    cache_minute.clone(),
-                ))
-                .or(list_roots(db_service.clone()))
-                .or(search_all(db_service.clone()))
-                .or(get_fee(db_service.clone()))
-                .or(get_user_by_address(db_service.clone()))
-                .or(upsert_user(db_service.clone()))
-                .or(upsert_collection_custom(
-                    db_service.clone(),
-                    auth_service.clone(),
-                ))
-                .or(sign_in(auth_service.clone())),
+                .or(versioned),
         )
+        .recover(handle_limit_rejection)
         .with(cors);
 
     let routes = api.with(warp::log("api"));
+    let service = warp::service(routes);
+    let request_timeout = cfg.rate_limit.request_timeout();
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let remote = RemoteAddr(conn.remote_addr());
+        let service = service.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |mut req| {
+                req.extensions_mut().insert(remote);
+                let mut service = service.clone();
+                async move {
+                    match tokio::time::timeout(request_timeout, service.call(req)).await {
+                        Ok(res) => res,
+                        Err(_) => Ok(Response::builder()
+                            .status(StatusCode::SERVICE_UNAVAILABLE)
+                            .body(Body::from("Request timeout"))
+                            .expect("valid timeout response")),
+                    }
+                }
+            }))
+        }
+    });
+
     log::info!("start http server on {}", cfg.http_address);
-    warp::serve(routes).run(cfg.http_address).await;
+    Server::bind(&cfg.http_address)
+        .serve(make_service)
+        .await
+        .expect("http server error");
 }
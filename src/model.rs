@@ -1,74 +1,20 @@
 use crate::db::{
-    MetaRoyalty, MetricsSummaryRecord, NftEventType, NftTraitRecord, OwnerFeeRecord, RootRecord,
-    Social, UserRecord,
+    parse_attributes, AdminAuditRecord, MetadataRefreshRecord, MetricsSummaryRecord,
+    ModerationRecord, NftMetadataOverrideRecord, NftTraitRecord, OwnerFeeRecord, ReportRecord,
+    RootRecord, UserRecord, VerificationRequestRecord, WriteAuditRecord,
 };
 use crate::{
-    db::{Address, AuctionStatus, DirectBuyState, DirectSellState, EventCategory, EventType},
+    db::{Address, EventCategory, EventType},
     token::TokenDict,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fmt::Display;
 
-use utoipa::ToSchema;
+pub use api_types::model::*;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VecWithTotal<T> {
-    #[serde(rename = "totalCount")]
-    pub count: i64,
-    pub items: Vec<T>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct VecWith<T> {
-    #[serde(rename = "totalCount")]
-    pub count: i64,
-    pub items: Vec<T>,
-    pub nft: Option<HashMap<Address, NFT>>,
-    pub collection: Option<HashMap<Address, Collection>>,
-    pub auction: Option<HashMap<Address, Auction>>,
-    pub direct_buy: Option<HashMap<Address, DirectBuy>>,
-    pub direct_sell: Option<HashMap<Address, DirectSell>>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct Contract {
-    pub address: Address,
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub owner: Option<Address>,
-    pub verified: Option<bool>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct Price {
-    #[serde(rename = "priceToken")]
-    pub token: Address,
-    pub price: String,
-    #[serde(rename = "usdPrice")]
-    pub usd_price: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct Fee {
-    pub numerator: i32,
-    pub denominator: i32,
-}
-
-#[derive(Debug, Clone, Serialize, ToSchema)]
-pub struct NFTPrice {
-    #[serde(rename = "usdPrice")]
-    pub usd_price: String,
-    pub ts: i64,
-}
-
-#[derive(Debug, Clone, Serialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NftTrait {
-    pub trait_type: Option<String>,
-    pub trait_value: Option<String>,
-    pub cnt: i64,
+/// Conversion of db rows holding token amounts, formatted with the token decimals
+pub trait FromDb<T> {
+    fn from_db(value: T, tokens: &TokenDict) -> Self;
 }
 
 impl From<NftTraitRecord> for NftTrait {
@@ -81,91 +27,6 @@ impl From<NftTraitRecord> for NftTrait {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NFT {
-    #[serde(flatten)]
-    pub contract: Contract,
-    pub collection: Address,
-    pub image: Option<String>,
-    pub mimetype: Option<String>,
-    pub full_image: Option<String>,
-    pub full_image_mimetype: Option<String>,
-    #[serde(rename = "type")]
-    pub typ: Option<String>,
-    pub auction: Option<Address>,
-    pub forsale: Option<Address>,
-    pub best_offer: Option<Address>,
-    pub manager: Option<Address>,
-    pub deal_price_usd: Option<String>,
-    pub floor_price: Option<Price>,
-    pub nft_id: Option<Address>,
-    pub royalty: Option<MetaRoyalty>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct Collection {
-    #[serde(flatten)]
-    pub contract: Contract,
-    pub verified: Option<bool>,
-    pub created_at: usize,
-    pub wallpaper: Option<String>,
-    pub logo: Option<String>,
-    pub owners_count: usize,
-    pub nft_count: usize,
-    pub lowest_price: Option<String>,
-    pub total_price: Option<String>,
-    pub first_mint: i64,
-    pub social: Option<Social>,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
-pub struct CollectionDetailsPreviewMeta {
-    pub source: Option<String>,
-    pub mimetype: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct CollectionDetails {
-    #[serde(flatten)]
-    pub collection: Collection,
-    pub floor_price_usd: Option<String>,
-    pub total_volume_usd: Option<String>,
-    pub attributes: Vec<Attribute>,
-    pub previews: Vec<CollectionDetailsPreviewMeta>,
-}
-
-#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Attribute {
-    pub trait_type: String,
-    pub trait_values: Vec<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct CollectionSimple {
-    pub address: Address,
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub verified: bool,
-    pub logo: Option<String>,
-    pub nft_count: i64,
-}
-
-#[derive(Debug, Clone, Serialize, ToSchema)]
-pub struct Root {
-    pub address: Address,
-    pub code: String,
-}
-
-#[derive(Debug, Clone, Serialize, ToSchema)]
-pub struct Roots {
-    pub roots: Vec<Root>,
-}
-
 impl From<RootRecord> for Root {
     fn from(value: RootRecord) -> Self {
         Self {
@@ -175,8 +36,8 @@ impl From<RootRecord> for Root {
     }
 }
 
-impl CollectionSimple {
-    pub fn from_db(db: crate::db::NftCollectionSimple) -> Self {
+impl From<crate::db::NftCollectionSimple> for CollectionSimple {
+    fn from(db: crate::db::NftCollectionSimple) -> Self {
         Self {
             address: db.address,
             name: Some(db.name.unwrap_or_default()),
@@ -199,90 +60,12 @@ pub struct Event {
     pub args: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, ToSchema, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Auction {
-    pub address: Address,
-    pub nft: Address,
-    pub status: AuctionStatus,
-    pub bid_token: Address,
-    pub wallet_for_bids: Option<Address>,
-    pub start_bid: Option<String>,
-    pub start_usd_bid: Option<String>,
-    pub min_bid: Option<String>,
-    pub min_usd_bid: Option<String>,
-    pub max_bid: Option<String>,
-    pub max_usd_bid: Option<String>,
-    pub start_time: Option<i64>,
-    pub finish_time: Option<i64>,
-    pub last_bid_from: Option<Address>,
-    #[serde(rename = "lastBidTime")]
-    pub last_bid_ts: Option<i64>,
-    pub last_bid_value: Option<String>,
-    pub last_bid_usd_value: Option<String>,
-    pub fee: Fee,
-}
-
-#[derive(Debug, Clone, Serialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct AuctionBid {
-    pub from: Address,
-    pub auction: Address,
-    pub nft: Address,
-    pub price: String,
-    pub usd_price: Option<String>,
-    pub active: bool,
-    pub created_at: i64,
-}
-
-#[derive(Debug, Clone, Serialize, ToSchema, Deserialize)]
-pub struct DirectSell {
-    pub address: Address,
-    pub nft: Address,
-    pub seller: Option<Address>,
-    pub price: Price,
-    pub status: DirectSellState,
-    #[serde(rename = "createdAt")]
-    pub created: i64,
-    #[serde(rename = "finishedAt")]
-    pub finished: Option<i64>,
-    #[serde(rename = "expiredAt")]
-    pub expired: Option<i64>,
-    pub fee: Fee,
-}
-
-#[derive(Debug, Clone, Serialize, ToSchema, Deserialize)]
-pub struct DirectBuy {
-    pub address: Address,
-    pub nft: Address,
-    pub buyer: Option<Address>,
-    pub price: Price,
-    pub status: DirectBuyState,
-    #[serde(rename = "createdAt")]
-    pub created: i64,
-    #[serde(rename = "finishedAt")]
-    pub finished: Option<i64>,
-    #[serde(rename = "expiredAt")]
-    pub expired: Option<i64>,
-    pub fee: Fee,
-}
-
 #[derive(Debug, Clone, Serialize)]
 pub struct CollectionAttributes {
     pub collection: Address,
     pub attributes: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct SearchResult {
-    pub address: Address,
-    pub object_type: String,
-    pub nft_name: Option<String>,
-    pub collection_name: Option<String>,
-    pub image: Option<String>,
-}
-
 impl CollectionAttributes {
     pub fn from_db(defs: &[crate::db::TraitDef]) -> Vec<Self> {
         let mut res = Vec::with_capacity(16);
@@ -320,9 +103,11 @@ impl CollectionAttributes {
     }
 }
 
-impl NFT {
-    pub fn from_db(nft: crate::db::NftDetails) -> Self {
+impl From<crate::db::NftDetails> for NFT {
+    fn from(nft: crate::db::NftDetails) -> Self {
         let parsed = nft.parse_meta();
+        let name = nft.display_name();
+        let overridden = nft.overridden();
 
         let floor_price = match (nft.floor_price, nft.floor_price_usd, nft.floor_price_token) {
             (Some(floor_price), Some(floor_price_usd), Some(floor_price_token)) => Some(Price {
@@ -335,7 +120,7 @@ impl NFT {
         NFT {
             contract: Contract {
                 address: nft.address.expect("null nft address"),
-                name: nft.name,
+                name,
                 description: nft.description,
                 owner: nft.owner.map(Address::from),
                 verified: None,
@@ -354,12 +139,13 @@ impl NFT {
             floor_price,
             nft_id: nft.nft_id,
             royalty: parsed.royalty,
+            overridden,
         }
     }
 }
 
-impl Collection {
-    pub fn from_db(db: crate::db::NftCollection) -> Self {
+impl From<crate::db::NftCollection> for Collection {
+    fn from(db: crate::db::NftCollection) -> Self {
         Collection {
             contract: Contract {
                 address: db.address,
@@ -368,7 +154,6 @@ impl Collection {
                 owner: Some(db.owner),
                 verified: Some(db.verified),
             },
-            verified: Some(db.verified),
             created_at: db.created.timestamp() as usize,
             logo: db.logo,
             wallpaper: db.wallpaper,
@@ -382,8 +167,10 @@ impl Collection {
     }
 }
 
-impl CollectionDetails {
-    pub fn from_db(db: crate::db::NftCollectionDetails) -> anyhow::Result<Self> {
+impl TryFrom<crate::db::NftCollectionDetails> for CollectionDetails {
+    type Error = anyhow::Error;
+
+    fn try_from(db: crate::db::NftCollectionDetails) -> anyhow::Result<Self> {
         let attributes: Vec<Attribute> = match db.attributes {
             None => vec![],
             Some(attributes) => serde_json::from_value(attributes)?,
@@ -398,7 +185,6 @@ impl CollectionDetails {
                     owner: Some(db.owner.unwrap_or_default()),
                     verified: db.verified,
                 },
-                verified: db.verified,
                 created_at: db.created.unwrap_or_default().timestamp() as usize,
                 logo: db.logo,
                 wallpaper: db.wallpaper,
@@ -417,8 +203,8 @@ impl CollectionDetails {
     }
 }
 
-impl Auction {
-    pub fn from_db(db: &crate::db::NftAuction, tokens: &TokenDict) -> Self {
+impl FromDb<&crate::db::NftAuction> for Auction {
+    fn from_db(db: &crate::db::NftAuction, tokens: &TokenDict) -> Self {
         let fee = match (db.fee_numerator, db.fee_denominator) {
             (Some(numerator), Some(denominator)) => Fee {
                 numerator,
@@ -432,7 +218,7 @@ impl Auction {
         let token = db.price_token.clone().unwrap_or_default();
         Auction {
             address: db.address.clone().unwrap_or_default(),
-            status: db.status.clone().unwrap_or_default(),
+            status: db.status.unwrap_or_default(),
             nft: db.nft.clone().unwrap_or_default(),
             bid_token: token.clone(),
             wallet_for_bids: db.wallet_for_bids.clone(),
@@ -456,10 +242,9 @@ impl Auction {
     }
 }
 
-impl AuctionBid {
-    pub fn from_db(
-        bid: &crate::db::NftAuctionBid,
-        auction: &crate::db::NftAuction,
+impl FromDb<(&crate::db::NftAuctionBid, &crate::db::NftAuction)> for AuctionBid {
+    fn from_db(
+        (bid, auction): (&crate::db::NftAuctionBid, &crate::db::NftAuction),
         tokens: &TokenDict,
     ) -> Self {
         let token = auction.price_token.clone().unwrap_or_default();
@@ -473,8 +258,10 @@ impl AuctionBid {
             active: bid.active,
         }
     }
+}
 
-    pub fn from_extended(bid: &crate::db::NftAuctionBidExt, tokens: &TokenDict) -> Self {
+impl FromDb<&crate::db::NftAuctionBidExt> for AuctionBid {
+    fn from_db(bid: &crate::db::NftAuctionBidExt, tokens: &TokenDict) -> Self {
         let token = bid.price_token.clone().unwrap_or_default();
         AuctionBid {
             from: bid.buyer.clone(),
@@ -488,8 +275,8 @@ impl AuctionBid {
     }
 }
 
-impl DirectSell {
-    pub fn from_db(val: &crate::db::NftDirectSell, tokens: &TokenDict) -> Self {
+impl FromDb<&crate::db::NftDirectSell> for DirectSell {
+    fn from_db(val: &crate::db::NftDirectSell, tokens: &TokenDict) -> Self {
         let fee = match (val.fee_numerator, val.fee_denominator) {
             (Some(numerator), Some(denominator)) => Fee {
                 numerator,
@@ -503,7 +290,8 @@ impl DirectSell {
         DirectSell {
             address: val.address.clone(),
             nft: val.nft.clone(),
-            status: val.state.clone(),
+            status: val.state,
+            valid: val.valid,
             seller: val.seller.clone(),
             price: Price {
                 token: val.price_token.clone(),
@@ -518,8 +306,8 @@ impl DirectSell {
     }
 }
 
-impl DirectBuy {
-    pub fn from_db(val: &crate::db::NftDirectBuy, tokens: &TokenDict) -> Self {
+impl FromDb<&crate::db::NftDirectBuy> for DirectBuy {
+    fn from_db(val: &crate::db::NftDirectBuy, tokens: &TokenDict) -> Self {
         let fee = match (val.fee_numerator, val.fee_denominator) {
             (Some(numerator), Some(denominator)) => Fee {
                 numerator,
@@ -534,7 +322,8 @@ impl DirectBuy {
             address: val.address.clone(),
             nft: val.nft.clone(),
             buyer: val.buyer.clone(),
-            status: val.state.clone(),
+            status: val.state,
+            valid: val.valid,
             price: Price {
                 token: val.price_token.clone(),
                 price: tokens.format_value(&val.price_token, &val.price),
@@ -548,185 +337,63 @@ impl DirectBuy {
     }
 }
 
-impl NFTPrice {
-    pub fn from_db(val: crate::db::NftPrice) -> Self {
+impl From<crate::db::NftPrice> for NFTPrice {
+    fn from(val: crate::db::NftPrice) -> Self {
         let usd_price = val.usd_price.to_string();
         let ts = val.ts.timestamp();
         NFTPrice { usd_price, ts }
     }
 }
 
-impl SearchResult {
-    pub fn from_db(val: crate::db::SearchResult) -> Self {
+impl From<crate::db::TokenUsdPrice> for TokenPrice {
+    fn from(val: crate::db::TokenUsdPrice) -> Self {
+        TokenPrice {
+            usd_price: val.usd_price.to_string(),
+            ts: val.ts.timestamp(),
+        }
+    }
+}
+
+impl From<crate::db::SearchResult> for SearchResult {
+    fn from(val: crate::db::SearchResult) -> Self {
         Self {
             address: val.address,
             image: val.image,
             object_type: val.object_type,
             nft_name: val.nft_name,
             collection_name: val.collection_name,
+            collection: val.collection,
+            nft_id: val.nft_id,
+            username: val.username,
+            score: val.score,
         }
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NftEvents {
-    pub data: Vec<NftEvent>,
-    pub total_rows: i64,
-}
-
-#[derive(Deserialize, Serialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NftEvent {
-    id: i64,
-    event_type: NftEventType,
-    name: Option<String>,
-    description: Option<String>,
-    datetime: i64,
-    address: String,
-    preview_url: Option<String>,
-    direct_sell: Option<NftEventDirectSell>,
-    direct_buy: Option<NftEventDirectBuy>,
-    auction: Option<NftEventAuction>,
-    mint: Option<NftEventMint>,
-    transfer: Option<NftEventTransfer>,
-}
-
-#[derive(Deserialize, Serialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NftEventDirectSell {
-    creator: String,
-    start_time: u64,
-    end_time: Option<u64>,
-    duration_time: Option<i64>,
-    price: String,
-    usd_price: Option<String>,
-    status: i64,
-    payment_token: String,
-    new_owner: Option<String>,
-}
-
-#[derive(Deserialize, Serialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NftEventDirectBuy {
-    creator: String,
-    start_time: i64,
-    end_time: i64,
-    duration_time: Option<i64>,
-    price: String,
-    usd_price: Option<String>,
-    status: i64,
-    spent_token: String,
-    old_owner: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NftEventAuction {
-    auction_active: Option<AuctionActive>,
-    auction_complete: Option<AuctionComplete>,
-    auction_canceled: Option<AuctionCanceled>,
-    auction_bid_placed: Option<AuctionBidPlaced>,
-}
-
-#[derive(Serialize, Deserialize, ToSchema, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct AuctionActive {
-    nft_owner: String,
-    auction_start_time: i64,
-    auction_end_time: i64,
-    auction_duration: i64,
-    state: i64,
-    payment_token: String,
-    price: String,
-    usd_price: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct AuctionComplete {
-    nft_owner: String,
-    auction_start_time: i64,
-    auction_end_time: i64,
-    auction_duration: i64,
-    state: i64,
-    payment_token: String,
-    price: String,
-    usd_price: Option<String>,
-    max_bid_value: String,
-    max_bid_address: String,
-}
-
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct AuctionCanceled {
-    nft_owner: String,
-    auction_start_time: i64,
-    auction_end_time: i64,
-    auction_duration: i64,
-    state: i64,
-    payment_token: String,
-    price: String,
-    usd_price: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct AuctionBidPlaced {
-    bid_sender: String,
-    payment_token: String,
-    bid_value: String,
-    usd_price: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NftEventMint {
-    owner: String,
-    creator: String,
-}
-
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct NftEventTransfer {
-    from: String,
-    to: String,
-}
-
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct MetricsSummaryBase {
-    total_rows_count: i32,
-    data: Vec<MetricsSummary>,
-}
-
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct MetricsSummary {
-    pub collection: String,
-    pub name: Option<String>,
-    pub logo: Option<String>,
-    pub floor_price: Option<String>,
-    pub total_volume_usd_now: String,
-    pub total_volume_usd_previous: String,
-    pub owners_count: i32,
-    pub nfts_count: i32,
-}
-
-impl From<Vec<MetricsSummaryRecord>> for MetricsSummaryBase {
-    fn from(values: Vec<MetricsSummaryRecord>) -> Self {
-        let total_rows_count = match values.first() {
-            None => 0,
-            Some(first_value) => first_value.total_rows_count,
-        };
-
+impl From<&crate::db::SuggestRecord> for Suggestion {
+    fn from(val: &crate::db::SuggestRecord) -> Self {
         Self {
-            total_rows_count,
-            data: values.into_iter().map(|v| v.into()).collect(),
+            address: val.address.clone(),
+            name: val.name.clone(),
+            image: val.image.clone(),
+            collection: val.collection.clone(),
         }
     }
 }
 
+/// Page of the metrics summary, the total count is repeated in every row
+pub fn metrics_summary_page(values: Vec<MetricsSummaryRecord>) -> MetricsSummaryBase {
+    let total_rows_count = match values.first() {
+        None => 0,
+        Some(first_value) => first_value.total_rows_count,
+    };
+
+    MetricsSummaryBase {
+        total_rows_count,
+        data: values.into_iter().map(|v| v.into()).collect(),
+    }
+}
+
 impl From<MetricsSummaryRecord> for MetricsSummary {
     fn from(value: MetricsSummaryRecord) -> Self {
         Self {
@@ -742,14 +409,6 @@ impl From<MetricsSummaryRecord> for MetricsSummary {
     }
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct OwnerFee {
-    pub fee: Fee,
-    pub nft_id: Option<String>,
-    pub collection: Option<String>,
-}
-
 impl From<OwnerFeeRecord> for OwnerFee {
     fn from(value: OwnerFeeRecord) -> Self {
         Self {
@@ -773,25 +432,152 @@ pub struct LoginData {
     pub with_signature_id: Option<i32>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JwtRole {
+    Admin,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JwtClaims {
     pub sub: String,
     pub exp: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<JwtRole>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct UserDto {
-    pub address: String,
-    pub logo_nft: Option<String>,
-    pub username: Option<String>,
-    pub bio: Option<String>,
-    pub twitter: Option<String>,
-    pub instagram: Option<String>,
-    pub facebook: Option<String>,
-    pub link: Option<String>,
-    pub email: Option<String>,
-    pub avatar_url: Option<String>,
+impl From<AdminAuditRecord> for AdminAuditEntry {
+    fn from(value: AdminAuditRecord) -> Self {
+        Self {
+            id: value.id,
+            admin: value.admin,
+            action: value.action,
+            target: value.target,
+            details: value.details,
+            created_at: value.created_at.and_utc().timestamp(),
+        }
+    }
+}
+
+impl From<VerificationRequestRecord> for VerificationRequest {
+    fn from(value: VerificationRequestRecord) -> Self {
+        Self {
+            id: value.id,
+            collection: value.collection,
+            applicant: value.applicant,
+            description: value.description,
+            links: serde_json::from_value(value.links).unwrap_or_default(),
+            status: value.status,
+            reason: value.reason,
+            reviewer: value.reviewer,
+            created_at: value.created_at.and_utc().timestamp(),
+            reviewed_at: value.reviewed_at.map(|d| d.and_utc().timestamp()),
+        }
+    }
+}
+
+/// `next_request_at` is left at `created_at`, the cooldown is added by the handler
+impl From<MetadataRefreshRecord> for MetadataRefresh {
+    fn from(value: MetadataRefreshRecord) -> Self {
+        let created_at = value.created_at.and_utc().timestamp();
+        Self {
+            id: value.id,
+            nft: value.nft,
+            collection: value.collection,
+            requested_by: value.requested_by,
+            status: value.status,
+            error: value.error,
+            created_at,
+            finished_at: value.finished_at.map(|d| d.and_utc().timestamp()),
+            metadata_updated: value.metadata_updated.map(|d| d.and_utc().timestamp()),
+            next_request_at: created_at,
+        }
+    }
+}
+
+impl From<NftMetadataOverrideRecord> for NftMetadataOverride {
+    fn from(value: NftMetadataOverrideRecord) -> Self {
+        Self {
+            nft: value.nft,
+            collection: value.collection,
+            name: value.name,
+            preview: value.preview,
+            preview_mimetype: value.preview_mimetype,
+            attributes: value.attributes.as_ref().map(parse_attributes),
+            updated_by: value.updated_by,
+            updated: value.updated.and_utc().timestamp(),
+        }
+    }
+}
+
+impl From<ReportRecord> for Report {
+    fn from(value: ReportRecord) -> Self {
+        Self {
+            id: value.id,
+            target_type: value.target_type,
+            target: value.target,
+            reason: value.reason,
+            comment: value.comment,
+            reporter: value.reporter,
+            status: value.status,
+            resolution: value.resolution,
+            resolver: value.resolver,
+            created_at: value.created_at.and_utc().timestamp(),
+            resolved_at: value.resolved_at.map(|d| d.and_utc().timestamp()),
+        }
+    }
+}
+
+impl From<ModerationRecord> for Moderation {
+    fn from(value: ModerationRecord) -> Self {
+        Self {
+            target_type: value.target_type,
+            target: value.address,
+            status: value.status,
+            reason: value.reason,
+            moderator: value.moderator,
+            updated_at: value.updated_at.and_utc().timestamp(),
+        }
+    }
+}
+
+impl From<WriteAuditRecord> for WriteAuditEntry {
+    fn from(value: WriteAuditRecord) -> Self {
+        let changes = changed_fields(value.before.as_ref(), &value.after);
+        Self {
+            id: value.id,
+            actor: value.actor,
+            action: value.action,
+            target: value.target,
+            before: value.before,
+            after: value.after,
+            changes,
+            details: value.details,
+            ip: value.ip,
+            created_at: value.created_at.and_utc().timestamp(),
+        }
+    }
+}
+
+/// `{"field": {"from": .., "to": ..}}` of the top level fields that differ
+fn changed_fields(
+    before: Option<&serde_json::Value>,
+    after: &serde_json::Value,
+) -> serde_json::Value {
+    let empty = serde_json::Map::new();
+    let before = before.and_then(|v| v.as_object()).unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let null = serde_json::Value::Null;
+    let changes = before
+        .keys()
+        .chain(after.keys().filter(|k| !before.contains_key(*k)))
+        .filter_map(|key| {
+            let from = before.get(key).unwrap_or(&null);
+            let to = after.get(key).unwrap_or(&null);
+            (from != to).then(|| (key.clone(), serde_json::json!({ "from": from, "to": to })))
+        })
+        .collect();
+    serde_json::Value::Object(changes)
 }
 
 impl From<UserRecord> for UserDto {
@@ -810,20 +596,3 @@ impl From<UserRecord> for UserDto {
         }
     }
 }
-
-#[derive(Clone, Deserialize, Serialize, ToSchema, Hash)]
-pub enum OrderDirection {
-    #[serde(rename = "asc")]
-    Asc,
-    #[serde(rename = "desc")]
-    Desc,
-}
-
-impl Display for OrderDirection {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            OrderDirection::Asc => write!(f, "asc"),
-            OrderDirection::Desc => write!(f, "desc"),
-        }
-    }
-}
@@ -1,5 +1,5 @@
 use super::error::Error;
-use crate::model::{JwtClaims, LoginData};
+use crate::model::{JwtClaims, JwtRole, LoginData};
 use base64::engine::general_purpose;
 use base64::Engine;
 use ed25519_dalek::{PublicKey, Verifier};
@@ -8,6 +8,7 @@ use nekoton::core::ton_wallet::compute_address;
 use nekoton::core::ton_wallet::WalletType;
 use sha2::Digest;
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::str::FromStr;
 use std::time::SystemTime;
 use ton_block::MsgAddressInt;
@@ -16,6 +17,7 @@ pub struct AuthService {
     access_token_lifetime: u32,
     jwt_secret: String,
     base_url: String,
+    admins: HashSet<String>,
 }
 
 impl AuthService {
@@ -24,13 +26,35 @@ impl AuthService {
             access_token_lifetime,
             jwt_secret,
             base_url,
+            admins: HashSet::new(),
         }
     }
 
+    /// Addresses signed in with the admin role
+    pub fn with_admins(mut self, admins: impl IntoIterator<Item = String>) -> Self {
+        self.admins = admins.into_iter().collect();
+        self
+    }
+
     pub fn authenticate(&self, headers: HeaderMap<HeaderValue>) -> anyhow::Result<String> {
+        Ok(self.claims(&headers)?.sub)
+    }
+
+    /// `None` for a valid token without the admin role. The allowlist is checked again,
+    /// so removing an address revokes its tokens on the next restart.
+    pub fn authenticate_admin(
+        &self,
+        headers: HeaderMap<HeaderValue>,
+    ) -> anyhow::Result<Option<String>> {
+        let claims = self.claims(&headers)?;
+        let is_admin = claims.role == Some(JwtRole::Admin) && self.admins.contains(&claims.sub);
+        Ok(is_admin.then_some(claims.sub))
+    }
+
+    fn claims(&self, headers: &HeaderMap<HeaderValue>) -> anyhow::Result<JwtClaims> {
         use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 
-        match self.jwt_from_header(&headers) {
+        match self.jwt_from_header(headers) {
             Ok(jwt) => {
                 let mut validation = Validation::new(Algorithm::default());
                 validation.leeway = 2;
@@ -42,7 +66,7 @@ impl AuthService {
                 )
                 .map_err(|_| Error::JwtToken)?;
 
-                Ok(decoded.claims.sub)
+                Ok(decoded.claims)
             }
             Err(e) => anyhow::bail!(e),
         }
@@ -176,6 +200,7 @@ impl AuthService {
         let claims = JwtClaims {
             sub: address.to_owned(),
             exp: expiration as usize,
+            role: self.admins.contains(address).then_some(JwtRole::Admin),
         };
 
         jsonwebtoken::encode(
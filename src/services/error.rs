@@ -19,6 +19,27 @@ pub enum Error {
     InvalidAuthHeader,
     #[error("No permission")]
     NoPermission,
+    #[error("Too many requests, retry in {retry_after_sec}s")]
+    TooManyRequests { retry_after_sec: u64 },
+    #[error("Daily api key quota exceeded")]
+    QuotaExceeded,
+    #[error("Invalid api key")]
+    InvalidApiKey,
+    #[error("Api key is disabled")]
+    ApiKeyDisabled,
+}
+
+impl Error {
+    fn limit_status(&self) -> Option<StatusCode> {
+        match self {
+            Error::TooManyRequests { .. } | Error::QuotaExceeded => {
+                Some(StatusCode::TOO_MANY_REQUESTS)
+            }
+            Error::InvalidApiKey => Some(StatusCode::UNAUTHORIZED),
+            Error::ApiKeyDisabled => Some(StatusCode::FORBIDDEN),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -38,6 +59,13 @@ pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible>
             Error::NoPermission => (StatusCode::UNAUTHORIZED, e.to_string()),
             Error::JwtToken => (StatusCode::UNAUTHORIZED, e.to_string()),
             Error::NoAuthHeader => (StatusCode::UNAUTHORIZED, e.to_string()),
+            Error::TooManyRequests { .. }
+            | Error::QuotaExceeded
+            | Error::InvalidApiKey
+            | Error::ApiKeyDisabled => (
+                e.limit_status().unwrap_or(StatusCode::BAD_REQUEST),
+                e.to_string(),
+            ),
             Error::JwtTokenCreation => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal Server Error".to_string(),
@@ -74,3 +102,29 @@ pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible>
 
     Ok(warp::reply::with_status(json, code))
 }
+
+/// Replies to rate limit and api key rejections, anything else is passed to warp's default handling
+pub async fn handle_limit_rejection(err: Rejection) -> Result<Box<dyn Reply>, Rejection> {
+    match err.find::<Error>().and_then(limit_reply) {
+        Some(reply) => Ok(reply),
+        None => Err(err),
+    }
+}
+
+/// The reply of a rate limit or api key error, `None` for the other errors
+pub fn limit_reply(e: &Error) -> Option<Box<dyn Reply>> {
+    let code = e.limit_status()?;
+    let json = warp::reply::json(&ErrorResponse {
+        status: code.to_string(),
+        message: e.to_string(),
+    });
+    let reply = warp::reply::with_status(json, code);
+    match e {
+        Error::TooManyRequests { retry_after_sec } => Some(Box::new(warp::reply::with_header(
+            reply,
+            "retry-after",
+            retry_after_sec.to_string(),
+        ))),
+        _ => Some(Box::new(reply)),
+    }
+}
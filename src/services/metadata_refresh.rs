@@ -0,0 +1,65 @@
+use crate::db::queries::Queries;
+use crate::db::repo::MetadataRefreshRepo;
+use chrono::NaiveDateTime;
+use moka::future::Cache;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Jobs older than this are not watched anymore
+const WATCH_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Drops the cached replies mentioning an nft once the indexer rewrote the metadata
+/// of a refresh request. Every instance keeps its own watermark, the caches are per process
+#[derive(Clone)]
+pub struct MetadataRefreshWatcher {
+    caches: Vec<Cache<u64, Value>>,
+}
+
+impl MetadataRefreshWatcher {
+    /// The caches must be built with `support_invalidation_closures`
+    pub fn new(caches: Vec<Cache<u64, Value>>) -> Self {
+        MetadataRefreshWatcher { caches }
+    }
+
+    /// Invalidates the nfts changed after `since`, returns the next watermark
+    pub async fn poll<D: MetadataRefreshRepo>(
+        &self,
+        db: &D,
+        since: Option<NaiveDateTime>,
+    ) -> sqlx::Result<Option<NaiveDateTime>> {
+        let changed = db.changed_metadata(since, WATCH_WINDOW).await?;
+        for record in &changed {
+            for cache in &self.caches {
+                let nft = record.nft.clone();
+                if let Err(e) = cache.invalidate_entries_if(move |_, value| mentions(value, &nft)) {
+                    log::error!("metadata refresh cache invalidation error: {e}");
+                }
+            }
+        }
+        Ok(changed.iter().map(|c| c.updated).chain(since).max())
+    }
+
+    pub async fn start(self, db: Queries, period: Duration) -> anyhow::Result<()> {
+        tokio::spawn(async move {
+            let mut since = None;
+            loop {
+                match self.poll(&db, since).await {
+                    Ok(next) => since = next,
+                    Err(e) => log::error!("metadata refresh watch error: {e}"),
+                }
+                tokio::time::sleep(period).await;
+            }
+        });
+        Ok(())
+    }
+}
+
+/// `nft` is one of the strings or object keys of `value`
+fn mentions(value: &Value, nft: &str) -> bool {
+    match value {
+        Value::String(s) => s == nft,
+        Value::Array(items) => items.iter().any(|v| mentions(v, nft)),
+        Value::Object(object) => object.iter().any(|(key, v)| key == nft || mentions(v, nft)),
+        _ => false,
+    }
+}
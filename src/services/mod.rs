@@ -1,2 +1,5 @@
 pub mod auth;
 pub mod error;
+pub mod metadata_refresh;
+pub mod rate_limit;
+pub mod suggest;
@@ -0,0 +1,541 @@
+use crate::cfg::{BucketConfig, RateLimitConfig};
+use crate::db::queries::Queries;
+use crate::db::ApiKeyRecord;
+use crate::handlers::router::ApiVersion;
+use crate::services::error::Error;
+use moka::future::Cache;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use warp::http::{HeaderMap, Method};
+use warp::path::FullPath;
+use warp::{Filter, Rejection};
+
+/// Peer address of the connection, put into the request extensions by the server
+#[derive(Debug, Clone, Copy)]
+pub struct RemoteAddr(pub SocketAddr);
+
+/// Where the client ip is read from, see [`RateLimitConfig::client_ip_source`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClientIpSource {
+    /// The peer address of the connection
+    #[default]
+    Peer,
+    /// The `x-forwarded-for` entry added by the outermost of `proxy_hops` trusted proxies,
+    /// everything left of it is written by the client
+    Forwarded { proxy_hops: usize },
+}
+
+/// Client ip the same way the rate limiter sees it, `None` when the peer is unknown
+pub fn client_ip(
+    source: ClientIpSource,
+) -> impl Filter<Extract = (Option<String>,), Error = Infallible> + Clone {
+    warp::header::headers_cloned()
+        .and(warp::ext::optional::<RemoteAddr>())
+        .map(move |headers: HeaderMap, remote: Option<RemoteAddr>| {
+            resolve_client_ip(&headers, remote, source)
+        })
+}
+
+fn resolve_client_ip(
+    headers: &HeaderMap,
+    remote: Option<RemoteAddr>,
+    source: ClientIpSource,
+) -> Option<String> {
+    if let ClientIpSource::Forwarded { proxy_hops } = source {
+        let forwarded = match headers.get("x-forwarded-for") {
+            Some(value) => {
+                let hops: Vec<&str> = value
+                    .to_str()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .collect();
+                // fewer entries than proxies means the request went around them
+                hops.len().checked_sub(proxy_hops.max(1)).map(|i| hops[i])
+            }
+            // a proxy that overwrites `x-real-ip` instead of appending
+            None => headers
+                .get("x-real-ip")
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+                .filter(|v| !v.is_empty()),
+        };
+        if let Some(ip) = forwarded {
+            return Some(ip.to_string());
+        }
+    }
+    remote.map(|r| r.0.ip().to_string())
+}
+
+/// Longest wait reported to a client, however slow its bucket refills
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Slowest refill allowed for an api key, the table only asks for a positive rate
+const MIN_KEY_RATE_PER_SEC: f64 = 0.001;
+
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(cfg: &BucketConfig) -> Self {
+        TokenBucket {
+            tokens: cfg.burst as f64,
+            updated: Instant::now(),
+        }
+    }
+
+    /// Takes `cost` tokens, otherwise returns how long it takes to refill them
+    fn take(&mut self, cfg: &BucketConfig, cost: u32) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * cfg.per_sec).min(cfg.burst as f64);
+        self.updated = now;
+
+        let cost = cost as f64;
+        if self.tokens >= cost {
+            self.tokens -= cost;
+            Ok(())
+        } else {
+            let wait = (cost - self.tokens) / cfg.per_sec;
+            Err(Duration::try_from_secs_f64(wait)
+                .unwrap_or(MAX_RETRY_AFTER)
+                .min(MAX_RETRY_AFTER))
+        }
+    }
+}
+
+struct ApiKeyState {
+    record: Option<ApiKeyRecord>,
+    /// Requests not counted in `record.requests_today`, so the quota holds between reloads
+    used: AtomicI64,
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    db: Queries,
+    cfg: Arc<RateLimitConfig>,
+    buckets: Cache<String, Arc<Mutex<TokenBucket>>>,
+    api_keys: Cache<String, Arc<ApiKeyState>>,
+    /// Usage not yet written to `api_key_usage`
+    pending_usage: Arc<Mutex<HashMap<String, i64>>>,
+}
+
+/// Limits of a key, out of range values of a hand edited row are clamped rather than trusted
+fn key_bucket(record: &ApiKeyRecord) -> BucketConfig {
+    let per_sec = if record.rate_per_sec.is_finite() {
+        record.rate_per_sec.max(MIN_KEY_RATE_PER_SEC)
+    } else {
+        MIN_KEY_RATE_PER_SEC
+    };
+    BucketConfig {
+        per_sec,
+        burst: record.burst.max(1) as u32,
+    }
+}
+
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+impl RateLimiter {
+    pub fn new(db: Queries, cfg: RateLimitConfig) -> Self {
+        RateLimiter {
+            db,
+            cfg: Arc::new(cfg),
+            buckets: Cache::builder()
+                .max_capacity(100_000)
+                .time_to_idle(Duration::from_secs(10 * 60))
+                .build(),
+            api_keys: Cache::builder()
+                .max_capacity(10_000)
+                .time_to_live(Duration::from_secs(60))
+                .build(),
+            pending_usage: Default::default(),
+        }
+    }
+
+    /// Passes the request through or rejects it with [`Error::TooManyRequests`] and friends,
+    /// see [`crate::services::error::handle_limit_rejection`]
+    pub fn filter(self) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+        warp::method()
+            .and(warp::path::full())
+            .and(warp::header::headers_cloned())
+            .and(warp::ext::optional::<RemoteAddr>())
+            .and(warp::any().map(move || self.clone()))
+            .and_then(
+                |method: Method,
+                 path: FullPath,
+                 headers: HeaderMap,
+                 remote: Option<RemoteAddr>,
+                 limiter: RateLimiter| async move {
+                    limiter
+                        .check(&method, path.as_str(), &headers, remote)
+                        .await
+                        .map_err(warp::reject::custom)
+                },
+            )
+            .untuple_one()
+    }
+
+    async fn check(
+        &self,
+        method: &Method,
+        path: &str,
+        headers: &HeaderMap,
+        remote: Option<RemoteAddr>,
+    ) -> Result<(), Error> {
+        if !self.cfg.enabled || method == Method::OPTIONS {
+            return Ok(());
+        }
+        let cost = self.route_cost(path);
+        let ip_key = format!("ip:{}", self.client_ip(headers, remote));
+
+        let Some(api_key) = headers.get(self.cfg.api_key_header.as_str()) else {
+            return self.take(ip_key, &self.cfg.ip, cost).await;
+        };
+
+        let key_hash = hash_api_key(api_key.to_str().unwrap_or_default());
+        let Some(state) = self.api_key(&key_hash).await else {
+            // the database is unavailable, serve the client with anonymous limits
+            return self.take(ip_key, &self.cfg.ip, cost).await;
+        };
+        let Some(record) = &state.record else {
+            // unknown keys still pay for the lookup from the ip bucket
+            self.take(ip_key, &self.cfg.ip, cost).await?;
+            return Err(Error::InvalidApiKey);
+        };
+        if !record.active {
+            return Err(Error::ApiKeyDisabled);
+        }
+        if let Some(quota) = record.daily_quota {
+            if record.requests_today + state.used.load(Ordering::Relaxed) >= quota {
+                return Err(Error::QuotaExceeded);
+            }
+        }
+
+        let bucket = key_bucket(record);
+        self.take(format!("key:{key_hash}"), &bucket, cost.min(bucket.burst))
+            .await?;
+
+        state.used.fetch_add(1, Ordering::Relaxed);
+        *self
+            .pending_usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key_hash)
+            .or_default() += 1;
+        Ok(())
+    }
+
+    /// Takes the tokens of a `/graphql` query on top of the route cost, once its complexity
+    /// is known. The bucket is the one that admitted the request
+    pub async fn charge_complexity(
+        &self,
+        headers: &HeaderMap,
+        remote: Option<RemoteAddr>,
+        complexity: usize,
+    ) -> Result<(), Error> {
+        let cost = (complexity / self.cfg.graphql_complexity_per_token.max(1)) as u32;
+        if !self.cfg.enabled || cost == 0 {
+            return Ok(());
+        }
+        if let Some(api_key) = headers.get(self.cfg.api_key_header.as_str()) {
+            let key_hash = hash_api_key(api_key.to_str().unwrap_or_default());
+            let state = self.api_key(&key_hash).await;
+            if let Some(record) = state.as_ref().and_then(|s| s.record.as_ref()) {
+                let bucket = key_bucket(record);
+                return self
+                    .take(format!("key:{key_hash}"), &bucket, cost.min(bucket.burst))
+                    .await;
+            }
+        }
+        let ip_key = format!("ip:{}", self.client_ip(headers, remote));
+        self.take(ip_key, &self.cfg.ip, cost.min(self.cfg.ip.burst))
+            .await
+    }
+
+    fn route_cost(&self, path: &str) -> u32 {
+        let path = ApiVersion::strip_prefix(path);
+        self.cfg
+            .route_costs
+            .iter()
+            .filter(|(route, _)| {
+                path.strip_prefix(route.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .max_by_key(|(route, _)| route.len())
+            .map_or(1, |(_, cost)| *cost)
+    }
+
+    fn client_ip(&self, headers: &HeaderMap, remote: Option<RemoteAddr>) -> String {
+        resolve_client_ip(headers, remote, self.cfg.client_ip_source())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    async fn take(&self, key: String, cfg: &BucketConfig, cost: u32) -> Result<(), Error> {
+        let bucket = self
+            .buckets
+            .get_with(key, async { Arc::new(Mutex::new(TokenBucket::new(cfg))) })
+            .await;
+        let result = bucket
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take(cfg, cost);
+        result.map_err(|wait| Error::TooManyRequests {
+            retry_after_sec: wait.as_secs_f64().ceil().max(1.0) as u64,
+        })
+    }
+
+    async fn api_key(&self, key_hash: &str) -> Option<Arc<ApiKeyState>> {
+        let limiter = self.clone();
+        let hash = key_hash.to_string();
+        self.api_keys
+            .try_get_with(key_hash.to_string(), async move {
+                let record = limiter.db.get_api_key(&hash).await?;
+                Ok::<_, sqlx::Error>(Arc::new(limiter.key_state(&hash, record)))
+            })
+            .await
+            .map_err(|e| log::error!("api key lookup failed: {e}"))
+            .ok()
+    }
+
+    /// A freshly loaded key, the usage not written to the database yet carries over
+    fn key_state(&self, key_hash: &str, record: Option<ApiKeyRecord>) -> ApiKeyState {
+        let pending = self
+            .pending_usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key_hash)
+            .copied()
+            .unwrap_or_default();
+        ApiKeyState {
+            record,
+            used: AtomicI64::new(pending),
+        }
+    }
+
+    /// Usage stays pending until it is written, a reload in between counts it twice rather
+    /// than not at all
+    async fn flush_usage(&self) {
+        let pending: Vec<(String, i64)> = self
+            .pending_usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(key_hash, requests)| (key_hash.clone(), *requests))
+            .collect();
+        for (key_hash, requests) in pending {
+            if let Err(e) = self.db.add_api_key_usage(&key_hash, requests).await {
+                log::error!("api key usage flush error: {e}");
+                continue;
+            }
+            let mut pending = self.pending_usage.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(left) = pending.get_mut(&key_hash) {
+                *left -= requests;
+                if *left <= 0 {
+                    pending.remove(&key_hash);
+                }
+            }
+        }
+    }
+
+    /// Periodically writes api key usage counters to the database
+    pub async fn start(self, period: Duration) -> anyhow::Result<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(period).await;
+                self.flush_usage().await;
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token::TokenDict;
+    use std::net::{Ipv4Addr, SocketAddrV4};
+
+    fn limiter(cfg: RateLimitConfig) -> RateLimiter {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/test")
+            .expect("lazy pool");
+        RateLimiter::new(Queries::new(Arc::new(pool), TokenDict::new(vec![])), cfg)
+    }
+
+    fn peer() -> Option<RemoteAddr> {
+        Some(RemoteAddr(SocketAddr::V4(SocketAddrV4::new(
+            Ipv4Addr::new(10, 0, 0, 9),
+            40000,
+        ))))
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (*name, value.parse().expect("header value")))
+            .map(|(name, value)| (warp::http::HeaderName::from_static(name), value))
+            .collect()
+    }
+
+    #[test]
+    fn client_ip_skips_entries_written_by_the_client() {
+        let spoofed = headers(&[("x-forwarded-for", "1.1.1.1, 203.0.113.7")]);
+        let ip = |headers: &HeaderMap, source| resolve_client_ip(headers, peer(), source);
+
+        assert_eq!(
+            ip(&spoofed, ClientIpSource::Peer).as_deref(),
+            Some("10.0.0.9")
+        );
+        let one = ClientIpSource::Forwarded { proxy_hops: 1 };
+        assert_eq!(ip(&spoofed, one).as_deref(), Some("203.0.113.7"));
+        let two = ClientIpSource::Forwarded { proxy_hops: 2 };
+        assert_eq!(ip(&spoofed, two).as_deref(), Some("1.1.1.1"));
+        // shorter than the proxy chain, the request didn't come through it
+        let three = ClientIpSource::Forwarded { proxy_hops: 3 };
+        assert_eq!(ip(&spoofed, three).as_deref(), Some("10.0.0.9"));
+
+        let real_ip = headers(&[("x-real-ip", "203.0.113.8")]);
+        assert_eq!(ip(&real_ip, one).as_deref(), Some("203.0.113.8"));
+        assert_eq!(ip(&HeaderMap::new(), one).as_deref(), Some("10.0.0.9"));
+    }
+
+    #[test]
+    fn forwarded_for_is_off_by_default() {
+        assert_eq!(
+            RateLimitConfig::default().client_ip_source(),
+            ClientIpSource::Peer
+        );
+    }
+
+    #[tokio::test]
+    async fn ip_bucket_refuses_a_burst_and_the_header_does_not_help() {
+        let limiter = limiter(RateLimitConfig {
+            ip: BucketConfig {
+                per_sec: 0.001,
+                burst: 3,
+            },
+            route_costs: [("/search".to_string(), 2)].into(),
+            ..Default::default()
+        });
+        let check = |path: &'static str, forwarded: &'static str| {
+            let limiter = limiter.clone();
+            async move {
+                let headers = headers(&[("x-forwarded-for", forwarded)]);
+                limiter.check(&Method::GET, path, &headers, peer()).await
+            }
+        };
+
+        assert!(check("/v2/search", "1.1.1.1").await.is_ok());
+        assert!(check("/v2/nft", "2.2.2.2").await.is_ok());
+        match check("/v2/nft", "3.3.3.3").await {
+            Err(Error::TooManyRequests { retry_after_sec }) => assert!(retry_after_sec > 1),
+            other => panic!("expected a rate limit, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn api_key_quota_is_counted_between_reloads() {
+        let limiter = limiter(RateLimitConfig::default());
+        let key_hash = hash_api_key("partner");
+        let record = ApiKeyRecord {
+            key_hash: key_hash.clone(),
+            name: "partner".to_string(),
+            rate_per_sec: 100.0,
+            burst: 100,
+            daily_quota: Some(3),
+            active: true,
+            requests_today: 1,
+        };
+        let stored = record.clone();
+        limiter
+            .api_keys
+            .insert(
+                key_hash.clone(),
+                Arc::new(ApiKeyState {
+                    record: Some(record),
+                    used: AtomicI64::new(0),
+                }),
+            )
+            .await;
+        let headers = headers(&[("x-api-key", "partner")]);
+
+        for _ in 0..2 {
+            assert!(limiter
+                .check(&Method::GET, "/v2/nfts", &headers, peer())
+                .await
+                .is_ok());
+        }
+        assert!(matches!(
+            limiter
+                .check(&Method::GET, "/v2/nfts", &headers, peer())
+                .await,
+            Err(Error::QuotaExceeded)
+        ));
+        assert_eq!(
+            limiter.pending_usage.lock().expect("usage lock")[&key_hash],
+            2
+        );
+
+        // reloaded before the usage was flushed, the database still reads one request
+        let reloaded = ApiKeyRecord {
+            requests_today: 1,
+            ..stored
+        };
+        limiter
+            .api_keys
+            .insert(
+                key_hash.clone(),
+                Arc::new(limiter.key_state(&key_hash, Some(reloaded))),
+            )
+            .await;
+        assert!(matches!(
+            limiter
+                .check(&Method::GET, "/v2/nfts", &headers, peer())
+                .await,
+            Err(Error::QuotaExceeded)
+        ));
+    }
+
+    #[test]
+    fn broken_key_limits_are_clamped() {
+        let record = |rate_per_sec: f64, burst: i32| ApiKeyRecord {
+            key_hash: String::new(),
+            name: String::new(),
+            rate_per_sec,
+            burst,
+            daily_quota: None,
+            active: true,
+            requests_today: 0,
+        };
+        for rate in [0.0, -5.0, 1e-300, f64::NAN, f64::INFINITY] {
+            let cfg = key_bucket(&record(rate, -1));
+            assert_eq!(cfg.per_sec, MIN_KEY_RATE_PER_SEC);
+            assert_eq!(cfg.burst, 1);
+
+            let mut bucket = TokenBucket::new(&cfg);
+            assert!(bucket.take(&cfg, 1).is_ok());
+            assert!(bucket.take(&cfg, 1).unwrap_err() <= MAX_RETRY_AFTER);
+        }
+    }
+
+    #[test]
+    fn retry_after_is_capped() {
+        let cfg = BucketConfig {
+            per_sec: 1e-300,
+            burst: 1,
+        };
+        let mut bucket = TokenBucket::new(&cfg);
+        assert!(bucket.take(&cfg, 1).is_ok());
+        assert_eq!(bucket.take(&cfg, 1), Err(MAX_RETRY_AFTER));
+    }
+}
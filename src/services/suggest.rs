@@ -0,0 +1,161 @@
+use crate::db::queries::Queries;
+use crate::db::repo::NftRepo;
+use crate::db::SuggestRecord;
+use crate::model::{SuggestRes, Suggestion};
+use api_types::SearchEntity;
+use moka::future::Cache;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Keys looked at by a single lookup, short prefixes of common words stop there
+const MAX_SCANNED_KEYS: usize = 50_000;
+
+/// Collection, nft and user names completed by `/search/suggest`. Loaded by the first
+/// request and reloaded on a schedule, so lookups don't reach the database
+#[derive(Clone)]
+pub struct SuggestIndex {
+    max_nfts: usize,
+    names: Cache<(), Arc<Names>>,
+}
+
+struct Names {
+    /// Best first: heaviest, then shortest name
+    entries: Vec<SuggestRecord>,
+    /// Normalized name of every entry
+    normalized: Vec<String>,
+    /// Entry and byte offset of every word start, sorted by the text from there on
+    keys: Vec<(u32, u32)>,
+}
+
+impl SuggestIndex {
+    pub fn new(max_nfts: usize) -> Self {
+        SuggestIndex {
+            max_nfts,
+            names: Cache::new(1),
+        }
+    }
+
+    /// Best `limit` names of every type with a word starting with `prefix`
+    pub async fn suggest<D: NftRepo>(
+        &self,
+        db: &D,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<SuggestRes, Arc<sqlx::Error>> {
+        let db = db.clone();
+        let max_nfts = self.max_nfts;
+        let names = self
+            .names
+            .try_get_with((), async move { load(&db, max_nfts).await.map(Arc::new) })
+            .await?;
+        Ok(names.lookup(&normalize(prefix), limit))
+    }
+
+    /// Reloads the names, returns how many there are
+    pub async fn refresh<D: NftRepo>(&self, db: &D) -> sqlx::Result<usize> {
+        let names = load(db, self.max_nfts).await?;
+        let count = names.entries.len();
+        self.names.insert((), Arc::new(names)).await;
+        Ok(count)
+    }
+
+    pub async fn start(self, db: Queries, period: Duration) -> anyhow::Result<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.refresh(&db).await {
+                    Ok(count) => log::debug!("search suggestions reloaded: {count} names"),
+                    Err(e) => log::error!("search suggestions reload error: {e}"),
+                }
+                tokio::time::sleep(period).await;
+            }
+        });
+        Ok(())
+    }
+}
+
+async fn load<D: NftRepo>(db: &D, max_nfts: usize) -> sqlx::Result<Names> {
+    let mut entries = db.suggest_entries(max_nfts).await?;
+    entries.sort_by(|a, b| {
+        b.weight
+            .cmp(&a.weight)
+            .then_with(|| a.name.len().cmp(&b.name.len()))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(Names::new(entries))
+}
+
+/// Lowercase words separated by single spaces
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Names {
+    fn new(entries: Vec<SuggestRecord>) -> Self {
+        let normalized: Vec<String> = entries.iter().map(|e| normalize(&e.name)).collect();
+        let mut keys = Vec::new();
+        for (entry, name) in normalized.iter().enumerate() {
+            let mut previous = None;
+            for (offset, c) in name.char_indices() {
+                let word_start =
+                    c.is_alphanumeric() && !previous.is_some_and(char::is_alphanumeric);
+                if offset == 0 || word_start {
+                    keys.push((entry as u32, offset as u32));
+                }
+                previous = Some(c);
+            }
+        }
+        let key = |(entry, offset): (u32, u32)| &normalized[entry as usize][offset as usize..];
+        keys.sort_unstable_by(|a, b| key(*a).cmp(key(*b)));
+        Names {
+            entries,
+            normalized,
+            keys,
+        }
+    }
+
+    fn key(&self, (entry, offset): (u32, u32)) -> &str {
+        &self.normalized[entry as usize][offset as usize..]
+    }
+
+    fn lookup(&self, prefix: &str, limit: usize) -> SuggestRes {
+        if prefix.is_empty() {
+            return SuggestRes::default();
+        }
+        // per type of `SearchEntity::ALL`
+        let mut found: [Vec<(bool, u32)>; 3] = Default::default();
+        let start = self.keys.partition_point(|k| self.key(*k) < prefix);
+        for &(entry, offset) in self.keys[start..]
+            .iter()
+            .take_while(|k| self.key(**k).starts_with(prefix))
+            .take(MAX_SCANNED_KEYS)
+        {
+            let object_type = self.entries[entry as usize].object_type.as_str();
+            if let Some(i) = SearchEntity::ALL
+                .iter()
+                .position(|e| e.as_str() == object_type)
+            {
+                found[i].push((offset != 0, entry));
+            }
+        }
+
+        // names starting with the prefix come before those with a later word matching it
+        let [nfts, collections, users] = found.map(|mut matches| {
+            matches.sort_unstable_by_key(|(later_word, entry)| (*entry, *later_word));
+            matches.dedup_by_key(|(_, entry)| *entry);
+            matches.sort_unstable();
+            matches
+                .into_iter()
+                .take(limit)
+                .map(|(_, entry)| Suggestion::from(&self.entries[entry as usize]))
+                .collect()
+        });
+        SuggestRes {
+            collections,
+            nfts,
+            users,
+        }
+    }
+}
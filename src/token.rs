@@ -35,13 +35,8 @@ impl TokenDict {
         TokenDict(Arc::new(map))
     }
 
-    pub async fn load() -> anyhow::Result<Self> {
-        let resp = reqwest::get(
-            "https://raw.githubusercontent.com/broxus/ton-assets/master/manifest.json",
-        )
-        .await?
-        .json::<Manifest>()
-        .await?;
+    pub async fn load(manifest_url: &str) -> anyhow::Result<Self> {
+        let resp = reqwest::get(manifest_url).await?.json::<Manifest>().await?;
         Ok(Self::new(resp.tokens))
     }
 
@@ -0,0 +1,45 @@
+use super::provider::{PriceProvider, UsdPrices};
+use crate::db::Address;
+use async_trait::async_trait;
+
+/// Serves fixed prices, either given directly or read from a yaml/json
+/// `{ token: usd_price }` file. Meant for local setups and tests.
+pub struct StaticPriceProvider {
+    path: Option<String>,
+    prices: UsdPrices,
+}
+
+impl StaticPriceProvider {
+    pub fn new(prices: UsdPrices) -> Self {
+        StaticPriceProvider { path: None, prices }
+    }
+
+    /// The file is re-read on every fetch so prices can be edited without a restart.
+    pub fn from_file(path: String) -> Self {
+        StaticPriceProvider {
+            path: Some(path),
+            prices: UsdPrices::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for StaticPriceProvider {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn fetch_prices(&self, tokens: &[Address]) -> anyhow::Result<UsdPrices> {
+        let prices = match &self.path {
+            Some(path) => {
+                let content = tokio::fs::read(path).await?;
+                serde_yaml::from_slice::<UsdPrices>(&content)?
+            }
+            None => self.prices.clone(),
+        };
+        Ok(prices
+            .into_iter()
+            .filter(|(token, _)| tokens.contains(token))
+            .collect())
+    }
+}
@@ -0,0 +1,68 @@
+use super::provider::{scale_by_decimals, PriceProvider, UsdPrices};
+use crate::db::Address;
+use crate::token::TokenDict;
+use async_trait::async_trait;
+use serde::Serialize;
+use sqlx::types::BigDecimal;
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenUsdPricesRequest {
+    pub currency_addresses: Vec<String>,
+}
+
+pub type TokenUsdPricesResponse = HashMap<String, String>;
+
+pub struct FlatQubeProvider {
+    http_client: reqwest::Client,
+    url: String,
+    tokens: TokenDict,
+}
+
+impl FlatQubeProvider {
+    pub fn new(http_client: reqwest::Client, url: String, tokens: TokenDict) -> Self {
+        FlatQubeProvider {
+            http_client,
+            url,
+            tokens,
+        }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for FlatQubeProvider {
+    fn name(&self) -> &'static str {
+        "flatqube"
+    }
+
+    async fn fetch_prices(&self, tokens: &[Address]) -> anyhow::Result<UsdPrices> {
+        let response = self
+            .http_client
+            .post(&self.url)
+            .json(&TokenUsdPricesRequest {
+                currency_addresses: tokens.to_vec(),
+            })
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TokenUsdPricesResponse>()
+            .await?;
+
+        let mut prices = UsdPrices::new();
+        for (token, price) in response {
+            let Some(decimals) = self.tokens.get(&token).map(|t| t.decimals) else {
+                log::warn!("flatqube returned price for unknown token {token}");
+                continue;
+            };
+            let price = match BigDecimal::from_str(&price) {
+                Ok(price) => price,
+                Err(e) => {
+                    log::warn!("flatqube returned malformed price {price:?} for {token}: {e}");
+                    continue;
+                }
+            };
+            prices.insert(token, scale_by_decimals(price, decimals));
+        }
+        Ok(prices)
+    }
+}
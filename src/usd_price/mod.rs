@@ -0,0 +1,338 @@
+pub mod file;
+pub mod flatqube;
+pub mod provider;
+pub mod venom_dex;
+
+use crate::cfg::{PriceProviderKind, PricesConfig};
+use crate::db::queries::Queries;
+use crate::db::{Address, TokenUsdPrice};
+use chrono::Utc;
+use sqlx::types::BigDecimal;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+pub use self::file::StaticPriceProvider;
+pub use self::flatqube::FlatQubeProvider;
+pub use self::provider::{PriceProvider, UsdPrices};
+pub use self::venom_dex::VenomDexProvider;
+
+/// The last accepted price of a token and the updates rejected since
+struct LastPrice {
+    price: BigDecimal,
+    accepted_at: Instant,
+    rejected_updates: u32,
+}
+
+#[derive(Clone)]
+pub struct CurrencyClient {
+    db: Queries,
+    /// Sorted by priority, highest first
+    providers: Arc<Vec<Box<dyn PriceProvider>>>,
+    last_prices: Arc<Mutex<HashMap<Address, LastPrice>>>,
+    max_deviation: BigDecimal,
+    max_outlier_updates: u32,
+    max_price_age: Duration,
+    retry_interval: Duration,
+}
+
+impl CurrencyClient {
+    pub fn new(
+        db: Queries,
+        main_token: String,
+        prices_url: String,
+        cfg: &PricesConfig,
+    ) -> anyhow::Result<Self> {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+
+        let mut providers: Vec<Box<dyn PriceProvider>> = Vec::new();
+        for kind in &cfg.providers {
+            let provider: Box<dyn PriceProvider> = match kind {
+                PriceProviderKind::FlatQube => Box::new(FlatQubeProvider::new(
+                    http_client.clone(),
+                    cfg.flatqube_url.clone(),
+                    db.tokens.clone(),
+                )),
+                PriceProviderKind::VenomDex => Box::new(VenomDexProvider::new(
+                    http_client.clone(),
+                    prices_url.clone(),
+                    vec![main_token.clone()],
+                    db.tokens.clone(),
+                )),
+                PriceProviderKind::File => match &cfg.file_path {
+                    Some(path) => Box::new(StaticPriceProvider::from_file(path.clone())),
+                    None => anyhow::bail!("prices.file_path is required for the file provider"),
+                },
+            };
+            providers.push(provider);
+        }
+
+        Ok(Self::with_providers(db, providers, cfg))
+    }
+
+    pub fn with_providers(
+        db: Queries,
+        providers: Vec<Box<dyn PriceProvider>>,
+        cfg: &PricesConfig,
+    ) -> Self {
+        let max_deviation = BigDecimal::try_from(cfg.max_deviation).unwrap_or_default();
+        CurrencyClient {
+            db,
+            providers: Arc::new(providers),
+            last_prices: Default::default(),
+            max_deviation,
+            max_outlier_updates: cfg.max_outlier_updates,
+            max_price_age: Duration::from_secs(cfg.max_price_age_sec),
+            retry_interval: Duration::from_secs(cfg.retry_interval_sec),
+        }
+    }
+
+    /// Asks every provider and picks, per token, the quote of the highest priority
+    /// provider that passes the outlier check.
+    pub async fn collect_prices(&self, tokens: &[Address]) -> UsdPrices {
+        let mut quotes: HashMap<Address, Vec<(&'static str, BigDecimal)>> = HashMap::new();
+        for provider in self.providers.iter() {
+            match provider.fetch_prices(tokens).await {
+                Ok(prices) => {
+                    for (token, price) in prices {
+                        if price <= BigDecimal::default() {
+                            log::warn!(
+                                "{} returned non-positive price {price} for {token}",
+                                provider.name()
+                            );
+                            continue;
+                        }
+                        quotes
+                            .entry(token)
+                            .or_default()
+                            .push((provider.name(), price));
+                    }
+                }
+                Err(e) => log::error!("{} prices request failed: {e}", provider.name()),
+            }
+        }
+
+        let mut last_prices = self.last_prices.lock().unwrap_or_else(|e| e.into_inner());
+        let mut accepted = UsdPrices::new();
+        for (token, candidates) in quotes {
+            let last = last_prices.get(&token);
+            match self.pick_price(last, &candidates) {
+                Some(price) => {
+                    last_prices.insert(
+                        token.clone(),
+                        LastPrice {
+                            price: price.clone(),
+                            accepted_at: Instant::now(),
+                            rejected_updates: 0,
+                        },
+                    );
+                    accepted.insert(token, price);
+                }
+                None => {
+                    let last = last_prices.get_mut(&token);
+                    log::warn!(
+                        "rejected outlier prices for {token}: {candidates:?}, last accepted {:?}",
+                        last.as_ref().map(|l| &l.price)
+                    );
+                    if let Some(last) = last {
+                        last.rejected_updates += 1;
+                    }
+                }
+            }
+        }
+        accepted
+    }
+
+    fn pick_price(
+        &self,
+        last: Option<&LastPrice>,
+        candidates: &[(&'static str, BigDecimal)],
+    ) -> Option<BigDecimal> {
+        let first = || candidates.first().map(|(_, price)| price.clone());
+        let Some(last) = last else {
+            return first();
+        };
+        // Outliers are held back for a while, a move that persists or follows a long gap is taken
+        if last.rejected_updates >= self.max_outlier_updates
+            || last.accepted_at.elapsed() >= self.max_price_age
+        {
+            return first();
+        }
+        let last = &last.price;
+
+        if let Some((_, price)) = candidates
+            .iter()
+            .find(|(_, price)| self.within_deviation(last, price))
+        {
+            return Some(price.clone());
+        }
+
+        // A jump confirmed by two independent providers is a real move, not an outlier
+        candidates
+            .iter()
+            .enumerate()
+            .find(|(i, (_, price))| {
+                candidates
+                    .iter()
+                    .skip(i + 1)
+                    .any(|(_, other)| self.within_deviation(price, other))
+            })
+            .map(|(_, (_, price))| price.clone())
+    }
+
+    fn within_deviation(&self, base: &BigDecimal, price: &BigDecimal) -> bool {
+        if *base <= BigDecimal::default() {
+            return true;
+        }
+        ((price - base).abs() / base) <= self.max_deviation
+    }
+
+    pub async fn update_prices(&self) -> anyhow::Result<usize> {
+        let tokens = self.db.tokens.addresses();
+        let prices = self.collect_prices(&tokens).await;
+        let ts = Utc::now().naive_utc();
+        let db_prices: Vec<TokenUsdPrice> = prices
+            .into_iter()
+            .map(|(token, usd_price)| TokenUsdPrice {
+                token,
+                usd_price,
+                ts,
+            })
+            .collect();
+        let updated = db_prices.len();
+        self.db.update_token_usd_prices(db_prices).await?;
+        Ok(updated)
+    }
+
+    pub async fn start(self, period: Duration) -> anyhow::Result<()> {
+        tokio::spawn(async move {
+            loop {
+                let delay = match self.update_prices().await {
+                    Ok(0) => {
+                        log::error!("usd prices update task: no provider returned prices");
+                        self.retry_interval
+                    }
+                    Ok(_) => period,
+                    Err(e) => {
+                        log::error!("usd prices update task error: {e}");
+                        self.retry_interval
+                    }
+                };
+                tokio::time::sleep(delay).await;
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token::TokenDict;
+    use std::str::FromStr;
+
+    const TOKEN: &str = "0:1111111111111111111111111111111111111111111111111111111111111111";
+    const OTHER: &str = "0:2222222222222222222222222222222222222222222222222222222222222222";
+
+    fn usd(price: &str) -> BigDecimal {
+        BigDecimal::from_str(price).expect("decimal")
+    }
+
+    fn fixed(prices: &[(&str, &str)]) -> Box<dyn PriceProvider> {
+        Box::new(StaticPriceProvider::new(
+            prices
+                .iter()
+                .map(|(token, price)| (token.to_string(), usd(price)))
+                .collect(),
+        ))
+    }
+
+    fn client(providers: Vec<Box<dyn PriceProvider>>) -> CurrencyClient {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/test")
+            .expect("lazy pool");
+        let db = Queries::new(Arc::new(pool), TokenDict::new(vec![]));
+        CurrencyClient::with_providers(db, providers, &PricesConfig::default())
+    }
+
+    fn accepted(client: &CurrencyClient, price: &str, age: Duration) {
+        let accepted_at = Instant::now().checked_sub(age).expect("instant in range");
+        client.last_prices.lock().expect("last prices").insert(
+            TOKEN.to_string(),
+            LastPrice {
+                price: usd(price),
+                accepted_at,
+                rejected_updates: 0,
+            },
+        );
+    }
+
+    #[tokio::test]
+    async fn providers_are_asked_in_priority_order() {
+        let client = client(vec![
+            Box::new(StaticPriceProvider::from_file("missing/prices.yaml".into())),
+            fixed(&[(TOKEN, "0.5")]),
+            fixed(&[(TOKEN, "0.7"), (OTHER, "2")]),
+        ]);
+
+        let prices = client.collect_prices(&[TOKEN.into(), OTHER.into()]).await;
+        // the first provider fails, the second one wins where it has a price
+        assert_eq!(prices.get(TOKEN), Some(&usd("0.5")));
+        assert_eq!(prices.get(OTHER), Some(&usd("2")));
+    }
+
+    #[tokio::test]
+    async fn an_outlier_is_rejected_until_it_persists() {
+        let client = client(vec![fixed(&[(TOKEN, "3")])]);
+        accepted(&client, "1", Duration::ZERO);
+
+        for _ in 0..PricesConfig::default().max_outlier_updates {
+            assert!(client.collect_prices(&[TOKEN.into()]).await.is_empty());
+        }
+        let prices = client.collect_prices(&[TOKEN.into()]).await;
+        assert_eq!(prices.get(TOKEN), Some(&usd("3")));
+        // the new price is the base for the next check
+        assert_eq!(
+            client.collect_prices(&[TOKEN.into()]).await.get(TOKEN),
+            Some(&usd("3"))
+        );
+    }
+
+    #[tokio::test]
+    async fn an_outlier_replaces_a_stale_price_or_one_confirmed_twice() {
+        let single = client(vec![fixed(&[(TOKEN, "3")])]);
+        accepted(&single, "1", Duration::from_secs(2 * 60 * 60));
+        assert_eq!(
+            single.collect_prices(&[TOKEN.into()]).await.get(TOKEN),
+            Some(&usd("3"))
+        );
+
+        let confirmed = client(vec![
+            fixed(&[(TOKEN, "9")]),
+            fixed(&[(TOKEN, "3")]),
+            fixed(&[(TOKEN, "3.1")]),
+        ]);
+        accepted(&confirmed, "1", Duration::ZERO);
+        assert_eq!(
+            confirmed.collect_prices(&[TOKEN.into()]).await.get(TOKEN),
+            Some(&usd("3"))
+        );
+    }
+
+    #[test]
+    fn prices_scale_by_any_number_of_decimals() {
+        assert_eq!(
+            provider::scale_by_decimals(usd("1500000000"), 9),
+            usd("1.5")
+        );
+        // past what fits an i64 power of ten
+        assert_eq!(
+            provider::scale_by_decimals(usd("2000000000000000000000000"), 24),
+            usd("2")
+        );
+    }
+}
@@ -0,0 +1,21 @@
+use crate::db::Address;
+use async_trait::async_trait;
+use sqlx::types::BigDecimal;
+use std::collections::HashMap;
+
+/// USD price of the smallest token unit (the provider quote divided by `10^decimals`),
+/// multiply raw on-chain amounts by it
+pub type UsdPrices = HashMap<Address, BigDecimal>;
+
+/// `value / 10^decimals`, exact for any number of decimals
+pub fn scale_by_decimals(value: BigDecimal, decimals: u32) -> BigDecimal {
+    value * BigDecimal::new(1.into(), decimals as i64)
+}
+
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Returns prices for the subset of `tokens` the provider knows about.
+    async fn fetch_prices(&self, tokens: &[Address]) -> anyhow::Result<UsdPrices>;
+}
@@ -0,0 +1,72 @@
+use super::provider::{scale_by_decimals, PriceProvider, UsdPrices};
+use crate::db::Address;
+use crate::token::TokenDict;
+use async_trait::async_trait;
+use serde::Deserialize;
+use sqlx::types::BigDecimal;
+
+const DEFAULT_DECIMALS: u32 = 9;
+
+#[derive(Deserialize)]
+struct VenomDexPriceResponse {
+    pub price: BigDecimal,
+}
+
+/// Quotes single tokens via `{prices_url}{token}`, used for the native token.
+pub struct VenomDexProvider {
+    http_client: reqwest::Client,
+    prices_url: String,
+    quoted_tokens: Vec<Address>,
+    tokens: TokenDict,
+}
+
+impl VenomDexProvider {
+    pub fn new(
+        http_client: reqwest::Client,
+        prices_url: String,
+        quoted_tokens: Vec<Address>,
+        tokens: TokenDict,
+    ) -> Self {
+        VenomDexProvider {
+            http_client,
+            prices_url,
+            quoted_tokens,
+            tokens,
+        }
+    }
+
+    async fn get_price(&self, token: &str) -> reqwest::Result<VenomDexPriceResponse> {
+        let url = format!("{}{token}", self.prices_url);
+        self.http_client
+            .post(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<VenomDexPriceResponse>()
+            .await
+    }
+}
+
+#[async_trait]
+impl PriceProvider for VenomDexProvider {
+    fn name(&self) -> &'static str {
+        "venom_dex"
+    }
+
+    async fn fetch_prices(&self, tokens: &[Address]) -> anyhow::Result<UsdPrices> {
+        let mut prices = UsdPrices::new();
+        for token in self.quoted_tokens.iter().filter(|t| tokens.contains(t)) {
+            match self.get_price(token).await {
+                Ok(response) => {
+                    let decimals = self
+                        .tokens
+                        .get(token)
+                        .map_or(DEFAULT_DECIMALS, |t| t.decimals);
+                    prices.insert(token.clone(), scale_by_decimals(response.price, decimals));
+                }
+                Err(e) => log::warn!("venom dex price request for {token} failed: {e}"),
+            }
+        }
+        Ok(prices)
+    }
+}
@@ -0,0 +1,447 @@
+//! Migrations and queries run against postgres. Each test creates its own database on the server
+//! of `DATABASE_URL` and drops it afterwards, without `DATABASE_URL` the tests pass without running.
+//! The query tests start from `seed/dev.sql`, the addresses below are its fixtures.
+use api::db::bootstrap::{load_seed, run_migrations};
+use api::db::queries::Queries;
+use api::db::*;
+use api::token::TokenDict;
+use api_types::NftMediaKind;
+use sqlx::migrate::Migrate;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use sqlx::Executor;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static DATABASES: AtomicUsize = AtomicUsize::new(0);
+
+const ADMIN: &str = "0:00000000000000000000000000000000000000000000000000000000000000ad";
+const ALICE: &str = "0:48f856eeb2f5f4bd4d7167870519e59af1d9a8fb85594c6632b65548fe4a60fe";
+const BOB: &str = "0:9d3fd4ea914f792254b9062674b8440ae851efcacb820fd88bc279be2a66c20f";
+const CAROL: &str = "0:4644e58639778ced9632a0ac415af5d504bcc6834ca915206358defe8061735e";
+const PUNKS: &str = "0:630db80c20508ea0972b00d005b2c84ee4f52b34abc8e269e5fc3e00fda26453";
+const SPIRITS: &str = "0:f602079b31cc06e1f474ab0e5dfa78c2647889284b4b9d629aeea60ba0bdfa45";
+const PUNK_1: &str = "0:516d02d94bb0c0fedf45f39394b4a62a0c331e238736cb73cd6975b7a46a6e75";
+const PUNK_2: &str = "0:676d3cc00846d00090b5ea5e6d15f640402b34f8fcdf14e85c3a95625f758e32";
+const PUNK_3: &str = "0:3c7e02f42822cb4e58defe4a28f85bbfb2c94662b52014f592f6a9670617373e";
+/// alice auctions Punk #1
+const PUNK_1_AUCTION: &str = "0:aeb1be718d9956911a6fc88e42bec24b5b10305596605ddfc4f3a29db0e37768";
+/// Punk #2 for sale by alice
+const PUNK_2_SELL: &str = "0:c26728be5ca4f779cc91bb778b84fceb8d1e2d5a92a2d7646f7e1127f01daafb";
+/// carol's offer for bob's Punk #3
+const PUNK_3_BUY: &str = "0:c9f273544893e6f7a8be3fcbf9601140ad6c0885a2c82b84233a77a7a1ae1c90";
+
+struct TestDb {
+    admin: PgPool,
+    name: String,
+    pool: PgPool,
+}
+
+impl TestDb {
+    /// An empty database, `None` when no server is configured
+    async fn empty() -> Option<TestDb> {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL is not set, skipping");
+            return None;
+        };
+        let options = PgConnectOptions::from_str(&url).expect("valid DATABASE_URL");
+        let admin = PgPoolOptions::new()
+            .max_connections(1)
+            .connect_with(options.clone())
+            .await
+            .expect("connect to DATABASE_URL");
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock after epoch")
+            .subsec_nanos();
+        let name = format!(
+            "api_test_{}_{}_{nanos}",
+            std::process::id(),
+            DATABASES.fetch_add(1, Ordering::Relaxed)
+        );
+        admin
+            .execute(format!("create database {name}").as_str())
+            .await
+            .expect("create test database");
+        let pool = PgPoolOptions::new()
+            .max_connections(4)
+            .connect_with(options.database(&name))
+            .await
+            .expect("connect to the test database");
+        Some(TestDb { admin, name, pool })
+    }
+
+    /// Migrated and loaded with the development fixtures
+    async fn seeded() -> Option<TestDb> {
+        let db = TestDb::empty().await?;
+        run_migrations(&db.pool).await.expect("migrations");
+        load_seed(&db.pool, "seed/dev.sql").await.expect("fixtures");
+        Some(db)
+    }
+
+    fn queries(&self) -> Queries {
+        Queries::new(Arc::new(self.pool.clone()), TokenDict::new(vec![]))
+    }
+
+    async fn exec(&self, sql: &str) {
+        self.pool.execute(sql).await.expect(sql);
+    }
+
+    async fn drop(self) {
+        self.pool.close().await;
+        self.admin
+            .execute(format!("drop database if exists {}", self.name).as_str())
+            .await
+            .expect("drop test database");
+    }
+}
+
+#[tokio::test]
+async fn migrations_apply_on_a_fresh_database_and_again() {
+    let Some(db) = TestDb::empty().await else {
+        return;
+    };
+    run_migrations(&db.pool).await.expect("first run");
+    run_migrations(&db.pool)
+        .await
+        .expect("second run is a no-op");
+    load_seed(&db.pool, "seed/dev.sql").await.expect("fixtures");
+    load_seed(&db.pool, "seed/dev.sql")
+        .await
+        .expect("fixtures reload");
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn migrations_leave_an_indexer_database_alone() {
+    let Some(db) = TestDb::empty().await else {
+        return;
+    };
+    // the indexer created the tables and keeps its own migrations in the same table
+    db.pool
+        .execute(include_str!("../migrations/20261018090000_read_schema.sql"))
+        .await
+        .expect("schema of the indexer");
+    let mut conn = db.pool.acquire().await.expect("connection");
+    conn.ensure_migrations_table()
+        .await
+        .expect("migrations table");
+    drop(conn);
+    db.pool
+        .execute(
+            "insert into _sqlx_migrations (version, description, success, checksum, execution_time)
+             values (20220301000000, 'indexer schema', true, '\\x00', 1),
+                    (20230101000000, 'read schema of an older api', true, '\\x00', 1);",
+        )
+        .await
+        .expect("indexer migrations");
+    run_migrations(&db.pool)
+        .await
+        .expect("api migrations apply");
+    let versions: Vec<i64> =
+        sqlx::query_scalar("select version from _sqlx_migrations order by version")
+            .fetch_all(&db.pool)
+            .await
+            .expect("applied migrations");
+    assert_eq!(versions[..2], [20220301000000, 20230101000000]);
+    assert!(versions.contains(&20261018090000));
+    run_migrations(&db.pool)
+        .await
+        .expect("second run is a no-op");
+    db.drop().await;
+}
+
+/// Names of the verified nfts `/nfts` lists for the collections, by name
+async fn listed(
+    queries: &Queries,
+    collections: &[&str],
+    include_hidden: bool,
+    filters: &NftFilters,
+) -> Vec<String> {
+    let collections: Vec<Address> = collections.iter().map(|c| c.to_string()).collect();
+    queries
+        .nft_search(
+            &[],
+            &collections,
+            None,
+            None,
+            Some(true),
+            100,
+            0,
+            &[],
+            None,
+            false,
+            include_hidden,
+            filters,
+        )
+        .await
+        .expect("nft list")
+        .into_iter()
+        .filter_map(|n| n.name)
+        .collect()
+}
+
+#[tokio::test]
+async fn orders_that_cant_be_filled_are_invalid() {
+    let Some(db) = TestDb::seeded().await else {
+        return;
+    };
+    let queries = &db.queries();
+    let sell = || async {
+        queries
+            .get_direct_sell(&PUNK_2_SELL.to_string())
+            .await
+            .expect("sell")
+            .expect("sell exists")
+    };
+    let buy = || async {
+        queries
+            .get_direct_buy(&PUNK_3_BUY.to_string())
+            .await
+            .expect("buy")
+            .expect("buy exists")
+    };
+    let details = |nft: &'static str| async move {
+        queries
+            .get_nft_details(&nft.to_string())
+            .await
+            .expect("details")
+            .expect("nft exists")
+    };
+    let sell_count = || async {
+        queries
+            .nft_sell_count(150_000_000_000)
+            .await
+            .expect("sell count")
+    };
+
+    assert!(sell().await.valid);
+    assert!(buy().await.valid);
+    assert_eq!(sell_count().await, Some(1));
+    assert_eq!(details(PUNK_2).await.forsale.as_deref(), Some(PUNK_2_SELL));
+    assert!(details(PUNK_2).await.floor_price.is_some());
+    assert_eq!(
+        details(PUNK_3).await.best_offer.as_deref(),
+        Some(PUNK_3_BUY)
+    );
+
+    // outside of the market bob got the nft alice sells and carol the one she offers for
+    db.exec(&format!(
+        "update nft set owner = '{BOB}' where address = '{PUNK_2}';
+         update nft set owner = '{CAROL}' where address = '{PUNK_3}';"
+    ))
+    .await;
+    let stale = sell().await;
+    assert!(!stale.valid);
+    assert_eq!(stale.state, DirectSellState::Active);
+    assert!(!buy().await.valid);
+    assert_eq!(sell_count().await, Some(0));
+    assert!(details(PUNK_2).await.floor_price.is_none());
+    assert!(details(PUNK_3).await.best_offer.is_none());
+
+    // the sell expired before the indexer caught up, the nft of the offer was burned
+    db.exec(&format!(
+        "update nft set owner = '{ALICE}' where address = '{PUNK_2}';
+         update nft_direct_sell set expired_at = now() - interval '1 minute' where address = '{PUNK_2_SELL}';
+         update nft set owner = '{BOB}' where address = '{PUNK_3}';
+         update nft set burned = true where address = '{PUNK_3}';"
+    ))
+    .await;
+    let expired = sell().await;
+    assert!(!expired.valid);
+    assert_eq!(expired.state, DirectSellState::Expired);
+    assert_eq!(sell_count().await, Some(0));
+    assert!(!buy().await.valid);
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn hidden_nfts_and_collections_are_left_out_unless_asked() {
+    let Some(db) = TestDb::seeded().await else {
+        return;
+    };
+    let queries = &db.queries();
+    let all = NftFilters::default();
+    queries
+        .set_moderation(
+            ADMIN,
+            ModerationTarget::Nft,
+            PUNK_1,
+            ModerationStatus::Hidden,
+            Some("stolen"),
+        )
+        .await
+        .expect("hide nft")
+        .expect("nft exists");
+
+    assert_eq!(
+        listed(queries, &[PUNKS], false, &all).await,
+        ["Punk #2", "Punk #3"]
+    );
+    assert_eq!(
+        listed(queries, &[PUNKS], true, &all).await,
+        ["Punk #1", "Punk #2", "Punk #3"]
+    );
+
+    // paged after the hidden events are left out, the count doesn't include them either
+    let events = |include_hidden: bool| async move {
+        let record = queries
+            .list_events(
+                None,
+                &[PUNKS.to_string()],
+                None,
+                &[],
+                0,
+                2,
+                true,
+                None,
+                include_hidden,
+            )
+            .await
+            .expect("events");
+        record.content.expect("events content")
+    };
+    let public = events(false).await;
+    assert_eq!(public["totalRows"], 5);
+    let data = public["data"].as_array().cloned().unwrap_or_default();
+    assert_eq!(data.len(), 2);
+    assert!(data.iter().all(|e| e["address"] != PUNK_1));
+    assert_eq!(events(true).await["totalRows"], 9);
+
+    let orders: Vec<Address> = queries
+        .collection_orders(&PUNKS.to_string())
+        .await
+        .expect("orders")
+        .into_iter()
+        .map(|o| o.address)
+        .collect();
+    assert!(!orders.contains(&PUNK_1_AUCTION.to_string()));
+
+    queries
+        .set_moderation(
+            ADMIN,
+            ModerationTarget::Collection,
+            SPIRITS,
+            ModerationStatus::Hidden,
+            None,
+        )
+        .await
+        .expect("hide collection")
+        .expect("collection exists");
+    assert!(listed(queries, &[SPIRITS], false, &all).await.is_empty());
+    assert_eq!(
+        listed(queries, &[SPIRITS], true, &all).await,
+        ["Birch Spirit", "Oak Spirit"]
+    );
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn nft_list_filters_by_media_text_mint_id_and_manager() {
+    let Some(db) = TestDb::seeded().await else {
+        return;
+    };
+    let queries = &db.queries();
+    db.exec(&format!(
+        r#"update nft_metadata set meta = meta || '{{"animation_url": "https://assets.example.com/punks/2.mp4"}}'
+           where nft = '{PUNK_2}';
+           update nft_metadata set meta = jsonb_set(meta, '{{files}}', '[{{"source": "https://assets.example.com/punks/3.glb"}}]')
+           where nft = '{PUNK_3}';"#
+    ))
+    .await;
+    let punks =
+        |filters: NftFilters| async move { listed(queries, &[PUNKS], false, &filters).await };
+    let media = |media: &[NftMediaKind]| NftFilters {
+        media: media.to_vec(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        punks(media(&[NftMediaKind::Image])).await,
+        ["Punk #1", "Punk #2"]
+    );
+    assert_eq!(punks(media(&[NftMediaKind::Video])).await, ["Punk #2"]);
+    assert_eq!(
+        punks(media(&[NftMediaKind::Video, NftMediaKind::Model])).await,
+        ["Punk #2", "Punk #3"]
+    );
+
+    let text = |text: &str| NftFilters {
+        text: Some(text.to_string()),
+        ..Default::default()
+    };
+    assert_eq!(punks(text("HAT")).await, ["Punk #2"]);
+    assert_eq!(punks(text("punk sun")).await, ["Punk #3"]);
+
+    let minted: i64 = sqlx::query_scalar(
+        "select created_at from nft_events where nft = $1 and event_type = 'nft_created'",
+    )
+    .bind(PUNK_2)
+    .fetch_one(&db.pool)
+    .await
+    .expect("mint of Punk #2");
+    let mint = NftFilters {
+        minted_from: Some(minted),
+        minted_to: Some(minted),
+        ..Default::default()
+    };
+    assert_eq!(punks(mint).await, ["Punk #2"]);
+    let later = NftFilters {
+        minted_from: Some(minted + 1),
+        ..Default::default()
+    };
+    assert_eq!(punks(later).await, ["Punk #3"]);
+
+    let ids = NftFilters {
+        nft_id_from: Some(2),
+        nft_id_to: Some(3),
+        ..Default::default()
+    };
+    assert_eq!(punks(ids).await, ["Punk #2", "Punk #3"]);
+
+    let managed = NftFilters {
+        manager: Some(BOB.to_string()),
+        media: vec![NftMediaKind::Model],
+        ..Default::default()
+    };
+    assert_eq!(punks(managed).await, ["Punk #3"]);
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn order_book_has_the_orders_that_can_be_filled() {
+    let Some(db) = TestDb::seeded().await else {
+        return;
+    };
+    let queries = &db.queries();
+    let orders = || async {
+        let mut orders: Vec<(String, bool, bool)> = queries
+            .collection_orders(&PUNKS.to_string())
+            .await
+            .expect("orders")
+            .into_iter()
+            .map(|o| (o.address, o.bid, o.auction))
+            .collect();
+        orders.sort();
+        orders
+    };
+
+    // the cancelled sell of Punk #3 isn't there
+    let mut expected = vec![
+        (PUNK_1_AUCTION.to_string(), false, true),
+        (PUNK_3_BUY.to_string(), true, false),
+        (PUNK_2_SELL.to_string(), false, false),
+    ];
+    expected.sort();
+    assert_eq!(orders().await, expected);
+
+    db.exec(&format!(
+        "update nft set burned = true where address = '{PUNK_1}';
+         update nft set owner = '{BOB}' where address = '{PUNK_2}';
+         update nft_direct_buy set expired_at = now() - interval '1 minute' where address = '{PUNK_3_BUY}';"
+    ))
+    .await;
+    assert!(orders().await.is_empty());
+    db.drop().await;
+}
@@ -0,0 +1,1535 @@
+//! Handlers served from `InMemoryRepo`, no database needed.
+use api::db::memory::{InMemoryRepo, MemoryState};
+use api::db::*;
+use api::handlers::{
+    admin, auction, collection, collection_custom, events, metadata_override, metadata_refresh,
+    metadata_report, moderation, nft, orderbook, quote, verification,
+};
+use api::model::NftEvent;
+use api::services::auth::AuthService;
+use api::services::metadata_refresh::MetadataRefreshWatcher;
+use api::services::rate_limit::ClientIpSource;
+use api::services::suggest::SuggestIndex;
+use api::token::TokenDict;
+use chrono::{DateTime, NaiveDateTime};
+use moka::future::Cache;
+use serde_json::{json, Value};
+use sqlx::types::BigDecimal;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::StatusCode;
+
+const VERIFIED: &str = "0:c1";
+const UNVERIFIED: &str = "0:c2";
+const ALICE: &str = "0:a1";
+const BOB: &str = "0:b0";
+const TOKEN: &str = "0:70";
+/// A load balancer in front of an ingress, both append to `x-forwarded-for`
+const FORWARDED: ClientIpSource = ClientIpSource::Forwarded { proxy_hops: 2 };
+const ADMIN: &str = "0:ad";
+
+fn ts(secs: i64) -> NaiveDateTime {
+    DateTime::from_timestamp(secs, 0)
+        .expect("valid timestamp")
+        .naive_utc()
+}
+
+fn nft(address: &str, collection: &str, owner: &str, name: &str) -> NftDetails {
+    NftDetails {
+        address: Some(address.to_string()),
+        collection: Some(collection.to_string()),
+        meta: Some(json!({
+            "files": [{ "source": format!("https://img/{address}.png"), "mimetype": "image/png" }],
+            "preview": { "source": format!("https://img/{address}.small.png"), "mimetype": "image/png" },
+            "attributes": [{ "trait_type": "Background", "value": if name.ends_with('1') { "Red" } else { "Blue" } }],
+        })),
+        owner: Some(owner.to_string()),
+        manager: Some(owner.to_string()),
+        name: Some(name.to_string()),
+        burned: Some(false),
+        description: None,
+        updated: Some(ts(1_700_000_000)),
+        tx_lt: Some(1),
+        auction: None,
+        forsale: None,
+        auction_status: None,
+        forsale_status: None,
+        best_offer: None,
+        floor_price_usd: None,
+        deal_price_usd: None,
+        total_count: 0,
+        floor_price: None,
+        floor_price_token: None,
+        nft_id: None,
+        meta_override: None,
+    }
+}
+
+fn collection(address: &str, owner: &str, verified: bool) -> NftCollectionDetails {
+    NftCollectionDetails {
+        address: Some(address.to_string()),
+        owner: Some(owner.to_string()),
+        name: Some(format!("Collection {address}")),
+        description: None,
+        created: Some(ts(1_690_000_000)),
+        updated: Some(ts(1_690_000_000)),
+        verified: Some(verified),
+        wallpaper: None,
+        logo: None,
+        owners_count: Some(2),
+        nft_count: Some(5),
+        max_price: None,
+        total_price: None,
+        floor_price_usd: None,
+        total_volume_usd: None,
+        attributes: None,
+        cnt: 0,
+        previews: json!([]),
+        first_mint: Some(ts(1_690_000_000)),
+        social: None,
+    }
+}
+
+fn auction(address: &str, nft: &str) -> NftAuction {
+    NftAuction {
+        address: Some(address.to_string()),
+        nft: Some(nft.to_string()),
+        wallet_for_bids: None,
+        price_token: Some(TOKEN.to_string()),
+        start_price: Some(BigDecimal::from(100)),
+        max_bid: None,
+        min_bid: Some(BigDecimal::from(100)),
+        start_usd_price: None,
+        max_usd_bid: None,
+        min_usd_bid: None,
+        status: Some(AuctionStatus::Active),
+        created_at: Some(ts(1_700_000_000)),
+        finished_at: Some(ts(1_800_000_000)),
+        tx_lt: Some(1),
+        bids_count: Some(0),
+        last_bid_from: None,
+        last_bid_ts: None,
+        last_bid_value: None,
+        last_bid_usd_value: None,
+        cnt: 0,
+        fee_numerator: None,
+        fee_denominator: None,
+    }
+}
+
+/// Active orders can be filled unless the test says otherwise, the fake doesn't check the nft
+fn direct_sell(address: &str, nft: &str, state: DirectSellState) -> NftDirectSell {
+    NftDirectSell {
+        address: address.to_string(),
+        nft: nft.to_string(),
+        collection: Some(VERIFIED.to_string()),
+        seller: Some(ALICE.to_string()),
+        price_token: TOKEN.to_string(),
+        price: BigDecimal::from(50),
+        usd_price: None,
+        state,
+        valid: state == DirectSellState::Active,
+        created: ts(1_700_000_000),
+        updated: ts(1_700_000_000),
+        finished_at: None,
+        expired_at: None,
+        tx_lt: 1,
+        cnt: 0,
+        fee_numerator: None,
+        fee_denominator: None,
+    }
+}
+
+fn direct_buy(address: &str, nft: &str, state: DirectBuyState) -> NftDirectBuy {
+    NftDirectBuy {
+        address: address.to_string(),
+        nft: nft.to_string(),
+        collection: Some(VERIFIED.to_string()),
+        buyer: Some(BOB.to_string()),
+        price_token: TOKEN.to_string(),
+        price: BigDecimal::from(40),
+        usd_price: None,
+        state,
+        valid: state == DirectBuyState::Active,
+        created: ts(1_700_000_000),
+        updated: ts(1_700_000_000),
+        finished_at: None,
+        expired_at: None,
+        tx_lt: 1,
+        cnt: 0,
+        fee_numerator: None,
+        fee_denominator: None,
+    }
+}
+
+fn event(id: i64, nft: &str) -> NftEvent {
+    serde_json::from_value(json!({
+        "id": id,
+        "eventType": "transfer",
+        "name": null,
+        "description": null,
+        "datetime": 1_700_000_000 + id,
+        "address": nft,
+        "previewUrl": null,
+        "directSell": null,
+        "directBuy": null,
+        "auction": null,
+        "mint": null,
+        "transfer": { "from": ALICE, "to": BOB },
+    }))
+    .expect("valid event")
+}
+
+/// Five nfts of a verified collection (one on auction, one for sale) and one unverified
+fn repo() -> InMemoryRepo {
+    let mut nfts: Vec<NftDetails> = (1..=5)
+        .map(|i| nft(&format!("0:n{i}"), VERIFIED, ALICE, &format!("Nft {i}")))
+        .collect();
+    nfts[0].auction = Some("0:au1".to_string());
+    nfts[0].auction_status = Some(AuctionStatus::Active);
+    nfts[1].forsale = Some("0:ds1".to_string());
+    nfts[1].forsale_status = Some(DirectSellState::Active);
+    nfts[1].best_offer = Some("0:db1".to_string());
+    nfts.push(nft("0:n9", UNVERIFIED, BOB, "Stray"));
+
+    let state = MemoryState {
+        nfts,
+        traits: vec![(
+            "0:n1".to_string(),
+            NftTraitRecord {
+                trait_type: Some("Background".to_string()),
+                trait_value: Some("Red".to_string()),
+                cnt: 1,
+            },
+        )],
+        collections: vec![
+            collection(VERIFIED, ALICE, true),
+            collection(UNVERIFIED, BOB, false),
+        ],
+        auctions: vec![auction("0:au1", "0:n1")],
+        direct_sells: vec![direct_sell("0:ds1", "0:n2", DirectSellState::Active)],
+        direct_buys: vec![
+            direct_buy("0:db1", "0:n2", DirectBuyState::Active),
+            direct_buy("0:db2", "0:n2", DirectBuyState::Cancelled),
+        ],
+        events: (1..=7).map(|id| event(id, "0:n1")).collect(),
+        ..Default::default()
+    };
+    InMemoryRepo::new(TokenDict::new(vec![]), state)
+}
+
+fn cache() -> Cache<u64, Value> {
+    Cache::builder()
+        .time_to_live(Duration::from_secs(10))
+        .build()
+}
+
+fn auth() -> Arc<AuthService> {
+    Arc::new(AuthService::new(60, "secret".into(), String::new()))
+}
+
+fn body(response: &warp::http::Response<warp::hyper::body::Bytes>) -> Value {
+    assert_eq!(response.status(), StatusCode::OK, "{:?}", response.body());
+    serde_json::from_slice(response.body()).expect("json body")
+}
+
+#[tokio::test]
+async fn nft_list_pages_without_counting() {
+    let db = repo();
+    let filter = nft::get_nft_list_by_query(db, cache(), auth());
+
+    let first = warp::test::request()
+        .path("/nfts?limit=2")
+        .reply(&filter)
+        .await;
+    let first = body(&first);
+    assert_eq!(first["items"].as_array().map(Vec::len), Some(2));
+    // one more row was fetched to tell there is a next page
+    assert_eq!(first["totalCount"], 3);
+
+    let last = warp::test::request()
+        .path("/nfts?limit=2&offset=4")
+        .reply(&filter)
+        .await;
+    let last = body(&last);
+    assert_eq!(last["items"].as_array().map(Vec::len), Some(1));
+    assert_eq!(last["totalCount"], 5);
+
+    let counted = warp::test::request()
+        .path("/nfts?limit=2&withCount=true&verified=false")
+        .reply(&filter)
+        .await;
+    assert_eq!(body(&counted)["totalCount"], 6);
+}
+
+#[tokio::test]
+async fn nft_list_hydrates_related_objects() {
+    let filter = nft::get_nft_list_by_query(repo(), cache(), auth());
+    let response = warp::test::request()
+        .path("/nfts?limit=2&order.field=name&order.direction=asc")
+        .reply(&filter)
+        .await;
+    let body = body(&response);
+
+    let items = body["items"].as_array().cloned().unwrap_or_default();
+    assert_eq!(items[0]["address"], "0:n1");
+    assert_eq!(items[0]["auction"], "0:au1");
+    assert_eq!(items[1]["forsale"], "0:ds1");
+    assert_eq!(body["collection"][VERIFIED]["verified"], true);
+    assert_eq!(body["auction"]["0:au1"]["nft"], "0:n1");
+    assert_eq!(body["directSell"]["0:ds1"]["nft"], "0:n2");
+    assert_eq!(body["directBuy"]["0:db1"]["nft"], "0:n2");
+}
+
+#[tokio::test]
+async fn nft_list_filters_by_attributes() {
+    let filter = nft::get_nft_list_by_query(repo(), cache(), auth());
+    let response = warp::test::request()
+        .path("/nfts?attributes[Background]=Red&withCount=true")
+        .reply(&filter)
+        .await;
+    let body = body(&response);
+    assert_eq!(body["totalCount"], 1);
+    assert_eq!(body["items"][0]["address"], "0:n1");
+}
+
+#[tokio::test]
+async fn nft_list_rejects_unknown_media() {
+    let filter = nft::get_nft_list_by_query(repo(), cache(), auth());
+    let valid = warp::test::request()
+        .path("/nfts?media=video,3d&text=cat&nftIdFrom=2&manager=0:b0")
+        .reply(&filter)
+        .await;
+    assert_eq!(valid.status(), StatusCode::OK);
+
+    let invalid = warp::test::request()
+        .path("/nfts?media=gif")
+        .reply(&filter)
+        .await;
+    assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn nft_list_is_served_from_cache() {
+    let db = repo();
+    let filter = nft::get_nft_list(db.clone(), cache(), auth());
+    let request = || {
+        warp::test::request()
+            .method("POST")
+            .path("/nfts")
+            .json(&json!({ "limit": 2 }))
+    };
+
+    let first = body(&request().reply(&filter).await);
+    let calls = db.calls();
+    assert!(calls > 0);
+
+    // the repository changes, the cached page doesn't
+    db.state_mut().nfts.clear();
+    let second = body(&request().reply(&filter).await);
+    assert_eq!(first, second);
+    assert_eq!(db.calls(), calls);
+
+    let other = warp::test::request()
+        .method("POST")
+        .path("/nfts")
+        .json(&json!({ "limit": 3 }))
+        .reply(&filter)
+        .await;
+    assert_eq!(body(&other)["items"], json!([]));
+    assert!(db.calls() > calls);
+}
+
+#[tokio::test]
+async fn cacheable_get_answers_not_modified() {
+    let filter = collection::list_collections_by_query(repo(), cache());
+    let response = warp::test::request()
+        .path("/collections")
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response
+        .headers()
+        .get("etag")
+        .cloned()
+        .expect("etag header");
+
+    let again = warp::test::request()
+        .path("/collections")
+        .header("if-none-match", etag)
+        .reply(&filter)
+        .await;
+    assert_eq!(again.status(), StatusCode::NOT_MODIFIED);
+    assert!(again.body().is_empty());
+}
+
+#[tokio::test]
+async fn collections_default_to_verified() {
+    let filter = collection::list_collections_by_query(repo(), cache());
+    let verified = body(
+        &warp::test::request()
+            .path("/collections")
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(verified["totalCount"], 1);
+    assert_eq!(verified["items"][0]["address"], VERIFIED);
+
+    let all = body(
+        &warp::test::request()
+            .path("/collections?verified=false")
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(all["totalCount"], 2);
+}
+
+#[tokio::test]
+async fn nft_details_hydrate_listings_and_traits() {
+    let filter = nft::get_nft_by_query(repo(), Duration::from_secs(1));
+
+    let on_auction = body(
+        &warp::test::request()
+            .path("/nft/details?nft=0:n1")
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(on_auction["nft"]["address"], "0:n1");
+    assert!(on_auction["collection"][VERIFIED].is_object());
+    assert_eq!(on_auction["auction"]["0:au1"]["address"], "0:au1");
+    assert_eq!(on_auction["traits"][0]["traitType"], "Background");
+
+    let for_sale = body(
+        &warp::test::request()
+            .path("/nft/details?nft=0:n2")
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(for_sale["directSell"]["0:ds1"]["address"], "0:ds1");
+    // only the active offers are listed
+    let offers = for_sale["directBuy"]
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+    assert_eq!(offers.keys().collect::<Vec<_>>(), vec!["0:db1"]);
+
+    let missing = warp::test::request()
+        .path("/nft/details?nft=0:nope")
+        .reply(&filter)
+        .await;
+    assert_eq!(missing.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn nft_details_read_every_metadata_layout() {
+    let db = repo();
+    db.state_mut().nfts[2].meta = Some(json!({
+        "image": "ipfs://cat.PNG?v=2",
+        "animation_url": { "uri": "https://img/cat.mp4" },
+        "audio": "https://img/purr.mp3",
+        "externalUrl": "https://cats.example",
+        "attributes": [
+            { "traitType": "Lives", "value": 9, "display_type": "number", "max_value": 9 },
+            { "trait_type": "Fluffy", "value": true },
+            { "trait_type": "Empty", "value": null },
+            "Orange",
+        ],
+    }));
+    db.state_mut().nfts[3].meta =
+        Some(json!({ "files": "not a list", "traits": { "Eyes": "Green" } }));
+    let filter = nft::get_nft_by_query(db, Duration::from_secs(1));
+    let details = |address: &str| {
+        warp::test::request()
+            .path(&format!("/nft/details?nft={address}"))
+            .reply(&filter)
+    };
+
+    let tip = body(&details("0:n1").await);
+    assert_eq!(tip["metadata"]["files"][0]["role"], "preview");
+    assert_eq!(tip["metadata"]["files"][1]["role"], "image");
+    assert_eq!(tip["nft"]["fullImage"], "https://img/0:n1.png");
+
+    let opensea = body(&details("0:n3").await);
+    let metadata = &opensea["metadata"];
+    assert_eq!(
+        metadata["files"],
+        json!([
+            { "source": "ipfs://cat.PNG?v=2", "mimetype": "image/png", "role": "image" },
+            { "source": "https://img/cat.mp4", "mimetype": "video/mp4", "role": "video" },
+            { "source": "https://img/purr.mp3", "mimetype": "audio/mpeg", "role": "audio" },
+        ])
+    );
+    assert_eq!(metadata["externalUrl"], "https://cats.example");
+    assert_eq!(
+        metadata["attributes"],
+        json!([
+            { "traitType": "Lives", "value": "9", "kind": "number", "displayType": "number", "maxValue": "9" },
+            { "traitType": "Fluffy", "value": "true", "kind": "boolean", "displayType": null, "maxValue": null },
+        ])
+    );
+    assert_eq!(opensea["nft"]["image"], "ipfs://cat.PNG?v=2");
+
+    // what doesn't fit is skipped instead of dropping the rest
+    let odd = body(&details("0:n4").await);
+    assert_eq!(odd["metadata"]["files"][0]["source"], "not a list");
+    assert_eq!(odd["metadata"]["attributes"][0]["value"], "Green");
+}
+
+#[tokio::test]
+async fn events_page_with_and_without_count() {
+    let filter = events::get_events_by_query(repo(), cache(), auth());
+
+    let page = body(
+        &warp::test::request()
+            .path("/events?nft=0:n1&limit=3&offset=3")
+            .reply(&filter)
+            .await,
+    );
+    let ids: Vec<i64> = page["data"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|e| e["id"].as_i64())
+        .collect();
+    assert_eq!(ids, vec![4, 3, 2]);
+    assert_eq!(page["totalRows"], 7);
+
+    let counted = body(
+        &warp::test::request()
+            .path("/events?nft=0:n1&limit=3&withCount=true")
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(counted["totalRows"], 7);
+    assert_eq!(counted["data"].as_array().map(Vec::len), Some(3));
+}
+
+#[tokio::test]
+async fn auction_bids_require_an_auction() {
+    let filter = auction::get_auction_bids(repo());
+    let response = warp::test::request()
+        .method("POST")
+        .path("/auction/bids")
+        .json(&json!({ "auction": "0:nope" }))
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.body(), "auction not found");
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/auction/bids")
+        .json(&json!({ "auction": "0:au1" }))
+        .reply(&filter)
+        .await;
+    let body = body(&response);
+    assert_eq!(body["totalCount"], 0);
+    assert_eq!(body["auction"], json!({}));
+}
+
+#[tokio::test]
+async fn collection_verification_requires_the_admin_role() {
+    let db = repo();
+    let auth = AuthService::new(60, "secret".into(), String::new()).with_admins([ADMIN.into()]);
+    let admin_token = auth.create_jwt(ADMIN);
+    let user_token = auth.create_jwt(ALICE);
+    let filter = admin::set_collection_verified(db.clone(), Arc::new(auth), cache());
+    let unverify = |collection: &str| {
+        warp::test::request()
+            .method("POST")
+            .path("/admin/collections/verified")
+            .json(&json!({ "collection": collection, "verified": false, "comment": "spam" }))
+    };
+
+    let response = unverify(VERIFIED).reply(&filter).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = unverify(VERIFIED)
+        .header("authorization", format!("Bearer {user_token}"))
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = unverify("0:nope")
+        .header("authorization", format!("Bearer {admin_token}"))
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(db.state().admin_audit.is_empty());
+
+    let response = unverify(VERIFIED)
+        .header("authorization", format!("Bearer {admin_token}"))
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let state = db.state();
+    assert_eq!(state.collections[0].verified, Some(false));
+    let [entry] = state.admin_audit.as_slice() else {
+        panic!("one audit entry expected, got {:?}", state.admin_audit);
+    };
+    assert_eq!(entry.admin, ADMIN);
+    assert_eq!(entry.action, AdminAction::CollectionUnverified);
+    assert_eq!(entry.target.as_deref(), Some(VERIFIED));
+    assert_eq!(entry.details["previous"], true);
+    assert_eq!(entry.details["comment"], "spam");
+}
+
+#[tokio::test]
+async fn approved_verification_request_verifies_the_collection() {
+    let db = repo();
+    let auth = AuthService::new(60, "secret".into(), String::new()).with_admins([ADMIN.into()]);
+    let bearer = |address: &str| format!("Bearer {}", auth.create_jwt(address));
+    let (owner, other, admin) = (bearer(BOB), bearer(ALICE), bearer(ADMIN));
+    let auth = Arc::new(auth);
+    let collections_cache = cache();
+    let nfts_cache = cache();
+    let create = verification::create_verification_request(db.clone(), auth.clone());
+    let poll = verification::list_own_verification_requests(db.clone(), auth.clone());
+    let approve = verification::approve_verification_request(
+        db.clone(),
+        auth.clone(),
+        vec![collections_cache.clone(), nfts_cache.clone()],
+    );
+    let collections = collection::list_collections_by_query(db.clone(), collections_cache);
+    let apply = |token: &str| {
+        warp::test::request()
+            .method("POST")
+            .path("/collections/verification-requests")
+            .header("authorization", token)
+            .json(&json!({
+                "collection": UNVERIFIED,
+                "description": "Original art by Bob",
+                "links": ["https://bob.art"],
+            }))
+    };
+
+    let response = apply(&other).reply(&create).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let request = body(&apply(&owner).reply(&create).await);
+    assert_eq!(request["status"], "pending");
+    assert_eq!(request["links"], json!(["https://bob.art"]));
+    let response = apply(&owner).reply(&create).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let verified = warp::test::request().path("/collections");
+    let before = body(&verified.reply(&collections).await);
+    assert_eq!(before["totalCount"], 1);
+    nfts_cache.insert(1, json!({ "totalCount": 0 })).await;
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/admin/verification-requests/approve")
+        .header("authorization", &owner)
+        .json(&json!({ "id": request["id"] }))
+        .reply(&approve)
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let approved = body(
+        &warp::test::request()
+            .method("POST")
+            .path("/admin/verification-requests/approve")
+            .header("authorization", &admin)
+            .json(&json!({ "id": request["id"] }))
+            .reply(&approve)
+            .await,
+    );
+    assert_eq!(approved["status"], "approved");
+    assert_eq!(approved["reviewer"], ADMIN);
+
+    // the cached list from before the approval is dropped
+    let after = body(
+        &warp::test::request()
+            .path("/collections")
+            .reply(&collections)
+            .await,
+    );
+    assert_eq!(after["totalCount"], 2);
+    assert!(nfts_cache.get(&1).is_none());
+
+    let polled = body(
+        &warp::test::request()
+            .path("/collections/verification-requests")
+            .header("authorization", &owner)
+            .reply(&poll)
+            .await,
+    );
+    assert_eq!(polled["totalCount"], 1);
+    assert_eq!(polled["items"][0]["status"], "approved");
+    let actions: Vec<AdminAction> = db.state().admin_audit.iter().map(|a| a.action).collect();
+    assert_eq!(
+        actions,
+        [
+            AdminAction::CollectionVerified,
+            AdminAction::NftVerifiedRefreshed
+        ]
+    );
+}
+
+#[tokio::test]
+async fn hidden_nfts_are_left_out_unless_an_admin_asks() {
+    let db = repo();
+    let auth = AuthService::new(60, "secret".into(), String::new()).with_admins([ADMIN.into()]);
+    let bearer = |address: &str| format!("Bearer {}", auth.create_jwt(address));
+    let (user, admin) = (bearer(BOB), bearer(ADMIN));
+    let auth = Arc::new(auth);
+    let report = moderation::create_report(db.clone(), auth.clone());
+    let moderate = moderation::set_moderation(db.clone(), auth.clone(), vec![]);
+    let nfts = nft::get_nft_list_by_query(db.clone(), cache(), auth.clone());
+    let events = events::get_events_by_query(db.clone(), cache(), auth.clone());
+    let send_report = || {
+        warp::test::request()
+            .method("POST")
+            .path("/reports")
+            .header("authorization", &user)
+            .json(&json!({ "targetType": "nft", "target": "0:n1", "reason": "stolen" }))
+    };
+
+    let created = body(&send_report().reply(&report).await);
+    assert_eq!(created["status"], "open");
+    let response = send_report().reply(&report).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let hide = |token: &str| {
+        warp::test::request()
+            .method("POST")
+            .path("/admin/moderation/status")
+            .header("authorization", token)
+            .json(&json!({ "targetType": "nft", "target": "0:n1", "status": "hidden", "reason": "stolen" }))
+    };
+    let response = hide(&user).reply(&moderate).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = hide(&admin).reply(&moderate).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(db.state().reports[0].status, ReportStatus::Resolved);
+    assert_eq!(
+        db.state().admin_audit[0].action,
+        AdminAction::ModerationChanged
+    );
+
+    let addresses = |list: &Value| -> Vec<String> {
+        let items = list["items"].as_array().cloned().unwrap_or_default();
+        items
+            .iter()
+            .filter_map(|n| n["address"].as_str().map(str::to_string))
+            .collect()
+    };
+    let public = body(&warp::test::request().path("/nfts").reply(&nfts).await);
+    assert!(!addresses(&public).contains(&"0:n1".to_string()));
+    let feed = body(&warp::test::request().path("/events").reply(&events).await);
+    assert_eq!(feed["data"], json!([]));
+
+    let response = warp::test::request()
+        .path("/nfts")
+        .header("x-include-hidden", "true")
+        .header("authorization", &user)
+        .reply(&nfts)
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = warp::test::request()
+        .path("/nfts")
+        .header("x-include-hidden", "true")
+        .header("authorization", &admin)
+        .reply(&nfts)
+        .await;
+    assert_eq!(response.headers()["cache-control"], "private, no-store");
+    assert!(addresses(&body(&response)).contains(&"0:n1".to_string()));
+}
+
+#[tokio::test]
+async fn collection_customization_is_kept_in_the_history_and_can_be_reverted() {
+    let db = repo();
+    let auth = AuthService::new(60, "secret".into(), String::new()).with_admins([ADMIN.into()]);
+    let bearer = |address: &str| format!("Bearer {}", auth.create_jwt(address));
+    let (owner, other, admin) = (bearer(ALICE), bearer(BOB), bearer(ADMIN));
+    let auth = Arc::new(auth);
+    let upsert = collection_custom::upsert_collection_custom(db.clone(), auth.clone(), FORWARDED);
+    let history = collection_custom::get_collection_custom_history(db.clone(), auth.clone());
+    let revert = collection_custom::revert_collection_custom(db.clone(), auth.clone(), FORWARDED);
+    let customize = |token: &str, name: &str| {
+        warp::test::request()
+            .method("POST")
+            .path("/collections-custom")
+            .header("authorization", token)
+            // the first entry is made up by the client, two proxies appended the others
+            .header("x-forwarded-for", "198.51.100.1, 203.0.113.7, 10.0.0.1")
+            .json(&json!({ "address": VERIFIED, "name": name, "social": {} }))
+    };
+    let read_history = |token: &str| {
+        warp::test::request()
+            .path(&format!("/collection/{VERIFIED}/custom/history"))
+            .header("authorization", token)
+    };
+
+    let response = customize(&other, "Stolen").reply(&upsert).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    for name in ["First", "Second"] {
+        let response = customize(&owner, name).reply(&upsert).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = read_history(&other).reply(&history).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let entries = body(&read_history(&owner).reply(&history).await);
+    assert_eq!(entries["totalCount"], 2);
+    let latest = &entries["items"][0];
+    assert_eq!(latest["actor"], ALICE);
+    assert_eq!(latest["action"], "collection_custom_updated");
+    assert_eq!(
+        latest["changes"],
+        json!({ "name": { "from": "First", "to": "Second" } })
+    );
+    assert_eq!(latest["ip"], Value::Null);
+    let first = entries["items"][1]["id"].clone();
+    assert_eq!(entries["items"][1]["before"], Value::Null);
+
+    let revert_to = |id: &Value| {
+        warp::test::request()
+            .method("POST")
+            .path(&format!("/collection/{VERIFIED}/custom/revert"))
+            .header("authorization", &owner)
+            .json(&json!({ "id": id }))
+    };
+    let response = revert_to(&json!(99)).reply(&revert).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = revert_to(&first).reply(&revert).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(db.state().collections[0].name.as_deref(), Some("First"));
+
+    let entries = body(&read_history(&admin).reply(&history).await);
+    assert_eq!(entries["totalCount"], 3);
+    let reverted = &entries["items"][0];
+    assert_eq!(reverted["action"], "collection_custom_reverted");
+    assert_eq!(reverted["details"]["revertedTo"], first);
+    assert_eq!(reverted["changes"]["name"]["to"], "First");
+    assert_eq!(entries["items"][1]["ip"], "203.0.113.7");
+}
+
+#[tokio::test]
+async fn search_takes_typed_queries_and_pages_with_a_cursor() {
+    let db = repo();
+    {
+        let mut state = db.state_mut();
+        state.nfts[3].nft_id = Some("4".to_string());
+        state.users.push(UserRecord {
+            address: BOB.to_string(),
+            username: Some("nft maker".to_string()),
+            ..Default::default()
+        });
+    }
+    let filter = events::search_all(db, auth());
+    let search = |query: Value| {
+        warp::test::request()
+            .method("POST")
+            .path("/search")
+            .json(&query)
+    };
+
+    // a plain text body is still the search text
+    let legacy = body(
+        &warp::test::request()
+            .method("POST")
+            .path("/search")
+            .body("Nft 3")
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(legacy["items"][0]["address"], "0:n3");
+    assert_eq!(legacy["items"][0]["score"], 5.0);
+
+    let mut cursor = Value::Null;
+    let mut seen = vec![];
+    for expected in [2, 2, 1] {
+        let page = body(
+            &search(json!({ "query": "nft", "types": ["nft"], "limit": 2, "cursor": cursor }))
+                .reply(&filter)
+                .await,
+        );
+        assert_eq!(page["count"], expected);
+        seen.extend(
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|i| i["address"].clone()),
+        );
+        cursor = page["nextCursor"].clone();
+    }
+    assert_eq!(cursor, Value::Null);
+    seen.sort_by_key(Value::to_string);
+    seen.dedup();
+    assert_eq!(seen.len(), 5, "{seen:?}");
+
+    let by_id = body(
+        &search(json!({ "query": "#4", "types": ["nft"] }))
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(by_id["items"][0]["address"], "0:n4");
+    assert_eq!(by_id["items"][0]["nftId"], "4");
+
+    let users = body(
+        &search(json!({ "query": "maker", "types": ["user"] }))
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(users["count"], 1);
+    assert_eq!(users["items"][0]["objectType"], "user");
+    assert_eq!(users["items"][0]["username"], "nft maker");
+
+    let scoped = body(
+        &search(json!({ "query": "nft", "collections": [UNVERIFIED] }))
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(scoped["count"], 0);
+
+    let response = search(json!({ "query": "nft", "cursor": "later" }))
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn search_suggest_completes_words_from_memory() {
+    let db = repo();
+    db.state_mut().nfts[2].name = Some("Red Fox".to_string());
+    let index = SuggestIndex::new(100);
+    let filter = events::search_suggest(db.clone(), index.clone(), Duration::from_secs(1));
+    let suggest = |query: &str| {
+        warp::test::request()
+            .path(&format!("/search/suggest?{query}"))
+            .reply(&filter)
+    };
+
+    let nfts = body(&suggest("q=NF&limit=3").await);
+    assert_eq!(nfts["nfts"].as_array().unwrap().len(), 3);
+    assert_eq!(nfts["nfts"][0]["name"], "Nft 1");
+    assert_eq!(nfts["collections"], json!([]));
+
+    // any word of the name, the unverified collection is left out
+    let fox = body(&suggest("q=fo").await);
+    assert_eq!(fox["nfts"][0]["address"], "0:n3");
+    assert_eq!(fox["nfts"][0]["collection"], VERIFIED);
+    let collections = body(&suggest("q=collection").await);
+    assert_eq!(collections["collections"].as_array().unwrap().len(), 1);
+    assert_eq!(collections["collections"][0]["address"], VERIFIED);
+
+    // names are read from the repo again only when the index is refreshed
+    db.state_mut().users.push(UserRecord {
+        address: BOB.to_string(),
+        username: Some("foxhound".to_string()),
+        ..Default::default()
+    });
+    assert_eq!(body(&suggest("q=fox").await)["users"], json!([]));
+    index.refresh(&db).await.unwrap();
+    let users = body(&suggest("q=fox").await);
+    assert_eq!(users["users"][0]["name"], "foxhound");
+    assert_eq!(users["nfts"][0]["name"], "Red Fox");
+
+    assert_eq!(suggest("").await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn metadata_refresh_is_queued_with_a_cooldown_and_drops_cached_replies() {
+    let db = repo();
+    let auth = Arc::new(AuthService::new(60, "secret".into(), String::new()));
+    let bearer = |address: &str| format!("Bearer {}", auth.create_jwt(address));
+    let cooldown = Duration::from_secs(600);
+    let refresh = metadata_refresh::request_metadata_refresh(db.clone(), auth.clone(), cooldown);
+    let status = metadata_refresh::get_metadata_refresh(db.clone(), cooldown);
+    let request = |nft: &str, token: Option<String>| {
+        let request = warp::test::request()
+            .method("POST")
+            .path(&format!("/nft/{nft}/refresh-metadata"));
+        match token {
+            Some(token) => request.header("authorization", token),
+            None => request,
+        }
+    };
+
+    let response = request("0:n1", None).reply(&refresh).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let job = body(&request("0:n1", Some(bearer(BOB))).reply(&refresh).await);
+    assert_eq!(job["status"], "pending");
+    assert_eq!(job["requestedBy"], BOB);
+    assert_eq!(
+        job["nextRequestAt"],
+        job["createdAt"].as_i64().unwrap() + 600
+    );
+    // the open job is returned again
+    let again = body(&request("0:n1", Some(bearer(ALICE))).reply(&refresh).await);
+    assert_eq!(again["id"], job["id"]);
+    let missing = request("0:nope", Some(bearer(BOB))).reply(&refresh).await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+    // the indexer wrote the new metadata
+    let cache = Cache::builder().support_invalidation_closures().build();
+    cache
+        .insert(1, json!({ "items": [{ "address": "0:n1" }] }))
+        .await;
+    cache
+        .insert(2, json!({ "items": [{ "address": "0:n2" }] }))
+        .await;
+    {
+        let mut state = db.state_mut();
+        state.metadata_refresh[0].status = MetadataRefreshStatus::Done;
+        state.nfts[0].updated = Some(ts(1_800_000_000));
+    }
+    let watcher = MetadataRefreshWatcher::new(vec![cache.clone()]);
+    let since = watcher.poll(&db, None).await.unwrap();
+    assert_eq!(since, Some(ts(1_800_000_000)));
+    assert!(cache.get(&1).is_none());
+    assert!(cache.get(&2).is_some());
+    assert_eq!(watcher.poll(&db, since).await.unwrap(), since);
+
+    let done = warp::test::request()
+        .path("/nft/0:n1/refresh-metadata")
+        .reply(&status)
+        .await;
+    let done = body(&done);
+    assert_eq!(done["status"], "done");
+    assert_eq!(done["metadataUpdated"], 1_800_000_000);
+    let throttled = request("0:n1", Some(bearer(BOB))).reply(&refresh).await;
+    assert_eq!(throttled.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(throttled.headers().contains_key("retry-after"));
+
+    // the collection owner queues the nfts out of their cooldown
+    let collection =
+        metadata_refresh::request_collection_metadata_refresh(db.clone(), auth.clone(), cooldown);
+    let request_collection = |token: String| {
+        warp::test::request()
+            .method("POST")
+            .path(&format!("/collection/{VERIFIED}/refresh-metadata"))
+            .header("authorization", token)
+    };
+    let response = request_collection(bearer(BOB)).reply(&collection).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let batch = body(&request_collection(bearer(ALICE)).reply(&collection).await);
+    assert_eq!(batch["queued"], 4);
+    assert_eq!(batch["skipped"], 1);
+    let batch = body(&request_collection(bearer(ALICE)).reply(&collection).await);
+    assert_eq!(batch["queued"], 0);
+}
+
+#[tokio::test]
+async fn metadata_overrides_replace_indexed_fields_with_provenance() {
+    let db = repo();
+    let auth = auth();
+    let bearer = |address: &str| format!("Bearer {}", auth.create_jwt(address));
+    let single =
+        metadata_override::write_metadata_override(db.clone(), auth.clone(), ClientIpSource::Peer);
+    let request = |nft: &str, token: String, payload: Value| {
+        warp::test::request()
+            .method("POST")
+            .path(&format!("/nft/{nft}/metadata-override"))
+            .header("authorization", token)
+            .json(&payload)
+    };
+    let payload = json!({
+        "name": "Fixed name",
+        "preview": "https://fixed/n2.webp",
+        "attributes": { "Background": "Green", "Eyes": "Laser" },
+    });
+
+    let response = request("0:n2", bearer(BOB), payload.clone())
+        .reply(&single)
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = request(
+        "0:n2",
+        bearer(ALICE),
+        json!({ "preview": "file:///etc/passwd" }),
+    )
+    .reply(&single)
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = request("0:n2", bearer(ALICE), payload).reply(&single).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let details = nft::get_nft(db.clone());
+    let response = warp::test::request()
+        .method("POST")
+        .path("/nft/details")
+        .json(&json!({ "nft": "0:n2" }))
+        .reply(&details)
+        .await;
+    let details = body(&response);
+    assert_eq!(details["nft"]["name"], "Fixed name");
+    assert_eq!(details["nft"]["image"], "https://fixed/n2.webp");
+    assert_eq!(details["nft"]["mimetype"], "image/webp");
+    assert_eq!(
+        details["nft"]["overridden"],
+        json!(["name", "preview", "attributes"])
+    );
+    let traits: Vec<&str> = details["metadata"]["attributes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["traitType"].as_str().unwrap())
+        .collect();
+    assert_eq!(traits, ["Background", "Eyes"]);
+    // files of the metadata are kept
+    assert_eq!(
+        details["metadata"]["files"][1]["source"],
+        "https://img/0:n2.png"
+    );
+
+    // bulk csv, nfts of other collections are skipped
+    let bulk =
+        metadata_override::write_metadata_overrides(db.clone(), auth.clone(), ClientIpSource::Peer);
+    let csv =
+        "nft,name,attributes\n0:n3,\"Nft \"\"three\"\"\",Background=Gold;Rank=1\n0:n9,Stolen,\n";
+    let response = warp::test::request()
+        .method("POST")
+        .path(&format!("/collection/{VERIFIED}/metadata-overrides"))
+        .header("authorization", bearer(ALICE))
+        .header("content-type", "text/csv")
+        .body(csv)
+        .reply(&bulk)
+        .await;
+    let batch = body(&response);
+    assert_eq!(batch["written"], 1);
+    assert_eq!(batch["skipped"], json!(["0:n9"]));
+
+    // errors point at the physical line, past a quoted field spanning two
+    let csv = "nft,name,attributes\n0:n3,\"Nft\nthree\",\n0:n4,Four,Rank\n";
+    let response = warp::test::request()
+        .method("POST")
+        .path(&format!("/collection/{VERIFIED}/metadata-overrides"))
+        .header("authorization", bearer(ALICE))
+        .header("content-type", "text/csv")
+        .body(csv)
+        .reply(&bulk)
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error = String::from_utf8(response.body().to_vec()).unwrap();
+    assert!(error.starts_with("line 4:"), "{error}");
+
+    let list = nft::get_nft_list_by_query(db.clone(), cache(), auth.clone());
+    let response = warp::test::request()
+        .path("/nfts?limit=5")
+        .reply(&list)
+        .await;
+    let items = body(&response)["items"].as_array().unwrap().clone();
+    let n3 = items.iter().find(|n| n["address"] == "0:n3").unwrap();
+    assert_eq!(n3["name"], "Nft \"three\"");
+    assert_eq!(n3["overridden"], json!(["name", "attributes"]));
+    assert_eq!(n3["image"], "https://img/0:n3.small.png");
+    let n1 = items.iter().find(|n| n["address"] == "0:n1").unwrap();
+    assert!(n1.get("overridden").is_none());
+
+    let overrides = metadata_override::list_metadata_overrides(db.clone());
+    let response = warp::test::request()
+        .path(&format!("/collection/{VERIFIED}/metadata-overrides"))
+        .reply(&overrides)
+        .await;
+    let overrides = body(&response);
+    assert_eq!(overrides["totalCount"], 2);
+    assert_eq!(overrides["items"][1]["attributes"][1]["traitType"], "Rank");
+
+    // removal restores the indexed values and is kept in the history
+    let remove =
+        metadata_override::remove_metadata_override(db.clone(), auth.clone(), ClientIpSource::Peer);
+    let removal = || {
+        warp::test::request()
+            .method("POST")
+            .path("/nft/0:n2/metadata-override/remove")
+            .header("authorization", bearer(ALICE))
+    };
+    assert_eq!(removal().reply(&remove).await.status(), StatusCode::OK);
+    assert_eq!(
+        removal().reply(&remove).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    let state = db.state();
+    let n2 = state
+        .nfts
+        .iter()
+        .find(|n| n.address.as_deref() == Some("0:n2"))
+        .unwrap();
+    assert!(n2.overridden().is_empty());
+    let history: Vec<WriteAction> = state
+        .write_audit
+        .iter()
+        .filter(|w| w.target == "0:n2")
+        .map(|w| w.action)
+        .collect();
+    assert_eq!(
+        history,
+        [
+            WriteAction::NftMetadataOverrideUpdated,
+            WriteAction::NftMetadataOverrideRemoved
+        ]
+    );
+}
+
+#[tokio::test]
+async fn metadata_report_lists_nfts_per_issue() {
+    let db = repo();
+    {
+        let mut state = db.state_mut();
+        let nfts = &mut state.nfts;
+        nfts[1].meta = None;
+        nfts[2].meta = Some(json!("{not json"));
+        nfts[3].name = Some("nft 5".to_string());
+        nfts[3].meta = Some(json!({
+            "image": "file:///n4.png",
+            "attributes": { "background": "Blue" },
+        }));
+        nfts[4].meta = Some(json!({
+            "files": [{ "source": "https://img/0:n5.mp4", "mimetype": "video/mp4" }],
+            "attributes": [{ "trait_type": "Background", "value": 7 }, { "value": "orphan" }],
+        }));
+        // fixed by the owner, the override counts
+        let mut fixed = nft("0:n6", VERIFIED, ALICE, "Nft 6");
+        fixed.meta = None;
+        fixed.meta_override = Some(json!({
+            "preview": { "source": "https://fixed/n6.png", "mimetype": "image/png" },
+            "attributes": [{ "trait_type": "Background", "value": "Red" }],
+        }));
+        nfts.push(fixed);
+        let mut burned = nft("0:n7", VERIFIED, ALICE, "Nft 1");
+        burned.burned = Some(true);
+        nfts.push(burned);
+    }
+    let auth = auth();
+    let owner = format!("Bearer {}", auth.create_jwt(ALICE));
+    let other = format!("Bearer {}", auth.create_jwt(BOB));
+    let filter = metadata_report::get_metadata_report(db, cache(), auth);
+
+    let response = warp::test::request()
+        .path("/collection/0:missing/metadata-report")
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // everyone but the owner gets the counts
+    for token in [None, Some(&other)] {
+        let mut request =
+            warp::test::request().path(&format!("/collection/{VERIFIED}/metadata-report"));
+        if let Some(token) = token {
+            request = request.header("authorization", token);
+        }
+        let report = body(&request.reply(&filter).await);
+        assert_eq!(report["checked"], 6);
+        assert_eq!(report["issues"][0]["count"], 2);
+        assert_eq!(report["issues"][0]["nfts"], json!([]));
+        assert_eq!(report["duplicateNames"][0]["nfts"], json!([]));
+    }
+
+    let response = warp::test::request()
+        .path(&format!("/collection/{VERIFIED}/metadata-report"))
+        .header("authorization", &owner)
+        .reply(&filter)
+        .await;
+    assert_eq!(response.headers()["cache-control"], "private, no-store");
+    let report = body(&response);
+    assert_eq!(report["checked"], 6);
+    assert_eq!(report["healthy"], 1);
+    assert_eq!(report["next"], Value::Null);
+    let issues: Vec<(&str, Vec<&str>)> = report["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| {
+            let nfts = i["nfts"].as_array().unwrap();
+            assert_eq!(i["count"], nfts.len());
+            (
+                i["issue"].as_str().unwrap(),
+                nfts.iter().map(|n| n.as_str().unwrap()).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            ("missing_metadata", vec!["0:n2", "0:n6"]),
+            ("unparsable_metadata", vec!["0:n3"]),
+            ("missing_preview", vec!["0:n5"]),
+            ("invalid_file_url", vec!["0:n4"]),
+            ("duplicate_name", vec!["0:n4", "0:n5"]),
+            ("attributes_not_array", vec!["0:n4"]),
+            ("malformed_attributes", vec!["0:n5"]),
+            ("inconsistent_trait_type", vec!["0:n4", "0:n5"]),
+        ]
+    );
+    assert_eq!(
+        report["duplicateNames"],
+        json!([{ "name": "nft 5", "nfts": ["0:n4", "0:n5"] }])
+    );
+    assert_eq!(
+        report["inconsistentTraits"],
+        json!([{
+            "traitType": "Background",
+            "spellings": ["Background", "background"],
+            "kinds": ["text", "number"],
+            "nfts": ["0:n4", "0:n5"],
+        }])
+    );
+
+    let page = body(
+        &warp::test::request()
+            .path(&format!(
+                "/collection/{VERIFIED}/metadata-report?after=0:n4"
+            ))
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(page["checked"], 2);
+}
+
+#[tokio::test]
+async fn quote_splits_price_into_fee_royalty_and_proceeds() {
+    let root = |address: &str, code: &str| RootRecord {
+        address: address.to_string(),
+        code: code.to_string(),
+    };
+    let db = repo();
+    {
+        let mut state = db.state_mut();
+        state.nfts[0].meta = Some(json!({
+            "royalty": { "description": "creator", "percent": "2.5%", "recipient": "0:c0" },
+        }));
+        state.nfts[1].burned = Some(true);
+        state.roots = vec![
+            root("0:r2", "sell"),
+            root("0:r1", "sell"),
+            root("0:r3", "auction"),
+        ];
+        state.default_fee = Some((3, 100));
+        state.token_prices.push(TokenUsdPrice {
+            token: "0:wever".to_string(),
+            usd_price: "0.000000001".parse().unwrap(),
+            ts: ts(1_700_000_000),
+        });
+    }
+    let filter = quote::get_quote(db.clone());
+    let quote = |query: &str| {
+        warp::test::request()
+            .path(&format!("/quote?{query}"))
+            .reply(&filter)
+    };
+
+    let response = quote("nft=0:n1&saleType=direct_sell&price=1000000001&token=0:wever").await;
+    let quoted = body(&response);
+    assert_eq!(quoted["price"], "1000000001");
+    assert_eq!(
+        quoted["royalty"]["fee"],
+        json!({ "numerator": 250, "denominator": 10000 })
+    );
+    assert_eq!(quoted["royalty"]["recipient"], "0:c0");
+    assert_eq!(quoted["royalty"]["amount"], "25000000");
+    let roots: Vec<&str> = quoted["roots"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["root"].as_str().unwrap())
+        .collect();
+    assert_eq!(roots, ["0:r1", "0:r2"]);
+    let root = &quoted["roots"][0];
+    assert_eq!(root["fee"], json!({ "numerator": 3, "denominator": 100 }));
+    assert_eq!(root["feeUnknown"], false);
+    assert_eq!(root["feeAmount"], "30000000");
+    assert_eq!(root["proceeds"], "945000001");
+    assert_eq!(
+        root["proceedsUsd"]
+            .as_str()
+            .unwrap()
+            .parse::<f64>()
+            .unwrap(),
+        0.945000001
+    );
+
+    // a root without a fee still nets the royalty off
+    db.state_mut().default_fee = None;
+    let response = quote("nft=0:n1&saleType=direct_sell&price=1000000001&token=0:wever").await;
+    let root = &body(&response)["roots"][0];
+    assert_eq!(root["fee"], Value::Null);
+    assert_eq!(root["feeUnknown"], true);
+    assert_eq!(root["feeAmount"], Value::Null);
+    assert_eq!(root["proceeds"], "975000001");
+
+    let response = quote("nft=0:n1&saleType=accept_offer&price=100&token=0:unknown").await;
+    let quoted = body(&response);
+    assert_eq!(quoted["roots"], json!([]));
+    assert_eq!(quoted["priceUsd"], Value::Null);
+
+    for (query, status) in [
+        (
+            "nft=0:n1&saleType=auction&price=1.5&token=0:wever",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "nft=0:n1&saleType=auction&price=-1&token=0:wever",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "nft=0:n1&saleType=auction&price=1e100000000&token=0:wever",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "nft=0:n1&saleType=auction&price=1e-9999999999&token=0:wever",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "nft=0:n1&saleType=auction&price=0&token=0:wever",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "nft=0:n2&saleType=auction&price=1&token=0:wever",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "nft=0:n8&saleType=auction&price=1&token=0:wever",
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        assert_eq!(quote(query).await.status(), status, "{query}");
+    }
+}
+
+#[tokio::test]
+async fn stale_orders_are_flagged_and_left_out_of_counts() {
+    let db = repo();
+    let details = nft::get_nft_by_query(db.clone(), Duration::from_secs(1));
+    let sell_count = nft::get_nft_sell_count(db.clone(), cache());
+
+    let fresh = body(
+        &warp::test::request()
+            .path("/nft/details?nft=0:n2")
+            .reply(&details)
+            .await,
+    );
+    assert_eq!(fresh["directSell"]["0:ds1"]["valid"], true);
+    assert_eq!(fresh["directBuy"]["0:db1"]["valid"], true);
+    let counted = warp::test::request()
+        .path("/nfts/sell-count?maxPrice=100")
+        .reply(&sell_count)
+        .await;
+    assert_eq!(body(&counted)["count"], 1);
+
+    // the sql decides validity, see tests/db.rs
+    db.state_mut().direct_sells[0].valid = false;
+    db.state_mut().direct_buys[0].valid = false;
+    let stale = body(
+        &warp::test::request()
+            .path("/nft/details?nft=0:n2")
+            .reply(&details)
+            .await,
+    );
+    assert_eq!(stale["directSell"]["0:ds1"]["valid"], false);
+    assert_eq!(stale["directSell"]["0:ds1"]["status"], "active");
+    assert_eq!(stale["directBuy"]["0:db1"]["valid"], false);
+    let counted = warp::test::request()
+        .path("/nfts/sell-count?maxPrice=60")
+        .reply(&sell_count)
+        .await;
+    assert_eq!(body(&counted)["count"], 0);
+}
+
+#[tokio::test]
+async fn orderbook_buckets_asks_and_bids() {
+    let db = repo();
+    {
+        let mut state = db.state_mut();
+        state.token_prices.push(TokenUsdPrice {
+            token: TOKEN.to_string(),
+            usd_price: "0.1".parse().unwrap(),
+            ts: ts(1_700_000_000),
+        });
+        let mut cheaper = direct_sell("0:ds2", "0:n3", DirectSellState::Active);
+        cheaper.price = BigDecimal::from(55);
+        state.direct_sells.push(cheaper);
+        // the seller no longer owns the nft
+        let mut stale = direct_sell("0:ds3", "0:n5", DirectSellState::Active);
+        stale.seller = Some(BOB.to_string());
+        stale.valid = false;
+        stale.price = BigDecimal::from(1);
+        state.direct_sells.push(stale);
+        state
+            .direct_buys
+            .push(direct_buy("0:db3", "0:n4", DirectBuyState::Active));
+    }
+    let filter = orderbook::get_orderbook(db, cache());
+
+    let book = body(
+        &warp::test::request()
+            .path(&format!("/collection/{VERIFIED}/orderbook"))
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(book["directSells"], 2);
+    assert_eq!(book["auctions"], 1);
+    assert_eq!(book["directBuys"], 2);
+    let native = &book["tokens"][0];
+    assert_eq!(native["token"], TOKEN);
+    assert_eq!(native["bestAsk"], "50");
+    assert_eq!(native["bestBid"], "40");
+    assert_eq!(native["spread"], "10");
+    assert_eq!(native["step"], "1");
+    let levels = |side: &Value| -> Vec<(String, u64, u64)> {
+        side.as_array()
+            .unwrap()
+            .iter()
+            .map(|l| {
+                (
+                    l["price"].as_str().unwrap().to_string(),
+                    l["count"].as_u64().unwrap(),
+                    l["cumulative"].as_u64().unwrap(),
+                )
+            })
+            .collect()
+    };
+    assert_eq!(
+        levels(&native["asks"]),
+        vec![
+            ("50".to_string(), 1, 1),
+            ("55".to_string(), 1, 2),
+            ("100".to_string(), 1, 3)
+        ]
+    );
+    assert_eq!(levels(&native["bids"]), vec![("40".to_string(), 2, 2)]);
+    assert_eq!(book["usd"]["bestAsk"], "5.0");
+    assert_eq!(book["usd"]["step"], "0.1");
+
+    let wide = body(
+        &warp::test::request()
+            .path(&format!(
+                "/collection/{VERIFIED}/orderbook?usdStep=10&levels=1"
+            ))
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(levels(&wide["usd"]["asks"]), vec![("0".to_string(), 2, 2)]);
+
+    let fine = body(
+        &warp::test::request()
+            .path(&format!(
+                "/collection/{VERIFIED}/orderbook?tokenStep=25&token={TOKEN}"
+            ))
+            .reply(&filter)
+            .await,
+    );
+    assert_eq!(fine["tokens"][0]["step"], "25");
+    assert_eq!(
+        levels(&fine["tokens"][0]["asks"]),
+        vec![("50".to_string(), 2, 2), ("100".to_string(), 1, 3)]
+    );
+
+    for query in [
+        "usdStep=-1",
+        "usdStep=1e-100000000",
+        "usdStep=1e100000000",
+        "tokenStep=1",
+    ] {
+        let bad = warp::test::request()
+            .path(&format!("/collection/{VERIFIED}/orderbook?{query}"))
+            .reply(&filter)
+            .await;
+        assert_eq!(bad.status(), StatusCode::BAD_REQUEST, "{query}");
+    }
+    let missing = warp::test::request()
+        .path("/collection/0:nope/orderbook")
+        .reply(&filter)
+        .await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}